@@ -78,6 +78,8 @@ export class CodeWorkerPool {
 
     const workerPath = path.join(this.distElectronPath, 'codeExecutor.js');
     this.worker = new Worker(workerPath, {
+      // vm.SourceTextModule backs the native ESM path
+      execArgv: ['--experimental-vm-modules'],
       workerData: {
         nodeModulesPath: this.nodeModulesPath,
         jsInputBuffer: this.options.jsInputBuffer,
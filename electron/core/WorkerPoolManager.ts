@@ -2,13 +2,22 @@
  * Worker Pool Manager
  *
  * Handles the lifecycle and communication with code execution workers.
- * Provides a scalable pool interface for JS/TS, Python, and WASI C/C++ workers.
+ * Provides a scalable pool interface for JS/TS, Python, and WASI C/C++/Rust
+ * workers.
  */
 
 import { Worker } from 'node:worker_threads';
 import path from 'node:path';
 import type { BrowserWindow } from 'electron';
+import type {
+  NativeCompilerOptions,
+  NativeProjectMode,
+  SandboxPermissions,
+  SerializedChildren,
+} from '@cheesejs/core';
+import { SHARED_INPUT_DISMISSED } from '@cheesejs/core';
 import type { Language } from '@cheesejs/core/contracts/workerTypes';
+import type { ModuleFormat } from '../transpiler/codeTransforms.js';
 
 import { createMainLogger } from './logger.js';
 
@@ -29,6 +38,25 @@ export interface ExecutionRequest {
     loopProtection?: boolean;
     magicComments?: boolean;
     workingDirectory?: string;
+    compilerOptions?: NativeCompilerOptions;
+    projectMode?: NativeProjectMode;
+    emitCompiledOutput?: boolean;
+    /** Set by the node-vm provider for snippets using `import`/`export` */
+    moduleFormat?: ModuleFormat;
+    /** Open JS/TS tabs by title, importable from the program */
+    moduleFiles?: Record<string, string>;
+    entryFile?: string;
+    permissions?: SandboxPermissions;
+    /** Heap limit for the JS/TS worker; 0 or unset keeps V8's default */
+    memoryLimitMb?: number;
+    /** REPL session whose state the run shares */
+    sessionId?: string;
+    /** Run every statement of the session run, reusing none */
+    runAllStatements?: boolean;
+    /** Profile the run and send the profile before completing */
+    profile?: boolean;
+    /** Run the tests a Python program defines after it */
+    runTests?: boolean;
   };
 }
 
@@ -39,12 +67,22 @@ export interface WorkerResult {
     | 'debug'
     | 'error'
     | 'complete'
+    | 'diagnostics'
+    | 'compiled-output'
+    | 'test'
+    | 'benchmark'
+    | 'profile'
+    | 'phase'
     | 'ready'
     | 'status'
     | 'prompt-request'
     | 'alert-request'
-    | 'input-request';
+    | 'permission-request'
+    | 'input-request'
+    | 'value-children';
   id: string;
+  /** Matches a `value-children` reply to its `expand-value` request */
+  requestId?: string;
   data?: unknown;
   line?: number;
   jsType?: string;
@@ -64,6 +102,12 @@ interface CodeWorkerInstance {
   activeExecutionId: string | null;
   jsInputBuffer: SharedArrayBuffer;
   jsInputLock: SharedArrayBuffer;
+  /** Heap limit the worker was started with */
+  memoryLimitMb: number;
+  /** Set once the worker ran out of memory, before it exits */
+  outOfMemory?: boolean;
+  /** Execution whose printed values the worker still holds */
+  lastExecutionId?: string;
 }
 
 interface PythonWorkerInstance {
@@ -77,6 +121,8 @@ interface WasiWorkerInstance {
   worker: Worker;
   isReady: boolean;
   activeExecutionId: string | null;
+  stdinBuffer: SharedArrayBuffer;
+  stdinLock: SharedArrayBuffer;
 }
 
 // ============================================================================
@@ -98,6 +144,16 @@ export class WorkerPoolManager {
     ReturnType<typeof setTimeout>
   >();
   private executionTimeouts = new Map<string, ReturnType<typeof setTimeout>>();
+  private pendingValueRequests = new Map<
+    string,
+    (children: SerializedChildren | null) => void
+  >();
+  private nextValueRequestId = 0;
+  /** Worker holding the state of each REPL session */
+  private sessionWorkers = new Map<
+    string,
+    CodeWorkerInstance | PythonWorkerInstance
+  >();
 
   // Reference to main window for IPC
   private mainWindow: BrowserWindow | null = null;
@@ -105,16 +161,29 @@ export class WorkerPoolManager {
   // Worker paths
   private readonly distElectronPath: string;
   private nodeModulesPath: string;
+  private readonly wasiCompileCacheDir?: string;
+  private readonly workspaceRoot?: string;
+
+  // Heap limit for new code workers, taken from the latest request
+  private codeWorkerMemoryLimitMb = 0;
 
   // Configuration
   private readonly FORCE_TERMINATION_TIMEOUT = 2000;
+  private readonly EXPAND_VALUE_TIMEOUT = 5000;
   private readonly MAX_CODE_WORKERS = 4;
   private readonly MAX_PYTHON_WORKERS = 2;
   private readonly MAX_WASI_WORKERS = 2;
 
-  constructor(distElectronPath: string, nodeModulesPath: string) {
+  constructor(
+    distElectronPath: string,
+    nodeModulesPath: string,
+    wasiCompileCacheDir?: string,
+    workspaceRoot?: string
+  ) {
     this.distElectronPath = distElectronPath;
     this.nodeModulesPath = nodeModulesPath;
+    this.wasiCompileCacheDir = wasiCompileCacheDir;
+    this.workspaceRoot = workspaceRoot;
   }
 
   // ============================================================================
@@ -150,18 +219,41 @@ export class WorkerPoolManager {
     );
   }
 
-  resolveJSInput(id: string, value: string): void {
+  /** Answers a prompt of a JS or WASI run; null dismisses it */
+  resolveJSInput(id: string, value: string | null): void {
     // Find the worker executing this ID
     const worker = this.codeWorkers.find((w) => w.activeExecutionId === id);
-    if (!worker || !worker.jsInputBuffer || !worker.jsInputLock) return;
+    if (worker?.jsInputBuffer && worker.jsInputLock) {
+      this.writeSharedInput(worker.jsInputBuffer, worker.jsInputLock, value);
+      return;
+    }
+
+    // WASI programs reading stdin use the same console prompt flow
+    const wasiWorker = this.wasiWorkers.find(
+      (w) => w.activeExecutionId === id
+    );
+    if (wasiWorker) {
+      this.writeSharedInput(
+        wasiWorker.stdinBuffer,
+        wasiWorker.stdinLock,
+        value
+      );
+      this.armWasiExecutionTimeout(wasiWorker, id);
+    }
+  }
 
+  private writeSharedInput(
+    inputBuffer: SharedArrayBuffer,
+    inputLock: SharedArrayBuffer,
+    value: string | null
+  ): void {
     // Reset buffer
-    const buffer = new Uint8Array(worker.jsInputBuffer);
+    const buffer = new Uint8Array(inputBuffer);
     buffer.fill(0);
 
     // Write string to buffer
     const encoder = new TextEncoder();
-    const encoded = encoder.encode(value);
+    const encoded = encoder.encode(value ?? '');
 
     if (encoded.length > buffer.length) {
       buffer.set(encoded.slice(0, buffer.length));
@@ -169,9 +261,9 @@ export class WorkerPoolManager {
       buffer.set(encoded);
     }
 
-    // Set lock to 1 (ready)
-    const lock = new Int32Array(worker.jsInputLock);
-    Atomics.store(lock, 0, 1);
+    // Set lock to 1 (ready), or mark the prompt as dismissed
+    const lock = new Int32Array(inputLock);
+    Atomics.store(lock, 0, value === null ? SHARED_INPUT_DISMISSED : 1);
 
     // Notify worker
     Atomics.notify(lock, 0);
@@ -189,6 +281,73 @@ export class WorkerPoolManager {
     }
   }
 
+  /**
+   * Children of a value printed by JS/TS execution `id`. Resolves null once
+   * the worker that ran it has started another run or is gone.
+   */
+  expandValue(id: string, handle: number): Promise<SerializedChildren | null> {
+    const instance = this.codeWorkers.find((w) => w.lastExecutionId === id);
+    if (!instance) return Promise.resolve(null);
+
+    const requestId = `value-${this.nextValueRequestId++}`;
+    return new Promise((resolve) => {
+      const timeout = setTimeout(() => {
+        this.pendingValueRequests.delete(requestId);
+        resolve(null);
+      }, this.EXPAND_VALUE_TIMEOUT);
+      this.pendingValueRequests.set(requestId, (children) => {
+        clearTimeout(timeout);
+        this.pendingValueRequests.delete(requestId);
+        resolve(children);
+      });
+      instance.worker.postMessage({
+        type: 'expand-value',
+        id,
+        handle,
+        requestId,
+      });
+    });
+  }
+
+  /** Drop the state of a REPL session, so its next run starts over */
+  resetSession(sessionId: string): void {
+    const bound = this.sessionWorkers.get(sessionId);
+    bound?.worker.postMessage({ type: 'reset-session', sessionId });
+    this.sessionWorkers.delete(sessionId);
+
+    // A code worker kept for its sessions gives way to the current limit
+    const instance = this.codeWorkers.find((w) => w === bound);
+    if (
+      instance &&
+      !instance.activeExecutionId &&
+      this.isStale(instance) &&
+      !this.holdsSession(instance)
+    ) {
+      this.retireCodeWorker(instance);
+    }
+  }
+
+  /** Whether the state of some REPL session lives in the worker */
+  private holdsSession(
+    worker: CodeWorkerInstance | PythonWorkerInstance
+  ): boolean {
+    return [...this.sessionWorkers.values()].includes(worker);
+  }
+
+  /**
+   * Runs of a REPL session wait for the worker holding its state, unless
+   * that worker is gone
+   */
+  private canRunOn(
+    task: QueuedExecution,
+    worker: CodeWorkerInstance | PythonWorkerInstance,
+    pool: readonly (CodeWorkerInstance | PythonWorkerInstance)[]
+  ): boolean {
+    const { sessionId } = task.request.options;
+    const bound = sessionId ? this.sessionWorkers.get(sessionId) : undefined;
+    return !bound || bound === worker || !pool.includes(bound);
+  }
+
   // ============================================================================
   // BACKWARDS COMPATIBILITY & ADMIN API
   // ============================================================================
@@ -245,8 +404,15 @@ export class WorkerPoolManager {
     const jsInputBuffer = new SharedArrayBuffer(10 * 1024);
     const jsInputLock = new SharedArrayBuffer(4);
 
+    const memoryLimitMb = this.codeWorkerMemoryLimitMb;
     const workerPath = path.join(this.distElectronPath, 'codeExecutor.js');
     const worker = new Worker(workerPath, {
+      // vm.SourceTextModule backs the native ESM path
+      execArgv: ['--experimental-vm-modules'],
+      resourceLimits:
+        memoryLimitMb > 0
+          ? { maxOldGenerationSizeMb: memoryLimitMb }
+          : undefined,
       workerData: {
         nodeModulesPath: this.nodeModulesPath,
         jsInputBuffer,
@@ -260,6 +426,7 @@ export class WorkerPoolManager {
       activeExecutionId: null,
       jsInputBuffer,
       jsInputLock,
+      memoryLimitMb,
     };
 
     this.codeWorkers.push(instance);
@@ -272,16 +439,34 @@ export class WorkerPoolManager {
         return;
       }
 
-      // Handle prompt/alert requests
+      // Handle prompt/alert/permission requests
       if (
         message.type === 'prompt-request' ||
-        message.type === 'alert-request'
+        message.type === 'alert-request' ||
+        message.type === 'permission-request'
       ) {
         // Already includes id from worker
         this.sendToRenderer('js-input-request', message);
         return;
       }
 
+      // Tests and benchmarks get their own timeout once the program is done
+      if (message.type === 'phase') {
+        const task = this.pendingExecutions.get(message.id);
+        if (task && instance.activeExecutionId === message.id) {
+          this.clearExecutionTimeout(message.id);
+          this.armJsExecutionTimeout(instance, message.id, task.request);
+        }
+        return;
+      }
+
+      if (message.type === 'value-children') {
+        this.pendingValueRequests.get(message.requestId ?? '')?.(
+          (message.data as SerializedChildren | null) ?? null
+        );
+        return;
+      }
+
       // Forward all other messages to renderer
       this.sendToRenderer('code-execution-result', message);
 
@@ -289,13 +474,23 @@ export class WorkerPoolManager {
         this.clearPendingCancellation(message.id);
         this.resolveExecution(message.id, message);
         instance.activeExecutionId = null;
+        if (this.isStale(instance) && !this.holdsSession(instance)) {
+          this.retireCodeWorker(instance);
+        }
         this.processJsQueue();
       }
     });
 
     worker.on('error', (error: unknown) => {
       const err = error instanceof Error ? error : new Error(String(error));
-      log.error('[WorkerPool] Code worker error:', err);
+      if ((err as NodeJS.ErrnoException).code === 'ERR_WORKER_OUT_OF_MEMORY') {
+        log.warn(
+          `[WorkerPool] Code worker ran out of memory (limit ${instance.memoryLimitMb} MB), respawning`
+        );
+        instance.outOfMemory = true;
+      } else {
+        log.error('[WorkerPool] Code worker error:', err);
+      }
       this.handleCodeWorkerCrash(instance, err);
     });
 
@@ -314,22 +509,73 @@ export class WorkerPoolManager {
   }
 
   private handleCodeWorkerCrash(instance: CodeWorkerInstance, error: Error) {
-    if (instance.activeExecutionId) {
-      this.resolveExecution(instance.activeExecutionId, {
+    const id = instance.activeExecutionId;
+    if (id) {
+      instance.activeExecutionId = null;
+      const limit =
+        instance.memoryLimitMb > 0 ? `${instance.memoryLimitMb} MB` : 'default';
+      this.resolveExecution(id, {
         type: 'error',
-        id: instance.activeExecutionId,
-        data: { name: 'WorkerCrash', message: error.message },
+        id,
+        data: instance.outOfMemory
+          ? {
+              name: 'OutOfMemoryError',
+              message: `Out of memory: the run exceeded the ${limit} heap limit and the worker was restarted`,
+            }
+          : { name: 'WorkerCrash', message: error.message },
       });
     }
     this.codeWorkers = this.codeWorkers.filter((w) => w !== instance);
     this.processJsQueue(); // might spawn a new one to replace it
   }
 
+  /**
+   * Use `limitMb` for code workers from now on. Idle workers started with
+   * another limit are replaced; busy ones are retired once they finish.
+   * Workers holding a REPL session keep it, and only run that session,
+   * until it is reset.
+   */
+  private setCodeWorkerMemoryLimit(limitMb: number): void {
+    if (limitMb === this.codeWorkerMemoryLimitMb) return;
+    this.codeWorkerMemoryLimitMb = limitMb;
+    for (const instance of this.codeWorkers) {
+      if (!instance.activeExecutionId && !this.holdsSession(instance)) {
+        this.retireCodeWorker(instance);
+      }
+    }
+  }
+
+  /** Whether the worker was started with another memory limit */
+  private isStale(instance: CodeWorkerInstance): boolean {
+    return instance.memoryLimitMb !== this.codeWorkerMemoryLimitMb;
+  }
+
+  /** Workers kept under an old memory limit only run their own sessions */
+  private hasLimitFor(
+    task: QueuedExecution,
+    worker: CodeWorkerInstance
+  ): boolean {
+    const { sessionId } = task.request.options;
+    return (
+      !this.isStale(worker) ||
+      (!!sessionId && this.sessionWorkers.get(sessionId) === worker)
+    );
+  }
+
+  private retireCodeWorker(instance: CodeWorkerInstance): void {
+    this.codeWorkers = this.codeWorkers.filter((w) => w !== instance);
+    instance.worker.terminate().catch(() => undefined);
+  }
+
   async executeCode(
     request: ExecutionRequest,
     transformedCode: string
   ): Promise<unknown> {
     return new Promise((resolve, reject) => {
+      const { memoryLimitMb } = request.options;
+      if (memoryLimitMb !== undefined) {
+        this.setCodeWorkerMemoryLimit(memoryLimitMb);
+      }
       this.jsQueue.push({ request, transformedCode, resolve, reject });
       this.processJsQueue();
     });
@@ -338,24 +584,23 @@ export class WorkerPoolManager {
   private processJsQueue() {
     // 1. Replenish workers if there is demand and capacity
     if (this.jsQueue.length > 0) {
-      const idleWorkers = this.codeWorkers.filter((w) => !w.activeExecutionId);
-      if (
-        idleWorkers.length === 0 &&
-        this.codeWorkers.length < this.MAX_CODE_WORKERS
-      ) {
+      const current = this.codeWorkers.filter((w) => !this.isStale(w));
+      const idleWorkers = current.filter((w) => !w.activeExecutionId);
+      if (idleWorkers.length === 0 && current.length < this.MAX_CODE_WORKERS) {
         this.spawnCodeWorker(); // async startup
       }
     }
 
     // 2. Assign tasks to idle ready workers
     for (const worker of this.codeWorkers) {
-      if (
-        worker.isReady &&
-        !worker.activeExecutionId &&
-        this.jsQueue.length > 0
-      ) {
-        const task = this.jsQueue.shift()!;
-        this.startJsExecution(worker, task);
+      if (!worker.isReady || worker.activeExecutionId) continue;
+      const index = this.jsQueue.findIndex(
+        (task) =>
+          this.canRunOn(task, worker, this.codeWorkers) &&
+          this.hasLimitFor(task, worker)
+      );
+      if (index !== -1) {
+        this.startJsExecution(worker, this.jsQueue.splice(index, 1)[0]);
       }
     }
   }
@@ -363,7 +608,9 @@ export class WorkerPoolManager {
   private startJsExecution(worker: CodeWorkerInstance, task: QueuedExecution) {
     const { id, options } = task.request;
     worker.activeExecutionId = id;
+    worker.lastExecutionId = id;
     this.pendingExecutions.set(id, task);
+    if (options.sessionId) this.sessionWorkers.set(options.sessionId, worker);
 
     worker.worker.postMessage({
       type: 'execute',
@@ -373,11 +620,27 @@ export class WorkerPoolManager {
         timeout: options.timeout ?? 30000,
         showUndefined: options.showUndefined ?? false,
         workingDirectory: options.workingDirectory,
+        moduleFormat: options.moduleFormat,
+        moduleFiles: options.moduleFiles,
+        entryFile: options.entryFile,
+        workspaceRoot: this.workspaceRoot,
+        permissions: options.permissions,
+        sessionId: options.sessionId,
+        runAllStatements: options.runAllStatements,
+        profile: options.profile,
       },
     });
 
-    // Safety fallback timeout
-    const timeoutMs = (options.timeout ?? 30000) + 5000;
+    this.armJsExecutionTimeout(worker, id, task.request);
+  }
+
+  // Safety fallback timeout
+  private armJsExecutionTimeout(
+    worker: CodeWorkerInstance,
+    id: string,
+    request: ExecutionRequest
+  ) {
+    const timeoutMs = (request.options.timeout ?? 30000) + 5000;
     const fallbackTimeout = setTimeout(() => {
       if (worker.activeExecutionId === id) {
         log.warn(
@@ -507,13 +770,12 @@ export class WorkerPoolManager {
     }
 
     for (const worker of this.pythonWorkers) {
-      if (
-        worker.isReady &&
-        !worker.activeExecutionId &&
-        this.pythonQueue.length > 0
-      ) {
-        const task = this.pythonQueue.shift()!;
-        this.startPythonExecution(worker, task);
+      if (!worker.isReady || worker.activeExecutionId) continue;
+      const index = this.pythonQueue.findIndex((task) =>
+        this.canRunOn(task, worker, this.pythonWorkers)
+      );
+      if (index !== -1) {
+        this.startPythonExecution(worker, this.pythonQueue.splice(index, 1)[0]);
       }
     }
   }
@@ -525,6 +787,7 @@ export class WorkerPoolManager {
     const { id, options, code } = task.request;
     worker.activeExecutionId = id;
     this.pendingExecutions.set(id, task);
+    if (options.sessionId) this.sessionWorkers.set(options.sessionId, worker);
 
     worker.worker.postMessage({
       type: 'execute',
@@ -534,6 +797,10 @@ export class WorkerPoolManager {
         timeout: options.timeout ?? 30000,
         showUndefined: options.showUndefined ?? false,
         workingDirectory: options.workingDirectory,
+        sessionId: options.sessionId,
+        runAllStatements: options.runAllStatements,
+        profile: options.profile,
+        runTests: options.runTests,
       },
     });
 
@@ -560,13 +827,25 @@ export class WorkerPoolManager {
       this.wasiWorkers.length
     );
 
+    // Same layout as the JS prompt buffers: null-terminated input + lock
+    const stdinBuffer = new SharedArrayBuffer(10 * 1024);
+    const stdinLock = new SharedArrayBuffer(4);
+
     const workerPath = path.join(this.distElectronPath, 'wasiExecutor.js');
-    const worker = new Worker(workerPath);
+    const worker = new Worker(workerPath, {
+      workerData: {
+        stdinBuffer,
+        stdinLock,
+        compileCacheDir: this.wasiCompileCacheDir,
+      },
+    });
 
     const instance: WasiWorkerInstance = {
       worker,
       isReady: false,
       activeExecutionId: null,
+      stdinBuffer,
+      stdinLock,
     };
 
     this.wasiWorkers.push(instance);
@@ -579,6 +858,14 @@ export class WorkerPoolManager {
         return;
       }
 
+      // The program is blocked on stdin: waiting for the user must not
+      // count against the execution timeout.
+      if (message.type === 'prompt-request') {
+        this.clearExecutionTimeout(message.id);
+        this.sendToRenderer('js-input-request', message);
+        return;
+      }
+
       this.sendToRenderer('code-execution-result', message);
 
       if (message.type === 'complete' || message.type === 'error') {
@@ -659,10 +946,21 @@ export class WorkerPoolManager {
       options: {
         timeout: options.timeout ?? 30000,
         workingDirectory: options.workingDirectory,
+        compilerOptions: options.compilerOptions,
+        projectMode: options.projectMode,
+        emitCompiledOutput: options.emitCompiledOutput,
       },
     });
 
-    const timeoutMs = (options.timeout ?? 30000) + 5000;
+    this.armWasiExecutionTimeout(worker, id);
+  }
+
+  private armWasiExecutionTimeout(worker: WasiWorkerInstance, id: string) {
+    const pending = this.pendingExecutions.get(id);
+    if (!pending) return;
+
+    this.clearExecutionTimeout(id);
+    const timeoutMs = (pending.request.options.timeout ?? 30000) + 5000;
     const fallbackTimeout = setTimeout(() => {
       if (worker.activeExecutionId === id) {
         log.warn(
@@ -817,6 +1115,10 @@ export class WorkerPoolManager {
 
   private cleanupExecutionTracking(id: string) {
     this.pendingExecutions.delete(id);
+    this.clearExecutionTimeout(id);
+  }
+
+  private clearExecutionTimeout(id: string) {
     const exTimer = this.executionTimeouts.get(id);
     if (exTimer) {
       clearTimeout(exTimer);
@@ -96,8 +96,22 @@ export function registerExecutionHandlers({
   // Handle input response from renderer to JS worker
   ipcMain.on(
     'js-input-response',
-    (_event: unknown, { id, value }: { id: string; value: string }) => {
+    (_event: unknown, { id, value }: { id: string; value: string | null }) => {
       workerPool.resolveJSInput(id, value);
     }
   );
+
+  // Children of a value a JS/TS run printed, fetched as the user expands it
+  ipcMain.handle(
+    'expand-value',
+    async (_event: unknown, { id, handle }: { id: string; handle: number }) =>
+      workerPool.expandValue(id, handle)
+  );
+
+  // Drop the state a REPL session kept in its worker
+  ipcMain.handle(
+    'reset-session',
+    async (_event: unknown, { sessionId }: { sessionId: string }) =>
+      workerPool.resetSession(sessionId)
+  );
 }
@@ -6,17 +6,91 @@ import {
 } from 'electron';
 import { promises as fs } from 'node:fs';
 import path from 'node:path';
+import { pathToFileURL } from 'node:url';
 import { spawn, ChildProcess } from 'node:child_process';
-import type { LspLanguageConfig } from '@cheesejs/core';
+import type { LspLanguageConfig, LspStartResult } from '@cheesejs/core';
 import { getDefaultLspConfig } from '@cheesejs/languages';
 import { appLog } from '../logger.js';
+import { getWorkspaceRoot } from './FilesystemHandlers.js';
 
 const activeLspProcesses = new Map<string, ChildProcess>();
+const activeLspProjects = new Map<string, LspProjectLayout>();
 
 interface LspConfig {
   languages: Record<string, LspLanguageConfig>;
 }
 
+/** On-disk project a language server is rooted at. */
+interface LspProjectLayout {
+  rootPath: string;
+  documentPath: string;
+}
+
+const RUST_SCRATCH_MANIFEST = [
+  '[package]',
+  'name = "cheesejs-scratch"',
+  'version = "0.1.0"',
+  'edition = "2021"',
+  '',
+  '[dependencies]',
+  '',
+  // Keeps a Cargo workspace the user's project may define from claiming it
+  '[workspace]',
+  '',
+].join('\n');
+
+async function writeFileIfMissing(
+  filePath: string,
+  content: string
+): Promise<void> {
+  try {
+    await fs.writeFile(filePath, content, { encoding: 'utf8', flag: 'wx' });
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code !== 'EEXIST') {
+      throw error;
+    }
+  }
+}
+
+/**
+ * rust-analyzer only analyzes files that belong to a crate, so the editor
+ * buffer is mirrored as `src/main.rs` of a scratch Cargo project that lives
+ * inside the workspace.
+ */
+async function ensureRustScratchProject(): Promise<LspProjectLayout> {
+  const rootPath = path.join(getWorkspaceRoot(), '.cheesejs', 'rust-analyzer');
+  const sourceDirectory = path.join(rootPath, 'src');
+  await fs.mkdir(sourceDirectory, { recursive: true });
+
+  // Generated, so rewritten to update manifests of earlier versions
+  await fs.writeFile(
+    path.join(rootPath, 'Cargo.toml'),
+    RUST_SCRATCH_MANIFEST,
+    'utf8'
+  );
+
+  const documentPath = path.join(sourceDirectory, 'main.rs');
+  await writeFileIfMissing(documentPath, 'fn main() {}\n');
+
+  return { rootPath, documentPath };
+}
+
+const LSP_PROJECT_SCAFFOLDS: Record<string, () => Promise<LspProjectLayout>> =
+  {
+    rust: ensureRustScratchProject,
+  };
+
+/**
+ * Languages added to the defaults after a user's lsp.json was written would
+ * otherwise never show up, so missing entries are filled from the registry.
+ */
+function withDefaultLanguages(config: LspConfig): LspConfig {
+  return {
+    ...config,
+    languages: { ...DEFAULT_LSP_CONFIG.languages, ...config.languages },
+  };
+}
+
 function getErrorMessage(error: unknown): string {
   return error instanceof Error ? error.message : String(error);
 }
@@ -31,6 +105,19 @@ export function getLspConfigPath(): string {
 
 const DEFAULT_LSP_CONFIG: LspConfig = getDefaultLspConfig();
 
+function toProjectUris(
+  project: LspProjectLayout | undefined
+): Pick<LspStartResult, 'rootUri' | 'documentUri'> {
+  if (!project) {
+    return {};
+  }
+
+  return {
+    rootUri: pathToFileURL(project.rootPath).href,
+    documentUri: pathToFileURL(project.documentPath).href,
+  };
+}
+
 export function registerLspHandlers(): void {
   ipcMain.handle('get-lsp-config', async () => {
     try {
@@ -55,7 +142,7 @@ export function registerLspHandlers(): void {
       if (!isLspConfig(parsed) || Object.keys(parsed.languages).length === 0) {
         return DEFAULT_LSP_CONFIG;
       }
-      return parsed;
+      return withDefaultLanguages(parsed);
     } catch (error) {
       appLog.error('[LspHandlers] Error reading LSP config:', error);
       return DEFAULT_LSP_CONFIG;
@@ -82,10 +169,16 @@ export function registerLspHandlers(): void {
 
   ipcMain.handle(
     'lsp:start',
-    async (event: IpcMainInvokeEvent, langId: string) => {
+    async (
+      event: IpcMainInvokeEvent,
+      langId: string
+    ): Promise<LspStartResult> => {
       try {
         if (activeLspProcesses.has(langId)) {
-          return { success: true };
+          return {
+            success: true,
+            ...toProjectUris(activeLspProjects.get(langId)),
+          };
         }
 
         const configPath = getLspConfigPath();
@@ -97,7 +190,7 @@ export function registerLspHandlers(): void {
           try {
             const parsed: unknown = JSON.parse(configData);
             if (isLspConfig(parsed)) {
-              config = parsed;
+              config = withDefaultLanguages(parsed);
             }
           } catch {
             // Ignore malformed config and fall back to defaults.
@@ -115,13 +208,19 @@ export function registerLspHandlers(): void {
           `Starting LSP for ${langId}: ${langConfig.command} ${langConfig.args?.join(' ')}`
         );
 
+        const scaffoldProject = LSP_PROJECT_SCAFFOLDS[langId];
+        const project = scaffoldProject ? await scaffoldProject() : undefined;
+
         const lspProcess = spawn(langConfig.command, langConfig.args || [], {
-          cwd: app.getAppPath(),
+          cwd: project?.rootPath ?? app.getAppPath(),
           env: process.env,
           shell: process.platform === 'win32',
         });
 
         activeLspProcesses.set(langId, lspProcess);
+        if (project) {
+          activeLspProjects.set(langId, project);
+        }
 
         lspProcess.stdout?.on('data', (data) => {
           if (!event.sender.isDestroyed()) {
@@ -138,6 +237,7 @@ export function registerLspHandlers(): void {
 
         const cleanup = () => {
           activeLspProcesses.delete(langId);
+          activeLspProjects.delete(langId);
         };
 
         lspProcess.on('exit', cleanup);
@@ -146,7 +246,7 @@ export function registerLspHandlers(): void {
           cleanup();
         });
 
-        return { success: true };
+        return { success: true, ...toProjectUris(project) };
       } catch (err: unknown) {
         appLog.error(`Failed to start LSP for ${langId}`, err);
         return { success: false, error: getErrorMessage(err) };
@@ -160,6 +260,7 @@ export function registerLspHandlers(): void {
       proc.kill();
     }
     activeLspProcesses.delete(langId);
+    activeLspProjects.delete(langId);
   });
 
   ipcMain.on(
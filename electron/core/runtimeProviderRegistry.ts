@@ -2,7 +2,10 @@ import {
   getRuntimeProviderId,
   type RuntimeProviderId,
 } from '@cheesejs/languages';
-import type { TransformOptions } from '../transpiler/codeTransforms';
+import {
+  usesModuleSyntax,
+  type TransformOptions,
+} from '../transpiler/codeTransforms';
 import type { ExecutionRequest, WorkerPoolManager } from './WorkerPoolManager';
 
 export const DEFAULT_RUNTIME_PROVIDER_ID: RuntimeProviderId = 'node-vm';
@@ -25,9 +28,19 @@ function buildTransformOptions(request: ExecutionRequest): TransformOptions {
     loopProtection: request.options.loopProtection ?? true,
     magicComments: request.options.magicComments ?? false,
     showUndefined: request.options.showUndefined ?? false,
+    moduleFormat: request.options.moduleFormat,
+    session: !!request.options.sessionId,
   };
 }
 
+/** Routes snippets written with `import`/`export` to the native ESM path */
+function withModuleFormat(request: ExecutionRequest): ExecutionRequest {
+  if (request.options.moduleFormat || !usesModuleSyntax(request.code)) {
+    return request;
+  }
+  return { ...request, options: { ...request.options, moduleFormat: 'esm' } };
+}
+
 function transformExecutionCode(
   request: ExecutionRequest,
   transformCode: RuntimeExecutionContext['transformCode']
@@ -47,8 +60,12 @@ const RUNTIME_EXECUTION_PROVIDERS: Record<
   'node-vm': {
     id: 'node-vm',
     async execute({ request, workerPool, transformCode }) {
-      const transformedCode = transformExecutionCode(request, transformCode);
-      return workerPool.executeCode(request, transformedCode);
+      const moduleRequest = withModuleFormat(request);
+      const transformedCode = transformExecutionCode(
+        moduleRequest,
+        transformCode
+      );
+      return workerPool.executeCode(moduleRequest, transformedCode);
     },
     isReady(workerPool) {
       return workerPool.isCodeWorkerReady();
@@ -72,6 +89,15 @@ const RUNTIME_EXECUTION_PROVIDERS: Record<
       return workerPool.isWasiWorkerReady();
     },
   },
+  'wasi-rustc': {
+    id: 'wasi-rustc',
+    async execute({ request, workerPool }) {
+      return workerPool.executeWasi(request);
+    },
+    isReady(workerPool) {
+      return workerPool.isWasiWorkerReady();
+    },
+  },
 };
 
 export function getRuntimeExecutionProvider(
@@ -43,12 +43,17 @@ let workerPool: WorkerPoolManager | null = null;
 async function initApp() {
   try {
     // Initialize secure workspace for filesystem operations
-    initWorkspace();
+    const workspaceRoot = initWorkspace();
 
     // Initialize Worker Pool
     await initPackagesDirectory();
     const nodeModulesPath = getNodeModulesPath();
-    workerPool = new WorkerPoolManager(__dirname, nodeModulesPath);
+    workerPool = new WorkerPoolManager(
+      __dirname,
+      nodeModulesPath,
+      path.join(app.getPath('userData'), 'wasi-compile-cache'),
+      workspaceRoot
+    );
 
     // Start workers early for performance (especially Python)
     await Promise.all([
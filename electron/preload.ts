@@ -5,6 +5,12 @@ import type {
   LspBridgeMessage,
   LspConfig,
   LspConfigApi,
+  LspStartResult,
+  NativeCompilerOptions,
+  NativeProjectMode,
+  SandboxCapability,
+  SandboxPermissions,
+  SerializedChildren,
 } from '@cheesejs/core';
 import type { Language } from '@cheesejs/core/contracts/workerTypes';
 
@@ -23,10 +29,24 @@ interface ExecutionOptions {
   loopProtection?: boolean;
   magicComments?: boolean;
   language?: Language;
+  compilerOptions?: NativeCompilerOptions;
+  projectMode?: NativeProjectMode;
+  emitCompiledOutput?: boolean;
+  profile?: boolean;
+  runTests?: boolean;
+  permissions?: SandboxPermissions;
+  memoryLimitMb?: number;
 }
 
 interface ExecutionResult {
-  type: 'result' | 'console' | 'debug' | 'error' | 'complete';
+  type:
+    | 'result'
+    | 'console'
+    | 'debug'
+    | 'error'
+    | 'complete'
+    | 'diagnostics'
+    | 'compiled-output';
   id: string;
   data?: unknown;
   line?: number;
@@ -43,9 +63,10 @@ interface InputRequest {
 }
 // JS Input request type (from worker)
 interface JSInputRequest {
-  type: 'prompt-request' | 'alert-request';
+  type: 'prompt-request' | 'alert-request' | 'permission-request';
   id: string;
   message: string;
+  capability?: SandboxCapability;
 }
 
 type InputRequestCallback = (request: InputRequest) => void;
@@ -210,9 +231,24 @@ contextBridge.exposeInMainWorld('codeRunner', {
   /**
    * Send input response back to JS worker
    */
-  sendJSInputResponse: (id: string, value: string) => {
+  sendJSInputResponse: (id: string, value: string | null) => {
     ipcRenderer.send('js-input-response', { id, value });
   },
+
+  /**
+   * Fetch the children of a printed value from the JS worker
+   */
+  expandValue: (
+    id: string,
+    handle: number
+  ): Promise<SerializedChildren | null> =>
+    ipcRenderer.invoke('expand-value', { id, handle }),
+
+  /**
+   * Drop the state a REPL session kept, so its next run starts over
+   */
+  resetSession: (sessionId: string): Promise<void> =>
+    ipcRenderer.invoke('reset-session', { sessionId }),
 });
 
 // ============================================================================
@@ -351,7 +387,7 @@ contextBridge.exposeInMainWorld('lspConfig', {
 } satisfies LspConfigApi);
 
 contextBridge.exposeInMainWorld('lspBridge', {
-  start: (langId: string): Promise<{ success: boolean; error?: string }> =>
+  start: (langId: string): Promise<LspStartResult> =>
     ipcRenderer.invoke('lsp:start', langId),
   stop: (langId: string): void => ipcRenderer.send('lsp:stop', langId),
   sendMessage: (langId: string, message: string): void =>
@@ -6,8 +6,11 @@
 import { describe, it, expect } from 'vitest';
 import { transformCode } from '../swcTranspiler.js';
 import {
+  applyCodeTransforms,
+  applyMagicComments,
   wrapTopLevelExpressions,
   transformConsoleTodebug,
+  usesModuleSyntax,
 } from '../codeTransforms.js';
 
 describe('Code Transformation Regression Tests', () => {
@@ -165,17 +168,47 @@ function foo() {
     });
 
     it('should handle multi-line template literal in console.log', () => {
-      // This is tricky because the console.log spans multiple lines
       const code = `console.log(\`line1
 line2\`);`;
 
-      // Note: transformConsoleTodebug works line-by-line, so this case
-      // might not be fully handled. The test documents current behavior.
       const result = transformConsoleTodebug(code);
 
-      // At minimum, it should not corrupt the code
-      expect(result).toContain('line1');
-      expect(result).toContain('line2');
+      expect(result).toBe(`debug(1, \`line1
+line2\`);`);
+    });
+  });
+
+  describe('Line numbers', () => {
+    it('should report source lines for TypeScript code', () => {
+      const code = `interface Point {
+  x: number;
+  y: number;
+}
+
+const p: Point = { x: 1, y: 2 };
+console.log(p.x);
+p.y;`;
+
+      const result = transformCode(code, {
+        showTopLevelResults: true,
+        loopProtection: true,
+      });
+
+      expect(result).toContain('debug(7, p.x)');
+      expect(result).toContain('debug(8, p.y)');
+    });
+
+    it('should keep lines after a magic comment in place', () => {
+      const code = `const x = 5; //?
+console.log(x);`;
+
+      const result = transformCode(code, {
+        magicComments: true,
+        showTopLevelResults: true,
+      });
+
+      expect(result).toContain('debug(1, x)');
+      expect(result).toContain('debug(2, x)');
     });
   });
 
@@ -216,4 +249,77 @@ test();
       expect(() => new Function(result)).not.toThrow();
     });
   });
+
+  describe('Test registrations', () => {
+    it('should not show the results of describe, it and hooks', () => {
+      const code = `describe('math', () => {
+  it('adds', () => expect(1 + 1).toBe(2));
+});
+test.skip('later', () => {});
+beforeEach(() => {});
+counter.test(1);`;
+      const result = wrapTopLevelExpressions(code);
+
+      expect(result).not.toContain("debug(1, describe");
+      expect(result).not.toContain('debug(4, test.skip');
+      expect(result).not.toContain('debug(5, beforeEach');
+      expect(result).toContain('debug(6, counter.test(1))');
+    });
+
+    it('should register tests again on every session run', () => {
+      const result = applyCodeTransforms(
+        "const x = 1;\nit('works', () => {});",
+        { session: true }
+      );
+
+      expect(result.match(/__session\.run\(/g)).toHaveLength(1);
+      expect(result).toContain("\nit('works', () => {});");
+    });
+  });
+
+  describe('Benchmark comments', () => {
+    it('should keep the statement and benchmark it', () => {
+      const code = `const sorted = data.toSorted(); //bench sort
+render(); //bench
+let x = 1; //benchmark`;
+
+      expect(applyMagicComments(code).split('\n')).toEqual([
+        'const sorted = data.toSorted(); __bench("sort", () => { const sorted = data.toSorted(); });',
+        'render(); __bench("render()", () => { render(); });',
+        'let x = 1; //benchmark',
+      ]);
+    });
+
+    it('should benchmark awaited statements with an async function', () => {
+      expect(applyMagicComments('await load(); //bench')).toBe(
+        'await load(); __bench("await load()", async () => { await load(); });'
+      );
+    });
+
+    it('should leave lines that open a block alone', () => {
+      const code = 'for (const item of items) { //bench';
+      expect(applyMagicComments(code)).toBe(code);
+    });
+  });
+
+  describe('Module syntax detection', () => {
+    it('should detect static imports and exports', () => {
+      expect(usesModuleSyntax(`import path from 'node:path';`)).toBe(true);
+      expect(usesModuleSyntax(`import { z } from 'zod';`)).toBe(true);
+      expect(usesModuleSyntax(`import 'reflect-metadata';`)).toBe(true);
+      expect(usesModuleSyntax(`const a = 1;\nexport { a };`)).toBe(true);
+      expect(usesModuleSyntax(`export default function main() {}`)).toBe(
+        true
+      );
+    });
+
+    it('should ignore dynamic imports and lookalike identifiers', () => {
+      expect(usesModuleSyntax(`const m = await import('node:os');`)).toBe(
+        false
+      );
+      expect(usesModuleSyntax(`importantThing();`)).toBe(false);
+      expect(usesModuleSyntax(`exported.value = 1;`)).toBe(false);
+      expect(usesModuleSyntax(`const fs = require('fs');`)).toBe(false);
+    });
+  });
 });
@@ -11,7 +11,7 @@ import { transformCode } from '../swcTranspiler.js';
 import {
   wrapTopLevelExpressions,
   transformConsoleTodebug,
-  findMatchingParen,
+  addLoopProtection,
 } from '../codeTransforms.js';
 
 describe('Edge Case Tests - Unusual User Inputs', () => {
@@ -475,26 +475,73 @@ class Derived extends getBase() {}`;
   });
 
   // ============================================================================
-  // PARENTHESIS MATCHING EDGE CASES
+  // SYNTAX THAT USED TO FOOL THE LINE-BASED TRANSFORMS
   // ============================================================================
-  describe('Parenthesis matching edge cases', () => {
-    it('should handle unbalanced parentheses (more open)', () => {
-      const code = 'fn(((test)';
-      const result = findMatchingParen(code, 3);
-      expect(result).toBe(-1);
+  describe('Syntax-aware instrumentation', () => {
+    it('should leave console calls inside strings and templates alone', () => {
+      const code =
+        'const s = "console.log(1)";\nconst t = `console.warn(${s})`;\nconsole.log(s, t);';
+      const result = transformConsoleTodebug(code);
+      expect(result).toContain('"console.log(1)"');
+      expect(result).toContain('`console.warn(${s})`');
+      expect(result).toContain('debug(3, s, t)');
+    });
+
+    it('should use the line a multi-line console call starts on', () => {
+      const code = `const a = 1;
+console.log(
+  a,
+  \`x
+y\`
+);`;
+      const result = transformConsoleTodebug(code);
+      expect(result).toContain('debug(2, \n  a,');
+      expect(result.split('\n')).toHaveLength(code.split('\n').length);
+    });
+
+    it('should not wrap statements inside functions written on one line', () => {
+      const code = 'function f() { 1 + 1; }\n2 + 2;';
+      expect(wrapTopLevelExpressions(code)).toBe(
+        'function f() { 1 + 1; }\ndebug(2, 2 + 2);'
+      );
+    });
+
+    it('should wrap multi-line top-level expressions once', () => {
+      const code = '[1, 2, 3]\n  .map((n) => n * 2)\n  .filter(Boolean);';
+      const result = wrapTopLevelExpressions(code);
+      expect(result.startsWith('debug(1, [1, 2, 3]')).toBe(true);
+      expect(result.endsWith('.filter(Boolean));')).toBe(true);
     });
 
-    it('should handle empty parentheses', () => {
-      const code = 'fn()';
-      const result = findMatchingParen(code, 3);
-      expect(result).toBe(3);
+    it('should keep labels attached to their loops', () => {
+      const code = `outer: for (let i = 0; i < 3; i++) {
+  for (let j = 0; j < 3; j++) {
+    if (j === 1) continue outer;
+  }
+}`;
+      const result = addLoopProtection(code);
+      expect(result).toMatch(/^\{ let __loopCounter0 = 0; outer: for/);
+      expect(() => new Function(result)).not.toThrow();
     });
 
-    it('should handle parentheses in regex', () => {
-      // This is tricky - regex can contain unbalanced parens
-      const code = '/\\(test\\)/';
-      // The function should handle this reasonably
-      expect(() => findMatchingParen(code, 0)).not.toThrow();
+    it('should guard loops whose body is a single statement', () => {
+      const code = 'let n = 0;\nwhile (true) n++;';
+      const result = addLoopProtection(code, { maxIterations: 50 });
+      expect(() => new Function(result)()).toThrow(
+        'Loop limit exceeded (50 iterations)'
+      );
+    });
+
+    it('should count iterations per loop entry', () => {
+      const code = `function count() {
+  let total = 0;
+  for (let i = 0; i < 40; i++) {
+    for (let j = 0; j < 40; j++) total++;
+  }
+  return total;
+}`;
+      const result = addLoopProtection(code, { maxIterations: 50 });
+      expect(new Function(`${result}\nreturn count();`)()).toBe(1600);
     });
   });
 
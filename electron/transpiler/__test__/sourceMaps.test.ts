@@ -0,0 +1,73 @@
+/**
+ * Tests for source maps from instrumented and transpiled code back to the
+ * editor
+ * @vitest-environment node
+ */
+import vm from 'vm';
+import { describe, it, expect } from 'vitest';
+import { instrumentCode } from '../codeTransforms.js';
+import { transformCode } from '../swcTranspiler.js';
+import { SourceMapper } from '../sourceMaps.js';
+
+function runAndCatch(code: string, filename: string): string {
+  try {
+    new vm.Script(code, { filename }).runInNewContext({
+      debug: () => undefined,
+      exports: {},
+    });
+  } catch (error) {
+    return (error as Error).stack ?? '';
+  }
+  throw new Error('expected the code to throw');
+}
+
+function withInlineMap(code: string, map: object): string {
+  const base64 = Buffer.from(JSON.stringify(map)).toString('base64');
+  return `${code}\n//# sourceMappingURL=data:application/json;base64,${base64}`;
+}
+
+describe('Source maps', () => {
+  it('map instrumented code back past inserted debug calls', () => {
+    const source = "const o = {};\n  console.log('é', o.a.b);";
+    const { code, map } = instrumentCode(source);
+    expect(code).toContain("debug(2, 'é', o.a.b)");
+
+    const program = withInlineMap(code, map!);
+    const stack = runAndCatch(program, 'program.js');
+    const mapper = SourceMapper.fromInlineComment(program, 'program.js');
+
+    expect(mapper?.locate(stack)).toEqual({
+      line: 2,
+      column: source.split('\n')[1].indexOf('.b') + 2,
+    });
+  });
+
+  it('point errors in transpiled TypeScript at the editor line', () => {
+    const code = transformCode(
+      `interface Options {
+  retries: number;
+}
+console.log('starting');
+function fail(options: Options): never {
+  throw new Error(\`failed after \${options.retries}\`);
+}
+fail({ retries: 3 });`,
+      { loopProtection: true }
+    );
+
+    const stack = runAndCatch(code, 'usercode.js');
+    const mapper = SourceMapper.fromInlineComment(code, 'usercode.js');
+
+    expect(mapper?.locate(stack)).toEqual({ line: 6, column: 9 });
+    expect(mapper?.remapStack(stack)).toMatch(/at fail \(usercode\.js:6:9\)/);
+    expect(mapper?.remapStack(stack)).toContain('usercode.js:8:');
+  });
+
+  it('account for lines the runner puts in front of the code', () => {
+    const code = transformCode(`const n = 1;\nnull.x;`);
+    const stack = runAndCatch(`(() => {\n${code}\n})()`, 'wrapped.js');
+    const mapper = SourceMapper.fromInlineComment(code, 'wrapped.js', 1);
+
+    expect(mapper?.locate(stack)?.line).toBe(2);
+  });
+});
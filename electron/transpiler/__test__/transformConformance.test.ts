@@ -0,0 +1,145 @@
+/**
+ * Conformance tests for the transform pipeline: every fixture goes through
+ * the main-process entry point and the transpiler worker, which must agree
+ * on the output and on what it prints when run.
+ * @vitest-environment node
+ */
+import { describe, it, expect } from 'vitest';
+import { transformCode, type TransformOptions } from '../swcTranspiler.js';
+import { transpileRequest } from '../../workers/swcTranspilerWorker.js';
+
+interface Fixture {
+  name: string;
+  code: string;
+  options?: TransformOptions;
+  /** `[line, ...values]` for every debug call, in order */
+  expected?: unknown[][];
+  /** Message the run is expected to throw */
+  throws?: string;
+}
+
+const FIXTURES: Fixture[] = [
+  {
+    name: 'leaves console text inside strings alone',
+    code: `const s = "console.log('no')";\nconsole.log(s);`,
+    expected: [[2, "console.log('no')"]],
+  },
+  {
+    name: 'shows top-level expressions on their own line',
+    code: `const a = 2;\na * 3;\n\na + 1;`,
+    expected: [
+      [2, 6],
+      [4, 3],
+    ],
+  },
+  {
+    name: 'keeps source lines across TypeScript-only syntax',
+    code: `interface Point {\n  x: number;\n}\nconst p: Point = { x: 1 };\np.x;`,
+    expected: [[5, 1]],
+  },
+  {
+    name: 'keeps multi-line templates in one call',
+    code: 'const t = `a\nb`;\nconsole.log(t);\nt.length;',
+    expected: [
+      [3, 'a\nb'],
+      [4, 3],
+    ],
+  },
+  {
+    name: 'only wraps expressions at the top level',
+    code: `function f(n) {\n  n + 1;\n  console.info('in', n);\n  return n;\n}\nf(4);`,
+    expected: [
+      [3, 'in', 4],
+      [6, 4],
+    ],
+  },
+  {
+    name: 'guards labelled loops without breaking the label',
+    code: `outer: for (let i = 0; i < 3; i++) {\n  for (const j of [1, 2]) {\n    if (j === 2) continue outer;\n    console.log(i, j);\n  }\n}`,
+    options: { loopProtection: true },
+    expected: [
+      [4, 0, 1],
+      [4, 1, 1],
+      [4, 2, 1],
+    ],
+  },
+  {
+    name: 'applies magic comments before instrumenting',
+    code: `const x = 5; //?\nx * 2; //?`,
+    options: { magicComments: true, showTopLevelResults: false },
+    expected: [
+      [1, 5],
+      [2, 10],
+    ],
+  },
+  {
+    name: 'skips result wrapping when disabled',
+    code: `1 + 1;\nconsole.warn('w');`,
+    options: { showTopLevelResults: false },
+    expected: [[2, 'w']],
+  },
+  {
+    name: 'stops infinite loops',
+    code: `let n = 0;\nwhile (true) n++;`,
+    options: { loopProtection: true, showTopLevelResults: false },
+    throws: 'Loop limit exceeded',
+  },
+  {
+    name: 'does not wrap directives',
+    code: `'use strict';\nconst v = 'value';\nv;`,
+    expected: [[3, 'value']],
+  },
+  {
+    name: 'uses the configured debug function',
+    code: `console.log('hi');\n40 + 2;`,
+    options: { debugFunctionName: '__jsDebug' },
+    expected: [
+      [1, 'hi'],
+      [2, 42],
+    ],
+  },
+];
+
+function run(code: string, debugFunctionName = 'debug'): unknown[][] {
+  const calls: unknown[][] = [];
+  const debug = (...args: unknown[]) => calls.push(args);
+  new Function(debugFunctionName, 'exports', code)(debug, {});
+  return calls;
+}
+
+describe('Transform pipeline conformance', () => {
+  it.each(FIXTURES)('$name', ({ code, options = {}, expected, throws }) => {
+    const main = transformCode(code, options);
+    const worker = transpileRequest({
+      type: 'transpile',
+      id: 'conformance',
+      code,
+      options,
+    });
+
+    expect(worker.type).toBe('result');
+    expect(worker.type === 'result' && worker.code).toBe(main);
+
+    if (throws) {
+      expect(() => run(main, options.debugFunctionName)).toThrow(throws);
+    } else {
+      expect(run(main, options.debugFunctionName)).toEqual(expected);
+    }
+  });
+
+  it('does not serve a cached result for different options', () => {
+    const code = `console.log(1);`;
+    const request = { type: 'transpile', id: 'cache', code } as const;
+
+    const plain = transpileRequest({ ...request, options: {} });
+    const renamed = transpileRequest({
+      ...request,
+      options: { debugFunctionName: '__jsDebug' },
+    });
+
+    expect(plain.type === 'result' && plain.code).toContain('debug(1, 1)');
+    expect(renamed.type === 'result' && renamed.code).toContain(
+      '__jsDebug(1, 1)'
+    );
+  });
+});
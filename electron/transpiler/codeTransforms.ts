@@ -3,12 +3,24 @@
  *
  * Common code transformation utilities used by both TypeScript and SWC transpilers.
  * This module extracts duplicated logic to maintain DRY principles.
+ *
+ * Console rewriting, loop protection and result wrapping walk the SWC AST
+ * of the source as written, so strings, template literals, labels and
+ * nested functions are told apart by the parser and every `debug` call
+ * carries the line the user sees in the editor.
  */
 
+import { createHash } from 'crypto';
+import { parseSync, type Span } from '@swc/core';
+import { SourceMapBuilder, type RawSourceMap } from './sourceMaps.js';
+
 // ============================================================================
 // TYPES
 // ============================================================================
 
+/** How the sandbox runs a snippet: classic script or native ES module */
+export type ModuleFormat = 'commonjs' | 'esm';
+
 export interface TransformOptions {
   showTopLevelResults?: boolean;
   loopProtection?: boolean;
@@ -24,395 +36,756 @@ export interface TransformOptions {
   debugFunctionName?: string;
   /** Enable using declarations (explicit resource management) */
   usingDeclarations?: boolean;
+  /** Keep `import`/`export` for the native ESM path (default: commonjs) */
+  moduleFormat?: ModuleFormat;
+  /** Source file name reported in transpilation errors */
+  filename?: string;
+  /** Guard top-level statements for a REPL session (scripts only) */
+  session?: boolean;
+}
+
+/** Name the editor program goes by in source maps and SWC errors */
+export const DEFAULT_SOURCE_NAME = 'index.tsx';
+
+/** Instrumented code with the map back to the text it came from */
+export interface InstrumentedCode {
+  code: string;
+  /** Absent when nothing was instrumented */
+  map?: RawSourceMap;
 }
 
 // ============================================================================
-// PARENTHESIS MATCHING
+// MODULE DETECTION
 // ============================================================================
 
+// Shared with the renderer, which runs the cells of module buffers together
+export { usesModuleSyntax } from '@cheesejs/core';
+
+// ============================================================================
+// AST INSTRUMENTATION
+// ============================================================================
+
+export interface AstNode {
+  type: string;
+  span: Span;
+  [key: string]: unknown;
+}
+
+/** Text inserted at, or replacing, a byte range of the source */
+interface SourceEdit {
+  start: number;
+  end: number;
+  text: string;
+  /** Tie-breaker at equal offsets: openers ascend, closers descend */
+  order: number;
+}
+
+/** Adds edits for one instrumentation concern while the AST is walked */
+export type InstrumentationPass = (
+  node: AstNode,
+  parent: AstNode | null,
+  source: InstrumentedSource
+) => void;
+
+function isAstNode(value: unknown): value is AstNode {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as AstNode).type === 'string'
+  );
+}
+
+/** Visits every node below `value`, parents before their children */
+function walk(
+  value: unknown,
+  visit: (node: AstNode, parent: AstNode | null) => void,
+  parent: AstNode | null = null
+): void {
+  if (Array.isArray(value)) {
+    for (const item of value) walk(item, visit, parent);
+    return;
+  }
+  if (typeof value !== 'object' || value === null) return;
+
+  // Argument lists hold `{ spread, expression }` wrappers without a type
+  const node = isAstNode(value) ? value : null;
+  if (node) visit(node, parent);
+  for (const [key, child] of Object.entries(value)) {
+    if (key !== 'span') walk(child, visit, node ?? parent);
+  }
+}
+
 /**
- * Find the matching closing parenthesis
- * Handles nested parentheses and string literals
+ * Source being instrumented. Edits are collected against the SWC AST and
+ * applied to the original text in one go, so everything the user wrote,
+ * including line breaks, stays where it was.
  */
-export function findMatchingParen(code: string, startIndex: number): number {
-  let depth = 1;
-  let i = startIndex;
-
-  while (i < code.length && depth > 0) {
-    const char = code[i];
-
-    // Check for comments first
-    if (char === '/') {
-      const nextChar = code[i + 1];
-      if (nextChar === '/') {
-        // Single line comment: skip until newline or end of string
-        i += 2;
-        while (i < code.length && code[i] !== '\n') {
-          i++;
-        }
-        continue;
-      } else if (nextChar === '*') {
-        // Block comment: skip until */
-        i += 2;
-        while (i < code.length - 1) {
-          if (code[i] === '*' && code[i + 1] === '/') {
-            i += 2;
-            break;
-          }
-          i++;
-        }
-        continue;
-      }
+export class InstrumentedSource {
+  readonly program: AstNode | null;
+  private readonly bytes: Buffer;
+  private readonly lineStarts: number[] = [0];
+  private readonly edits: SourceEdit[] = [];
+  private base = 0;
+  private nextOrder = 1;
+  private nextCounter = 0;
+
+  constructor(
+    private readonly code: string,
+    options: TransformOptions
+  ) {
+    // SWC spans are UTF-8 byte offsets
+    this.bytes = Buffer.from(code);
+    for (let i = 0; i < this.bytes.length; i++) {
+      if (this.bytes[i] === 0x0a) this.lineStarts.push(i + 1);
     }
+    this.program = this.parse(options);
+  }
 
-    if (char === '(') depth++;
-    else if (char === ')') depth--;
-
-    // Skip string literals
-    if (char === '"' || char === "'" || char === '`') {
-      const quote = char;
-      i++;
-      while (i < code.length && code[i] !== quote) {
-        if (code[i] === '\\') i++; // Skip escaped chars
-        i++;
-      }
+  /**
+   * Spans keep growing across `parseSync` calls, so a leading `;` marks
+   * where this source starts. Unparseable code is left alone; the
+   * transpiler reports the syntax error.
+   */
+  private parse(options: TransformOptions): AstNode | null {
+    const hashbang = /^#![^\n]*/.exec(this.code)?.[0] ?? '';
+    const blanked = ' '.repeat(Buffer.byteLength(hashbang));
+    const source = blanked + this.code.slice(hashbang.length);
+    try {
+      const program = parseSync(`;${source}`, {
+        syntax: 'typescript',
+        tsx: options.jsx !== false,
+        decorators: options.experimentalDecorators ?? true,
+        dynamicImport: true,
+      });
+      this.base = program.span.start + 1;
+      return program as unknown as AstNode;
+    } catch {
+      return null;
     }
-    i++;
   }
 
-  return depth === 0 ? i - 1 : -1;
-}
+  start(node: { span: Span }): number {
+    return node.span.start - this.base;
+  }
 
-// ============================================================================
-// CONSOLE TO DEBUG TRANSFORMATION
-// ============================================================================
+  end(node: { span: Span }): number {
+    return node.span.end - this.base;
+  }
 
-/**
- * Transform
- * Uses a parser-based approach to handle nested parentheses
- */
-export function transformConsoleTodebug(code: string): string {
-  const lines = code.split('\n');
-  const result: string[] = [];
+  /** 1-based line of the node in the original source */
+  line(node: { span: Span }): number {
+    return this.lineIndex(this.start(node)) + 1;
+  }
 
-  for (let i = 0; i < lines.length; i++) {
-    let line = lines[i];
-    const lineNumber = i + 1;
+  private lineIndex(offset: number): number {
+    let low = 0;
+    let high = this.lineStarts.length - 1;
+    while (low < high) {
+      const mid = (low + high + 1) >> 1;
+      if (this.lineStarts[mid] <= offset) low = mid;
+      else high = mid - 1;
+    }
+    return low;
+  }
 
-    // Pattern to find console.xxx( start positions
-    const consoleStartRegex = /\bconsole\.(log|warn|error|info|debug)\s*\(/g;
-    let match;
-    let offset = 0;
+  /** 0-based line and UTF-16 column of a byte offset, for the source map */
+  private position(offset: number): [line: number, column: number] {
+    const line = this.lineIndex(offset);
+    const lineStart = this.lineStarts[line];
+    return [line, this.bytes.toString('utf8', lineStart, offset).length];
+  }
 
-    while ((match = consoleStartRegex.exec(lines[i])) !== null) {
-      const fullMatchStart = match.index + offset;
-      const openParenPos = fullMatchStart + match[0].length - 1;
+  /** `closing` inserts nest inside earlier ones at the same offset */
+  insert(offset: number, text: string, closing = false): void {
+    const order = closing ? -this.nextOrder++ : this.nextOrder++;
+    this.edits.push({ start: offset, end: offset, text, order });
+  }
 
-      // Find the matching close paren in the current line
-      const closeParenPos = findMatchingParen(line, openParenPos + 1);
+  /** Source text of the node */
+  text(node: { span: Span }): string {
+    return this.bytes.toString('utf8', this.start(node), this.end(node));
+  }
 
-      if (closeParenPos !== -1) {
-        const args = line.slice(openParenPos + 1, closeParenPos).trim();
-        const before = line.slice(0, fullMatchStart);
-        const after = line.slice(closeParenPos + 1);
+  /** Byte offset of the first `text` at or after `from` */
+  find(text: string, from: number): number {
+    return this.bytes.indexOf(text, from);
+  }
 
-        let replacement;
-        // Check if args is effectively empty (only comments/whitespace)
-        const strippedArgs = args
-          .replace(/\/\*[\s\S]*?\*\/|\/\/.*$/gm, '')
-          .trim();
+  replace(start: number, end: number, text: string): void {
+    this.edits.push({ start, end, text, order: this.nextOrder++ });
+  }
 
-        if (strippedArgs === '') {
-          replacement = `debug(${lineNumber})`;
-        } else {
-          replacement = `debug(${lineNumber}, ${args})`;
-        }
+  createCounter(): string {
+    return `__loopCounter${this.nextCounter++}`;
+  }
 
-        line = before + replacement + after;
-        offset += replacement.length - (closeParenPos + 1 - fullMatchStart);
-      }
+  /** Applies the edits, mapping every inserted text to where it went */
+  apply(sourceName: string): InstrumentedCode {
+    if (this.edits.length === 0) return { code: this.code };
+
+    const edits = [...this.edits].sort(
+      (a, b) =>
+        a.start - b.start ||
+        a.end - a.start - (b.end - b.start) ||
+        a.order - b.order
+    );
+    const map = new SourceMapBuilder();
+    const chunks: string[] = [];
+    const copy = (start: number, end: number) => {
+      if (start >= end) return;
+      const text = this.bytes.toString('utf8', start, end);
+      map.copy(text, ...this.position(start));
+      chunks.push(text);
+    };
+
+    let cursor = 0;
+    for (const edit of edits) {
+      copy(cursor, edit.start);
+      map.insert(edit.text, ...this.position(edit.start));
+      chunks.push(edit.text);
+      cursor = Math.max(cursor, edit.end);
     }
+    copy(cursor, this.bytes.length);
+    return { code: chunks.join(''), map: map.toJSON(sourceName) };
+  }
+}
+
+/** Parses `code` once and runs every pass over the same AST */
+function instrumentSource(
+  code: string,
+  passes: InstrumentationPass[],
+  options: TransformOptions = {}
+): InstrumentedCode {
+  const source = new InstrumentedSource(code, options);
+  if (!source.program || passes.length === 0) return { code };
+
+  walk(source.program, (node, parent) => {
+    for (const pass of passes) pass(node, parent, source);
+  });
+  return source.apply(options.filename ?? DEFAULT_SOURCE_NAME);
+}
 
-    result.push(line);
+function unwrapParens(node: AstNode): AstNode {
+  let current = node;
+  while (current.type === 'ParenthesisExpression') {
+    current = current.expression as AstNode;
   }
+  return current;
+}
 
-  return result.join('\n');
+function isIdentifier(node: unknown, name?: string): boolean {
+  return (
+    isAstNode(node) &&
+    node.type === 'Identifier' &&
+    (name === undefined || node.value === name)
+  );
+}
+
+/** Name of the method in `object.method(...)`, if the callee is one */
+function getMemberCall(
+  node: AstNode
+): { object: AstNode; method: string } | null {
+  if (node.type !== 'CallExpression') return null;
+  const callee = node.callee as AstNode;
+  if (callee.type !== 'MemberExpression') return null;
+  const property = callee.property as AstNode;
+  if (property.type !== 'Identifier') return null;
+  return {
+    object: callee.object as AstNode,
+    method: property.value as string,
+  };
+}
+
+/** Helper the code worker defines for `//bench` lines */
+export const BENCH_FUNCTION_NAME = '__bench';
+
+// Test runner and benchmark globals, which queue work for after the
+// program rather than return values
+const REGISTRATION_NAMES = new Set([
+  'describe',
+  'it',
+  'test',
+  'beforeAll',
+  'afterAll',
+  'beforeEach',
+  'afterEach',
+  'bench',
+  BENCH_FUNCTION_NAME,
+]);
+
+/** `it(...)`, `describe.skip(...)`, `bench(...)` and the like */
+function isRegistration(node: AstNode): boolean {
+  if (node.type !== 'CallExpression') return false;
+  const target = getMemberCall(node)?.object ?? (node.callee as AstNode);
+  return (
+    isIdentifier(target) && REGISTRATION_NAMES.has(target.value as string)
+  );
 }
 
 // ============================================================================
-// LOOP PROTECTION
+// CONSOLE TO DEBUG TRANSFORMATION
 // ============================================================================
 
+const DEBUG_CONSOLE_METHODS = new Set([
+  'log',
+  'warn',
+  'error',
+  'info',
+  'debug',
+]);
+
+function isConsoleCall(node: AstNode): boolean {
+  const call = getMemberCall(node);
+  return !!call && isIdentifier(call.object, 'console');
+}
+
+function getDebugFunctionName(options: TransformOptions): string {
+  return options.debugFunctionName ?? 'debug';
+}
+
+/** `console.log(a, b)` becomes `debug(<line>, a, b)` */
+function createConsolePass(debugFn: string): InstrumentationPass {
+  return (node, _parent, source) => {
+    const call = getMemberCall(node);
+    if (
+      !call ||
+      !isIdentifier(call.object, 'console') ||
+      !DEBUG_CONSOLE_METHODS.has(call.method)
+    ) {
+      return;
+    }
+
+    // Tag the call right after its `(`, keeping comments and type arguments
+    const callee = node.callee as AstNode;
+    const typeArguments = node.typeArguments as AstNode | null | undefined;
+    const openParen = source.find('(', source.end(typeArguments ?? callee));
+    const hasArgs = (node.arguments as unknown[]).length > 0;
+    const line = source.line(node);
+    source.replace(source.start(callee), source.end(callee), debugFn);
+    source.insert(openParen + 1, hasArgs ? `${line}, ` : `${line}`);
+  };
+}
+
 /**
- * Add loop protection to prevent infinite loops
- * Includes cancellation checkpoints for cooperative cancellation
+ * Transform console.log/warn/error/info/debug calls into debug calls
+ * tagged with their source line
  */
-export function addLoopProtection(
+export function transformConsoleTodebug(
   code: string,
-  options: {
-    maxIterations?: number;
-    cancellationCheckInterval?: number;
-    includeCancellationCheck?: boolean;
-  } = {}
+  options: TransformOptions = {}
 ): string {
-  const {
-    maxIterations = 10000,
-    cancellationCheckInterval = 100,
-    includeCancellationCheck = true,
-  } = options;
-
-  let loopCounter = 0;
+  const pass = createConsolePass(getDebugFunctionName(options));
+  return instrumentSource(code, [pass], options).code;
+}
 
-  // Match while, for, do-while loops
-  const loopPattern = /(while\s*\([^)]*\)\s*\{|for\s*\([^)]*\)\s*\{|do\s*\{)/g;
+// ============================================================================
+// LOOP PROTECTION
+// ============================================================================
 
-  return code.replace(loopPattern, (match) => {
-    const counterVar = `__loopCounter${loopCounter++}`;
+export interface LoopProtectionOptions {
+  maxIterations?: number;
+  cancellationCheckInterval?: number;
+  includeCancellationCheck?: boolean;
+}
 
-    let checkCode = `let ${counterVar} = 0;
-if (++${counterVar} > ${maxIterations}) throw new Error("Loop limit exceeded (${maxIterations} iterations)");`;
+const LOOP_TYPES = new Set([
+  'ForStatement',
+  'ForInStatement',
+  'ForOfStatement',
+  'WhileStatement',
+  'DoWhileStatement',
+]);
+
+function createLoopGuardPass({
+  maxIterations = 1_000_000,
+  cancellationCheckInterval = 100,
+  includeCancellationCheck = true,
+}: LoopProtectionOptions = {}): InstrumentationPass {
+  // Labels move with their loop, so the counter goes in front of them
+  const labelled = new Map<AstNode, AstNode>();
+
+  return (node, _parent, source) => {
+    if (node.type === 'LabeledStatement') {
+      labelled.set(node.body as AstNode, labelled.get(node) ?? node);
+      return;
+    }
+    if (!LOOP_TYPES.has(node.type)) return;
 
-    // Add cancellation checkpoint if enabled
+    const counter = source.createCounter();
+    let guard = `if (++${counter} > ${maxIterations}) throw new Error("Loop limit exceeded (${maxIterations} iterations)");`;
     if (includeCancellationCheck) {
-      checkCode += `
-if (${counterVar} % ${cancellationCheckInterval} === 0 && typeof __checkCancellation__ !== 'undefined' && __checkCancellation__()) throw new Error("Execution cancelled");`;
+      guard += ` if (${counter} % ${cancellationCheckInterval} === 0 && typeof __checkCancellation__ !== 'undefined' && __checkCancellation__()) throw new Error("Execution cancelled");`;
     }
 
-    // Insert the check after the opening brace
-    const braceIndex = match.lastIndexOf('{');
-    return match.slice(0, braceIndex + 1) + '\n' + checkCode + '\n';
-  });
+    // The counter restarts each time the loop is entered, not per iteration
+    const statement = labelled.get(node) ?? node;
+    source.insert(source.start(statement), `{ let ${counter} = 0; `);
+    source.insert(source.end(statement), ' }', true);
+
+    const body = node.body as AstNode;
+    if (body.type === 'BlockStatement') {
+      source.insert(source.start(body) + 1, ` ${guard}`);
+    } else {
+      source.insert(source.start(body), `{ ${guard} `);
+      source.insert(source.end(body), ' }', true);
+    }
+  };
+}
+
+/**
+ * Add loop protection to prevent infinite loops
+ * Includes cancellation checkpoints for cooperative cancellation
+ */
+export function addLoopProtection(
+  code: string,
+  options: LoopProtectionOptions = {},
+  transformOptions: TransformOptions = {}
+): string {
+  return instrumentSource(
+    code,
+    [createLoopGuardPass(options)],
+    transformOptions
+  ).code;
 }
 
 // ============================================================================
 // EXPRESSION WRAPPING
 // ============================================================================
 
-/**
- * Patterns that should be skipped when wrapping top-level expressions
- */
-const SKIP_PATTERNS = [
-  'import ',
-  'export ',
-  'const ',
-  'let ',
-  'var ',
-  'function ',
-  'async ',
-  'class ',
-  'interface ',
-  'type ',
-  'enum ',
-  'if ',
-  'if(',
-  'else ',
-  'else{',
-  'for ',
-  'for(',
-  'while ',
-  'while(',
-  'switch ',
-  'try ',
-  'try{',
-  'catch ',
-  'catch(',
-  'finally ',
-  'finally{',
-  'return ',
-  'return;',
-  'throw ',
-  'break',
-  'continue',
-  '{',
-  '}',
-  '});',
-  ']);',
-  ']',
-  '[',
-  '(',
-  ')',
-  'debug(',
-  'await debug(',
-  'yield ',
-  '.',
-  '//',
-  '/*',
-  '*',
-];
+const PROMISE_CHAIN_METHODS = new Set(['then', 'catch', 'finally']);
 
 /**
- * Check if a line should be skipped based on skip patterns
+ * Top-level expressions whose value is not worth showing: assignments,
+ * calls that already print, `void`, IIFEs and promise chains
  */
-function shouldSkipLine(trimmed: string): boolean {
-  // Skip empty lines
-  if (!trimmed) return true;
-
-  // Check skip patterns
-  for (const pattern of SKIP_PATTERNS) {
-    if (trimmed.startsWith(pattern)) return true;
+function shouldShowResult(expression: AstNode, debugFn: string): boolean {
+  const node = unwrapParens(expression);
+  switch (node.type) {
+    case 'AssignmentExpression':
+      return false;
+    case 'UnaryExpression':
+      return node.operator !== 'void';
+    case 'AwaitExpression':
+      return shouldShowResult(node.argument as AstNode, debugFn);
+    case 'CallExpression': {
+      const callee = unwrapParens(node.callee as AstNode);
+      if (
+        isIdentifier(callee, debugFn) ||
+        callee.type === 'FunctionExpression' ||
+        callee.type === 'ArrowFunctionExpression' ||
+        isRegistration(node)
+      ) {
+        return false;
+      }
+      const call = getMemberCall(node);
+      return !(
+        call &&
+        (isConsoleCall(node) || PROMISE_CHAIN_METHODS.has(call.method))
+      );
+    }
+    default:
+      return true;
   }
+}
 
-  // Skip closing brackets with just semicolons
-  if (/^[\])}]+;?$/.test(trimmed)) return true;
-
-  // Skip lines that are just commas or array/object continuations
-  if (/^[,\]})]+$/.test(trimmed)) return true;
+function createResultsPass(debugFn: string): InstrumentationPass {
+  return (node, parent, source) => {
+    if (
+      node.type !== 'ExpressionStatement' ||
+      !parent ||
+      parent !== source.program
+    ) {
+      return;
+    }
 
-  return false;
+    const expression = node.expression as AstNode;
+    const body = parent.body as AstNode[];
+    // body[0] is the `;` added in front of the source before parsing
+    const isDirective =
+      expression.type === 'StringLiteral' &&
+      body.slice(1, body.indexOf(node)).every(isDirectiveStatement);
+    if (isDirective || !shouldShowResult(expression, debugFn)) return;
+
+    // A bare sequence would turn into extra debug arguments
+    const isSequence = expression.type === 'SequenceExpression';
+    source.insert(
+      source.start(expression),
+      `${debugFn}(${source.line(node)}, ${isSequence ? '(' : ''}`
+    );
+    source.insert(source.end(expression), isSequence ? '))' : ')', true);
+  };
 }
 
 /**
- * Count brace depth change in a line (considering strings and template literals)
- * Returns the net change in depth for the line
+ * Wrap top-level expressions in debug calls tagged with the line they
+ * start on
  */
-function countBraceDepthChange(
-  line: string,
-  currentInsideTemplateLiteral: boolean
-): { depthChange: number; insideTemplateLiteral: boolean } {
-  let depthChange = 0;
-  let insideTemplateLiteral = currentInsideTemplateLiteral;
-  let i = 0;
-
-  while (i < line.length) {
-    const char = line[i];
-
-    // Skip escaped characters
-    if (char === '\\' && i + 1 < line.length) {
-      i += 2;
-      continue;
-    }
+export function wrapTopLevelExpressions(
+  code: string,
+  options: TransformOptions = {}
+): string {
+  const pass = createResultsPass(getDebugFunctionName(options));
+  return instrumentSource(code, [pass], options).code;
+}
 
-    // Handle template literals
-    if (char === '`') {
-      insideTemplateLiteral = !insideTemplateLiteral;
-      i++;
-      continue;
-    }
+// ============================================================================
+// REPL SESSIONS
+// ============================================================================
 
-    // Skip content inside template literals
-    if (insideTemplateLiteral) {
-      i++;
-      continue;
-    }
+/** Helper the code worker defines for runs in a REPL session */
+export const SESSION_RUNTIME_NAME = '__session';
+
+// Declarations without side effects, which every run declares again
+const REDECLARED_STATEMENT_TYPES = new Set([
+  'EmptyStatement',
+  'FunctionDeclaration',
+  'TsInterfaceDeclaration',
+  'TsTypeAliasDeclaration',
+  'TsEnumDeclaration',
+  'TsModuleDeclaration',
+  'ImportDeclaration',
+]);
+
+function isDirectiveStatement(statement: AstNode): boolean {
+  return (
+    statement.type === 'ExpressionStatement' &&
+    (statement.expression as AstNode).type === 'StringLiteral'
+  );
+}
 
-    // Skip regular strings
-    if (char === '"' || char === "'") {
-      const quote = char;
-      i++;
-      while (i < line.length) {
-        if (line[i] === '\\' && i + 1 < line.length) {
-          i += 2;
-          continue;
-        }
-        if (line[i] === quote) {
-          break;
-        }
-        i++;
+/** Adds the names a binding pattern declares to `names` */
+function collectBoundNames(pattern: unknown, names: string[]): void {
+  if (!isAstNode(pattern)) return;
+  switch (pattern.type) {
+    case 'Identifier':
+      names.push(pattern.value as string);
+      break;
+    case 'ArrayPattern':
+      for (const element of pattern.elements as unknown[]) {
+        collectBoundNames(element, names);
       }
-      i++;
-      continue;
-    }
-
-    // Count braces
-    if (char === '{') {
-      depthChange++;
-    } else if (char === '}') {
-      depthChange--;
-    }
-
-    i++;
+      break;
+    case 'ObjectPattern':
+      for (const property of pattern.properties as AstNode[]) {
+        collectBoundNames(
+          property.type === 'KeyValuePatternProperty'
+            ? property.value
+            : property.type === 'AssignmentPatternProperty'
+              ? property.key
+              : property,
+          names
+        );
+      }
+      break;
+    case 'AssignmentPattern':
+      collectBoundNames(pattern.left, names);
+      break;
+    case 'RestElement':
+      collectBoundNames(pattern.argument, names);
+      break;
   }
-
-  return { depthChange, insideTemplateLiteral };
 }
 
 /**
- * Wrap top-level expressions in debug calls
+ * Names a top-level declaration keeps across runs, or null when the
+ * statement is not a declaration that can become global assignments
  */
-export function wrapTopLevelExpressions(code: string): string {
-  const lines = code.split('\n');
-  const result: string[] = [];
-  let insideTemplateLiteral = false;
-  let braceDepth = 0; // Track nesting level
-
-  for (let i = 0; i < lines.length; i++) {
-    const line = lines[i];
-    const trimmed = line.trim();
-    const lineNumber = i + 1;
-
-    // Track if we're inside a multi-line template literal
-    const wasInsideTemplateLiteral = insideTemplateLiteral;
-
-    // Calculate brace depth change for this line
-    const { depthChange, insideTemplateLiteral: newInsideTemplateLiteral } =
-      countBraceDepthChange(line, insideTemplateLiteral);
-    insideTemplateLiteral = newInsideTemplateLiteral;
-
-    // Update brace depth BEFORE processing (to handle lines that open a block)
-    const depthAtLineStart = braceDepth;
-    braceDepth += depthChange;
-
-    // Skip if we were inside a template literal at the start of this line
-    if (wasInsideTemplateLiteral) {
-      result.push(line);
-      continue;
+function getPersistedNames(statement: AstNode): string[] | null {
+  if (statement.type === 'VariableDeclaration') {
+    // `using` declarations are disposed at the end of their scope
+    const kind = statement.kind as string;
+    if (statement.declare || !['var', 'let', 'const'].includes(kind)) {
+      return null;
     }
-
-    // Skip if not at top level (inside a function, class, etc.)
-    if (depthAtLineStart > 0) {
-      result.push(line);
-      continue;
+    const names: string[] = [];
+    for (const declarator of statement.declarations as AstNode[]) {
+      collectBoundNames(declarator.id, names);
     }
-
-    // Skip lines based on patterns
-    if (shouldSkipLine(trimmed)) {
-      result.push(line);
-      continue;
+    return names;
+  }
+  if (statement.type === 'ClassDeclaration') {
+    // Class expressions cannot be abstract or take legacy decorators
+    const decorators = statement.decorators as unknown[] | undefined;
+    if (statement.declare || statement.isAbstract || decorators?.length) {
+      return null;
     }
+    return [(statement.identifier as AstNode).value as string];
+  }
+  return null;
+}
 
-    // Skip if it's an assignment
-    if (
-      /^[a-zA-Z_$][a-zA-Z0-9_$]*\s*=\s*/.test(trimmed) &&
-      !trimmed.startsWith('==')
-    ) {
-      result.push(line);
-      continue;
-    }
+/** Console calls and bare names, which only show something */
+function isOutputStatement(statement: AstNode): boolean {
+  if (statement.type !== 'ExpressionStatement') return false;
+  const expression = unwrapParens(statement.expression as AstNode);
+  return (
+    isConsoleCall(expression) ||
+    expression.type === 'Identifier' ||
+    expression.type === 'MemberExpression'
+  );
+}
 
-    // Check if next line starts with . (method chaining) - don't wrap this line
-    const nextLine = i + 1 < lines.length ? lines[i + 1].trim() : '';
-    if (nextLine.startsWith('.')) {
-      result.push(line);
-      continue;
-    }
+/** Whether a top-level statement can be skipped by a later run */
+function isGuardedStatement(statement: AstNode): boolean {
+  if (REDECLARED_STATEMENT_TYPES.has(statement.type)) return false;
+  // Results are cleared before every run, so output is shown again
+  if (isOutputStatement(statement)) return false;
+  // A run registers its tests and benchmarks again every time
+  if (
+    statement.type === 'ExpressionStatement' &&
+    isRegistration(statement.expression as AstNode)
+  ) {
+    return false;
+  }
+  if (
+    statement.type === 'VariableDeclaration' ||
+    statement.type === 'ClassDeclaration'
+  ) {
+    return getPersistedNames(statement) !== null;
+  }
+  return true;
+}
 
-    // Skip if this line starts a template literal that continues to the next line
-    if (insideTemplateLiteral) {
-      result.push(line);
-      continue;
-    }
+/** `const a: T = 1, { b } = o;` becomes `;(a = 1, { b } = o);` */
+function rewriteDeclaration(
+  statement: AstNode,
+  source: InstrumentedSource
+): void {
+  if (statement.type === 'ClassDeclaration') {
+    const name = (statement.identifier as AstNode).value as string;
+    source.insert(source.start(statement), `;${name} = `);
+    return;
+  }
 
-    // Skip if this line changes depth (opens or closes a block)
-    if (depthChange !== 0) {
-      result.push(line);
-      continue;
+  const declarators = statement.declarations as AstNode[];
+  const last = declarators[declarators.length - 1];
+  source.replace(source.start(statement), source.start(declarators[0]), ';(');
+  // Later closing inserts land first, so `= undefined` comes before `)`
+  source.insert(source.end(last), ')', true);
+  for (const declarator of declarators) {
+    const annotation = (declarator.id as AstNode).typeAnnotation;
+    if (isAstNode(annotation)) {
+      source.replace(source.start(annotation), source.end(annotation), '');
     }
-
-    // Wrap simple expressions that end with semicolon on a single line
-    if (
-      trimmed.endsWith(';') &&
-      !trimmed.includes('.then(') &&
-      !trimmed.includes('.catch(')
-    ) {
-      const expr = trimmed.slice(0, -1);
-      const indent = line.match(/^\s*/)?.[0] || '';
-      result.push(`${indent}debug(${lineNumber}, ${expr});`);
-    } else {
-      result.push(line);
+    if (!declarator.init) {
+      source.insert(source.end(declarator), ' = undefined', true);
     }
   }
+}
 
-  return result.join('\n');
+/**
+ * Keys top-level statements by their text; repeated statements are told
+ * apart by how many identical ones come before them
+ */
+function getStatementKeys(
+  statements: AstNode[],
+  source: InstrumentedSource
+): string[] {
+  const seen = new Map<string, number>();
+  return statements.map((statement) => {
+    const hash = createHash('sha1')
+      .update(source.text(statement))
+      .digest('hex')
+      .slice(0, 16);
+    const occurrence = seen.get(hash) ?? 0;
+    seen.set(hash, occurrence + 1);
+    return occurrence > 0 ? `${hash}-${occurrence}` : hash;
+  });
+}
+
+/** Identifiers mentioned anywhere in `node`, property names included */
+function collectIdentifiers(node: AstNode): Set<string> {
+  const names = new Set<string>();
+  walk(node, (child) => {
+    if (child.type === 'Identifier') names.add(child.value as string);
+  });
+  return names;
+}
+
+/**
+ * Index of the first guarded statement that mentions each function,
+ * directly or through the other functions, or -1 when none does
+ */
+function getFirstCallers(functions: AstNode[], guarded: AstNode[]): number[] {
+  const statementNames = guarded.map(collectIdentifiers);
+  const declared = functions.map((declaration) => ({
+    name: (declaration.identifier as AstNode).value as string,
+    mentions: collectIdentifiers(declaration),
+  }));
+
+  return declared.map(({ name }) => {
+    const reaching = new Set([name]);
+    for (let grew = true; grew; ) {
+      grew = false;
+      for (const other of declared) {
+        if (
+          !reaching.has(other.name) &&
+          [...reaching].some((reached) => other.mentions.has(reached))
+        ) {
+          reaching.add(other.name);
+          grew = true;
+        }
+      }
+    }
+    return statementNames.findIndex((names) =>
+      [...reaching].some((reached) => names.has(reached))
+    );
+  });
+}
+
+/**
+ * Runs in a REPL session share one context, so every top-level statement
+ * is guarded by its key. The statements before the first new or changed
+ * one are skipped when they completed in an earlier run; from there on
+ * everything runs again, as it may read what changed. A changed function
+ * counts as a change of the first statement that calls it. Declarations
+ * become assignments to globals declared up front, so their values
+ * outlive the run; functions, types and other side-effect-free
+ * declarations are simply declared again, and output is shown every run.
+ */
+function createSessionPass(): InstrumentationPass {
+  return (node, _parent, source) => {
+    if (node !== source.program) return;
+
+    // body[0] is the `;` added in front of the source before parsing
+    const body = (node.body as AstNode[]).slice(1);
+    const firstStatement = body.findIndex(
+      (statement) => !isDirectiveStatement(statement)
+    );
+    if (firstStatement === -1) return;
+    const guarded = body.slice(firstStatement).filter(isGuardedStatement);
+    if (guarded.length === 0) return;
+
+    const keys = getStatementKeys(guarded, source);
+    const names = new Set(
+      guarded.flatMap((statement) => getPersistedNames(statement) ?? [])
+    );
+    const functions = body
+      .slice(firstStatement)
+      .filter((statement) => statement.type === 'FunctionDeclaration');
+    const functionKeys = getStatementKeys(functions, source);
+    const callers = getFirstCallers(functions, guarded)
+      .map((caller, index): [string, number] => [functionKeys[index], caller])
+      .filter(([, caller]) => caller !== -1);
+    source.insert(
+      source.start(body[firstStatement]),
+      `${SESSION_RUNTIME_NAME}.begin(${JSON.stringify([...names])}, ${JSON.stringify(keys)}, ${JSON.stringify(callers)}); `
+    );
+
+    guarded.forEach((statement, index) => {
+      const key = JSON.stringify(keys[index]);
+      source.insert(
+        source.start(statement),
+        `if (${SESSION_RUNTIME_NAME}.run(${key})) { `
+      );
+      source.insert(
+        source.end(statement),
+        `; ${SESSION_RUNTIME_NAME}.done(${key}); }`,
+        true
+      );
+      if (getPersistedNames(statement)) rewriteDeclaration(statement, source);
+    });
+  };
 }
 
 // ============================================================================
@@ -420,10 +793,15 @@ export function wrapTopLevelExpressions(code: string): string {
 // ============================================================================
 
 /**
- * Apply magic comments (//?  or //?) to inject debug calls
+ * Apply magic comments (//?  or //?) to inject debug calls, and `//bench`
+ * comments to benchmark the statement they end
  * This must run BEFORE TypeScript transpilation since TS removes comments
  */
-export function applyMagicComments(code: string): string {
+export function applyMagicComments(
+  code: string,
+  options: TransformOptions = {}
+): string {
+  const debugFn = getDebugFunctionName(options);
   const lines = code.split('\n');
   const result: string[] = [];
 
@@ -431,6 +809,12 @@ export function applyMagicComments(code: string): string {
     const line = lines[i];
     const lineNumber = i + 1;
 
+    const benchLine = applyBenchComment(line);
+    if (benchLine !== null) {
+      result.push(benchLine);
+      continue;
+    }
+
     // Check for magic comment: //? or //?
     const magicMatch = line.match(/(.+?)\/\/\?\s*(.*)$/);
 
@@ -444,15 +828,16 @@ export function applyMagicComments(code: string): string {
 
       if (varMatch) {
         const [, keyword, varName, value] = varMatch;
-        // Output: const x = value; debug(line, x);
-        result.push(`${keyword} ${varName} = ${value};`);
-        result.push(`debug(${lineNumber}, ${varName});`);
+        // Output: const x = value; debug(line, x); on the same line
+        result.push(
+          `${keyword} ${varName} = ${value}; ${debugFn}(${lineNumber}, ${varName});`
+        );
       } else {
         // For expressions: expr //? -> debug(line, expr)
         const exprMatch = codePart.match(/^(.+?);?\s*$/);
         if (exprMatch) {
           const expr = exprMatch[1];
-          result.push(`debug(${lineNumber}, ${expr});`);
+          result.push(`${debugFn}(${lineNumber}, ${expr});`);
         } else {
           result.push(line);
         }
@@ -465,31 +850,118 @@ export function applyMagicComments(code: string): string {
   return result.join('\n');
 }
 
+const MAX_BENCH_NAME_LENGTH = 40;
+
+/**
+ * `stmt; //bench name` keeps the statement and also benchmarks it, named
+ * after the comment or the statement itself. Null for other lines, and
+ * for lines that do not end a statement.
+ */
+function applyBenchComment(line: string): string | null {
+  const match = line.match(/(.+?)\/\/bench(?:\s+(.*?))?\s*$/);
+  if (!match) return null;
+
+  const statement = match[1].trim().replace(/;$/, '');
+  if (!statement || /[{([,]$/.test(statement)) return null;
+
+  let name = match[2]?.trim() || statement;
+  if (name.length > MAX_BENCH_NAME_LENGTH) {
+    name = `${name.slice(0, MAX_BENCH_NAME_LENGTH - 1)}…`;
+  }
+  const arrow = /\bawait\b/.test(statement) ? 'async () =>' : '() =>';
+  return `${statement}; ${BENCH_FUNCTION_NAME}(${JSON.stringify(name)}, ${arrow} { ${statement}; });`;
+}
+
 // ============================================================================
 // FULL TRANSFORM PIPELINE
 // ============================================================================
 
 /**
- * Apply all code transformations after transpilation
+ * One step of the transform pipeline. Source passes rewrite the text in
+ * order; AST passes then share a single parse of the result.
+ */
+export type TransformPass = {
+  name: string;
+  isEnabled: (options: TransformOptions) => boolean;
+} & (
+  | {
+      kind: 'source';
+      transform: (code: string, options: TransformOptions) => string;
+    }
+  | {
+      kind: 'ast';
+      create: (options: TransformOptions) => InstrumentationPass;
+    }
+);
+
+/** Passes every entry point runs, in order */
+export const DEFAULT_TRANSFORM_PASSES: readonly TransformPass[] = [
+  {
+    name: 'magic-comments',
+    kind: 'source',
+    isEnabled: (options) => !!options.magicComments,
+    transform: applyMagicComments,
+  },
+  {
+    // First, so its guards enclose what the other passes insert
+    name: 'repl-session',
+    kind: 'ast',
+    isEnabled: (options) => !!options.session && options.moduleFormat !== 'esm',
+    create: () => createSessionPass(),
+  },
+  {
+    name: 'console-to-debug',
+    kind: 'ast',
+    isEnabled: () => true,
+    create: (options) => createConsolePass(getDebugFunctionName(options)),
+  },
+  {
+    name: 'loop-protection',
+    kind: 'ast',
+    isEnabled: (options) => !!options.loopProtection,
+    create: () => createLoopGuardPass(),
+  },
+  {
+    name: 'top-level-results',
+    kind: 'ast',
+    isEnabled: (options) => options.showTopLevelResults !== false,
+    create: (options) => createResultsPass(getDebugFunctionName(options)),
+  },
+];
+
+/**
+ * Apply all code transformations to the source before transpilation,
+ * parsing it only once
  */
 export function applyCodeTransforms(
   code: string,
-  options: TransformOptions = {}
+  options: TransformOptions = {},
+  passes: readonly TransformPass[] = DEFAULT_TRANSFORM_PASSES
 ): string {
-  let transformed = code;
-
-  // Transform console calls to debug
-  transformed = transformConsoleTodebug(transformed);
+  return instrumentCode(code, options, passes).code;
+}
 
-  // Apply loop protection if enabled
-  if (options.loopProtection) {
-    transformed = addLoopProtection(transformed);
-  }
+/**
+ * Same as `applyCodeTransforms`, also returning the source map of the AST
+ * passes. Source passes must leave every line where it was, so the map
+ * still points at the right editor line.
+ */
+export function instrumentCode(
+  code: string,
+  options: TransformOptions = {},
+  passes: readonly TransformPass[] = DEFAULT_TRANSFORM_PASSES
+): InstrumentedCode {
+  let transformed = code;
+  const astPasses: InstrumentationPass[] = [];
 
-  // Apply expression wrapping if enabled
-  if (options.showTopLevelResults !== false) {
-    transformed = wrapTopLevelExpressions(transformed);
+  for (const pass of passes) {
+    if (!pass.isEnabled(options)) continue;
+    if (pass.kind === 'source') {
+      transformed = pass.transform(transformed, options);
+    } else {
+      astPasses.push(pass.create(options));
+    }
   }
 
-  return transformed;
+  return instrumentSource(transformed, astPasses, options);
 }
@@ -0,0 +1,212 @@
+/**
+ * Source Maps
+ *
+ * Maps transpiled and instrumented code back to the editor. The
+ * instrumentation passes record where every edit came from, SWC composes
+ * that map with its own, and the code worker reads the result from the
+ * inline comment at the end of the code to remap stack frames.
+ */
+
+import {
+  SourceMap,
+  type SourceMapPayload,
+  type SourceMapping,
+} from 'module';
+
+/** Version 3 source map, as SWC reads and writes it */
+export interface RawSourceMap {
+  version: 3;
+  file?: string;
+  sources: string[];
+  names: string[];
+  mappings: string;
+  sourcesContent?: string[];
+}
+
+/** 1-based line and column, as V8 prints them in stack frames */
+export interface SourcePosition {
+  line: number;
+  column: number;
+}
+
+// ============================================================================
+// GENERATION
+// ============================================================================
+
+const BASE64_DIGITS =
+  'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
+
+// Identifiers and numbers, or any other single non-blank character
+const TOKEN_PATTERN = /[\p{L}\p{N}_$]+|\S/gu;
+
+function encodeVlq(value: number): string {
+  let rest = value < 0 ? (-value << 1) | 1 : value << 1;
+  let encoded = '';
+  do {
+    let digit = rest & 0b11111;
+    rest >>>= 5;
+    if (rest > 0) digit |= 0b100000;
+    encoded += BASE64_DIGITS[digit];
+  } while (rest > 0);
+  return encoded;
+}
+
+/**
+ * Builds the mappings of one generated file from one source, written out
+ * in order: copied text keeps its position, inserted text points at the
+ * place it was inserted.
+ */
+export class SourceMapBuilder {
+  private readonly lines: string[][] = [[]];
+  private column = 0;
+  private previousColumn = 0;
+  private previousLine = 0;
+  private previousOriginalColumn = 0;
+
+  /**
+   * Appends text taken from the source at the 0-based `line`/`column`.
+   * Every token gets its own segment, since lookups do not interpolate.
+   */
+  copy(text: string, line: number, column: number): void {
+    text.split('\n').forEach((lineText, index) => {
+      if (index > 0) this.newLine();
+      const startColumn = this.column;
+      const originalColumn = index === 0 ? column : 0;
+      for (const token of lineText.matchAll(TOKEN_PATTERN)) {
+        this.column = startColumn + token.index!;
+        this.addSegment(line + index, originalColumn + token.index!);
+      }
+      this.column = startColumn + lineText.length;
+    });
+  }
+
+  /** Appends generated text that stands for the source at `line`/`column` */
+  insert(text: string, line: number, column: number): void {
+    this.addSegment(line, column);
+    const lastBreak = text.lastIndexOf('\n');
+    if (lastBreak === -1) {
+      this.column += text.length;
+      return;
+    }
+    for (let i = text.indexOf('\n'); i !== -1; i = text.indexOf('\n', i + 1)) {
+      this.newLine();
+      this.addSegment(line, column);
+    }
+    this.column = text.length - lastBreak - 1;
+  }
+
+  toJSON(source: string): RawSourceMap {
+    return {
+      version: 3,
+      sources: [source],
+      names: [],
+      mappings: this.lines.map((segments) => segments.join(',')).join(';'),
+    };
+  }
+
+  private addSegment(line: number, column: number): void {
+    const segments = this.lines[this.lines.length - 1];
+    segments.push(
+      encodeVlq(this.column - this.previousColumn) +
+        encodeVlq(0) +
+        encodeVlq(line - this.previousLine) +
+        encodeVlq(column - this.previousOriginalColumn)
+    );
+    this.previousColumn = this.column;
+    this.previousLine = line;
+    this.previousOriginalColumn = column;
+  }
+
+  private newLine(): void {
+    this.lines.push([]);
+    this.column = 0;
+    this.previousColumn = 0;
+  }
+}
+
+// ============================================================================
+// REMAPPING
+// ============================================================================
+
+const INLINE_SOURCE_MAP_PATTERN =
+  /\n\/\/# sourceMappingURL=data:application\/json;(?:charset=utf-8;)?base64,([A-Za-z0-9+/=]+)\s*$/;
+
+function escapeRegExp(text: string): string {
+  return text.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+}
+
+/**
+ * Maps positions in code run by the worker back to the editor. Stack
+ * frames name the code `file`; `lineOffset` counts the lines the runner
+ * put in front of it.
+ */
+export class SourceMapper {
+  private readonly map: SourceMap;
+  private readonly framePattern: RegExp;
+
+  constructor(
+    payload: RawSourceMap,
+    file: string,
+    private readonly lineOffset = 0
+  ) {
+    this.map = new SourceMap(payload as unknown as SourceMapPayload);
+    this.framePattern = new RegExp(
+      `(${escapeRegExp(file)}):(\\d+):(\\d+)`,
+      'g'
+    );
+  }
+
+  /** Reads the inline map SWC appends to the code, if there is one */
+  static fromInlineComment(
+    code: string,
+    file: string,
+    lineOffset = 0
+  ): SourceMapper | null {
+    const match = INLINE_SOURCE_MAP_PATTERN.exec(code);
+    if (!match) return null;
+    try {
+      const json = Buffer.from(match[1], 'base64').toString('utf8');
+      const payload = JSON.parse(json) as RawSourceMap;
+      return new SourceMapper(payload, file, lineOffset);
+    } catch {
+      return null;
+    }
+  }
+
+  /** Editor position of a 1-based position in the running code */
+  originalPosition(line: number, column: number): SourcePosition | null {
+    const generatedLine = line - 1 - this.lineOffset;
+    if (generatedLine < 0) return null;
+
+    // `findEntry` returns an empty object when nothing maps there
+    const entry: Partial<SourceMapping> = this.map.findEntry(
+      generatedLine,
+      Math.max(column - 1, 0)
+    );
+    if (entry.originalLine === undefined) return null;
+    return {
+      line: entry.originalLine + 1,
+      column: (entry.originalColumn ?? 0) + 1,
+    };
+  }
+
+  /** Rewrites every frame of the code in `stack` to editor positions */
+  remapStack(stack: string): string {
+    return stack.replace(
+      this.framePattern,
+      (frame, file: string, line: string, column: string) => {
+        const position = this.originalPosition(Number(line), Number(column));
+        return position ? `${file}:${position.line}:${position.column}` : frame;
+      }
+    );
+  }
+
+  /** Editor position of the innermost frame of the code in `stack` */
+  locate(stack: string): SourcePosition | undefined {
+    for (const [, , line, column] of stack.matchAll(this.framePattern)) {
+      const position = this.originalPosition(Number(line), Number(column));
+      if (position) return position;
+    }
+    return undefined;
+  }
+}
@@ -27,13 +27,17 @@
 import { transformSync, type Options } from '@swc/core';
 import {
   type TransformOptions,
+  type TransformPass,
+  DEFAULT_SOURCE_NAME,
+  DEFAULT_TRANSFORM_PASSES,
   applyCodeTransforms,
-  applyMagicComments,
+  instrumentCode,
 } from './codeTransforms.js';
+import type { RawSourceMap } from './sourceMaps.js';
 
-// Re-export TransformOptions and applyCodeTransforms for consumers
-export type { TransformOptions };
-export { applyCodeTransforms };
+// Re-export the transform pipeline for consumers
+export type { TransformOptions, TransformPass };
+export { DEFAULT_TRANSFORM_PASSES, applyCodeTransforms };
 
 /**
  * Get SWC target based on options
@@ -46,6 +50,25 @@ function getSwcTarget(options?: TransformOptions): 'es2022' | 'esnext' {
   return 'es2022';
 }
 
+/**
+ * Module output: `require` calls for the classic script path, or untouched
+ * `import`/`export` for the native ESM path
+ */
+function getSwcModuleConfig(options?: TransformOptions): Options['module'] {
+  if (options?.moduleFormat === 'esm') {
+    return { type: 'es6', strict: false, strictMode: false };
+  }
+
+  return {
+    type: 'commonjs',
+    strict: false,
+    strictMode: false,
+    noInterop: false,
+    // Import assertions/attributes interop
+    importInterop: 'swc',
+  };
+}
+
 /**
  * Transpile TypeScript/JSX code using SWC
  *
@@ -58,15 +81,20 @@ function getSwcTarget(options?: TransformOptions): 'es2022' | 'esnext' {
  *
  * Note: SWC 1.3+ automatically supports import attributes/assertions in parsing.
  * The parser handles the 'with' clause without explicit configuration.
+ *
+ * The output ends with an inline source map. Given the map of the
+ * instrumentation passes as `inputSourceMap`, it points past them to the
+ * code as written in the editor.
  */
 export function transpileWithSWC(
   code: string,
-  options?: TransformOptions
+  options?: TransformOptions,
+  inputSourceMap?: RawSourceMap
 ): string {
   const target = getSwcTarget(options);
 
   const swcOptions: Options = {
-    filename: 'index.tsx',
+    filename: options?.filename ?? DEFAULT_SOURCE_NAME,
     jsc: {
       parser: {
         syntax: 'typescript',
@@ -95,15 +123,11 @@ export function transpileWithSWC(
       // Preserve function names for debugging
       preserveAllComments: false,
     },
-    module: {
-      type: 'commonjs',
-      strict: false,
-      strictMode: false,
-      noInterop: false,
-      // Import assertions/attributes interop
-      importInterop: 'swc',
-    },
-    sourceMaps: false,
+    module: getSwcModuleConfig(options),
+    sourceMaps: 'inline',
+    inputSourceMap: inputSourceMap && JSON.stringify(inputSourceMap),
+    // The worker only needs positions; the editor already has the source
+    inlineSourcesContent: false,
     isModule: true,
   };
 
@@ -118,32 +142,27 @@ export function transpileWithSWC(
 }
 
 /**
- * Full transform pipeline using SWC
+ * Full transform pipeline using SWC. The main process and the transpiler
+ * worker both go through here, so they instrument code identically.
  */
 export function transformCode(
   code: string,
-  options: TransformOptions = {}
+  options: TransformOptions = {},
+  passes: readonly TransformPass[] = DEFAULT_TRANSFORM_PASSES
 ): string {
   if (!code.trim()) return '';
 
   try {
-    // Step 1: Apply magic comments BEFORE transpilation
-    let processedCode = code;
-    if (options.magicComments) {
-      processedCode = applyMagicComments(code);
-    }
-
-    // Step 2: Transpile TS/JSX with SWC (much faster than TSC), passing options
-    const transpiled = transpileWithSWC(processedCode, options);
-
-    // Step 3: Apply other code transformations
-    const transformed = applyCodeTransforms(transpiled, options);
+    // Instrument the source as written, so debug calls carry the editor's
+    // line numbers rather than those of the transpiled output
+    const instrumented = instrumentCode(code, options, passes);
 
-    return transformed;
+    // Transpile TS/JSX with SWC (much faster than TSC), passing options
+    return transpileWithSWC(instrumented.code, options, instrumented.map);
   } catch (error) {
     // If transpilation fails, try to apply transforms to original code
     console.error('SWC Transpilation error:', error);
-    return applyCodeTransforms(code, options);
+    return applyCodeTransforms(code, options, passes);
   }
 }
 
@@ -0,0 +1,265 @@
+/**
+ * Async Work Tracker
+ *
+ * Hands the JS/TS sandbox wrapped timers, `fetch` and `Promise` so the
+ * executor can tell when a run's scheduled work has drained. A run is idle
+ * once no timeouts, intervals, immediates or requests are outstanding;
+ * promises that are still pending at that point have nothing left to settle
+ * them, so they are reported rather than waited for.
+ */
+
+import type { PendingAsyncWork } from '@cheesejs/core';
+
+type TimerCallback = (...args: unknown[]) => void;
+
+const RESPONSE_BODY_METHODS = [
+  'arrayBuffer',
+  'blob',
+  'formData',
+  'json',
+  'text',
+] as const;
+
+export class AsyncWorkTracker {
+  private readonly timeouts = new Set<NodeJS.Timeout>();
+  private readonly intervals = new Set<NodeJS.Timeout>();
+  private readonly immediates = new Set<NodeJS.Immediate>();
+  private pendingPromises = 0;
+  private pendingRequests = 0;
+  private disposed = false;
+  private idleWaiters: Array<() => void> = [];
+
+  get isDisposed(): boolean {
+    return this.disposed;
+  }
+
+  isIdle(): boolean {
+    return (
+      this.timeouts.size === 0 &&
+      this.intervals.size === 0 &&
+      this.immediates.size === 0 &&
+      this.pendingRequests === 0
+    );
+  }
+
+  /** Outstanding work, or undefined when nothing is left */
+  getPending(): PendingAsyncWork | undefined {
+    const pending: PendingAsyncWork = {
+      timeouts: this.timeouts.size + this.immediates.size,
+      intervals: this.intervals.size,
+      promises: this.pendingPromises,
+      requests: this.pendingRequests,
+    };
+    return Object.values(pending).some((count) => count > 0)
+      ? pending
+      : undefined;
+  }
+
+  /**
+   * Resolves once the run is idle or `timeoutMs` has passed, whichever comes
+   * first. Callers should `dispose()` afterwards to stop leftover timers.
+   */
+  waitForIdle(timeoutMs: number): Promise<void> {
+    return new Promise((resolve) => {
+      const waiter = () => {
+        clearTimeout(deadline);
+        resolve();
+      };
+      const deadline = setTimeout(() => {
+        this.idleWaiters = this.idleWaiters.filter((entry) => entry !== waiter);
+        resolve();
+      }, Math.max(0, timeoutMs));
+      this.idleWaiters.push(waiter);
+      this.scheduleIdleCheck();
+    });
+  }
+
+  /** Cancels every leftover timer and releases anyone waiting for idle */
+  dispose(): void {
+    if (this.disposed) return;
+    this.disposed = true;
+
+    this.timeouts.forEach((handle) => clearTimeout(handle));
+    this.intervals.forEach((handle) => clearInterval(handle));
+    this.immediates.forEach((handle) => clearImmediate(handle));
+    this.timeouts.clear();
+    this.intervals.clear();
+    this.immediates.clear();
+    this.notifyIdle();
+  }
+
+  /** Timer, fetch and Promise globals for the sandbox context */
+  createGlobals(): Record<string, unknown> {
+    return {
+      setTimeout: this.setTimeout,
+      clearTimeout: this.clearTimeout,
+      setInterval: this.setInterval,
+      clearInterval: this.clearInterval,
+      setImmediate: this.setImmediate,
+      clearImmediate: this.clearImmediate,
+      Promise: this.createPromiseClass(),
+      fetch: typeof fetch !== 'undefined' ? this.fetch : undefined,
+    };
+  }
+
+  private readonly setTimeout = (
+    callback: TimerCallback,
+    delay?: number,
+    ...args: unknown[]
+  ): NodeJS.Timeout => {
+    const handle = setTimeout(() => {
+      this.timeouts.delete(handle);
+      try {
+        callback(...args);
+      } finally {
+        this.scheduleIdleCheck();
+      }
+    }, delay);
+    this.adopt(this.timeouts, handle, clearTimeout);
+    return handle;
+  };
+
+  // Timers scheduled by leftovers of a finished run never fire
+  private adopt<H>(handles: Set<H>, handle: H, clear: (handle: H) => void) {
+    if (this.disposed) {
+      clear(handle);
+    } else {
+      handles.add(handle);
+    }
+  }
+
+  private readonly clearTimeout = (handle?: NodeJS.Timeout): void => {
+    if (!handle) return;
+    clearTimeout(handle);
+    if (this.timeouts.delete(handle)) this.scheduleIdleCheck();
+  };
+
+  private readonly setInterval = (
+    callback: TimerCallback,
+    delay?: number,
+    ...args: unknown[]
+  ): NodeJS.Timeout => {
+    const handle = setInterval(() => callback(...args), delay);
+    this.adopt(this.intervals, handle, clearInterval);
+    return handle;
+  };
+
+  private readonly clearInterval = (handle?: NodeJS.Timeout): void => {
+    if (!handle) return;
+    clearInterval(handle);
+    if (this.intervals.delete(handle)) this.scheduleIdleCheck();
+  };
+
+  private readonly setImmediate = (
+    callback: TimerCallback,
+    ...args: unknown[]
+  ): NodeJS.Immediate => {
+    const handle = setImmediate(() => {
+      this.immediates.delete(handle);
+      try {
+        callback(...args);
+      } finally {
+        this.scheduleIdleCheck();
+      }
+    });
+    this.adopt(this.immediates, handle, clearImmediate);
+    return handle;
+  };
+
+  private readonly clearImmediate = (handle?: NodeJS.Immediate): void => {
+    if (!handle) return;
+    clearImmediate(handle);
+    if (this.immediates.delete(handle)) this.scheduleIdleCheck();
+  };
+
+  private readonly fetch = async (
+    ...args: Parameters<typeof fetch>
+  ): Promise<Response> => {
+    const response = await this.trackRequest(fetch(...args));
+
+    // Reading the body is another round trip the run may still be waiting on
+    for (const method of RESPONSE_BODY_METHODS) {
+      const read = response[method].bind(response);
+      Object.defineProperty(response, method, {
+        value: () => this.trackRequest(read()),
+        configurable: true,
+      });
+    }
+    return response;
+  };
+
+  private async trackRequest<T>(request: Promise<T>): Promise<T> {
+    this.pendingRequests++;
+    try {
+      return await request;
+    } finally {
+      this.pendingRequests--;
+      this.scheduleIdleCheck();
+    }
+  }
+
+  /**
+   * Promise subclass that counts instances until they settle. Derived
+   * promises (`then`, `catch`) are plain promises, so chains are counted
+   * once rather than per link.
+   */
+  private createPromiseClass(): PromiseConstructor {
+    // eslint-disable-next-line @typescript-eslint/no-this-alias
+    const tracker = this;
+
+    class TrackedPromise<T> extends Promise<T> {
+      static get [Symbol.species]() {
+        return Promise;
+      }
+
+      constructor(
+        executor: (
+          resolve: (value: T | PromiseLike<T>) => void,
+          reject: (reason?: unknown) => void
+        ) => void
+      ) {
+        let settled = false;
+        const settle = () => {
+          if (settled) return;
+          settled = true;
+          tracker.pendingPromises--;
+        };
+        tracker.pendingPromises++;
+
+        super((resolve, reject) => {
+          try {
+            executor(
+              (value) => {
+                settle();
+                resolve(value);
+              },
+              (reason) => {
+                settle();
+                reject(reason);
+              }
+            );
+          } catch (error) {
+            settle();
+            throw error;
+          }
+        });
+      }
+    }
+
+    return TrackedPromise as unknown as PromiseConstructor;
+  }
+
+  // Checks after pending microtasks have run, since they may schedule more
+  private scheduleIdleCheck(): void {
+    if (this.idleWaiters.length === 0) return;
+    setImmediate(() => {
+      if (this.isIdle()) this.notifyIdle();
+    });
+  }
+
+  private notifyIdle(): void {
+    const waiters = this.idleWaiters;
+    this.idleWaiters = [];
+    waiters.forEach((waiter) => waiter());
+  }
+}
@@ -38,6 +38,10 @@ interface SmartScriptCacheOptions {
 
 export type { SmartScriptCacheOptions };
 
+/** File name and line shift stack frames of cached scripts report */
+export const SCRIPT_FILENAME = 'usercode.js';
+export const SCRIPT_LINE_OFFSET = -2;
+
 // ============================================================================
 // SMART SCRIPT CACHE
 // ============================================================================
@@ -129,8 +133,8 @@ export class SmartScriptCache {
     this.metrics.misses++;
 
     const script = new vm.Script(code, {
-      filename: 'usercode.js',
-      lineOffset: -2,
+      filename: SCRIPT_FILENAME,
+      lineOffset: SCRIPT_LINE_OFFSET,
       columnOffset: 0,
     });
 
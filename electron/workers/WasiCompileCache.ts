@@ -0,0 +1,411 @@
+/**
+ * WASI Compile Cache
+ *
+ * Content-addressed disk cache for `.wasm` modules produced by the WASI
+ * executor. Keys are SHA-256 hashes of everything that affects the output
+ * (source, language, compiler flags and toolchain version), so re-running
+ * unchanged code skips clang/rustc entirely. Headers found through include
+ * paths are recorded with each module, and a lookup misses once one of them
+ * has changed.
+ *
+ * Features:
+ * - Async disk I/O for non-blocking operations
+ * - LRU eviction when the cache exceeds its size budget
+ * - Atomic writes (temp file + rename) so concurrent workers never observe
+ *   a partially written module
+ * - Automatic manifest validation on load
+ */
+
+import crypto from 'crypto';
+import fs from 'fs/promises';
+import path from 'path';
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+interface CompileCacheEntry {
+  hash: string;
+  lastUsed: number;
+  accessCount: number;
+  size: number;
+  /** Files the module was compiled from besides its source, with hashes */
+  dependencies?: [file: string, hash: string][];
+}
+
+interface CompileCacheManifest {
+  version: number;
+  entries: Map<string, CompileCacheEntry>;
+  totalSize: number;
+  lastUpdated: number;
+}
+
+export interface WasiCompileCacheOptions {
+  /** Directory path for cached modules */
+  cacheDir: string;
+  /** Maximum disk cache size in bytes (default: 256MB) */
+  maxDiskSize?: number;
+}
+
+export interface CompileCacheKeyInput {
+  language: string;
+  source: string;
+  compiler: string;
+  flags: string[];
+  toolchainVersion: string;
+}
+
+interface CompileCacheMetrics {
+  hits: number;
+  misses: number;
+  writes: number;
+  evictions: number;
+}
+
+const MANIFEST_VERSION = 2;
+
+// WASI workers are threads of one process, so the pid alone is not unique
+function createTempSuffix(): string {
+  return crypto.randomBytes(6).toString('hex');
+}
+
+async function hashFile(filePath: string): Promise<string | null> {
+  try {
+    const content = await fs.readFile(filePath);
+    return crypto.createHash('sha256').update(content).digest('hex');
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Prerequisites of the first rule of a Make-style dependency file, as
+ * written by `clang -MD`
+ */
+export function parseDependencyFile(text: string): string[] {
+  const rule = text.replace(/\\\r?\n/g, ' ');
+  const separator = rule.indexOf(': ');
+  if (separator === -1) return [];
+  const end = rule.indexOf('\n', separator);
+  const prerequisites = rule.slice(separator + 2, end === -1 ? undefined : end);
+  return (prerequisites.match(/(?:\\[ #]|\S)+/g) ?? []).map((file) =>
+    file.replace(/\\([ #])/g, '$1').replace(/\$\$/g, '$')
+  );
+}
+
+// ============================================================================
+// WASI COMPILE CACHE
+// ============================================================================
+
+export class WasiCompileCache {
+  private manifest: CompileCacheManifest;
+  private cacheDir: string;
+  private maxDiskSize: number;
+  private initialized = false;
+  private initPromise: Promise<void> | null = null;
+
+  private metrics: CompileCacheMetrics = {
+    hits: 0,
+    misses: 0,
+    writes: 0,
+    evictions: 0,
+  };
+
+  constructor(options: WasiCompileCacheOptions) {
+    this.cacheDir = options.cacheDir;
+    this.maxDiskSize = options.maxDiskSize ?? 256 * 1024 * 1024; // 256MB
+    this.manifest = {
+      version: MANIFEST_VERSION,
+      entries: new Map(),
+      totalSize: 0,
+      lastUpdated: Date.now(),
+    };
+  }
+
+  /**
+   * Derive the cache key for a compilation
+   */
+  static computeKey(input: CompileCacheKeyInput): string {
+    return crypto
+      .createHash('sha256')
+      .update(
+        JSON.stringify([
+          MANIFEST_VERSION,
+          input.language,
+          input.compiler,
+          input.toolchainVersion,
+          input.flags,
+          input.source,
+        ]),
+        'utf8'
+      )
+      .digest('hex');
+  }
+
+  /**
+   * Initialize the cache (load manifest from disk)
+   */
+  async initialize(): Promise<void> {
+    if (this.initialized) return;
+    if (this.initPromise) return this.initPromise;
+
+    this.initPromise = this.doInitialize();
+    await this.initPromise;
+    this.initPromise = null;
+  }
+
+  private async doInitialize(): Promise<void> {
+    try {
+      await fs.mkdir(this.cacheDir, { recursive: true });
+      await this.loadManifest();
+    } catch (error) {
+      console.error('[WasiCompileCache] Initialization error:', error);
+    }
+    this.initialized = true;
+  }
+
+  /**
+   * Reload the manifest from disk. Other WASI workers share the directory,
+   * so this picks up modules they compiled since the last read.
+   */
+  private async loadManifest(): Promise<void> {
+    try {
+      const data = await fs.readFile(this.getManifestPath(), 'utf-8');
+      const parsed = JSON.parse(data);
+
+      if (
+        parsed.version === MANIFEST_VERSION &&
+        Array.isArray(parsed.entries)
+      ) {
+        this.manifest = {
+          version: MANIFEST_VERSION,
+          entries: new Map(parsed.entries),
+          totalSize: parsed.totalSize ?? 0,
+          lastUpdated: parsed.lastUpdated ?? Date.now(),
+        };
+        await this.validateEntries();
+      }
+    } catch {
+      // No existing manifest or invalid - start fresh
+    }
+  }
+
+  /**
+   * Drop manifest entries whose module file no longer exists
+   */
+  private async validateEntries(): Promise<void> {
+    for (const [hash, entry] of this.manifest.entries) {
+      try {
+        await fs.access(this.getModulePath(hash));
+      } catch {
+        this.manifest.entries.delete(hash);
+        this.manifest.totalSize -= entry.size;
+      }
+    }
+  }
+
+  /**
+   * Look up a compiled module. Returns the path of the cached `.wasm` file,
+   * or null on a miss, including when a file it depends on has changed.
+   */
+  async get(hash: string): Promise<string | null> {
+    await this.initialize();
+
+    let entry = this.manifest.entries.get(hash);
+    if (!entry) {
+      await this.loadManifest();
+      entry = this.manifest.entries.get(hash);
+    }
+
+    if (entry) {
+      const modulePath = this.getModulePath(hash);
+      try {
+        await fs.access(modulePath);
+        if (!(await this.hasCurrentDependencies(entry))) {
+          this.metrics.misses++;
+          return null;
+        }
+        entry.lastUsed = Date.now();
+        entry.accessCount++;
+        this.metrics.hits++;
+        await this.saveManifest();
+        return modulePath;
+      } catch {
+        // File missing - remove from manifest
+        this.manifest.entries.delete(hash);
+        this.manifest.totalSize -= entry.size;
+      }
+    }
+
+    this.metrics.misses++;
+    return null;
+  }
+
+  private async hasCurrentDependencies(
+    entry: CompileCacheEntry
+  ): Promise<boolean> {
+    for (const [file, hash] of entry.dependencies ?? []) {
+      if ((await hashFile(file)) !== hash) return false;
+    }
+    return true;
+  }
+
+  /**
+   * Store a freshly compiled module and return its cached path.
+   * `dependencies` are the files besides the source it was compiled from.
+   */
+  async put(
+    hash: string,
+    wasmPath: string,
+    dependencies: string[] = []
+  ): Promise<string> {
+    await this.initialize();
+
+    const modulePath = this.getModulePath(hash);
+    const { size } = await fs.stat(wasmPath);
+    if (size > this.maxDiskSize) {
+      return wasmPath;
+    }
+
+    const hashedDependencies: [string, string][] = [];
+    for (const file of dependencies) {
+      const fileHash = await hashFile(file);
+      // Gone since it was compiled, so the module can not be checked later
+      if (fileHash === null) return wasmPath;
+      hashedDependencies.push([file, fileHash]);
+    }
+
+    await this.ensureDiskSpace(size);
+
+    const tempPath = `${modulePath}.${createTempSuffix()}.tmp`;
+    await fs.copyFile(wasmPath, tempPath);
+    await fs.rename(tempPath, modulePath);
+
+    const previous = this.manifest.entries.get(hash);
+    if (previous) {
+      this.manifest.totalSize -= previous.size;
+    }
+    this.manifest.entries.set(hash, {
+      hash,
+      lastUsed: Date.now(),
+      accessCount: 1,
+      size,
+      dependencies: hashedDependencies,
+    });
+    this.manifest.totalSize += size;
+    this.metrics.writes++;
+
+    await this.saveManifest();
+    return modulePath;
+  }
+
+  /**
+   * Ensure there's enough disk space for a new entry
+   */
+  private async ensureDiskSpace(neededSize: number): Promise<void> {
+    while (
+      this.manifest.totalSize + neededSize > this.maxDiskSize &&
+      this.manifest.entries.size > 0
+    ) {
+      await this.evictOldestEntry();
+    }
+  }
+
+  /**
+   * Evict the least recently used entry
+   */
+  private async evictOldestEntry(): Promise<void> {
+    let oldestHash: string | null = null;
+    let oldestTime = Infinity;
+
+    for (const [hash, entry] of this.manifest.entries) {
+      if (entry.lastUsed < oldestTime) {
+        oldestTime = entry.lastUsed;
+        oldestHash = hash;
+      }
+    }
+
+    if (!oldestHash) return;
+
+    const entry = this.manifest.entries.get(oldestHash);
+    if (entry) {
+      this.manifest.totalSize -= entry.size;
+      this.manifest.entries.delete(oldestHash);
+
+      try {
+        await fs.unlink(this.getModulePath(oldestHash));
+      } catch {
+        // File already gone
+      }
+
+      this.metrics.evictions++;
+    }
+  }
+
+  /**
+   * Save manifest to disk
+   */
+  private async saveManifest(): Promise<void> {
+    const data = JSON.stringify({
+      version: this.manifest.version,
+      entries: Array.from(this.manifest.entries.entries()),
+      totalSize: this.manifest.totalSize,
+      lastUpdated: Date.now(),
+    });
+
+    const manifestPath = this.getManifestPath();
+    const tempPath = `${manifestPath}.${createTempSuffix()}.tmp`;
+    try {
+      await fs.writeFile(tempPath, data, 'utf-8');
+      await fs.rename(tempPath, manifestPath);
+    } catch (error) {
+      console.error('[WasiCompileCache] Failed to save manifest:', error);
+    }
+  }
+
+  private getManifestPath(): string {
+    return path.join(this.cacheDir, 'manifest.json');
+  }
+
+  private getModulePath(hash: string): string {
+    return path.join(this.cacheDir, `${hash}.wasm`);
+  }
+
+  /**
+   * Remove every cached module
+   */
+  async clear(): Promise<void> {
+    await this.initialize();
+
+    for (const [hash] of this.manifest.entries) {
+      try {
+        await fs.unlink(this.getModulePath(hash));
+      } catch {
+        // Ignore errors
+      }
+    }
+
+    this.manifest.entries.clear();
+    this.manifest.totalSize = 0;
+    await this.saveManifest();
+
+    this.metrics = { hits: 0, misses: 0, writes: 0, evictions: 0 };
+  }
+
+  /**
+   * Get cache metrics
+   */
+  getMetrics(): CompileCacheMetrics & {
+    hitRate: number;
+    entries: number;
+    diskSize: number;
+  } {
+    const total = this.metrics.hits + this.metrics.misses;
+    return {
+      ...this.metrics,
+      hitRate: total > 0 ? this.metrics.hits / total : 0,
+      entries: this.manifest.entries.size,
+      diskSize: this.manifest.totalSize,
+    };
+  }
+}
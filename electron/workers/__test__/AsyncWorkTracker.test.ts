@@ -0,0 +1,84 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect } from 'vitest';
+import { AsyncWorkTracker } from '../AsyncWorkTracker';
+
+type Globals = {
+  setTimeout: typeof setTimeout;
+  setInterval: typeof setInterval;
+  clearInterval: typeof clearInterval;
+  Promise: PromiseConstructor;
+};
+
+describe('AsyncWorkTracker', () => {
+  it('waits for timers scheduled from other timers', async () => {
+    const tracker = new AsyncWorkTracker();
+    const sandbox = tracker.createGlobals() as Globals;
+    const fired: string[] = [];
+
+    sandbox.setTimeout(() => {
+      fired.push('outer');
+      sandbox.setTimeout(() => fired.push('inner'), 5);
+    }, 5);
+
+    await tracker.waitForIdle(1000);
+
+    expect(fired).toEqual(['outer', 'inner']);
+    expect(tracker.getPending()).toBeUndefined();
+  });
+
+  it('treats a cleared interval as drained', async () => {
+    const tracker = new AsyncWorkTracker();
+    const sandbox = tracker.createGlobals() as Globals;
+    let ticks = 0;
+
+    const handle = sandbox.setInterval(() => {
+      if (++ticks === 3) sandbox.clearInterval(handle);
+    }, 1);
+
+    await tracker.waitForIdle(1000);
+
+    expect(ticks).toBe(3);
+    expect(tracker.isIdle()).toBe(true);
+  });
+
+  it('reports work left when the time budget runs out', async () => {
+    const tracker = new AsyncWorkTracker();
+    const sandbox = tracker.createGlobals() as Globals;
+    let ticks = 0;
+
+    sandbox.setInterval(() => ticks++, 1);
+    sandbox.setTimeout(() => undefined, 10_000);
+    new sandbox.Promise(() => undefined);
+
+    await tracker.waitForIdle(30);
+    const pending = tracker.getPending();
+    tracker.dispose();
+    const ticksAfterDispose = ticks;
+    await new Promise((resolve) => setTimeout(resolve, 10));
+
+    expect(pending).toEqual({
+      timeouts: 1,
+      intervals: 1,
+      promises: 1,
+      requests: 0,
+    });
+    expect(ticks).toBe(ticksAfterDispose);
+  });
+
+  it('counts promises until they settle', async () => {
+    const tracker = new AsyncWorkTracker();
+    const sandbox = tracker.createGlobals() as Globals;
+
+    const settled = new sandbox.Promise<number>((resolve) =>
+      sandbox.setTimeout(() => resolve(1), 5)
+    );
+    const chained = settled.then((value) => value + 1);
+
+    expect(tracker.getPending()?.promises).toBe(1);
+    await expect(chained).resolves.toBe(2);
+    await tracker.waitForIdle(1000);
+    expect(tracker.getPending()).toBeUndefined();
+  });
+});
@@ -0,0 +1,126 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect, beforeEach, afterEach } from 'vitest';
+import fs from 'fs/promises';
+import os from 'os';
+import path from 'path';
+import { parseDependencyFile, WasiCompileCache } from '../WasiCompileCache';
+
+const baseKey = {
+  language: 'c',
+  source: 'int main(){return 0;}',
+  compiler: '/usr/bin/clang',
+  flags: ['-O0'],
+  toolchainVersion: 'clang 18',
+};
+
+describe('WasiCompileCache', () => {
+  let tempDir: string;
+
+  beforeEach(async () => {
+    tempDir = await fs.mkdtemp(path.join(os.tmpdir(), 'wasi-cache-test-'));
+  });
+
+  afterEach(async () => {
+    await fs.rm(tempDir, { recursive: true, force: true });
+  });
+
+  async function writeModule(name: string, size: number): Promise<string> {
+    const filePath = path.join(tempDir, name);
+    await fs.writeFile(filePath, Buffer.alloc(size, 1));
+    return filePath;
+  }
+
+  it('derives keys from every compilation input', () => {
+    const key = WasiCompileCache.computeKey(baseKey);
+
+    expect(WasiCompileCache.computeKey({ ...baseKey })).toBe(key);
+    expect(
+      WasiCompileCache.computeKey({ ...baseKey, source: 'int x;' })
+    ).not.toBe(key);
+    expect(
+      WasiCompileCache.computeKey({ ...baseKey, flags: ['-O2'] })
+    ).not.toBe(key);
+    expect(
+      WasiCompileCache.computeKey({ ...baseKey, toolchainVersion: 'clang 19' })
+    ).not.toBe(key);
+  });
+
+  it('returns stored modules and survives a reload', async () => {
+    const cacheDir = path.join(tempDir, 'cache');
+    const cache = new WasiCompileCache({ cacheDir });
+    const key = WasiCompileCache.computeKey(baseKey);
+
+    expect(await cache.get(key)).toBeNull();
+
+    const cachedPath = await cache.put(key, await writeModule('a.wasm', 16));
+    expect(await cache.get(key)).toBe(cachedPath);
+
+    const reloaded = new WasiCompileCache({ cacheDir });
+    expect(await reloaded.get(key)).toBe(cachedPath);
+    expect(reloaded.getMetrics().hits).toBe(1);
+  });
+
+  it('hits while the headers a module was compiled against are unchanged', async () => {
+    const cache = new WasiCompileCache({
+      cacheDir: path.join(tempDir, 'cache'),
+    });
+    // A header in the working directory, found through `-I`
+    const header = path.join(tempDir, 'answer.h');
+    await fs.writeFile(header, '#define ANSWER 42\n');
+    const key = WasiCompileCache.computeKey({
+      ...baseKey,
+      flags: ['-O0', '-I', tempDir],
+    });
+
+    const cachedPath = await cache.put(
+      key,
+      await writeModule('a.wasm', 16),
+      [header]
+    );
+    expect(await cache.get(key)).toBe(cachedPath);
+
+    await fs.writeFile(header, '#define ANSWER 43\n');
+    expect(await cache.get(key)).toBeNull();
+
+    await fs.rm(header);
+    expect(await cache.get(key)).toBeNull();
+  });
+
+  it('evicts the least recently used module when over budget', async () => {
+    const cache = new WasiCompileCache({
+      cacheDir: path.join(tempDir, 'cache'),
+      maxDiskSize: 100,
+    });
+
+    await cache.put('first', await writeModule('first.wasm', 40));
+    await new Promise((resolve) => setTimeout(resolve, 5));
+    await cache.put('second', await writeModule('second.wasm', 40));
+    await new Promise((resolve) => setTimeout(resolve, 5));
+    // Touch "first" so "second" becomes the eviction candidate
+    await cache.get('first');
+    await cache.put('third', await writeModule('third.wasm', 40));
+
+    expect(await cache.get('first')).not.toBeNull();
+    expect(await cache.get('second')).toBeNull();
+    expect(await cache.get('third')).not.toBeNull();
+    expect(cache.getMetrics().evictions).toBe(1);
+  });
+});
+
+describe('parseDependencyFile', () => {
+  it('lists the prerequisites of a clang dependency file', () => {
+    const text = [
+      '/tmp/run/program.wasm: /tmp/run/program.c \\',
+      '  /work/my\\ lib/answer.h /sysroot/include/stdio.h',
+      '',
+    ].join('\n');
+
+    expect(parseDependencyFile(text)).toEqual([
+      '/tmp/run/program.c',
+      '/work/my lib/answer.h',
+      '/sysroot/include/stdio.h',
+    ]);
+  });
+});
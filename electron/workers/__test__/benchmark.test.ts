@@ -0,0 +1,86 @@
+/**
+ * @vitest-environment node
+ */
+import vm from 'vm';
+import { describe, it, expect } from 'vitest';
+import type { BenchmarkResult } from '@cheesejs/core';
+import { applyCodeTransforms } from '../../transpiler/codeTransforms';
+import { BenchmarkRunner, summarizeSamples } from '../benchmark';
+
+/** Declares the loop-protected benchmarks of `code` and runs them */
+async function runBenchmarks(code: string) {
+  const benchmarks = new BenchmarkRunner((stack) => {
+    const match = /usercode\.js:(\d+)/.exec(stack);
+    return match ? Number(match[1]) : undefined;
+  });
+  const context = vm.createContext({ bench: benchmarks.createBench() });
+  vm.runInContext(
+    applyCodeTransforms(code, {
+      loopProtection: true,
+      showTopLevelResults: false,
+    }),
+    context,
+    { filename: 'usercode.js' }
+  );
+
+  const results: BenchmarkResult[] = [];
+  await benchmarks.run((result) => results.push(result));
+  return { results, context };
+}
+
+describe('summarizeSamples', () => {
+  it('reports the mean, percentiles and margin of error', () => {
+    const result = summarizeSamples('sort', [3, 1, 5, 2, 4], 50);
+
+    expect(result).toMatchObject({
+      name: 'sort',
+      mean: 3,
+      p50: 3,
+      p99: 5,
+      samples: 5,
+      iterations: 50,
+    });
+    expect(result.opsPerSecond).toBeCloseTo(333.33);
+    // t(4) = 2.776 times a standard error of √(2.5 / 5)
+    expect(result.marginOfError).toBeCloseTo(65.43);
+  });
+});
+
+describe('BenchmarkRunner', () => {
+  it('times sync and async functions after warming up', async () => {
+    const { results } = await runBenchmarks(`let calls = 0;
+bench('sum', () => { calls++; return [1, 2, 3].reduce((a, b) => a + b) }, { time: 20, warmup: 5 });
+bench('tick', () => Promise.resolve(calls), { time: 20, warmup: 5 });`);
+
+    expect(results.map(({ name, line }) => [name, line])).toEqual([
+      ['sum', 2],
+      ['tick', 3],
+    ]);
+    for (const result of results) {
+      expect(result.samples).toBeGreaterThanOrEqual(10);
+      expect(result.iterations).toBeGreaterThanOrEqual(result.samples);
+      expect(result.opsPerSecond).toBeGreaterThan(0);
+      expect(result.p50).toBeLessThanOrEqual(result.p99);
+    }
+  });
+
+  it('is not stopped by loop protection', async () => {
+    const { context } = await runBenchmarks(`var total = 0;
+bench('loop', () => {
+  let count = 0;
+  for (let i = 0; i < 10000; i++) count++;
+  total += count;
+}, { time: 50 });`);
+
+    expect(context.total).toBeGreaterThan(1_000_000);
+  });
+
+  it('needs a function and ends the run when one throws', async () => {
+    await expect(runBenchmarks(`bench('nothing', 42);`)).rejects.toThrow(
+      'bench() needs a function to time'
+    );
+    await expect(
+      runBenchmarks(`bench('broken', () => { throw new Error('boom') });`)
+    ).rejects.toThrow('boom');
+  });
+});
@@ -0,0 +1,68 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect } from 'vitest';
+import {
+  MAX_LISTING_LINES,
+  parseAssemblyListing,
+  truncateListing,
+} from '../compiledOutput';
+
+const ASSEMBLY = [
+  '\t.text',
+  '\t.file\t"program.c"',
+  '\t.globl\tmain',
+  'main:',
+  '\t.file\t1 "/tmp/cheesejs-wasi-abc" "program.c"',
+  '\t.file\t2 "/usr/share/wasi-sysroot/include" "stdio.h"',
+  '\t.loc\t1 3 0',
+  '\t.functype\tmain () -> (i32)',
+  '.Ltmp0:',
+  '\t.loc\t1 4 3 prologue_end',
+  '\ti32.const\t42',
+  '\t.loc\t2 10 1',
+  '\tcall\tputs',
+  '\t.loc\t1 0 0',
+  '\tend_function',
+  '',
+  '\t.section\t.debug_abbrev,"",@',
+  '\t.int8\t1',
+].join('\n');
+
+describe('parseAssemblyListing', () => {
+  it('maps instructions to source lines and drops debug noise', () => {
+    const listing = parseAssemblyListing(ASSEMBLY, 'program.c');
+
+    expect(listing.format).toBe('assembly');
+    expect(listing.text.split('\n')).toEqual([
+      '\t.text',
+      '\t.globl\tmain',
+      'main:',
+      '\t.functype\tmain () -> (i32)',
+      '\ti32.const\t42',
+      '\tcall\tputs',
+      '\tend_function',
+    ]);
+    expect(listing.sourceLines).toEqual([null, null, null, 3, 4, null, null]);
+  });
+
+  it('omits the line map when there is no debug info', () => {
+    const listing = parseAssemblyListing('main:\n\tend_function', 'program.c');
+
+    expect(listing.sourceLines).toBeUndefined();
+  });
+});
+
+describe('truncateListing', () => {
+  it('cuts very long listings and says how much was dropped', () => {
+    const text = Array.from(
+      { length: MAX_LISTING_LINES + 5 },
+      (_, index) => `line ${index}`
+    ).join('\n');
+
+    const lines = truncateListing(text).split('\n');
+
+    expect(lines).toHaveLength(MAX_LISTING_LINES + 1);
+    expect(lines.at(-1)).toBe(';; ... 5 more lines not shown');
+  });
+});
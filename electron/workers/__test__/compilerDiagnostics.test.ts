@@ -0,0 +1,145 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect } from 'vitest';
+import {
+  parseClangDiagnostics,
+  parseRustcDiagnostics,
+} from '../compilerDiagnostics';
+
+describe('parseClangDiagnostics', () => {
+  it('reads locations, ranges, flags and fix-its', () => {
+    const output = [
+      "program.c:3:12:{3:5-3:11}: warning: unused variable 'answer' [-Wunused-variable]",
+      '    3 |     int answer = 42',
+      '      |         ^~~~~~',
+      "program.c:3:20: error: expected ';' at end of declaration",
+      'fix-it:"program.c":{3:20-3:20}:";"',
+      '1 warning and 1 error generated.',
+    ].join('\n');
+
+    const { diagnostics, text } = parseClangDiagnostics(output, 'program.c');
+
+    expect(diagnostics).toEqual([
+      {
+        severity: 'warning',
+        message: "unused variable 'answer'",
+        line: 3,
+        column: 12,
+        endLine: 3,
+        endColumn: 12,
+        code: '-Wunused-variable',
+      },
+      {
+        severity: 'error',
+        message: "expected ';' at end of declaration",
+        line: 3,
+        column: 20,
+        fixIts: [
+          { line: 3, column: 20, endLine: 3, endColumn: 20, replacement: ';' },
+        ],
+      },
+    ]);
+    expect(text).toContain(
+      "program.c:3:12: warning: unused variable 'answer' [-Wunused-variable]"
+    );
+    expect(text).not.toContain('fix-it:');
+  });
+
+  it('attributes project files and skips toolchain headers', () => {
+    const { diagnostics, text } = parseClangDiagnostics(
+      [
+        'In file included from program.c:1:',
+        '/usr/share/wasi-sysroot/include/stdio.h:9:1: warning: deprecated',
+        'src/helper.c:2:1: error: unknown type name "foo"',
+        'fix-it:"src/helper.c":{2:1-2:4}:"int"',
+        'program.c:5:3: note: in expansion of macro',
+      ].join('\n'),
+      'program.c'
+    );
+
+    expect(diagnostics).toEqual([
+      {
+        severity: 'error',
+        message: 'unknown type name "foo"',
+        file: 'src/helper.c',
+        line: 2,
+        column: 1,
+      },
+      {
+        severity: 'note',
+        message: 'in expansion of macro',
+        line: 5,
+        column: 3,
+      },
+    ]);
+    expect(text).toContain('stdio.h:9:1: warning: deprecated');
+  });
+});
+
+describe('parseRustcDiagnostics', () => {
+  it('uses the primary span and child suggestions', () => {
+    const output = [
+      JSON.stringify({
+        $message_type: 'diagnostic',
+        message: 'cannot find value `y` in this scope',
+        code: { code: 'E0425' },
+        level: 'error',
+        spans: [
+          {
+            file_name: 'main.rs',
+            line_start: 2,
+            line_end: 2,
+            column_start: 20,
+            column_end: 21,
+            is_primary: true,
+            suggested_replacement: null,
+          },
+        ],
+        children: [
+          {
+            message: 'a local variable with a similar name exists',
+            code: null,
+            level: 'help',
+            spans: [
+              {
+                file_name: 'main.rs',
+                line_start: 2,
+                line_end: 2,
+                column_start: 20,
+                column_end: 21,
+                is_primary: true,
+                suggested_replacement: 'x',
+              },
+            ],
+            children: [],
+            rendered: null,
+          },
+        ],
+        rendered: 'error[E0425]: cannot find value `y` in this scope\n',
+      }),
+      'error: linking with `rust-lld` failed',
+    ].join('\n');
+
+    const { diagnostics, text } = parseRustcDiagnostics(output, 'main.rs');
+
+    expect(diagnostics).toEqual([
+      {
+        severity: 'error',
+        message: 'cannot find value `y` in this scope',
+        line: 2,
+        column: 20,
+        endLine: 2,
+        endColumn: 21,
+        code: 'E0425',
+        fixIts: [
+          { line: 2, column: 20, endLine: 2, endColumn: 21, replacement: 'x' },
+        ],
+      },
+    ]);
+    expect(text).toBe(
+      'error[E0425]: cannot find value `y` in this scope\n' +
+        'error: linking with `rust-lld` failed'
+    );
+  });
+});
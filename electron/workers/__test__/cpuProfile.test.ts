@@ -0,0 +1,104 @@
+/**
+ * @vitest-environment node
+ */
+import type inspector from 'inspector';
+import { describe, it, expect } from 'vitest';
+import { CpuProfiler, toCpuProfile, type FrameLocator } from '../cpuProfile';
+
+const locateProgram: FrameLocator = ({ functionName, url, lineNumber }) =>
+  url === 'usercode.js'
+    ? { name: functionName || '(anonymous)', line: lineNumber + 1 }
+    : null;
+
+function frame(functionName: string, url = '', lineNumber = -1) {
+  return { functionName, url, lineNumber, columnNumber: 0, scriptId: '0' };
+}
+
+describe('toCpuProfile', () => {
+  // main() calls fib() directly and through a worker helper; samples are
+  // 1 ms apart
+  const profile: inspector.Profiler.Profile = {
+    nodes: [
+      { id: 1, callFrame: frame('(root)'), children: [2, 3, 6, 8] },
+      { id: 2, callFrame: frame('(program)') },
+      {
+        id: 3,
+        callFrame: frame('main', 'usercode.js', 0),
+        children: [4, 5],
+      },
+      {
+        id: 4,
+        callFrame: frame('helper', 'codeExecutor.js', 9),
+        children: [7],
+      },
+      { id: 5, callFrame: frame('fib', 'usercode.js', 4) },
+      { id: 6, callFrame: frame('(garbage collector)') },
+      { id: 7, callFrame: frame('fib', 'usercode.js', 4) },
+      { id: 8, callFrame: frame('(idle)') },
+    ],
+    startTime: 0,
+    endTime: 8000,
+    samples: [3, 7, 7, 4, 5, 2, 6, 8],
+    timeDeltas: [0, 1000, 1000, 1000, 1000, 1000, 1000, 1000],
+  };
+
+  it('keeps located frames and folds the rest into their callers', () => {
+    const { profiler, root } = toCpuProfile(profile, locateProgram);
+
+    expect(profiler).toBe('v8');
+    expect(root).toEqual({
+      name: '(root)',
+      selfTime: 1,
+      totalTime: 7,
+      children: [
+        {
+          name: 'main',
+          line: 1,
+          selfTime: 2,
+          totalTime: 5,
+          children: [
+            { name: 'fib', line: 5, selfTime: 3, totalTime: 3, children: [] },
+          ],
+        },
+        {
+          name: '(garbage collector)',
+          selfTime: 1,
+          totalTime: 1,
+          children: [],
+        },
+      ],
+    });
+  });
+
+  it('leaves out the time V8 spent idle', () => {
+    const { root } = toCpuProfile(profile, () => null);
+
+    expect(root).toEqual({
+      name: '(root)',
+      selfTime: 6,
+      totalTime: 7,
+      children: [
+        {
+          name: '(garbage collector)',
+          selfTime: 1,
+          totalTime: 1,
+          children: [],
+        },
+      ],
+    });
+  });
+});
+
+describe('CpuProfiler', () => {
+  it('profiles the worker until stopped', async () => {
+    const profiler = new CpuProfiler();
+    await profiler.start();
+    let total = 0;
+    for (let i = 0; i < 1e6; i++) total += i;
+    const profile = await profiler.stop();
+
+    expect(total).toBeGreaterThan(0);
+    expect(profile.nodes.length).toBeGreaterThan(0);
+    expect(profile.endTime).toBeGreaterThan(profile.startTime);
+  });
+});
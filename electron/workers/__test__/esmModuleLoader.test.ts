@@ -0,0 +1,181 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect, beforeEach, afterEach } from 'vitest';
+import fs from 'fs/promises';
+import os from 'os';
+import path from 'path';
+import vm from 'vm';
+import { pathToFileURL } from 'url';
+import { SANDBOX_PROFILES } from '@cheesejs/core';
+import { SandboxPermissionGate } from '../sandboxPermissions';
+import {
+  EsmModuleLoader,
+  isEsmFile,
+  resolvePackageExports,
+  splitPackageSpecifier,
+} from '../esmModuleLoader';
+
+describe('splitPackageSpecifier', () => {
+  it('separates package names from subpaths', () => {
+    expect(splitPackageSpecifier('lodash')).toEqual({
+      name: 'lodash',
+      subpath: '.',
+    });
+    expect(splitPackageSpecifier('lodash/fp/map')).toEqual({
+      name: 'lodash',
+      subpath: './fp/map',
+    });
+    expect(splitPackageSpecifier('@scope/pkg/utils')).toEqual({
+      name: '@scope/pkg',
+      subpath: './utils',
+    });
+  });
+});
+
+describe('resolvePackageExports', () => {
+  it('prefers the import condition over require', () => {
+    const exportsField = {
+      '.': { require: './index.cjs', import: './index.mjs' },
+    };
+
+    expect(resolvePackageExports(exportsField, '.')).toBe('./index.mjs');
+  });
+
+  it('accepts a bare string or condition map as the main export', () => {
+    expect(resolvePackageExports('./main.js', '.')).toBe('./main.js');
+    expect(
+      resolvePackageExports({ node: './node.js', default: './web.js' }, '.')
+    ).toBe('./node.js');
+  });
+
+  it('expands subpath patterns', () => {
+    const exportsField = {
+      '.': './index.js',
+      './features/*': { import: './dist/features/*.mjs' },
+    };
+
+    expect(resolvePackageExports(exportsField, './features/parse')).toBe(
+      './dist/features/parse.mjs'
+    );
+  });
+
+  it('returns null for subpaths the package does not export', () => {
+    expect(resolvePackageExports({ '.': './index.js' }, './internal')).toBe(
+      null
+    );
+    expect(
+      resolvePackageExports({ '.': { require: './index.cjs' } }, '.')
+    ).toBe(null);
+  });
+});
+
+describe('isEsmFile', () => {
+  let tempDir: string;
+
+  beforeEach(async () => {
+    tempDir = await fs.mkdtemp(path.join(os.tmpdir(), 'esm-loader-test-'));
+  });
+
+  afterEach(async () => {
+    await fs.rm(tempDir, { recursive: true, force: true });
+  });
+
+  it('follows the extension and the nearest package type', async () => {
+    await fs.mkdir(path.join(tempDir, 'esm/lib'), { recursive: true });
+    await fs.mkdir(path.join(tempDir, 'cjs'), { recursive: true });
+    await fs.writeFile(
+      path.join(tempDir, 'esm/package.json'),
+      JSON.stringify({ type: 'module' })
+    );
+    await fs.writeFile(path.join(tempDir, 'cjs/package.json'), '{}');
+
+    expect(isEsmFile(path.join(tempDir, 'esm/lib/index.js'))).toBe(true);
+    expect(isEsmFile(path.join(tempDir, 'esm/lib/index.cjs'))).toBe(false);
+    expect(isEsmFile(path.join(tempDir, 'cjs/index.js'))).toBe(false);
+    expect(isEsmFile(path.join(tempDir, 'cjs/index.mjs'))).toBe(true);
+  });
+});
+
+describe('EsmModuleLoader.locateError', () => {
+  const root = path.join(os.tmpdir(), 'cheese-project');
+  const loader = new EsmModuleLoader({
+    context: vm.createContext({}),
+    workspaceRoot: root,
+    entryFile: 'main.ts',
+    moduleFiles: { 'lib/utils.ts': 'export {};' },
+    requireModule: () => ({}),
+  });
+
+  it('points at the imported tab an error was thrown in', () => {
+    const stack = [
+      'Error: kaboom',
+      `    at boom (${path.join(root, 'lib/utils.ts')}:3:9)`,
+      `    at ${path.join(root, 'main.ts')}:2:1`,
+    ].join('\n');
+
+    expect(loader.locateError(stack)).toEqual({
+      file: path.join('lib', 'utils.ts'),
+      line: 3,
+      column: 9,
+    });
+  });
+
+  it('leaves errors thrown by the editor program to the caller', () => {
+    const stack = [
+      'Error: kaboom',
+      `    at ${path.join(root, 'main.ts')}:2:7`,
+      `    at boom (${path.join(root, 'lib/utils.ts')}:3:9)`,
+    ].join('\n');
+
+    expect(loader.locateError(stack)).toBeUndefined();
+  });
+});
+
+describe('EsmModuleLoader imports from disk', () => {
+  let tempDir: string;
+  let loader: EsmModuleLoader;
+  const importFrom = (specifier: string) =>
+    loader.link(specifier, {
+      identifier: loader.mainIdentifier,
+    } as vm.Module);
+
+  beforeEach(async () => {
+    tempDir = await fs.mkdtemp(path.join(os.tmpdir(), 'esm-loader-test-'));
+    await fs.mkdir(path.join(tempDir, 'project'));
+    await fs.writeFile(path.join(tempDir, 'project/data.json'), '{}');
+    await fs.writeFile(path.join(tempDir, 'secret.json'), '{}');
+    loader = new EsmModuleLoader({
+      context: vm.createContext({}),
+      workingDirectory: path.join(tempDir, 'project'),
+      requireModule: () => ({}),
+      permissions: new SandboxPermissionGate({
+        ...SANDBOX_PROFILES.standard,
+        fs: 'deny',
+      }),
+    });
+  });
+
+  afterEach(async () => {
+    await fs.rm(tempDir, { recursive: true, force: true });
+  });
+
+  it('rejects files outside the project directory', async () => {
+    const secret = path.join(tempDir, 'secret.json');
+    for (const specifier of [
+      secret,
+      pathToFileURL(secret).href,
+      '../secret.json',
+    ]) {
+      await expect(importFrom(specifier)).rejects.toThrow(
+        'only files in the project directory can be imported'
+      );
+    }
+  });
+
+  it('needs the fs permission to read project files', async () => {
+    await expect(importFrom('./data.json')).rejects.toThrow(
+      'Permission denied: file system access'
+    );
+  });
+});
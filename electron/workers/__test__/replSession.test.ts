@@ -0,0 +1,140 @@
+/**
+ * @vitest-environment node
+ */
+import vm from 'vm';
+import { describe, it, expect, beforeEach } from 'vitest';
+import { transformCode } from '../../transpiler/swcTranspiler';
+import { ReplSession } from '../replSession';
+
+let loads: number;
+let printed: unknown[][];
+
+function createSession(): ReplSession {
+  return new ReplSession(vm.createContext({}));
+}
+
+/** Runs `code` in the session the way the code worker does */
+async function run(session: ReplSession, code: string, reuse = true) {
+  const sessionRun = session.startRun(
+    {
+      debug: (_line: number, ...args: unknown[]) => printed.push(args),
+      load: () => {
+        loads++;
+        return [1, 2, 3];
+      },
+    },
+    reuse
+  );
+  const script = `(async () => {\n${transformCode(code, { session: true })}\n})()`;
+  try {
+    await vm.runInContext(script, session.context);
+    return { stats: sessionRun.finish() };
+  } catch (error) {
+    return { stats: sessionRun.finish(), error };
+  }
+}
+
+describe('ReplSession', () => {
+  beforeEach(() => {
+    loads = 0;
+    printed = [];
+  });
+
+  it('keeps declarations and skips statements that did not change', async () => {
+    const session = createSession();
+    await run(session, 'const data = load();\nlet total = data.length;');
+    const { stats } = await run(
+      session,
+      'const data = load();\nlet total = data.length * 2;\ntotal'
+    );
+
+    expect(loads).toBe(1);
+    expect(stats).toEqual({ ran: 1, reused: 1 });
+    expect(session.context.total).toBe(6);
+    expect(printed).toEqual([[6]]);
+  });
+
+  it('runs a statement again until it completes', async () => {
+    const session = createSession();
+    const code = "const data = load();\nthrow new Error('boom');";
+    await run(session, code);
+    const second = await run(session, code);
+
+    expect(loads).toBe(1);
+    expect((second.error as Error).message).toBe('boom');
+    expect(second.stats).toEqual({ ran: 1, reused: 1 });
+  });
+
+  it('declares functions and types again and keeps classes', async () => {
+    const session = createSession();
+    const code = `interface Point { x: number }
+function twice(n: number): number { return n * 2 }
+const size: number = twice(load().length);
+class Box { value = size }`;
+    await run(session, code);
+    await run(session, `${code}\nnew Box().value + twice(1)`);
+
+    expect(loads).toBe(1);
+    expect(printed).toEqual([[8]]);
+  });
+
+  it('runs everything after an edited declaration again', async () => {
+    const session = createSession();
+    await run(
+      session,
+      'const data = load();\nconst total = data.length;\ntotal'
+    );
+    await run(
+      session,
+      'const data = load().slice(1);\nconst total = data.length;\ntotal'
+    );
+    const { stats } = await run(
+      session,
+      'const data = load().slice(1);\nconst total = data.length;\ntotal'
+    );
+
+    expect(loads).toBe(2);
+    expect(session.context.total).toBe(2);
+    expect(printed).toEqual([[3], [2], [2]]);
+    expect(stats).toEqual({ ran: 0, reused: 2 });
+  });
+
+  it('runs the callers of a changed function again', async () => {
+    const session = createSession();
+    const program = (factor: number) => `function scale(n) {
+  return n * ${factor};
+}
+const data = load();
+const total = scale(data.length);`;
+    await run(session, program(2));
+    const { stats } = await run(session, program(3));
+
+    expect(loads).toBe(1);
+    expect(session.context.total).toBe(9);
+    expect(stats).toEqual({ ran: 1, reused: 1 });
+  });
+
+  it('runs every statement again without reuse and keeps the state', async () => {
+    const session = createSession();
+    await run(session, 'const data = load();', false);
+    await run(session, 'const data = load();', false);
+    const { stats } = await run(
+      session,
+      '\nconst size = data.length;\nsize',
+      false
+    );
+
+    expect(loads).toBe(2);
+    expect(stats).toEqual({ ran: 1, reused: 0 });
+    expect(printed).toEqual([[3]]);
+  });
+
+  it('forgets statements removed from the program', async () => {
+    const session = createSession();
+    await run(session, 'const data = load();');
+    await run(session, 'const other = 1;');
+    await run(session, 'const data = load();');
+
+    expect(loads).toBe(2);
+  });
+});
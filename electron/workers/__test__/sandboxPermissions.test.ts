@@ -0,0 +1,112 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect, vi } from 'vitest';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import Module from 'module';
+import { SANDBOX_PROFILES } from '@cheesejs/core';
+import {
+  SandboxPermissionGate,
+  createReadOnlyFs,
+  gatePackageBuiltins,
+} from '../sandboxPermissions';
+
+describe('SandboxPermissionGate', () => {
+  it('asks once per capability when the profile prompts', () => {
+    const prompt = vi.fn().mockReturnValueOnce('allow').mockReturnValue('deny');
+    const gate = new SandboxPermissionGate(SANDBOX_PROFILES.ask, prompt);
+
+    expect(gate.isAllowed('fs', "import 'fs'")).toBe(true);
+    expect(gate.isAllowed('fs', "import 'fs/promises'")).toBe(true);
+    expect(gate.isAllowed('net', "import 'http'")).toBe(false);
+
+    expect(prompt).toHaveBeenCalledTimes(2);
+    expect(prompt).toHaveBeenCalledWith('fs', "import 'fs'");
+  });
+
+  it('gates built-ins by capability and passes others through', () => {
+    const gate = new SandboxPermissionGate(SANDBOX_PROFILES.offline);
+    const load = vi.fn(() => ({}));
+
+    expect(() => gate.requireModule('node:https', load)).toThrow(
+      'Permission denied: network access'
+    );
+    expect(gate.requireModule('path', load)).toEqual({});
+    expect(load).toHaveBeenCalledTimes(1);
+  });
+
+  it('hides process.env when env access is denied', () => {
+    const denied = new SandboxPermissionGate({
+      ...SANDBOX_PROFILES.standard,
+      env: 'deny',
+    });
+    const asked = new SandboxPermissionGate(SANDBOX_PROFILES.ask, () => 'deny');
+
+    expect(denied.createEnv({ SECRET: '1' })).toEqual({});
+    const env = asked.createEnv({ SECRET: '1' });
+    expect(env.SECRET).toBeUndefined();
+    expect(Object.keys(env)).toEqual([]);
+  });
+
+  it('rejects fetch before the request is made', async () => {
+    const fetchImpl = vi.fn();
+    const gate = new SandboxPermissionGate(SANDBOX_PROFILES.offline);
+
+    await expect(
+      gate.wrapFetch(fetchImpl)('https://example.com')
+    ).rejects.toThrow('Permission denied: fetch');
+    expect(fetchImpl).not.toHaveBeenCalled();
+  });
+});
+
+describe('createReadOnlyFs', () => {
+  it('keeps reads and rejects writes, including through fs.promises', () => {
+    const readOnly = createReadOnlyFs(
+      fs as unknown as Record<string, unknown>,
+      'fs'
+    ) as unknown as typeof fs;
+
+    expect(readOnly.readFileSync).toBe(fs.readFileSync);
+    expect(() => readOnly.writeFileSync('x.txt', '')).toThrow(
+      'Permission denied'
+    );
+    expect(() => readOnly.promises.rm('x.txt')).toThrow('fs.promises.rm');
+    expect(() => readOnly.openSync('x.txt', 'w')).toThrow('fs.openSync');
+  });
+});
+
+describe('gatePackageBuiltins', () => {
+  it('checks the built-ins installed packages require', () => {
+    const packages = fs.mkdtempSync(path.join(os.tmpdir(), 'cheesejs-pkgs-'));
+    const packageDirectory = path.join(packages, 'node_modules', 'fetcher');
+    fs.mkdirSync(packageDirectory, { recursive: true });
+    fs.writeFileSync(
+      path.join(packageDirectory, 'index.js'),
+      "module.exports = require('node:http').request;"
+    );
+    const requirePackage = Module.createRequire(
+      path.join(packages, 'index.js')
+    );
+
+    let gate = new SandboxPermissionGate(SANDBOX_PROFILES.offline);
+    const restore = gatePackageBuiltins(
+      () => [packages],
+      () => gate
+    );
+    try {
+      expect(() => requirePackage('fetcher')).toThrow(
+        'Permission denied: network access'
+      );
+      // Other files are not checked
+      expect(() => Module.createRequire(__filename)('http')).not.toThrow();
+
+      gate = new SandboxPermissionGate(SANDBOX_PROFILES.standard);
+      expect(typeof requirePackage('fetcher')).toBe('function');
+    } finally {
+      restore();
+      fs.rmSync(packages, { recursive: true, force: true });
+    }
+  });
+});
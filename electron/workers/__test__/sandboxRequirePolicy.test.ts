@@ -3,6 +3,7 @@
  */
 import { describe, it, expect } from 'vitest';
 import {
+  getModuleCapability,
   isBlockedSandboxModule,
   normalizeModuleSpecifier,
 } from '../sandboxRequirePolicy';
@@ -17,7 +18,6 @@ describe('sandboxRequirePolicy', () => {
   });
 
   it('blocks high-risk built-ins', () => {
-    expect(isBlockedSandboxModule('child_process')).toBe(true);
     expect(isBlockedSandboxModule('node:vm')).toBe(true);
     expect(isBlockedSandboxModule('worker_threads')).toBe(true);
     expect(isBlockedSandboxModule('node:module')).toBe(true);
@@ -29,4 +29,13 @@ describe('sandboxRequirePolicy', () => {
     expect(isBlockedSandboxModule('lodash')).toBe(false);
     expect(isBlockedSandboxModule('./local-file')).toBe(false);
   });
+
+  it('leaves fs, network and process modules to the sandbox profile', () => {
+    expect(isBlockedSandboxModule('child_process')).toBe(false);
+    expect(getModuleCapability('node:child_process')).toBe('childProcess');
+    expect(getModuleCapability('fs/promises')).toBe('fs');
+    expect(getModuleCapability('https')).toBe('net');
+    expect(getModuleCapability('path')).toBeUndefined();
+    expect(getModuleCapability('lodash')).toBeUndefined();
+  });
 });
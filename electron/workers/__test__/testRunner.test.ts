@@ -0,0 +1,137 @@
+/**
+ * @vitest-environment node
+ */
+import vm from 'vm';
+import { describe, it, expect } from 'vitest';
+import type { TestCaseResult } from '@cheesejs/core';
+import { TestCollector, expect as sandboxExpect } from '../testRunner';
+
+/** Declares the tests of `code` in a sandbox and runs them */
+async function runTests(code: string) {
+  const tests = new TestCollector((stack) => {
+    const match = /usercode\.js:(\d+)/.exec(stack);
+    return match ? Number(match[1]) : undefined;
+  });
+  const context = vm.createContext({ ...tests.createGlobals(), log: [] });
+  vm.runInContext(code, context, { filename: 'usercode.js' });
+
+  const results: TestCaseResult[] = [];
+  const summary = await tests.run((result) => results.push(result));
+  return { results, summary, log: context.log as string[] };
+}
+
+function getFailure(assertion: () => unknown) {
+  try {
+    assertion();
+  } catch (error) {
+    return error as { message: string; diff?: string };
+  }
+  throw new Error('expected the assertion to fail');
+}
+
+describe('TestCollector', () => {
+  it('runs nested tests and reports each at its line', async () => {
+    const { results, summary } = await runTests(`describe('math', () => {
+  it('adds', () => expect(1 + 1).toBe(2));
+  it('subtracts', () => expect(3 - 1).toBe(1));
+});
+test.skip('later', () => {});
+it.todo('someday');`);
+
+    expect(
+      results.map(({ name, status, line }) => [name, status, line])
+    ).toEqual([
+      ['math › adds', 'passed', 2],
+      ['math › subtracts', 'failed', 3],
+      ['later', 'skipped', 5],
+      ['someday', 'skipped', 6],
+    ]);
+    expect(results[1].diff).toBe('Expected: 1\nReceived: 2');
+    expect(summary).toMatchObject({ passed: 1, failed: 1, skipped: 2 });
+  });
+
+  it('runs hooks around each test, outer ones first', async () => {
+    const { log } = await runTests(`beforeAll(() => log.push('all'));
+beforeEach(() => log.push('outer'));
+afterEach(() => log.push('/outer'));
+describe('suite', () => {
+  beforeEach(() => log.push('inner'));
+  afterEach(() => log.push('/inner'));
+  it('one', () => log.push('one'));
+});
+afterAll(() => log.push('/all'));`);
+
+    expect(log).toEqual([
+      'all',
+      'outer',
+      'inner',
+      'one',
+      '/inner',
+      '/outer',
+      '/all',
+    ]);
+  });
+
+  it('runs only focused tests once one is marked', async () => {
+    const { results } = await runTests(`it('a', () => {});
+describe.only('b', () => { it('c', () => {}) });
+it.only('d', async () => { await Promise.reject(new TypeError('nope')) });`);
+
+    expect(results.map(({ status }) => status)).toEqual([
+      'skipped',
+      'passed',
+      'failed',
+    ]);
+    expect(results[2].message).toBe('TypeError: nope');
+  });
+
+  it('fails the tests of a suite whose beforeAll threw', async () => {
+    const { results } = await runTests(`describe('db', () => {
+  beforeAll(() => { throw new Error('no connection') });
+  it('reads', () => {});
+});
+it('outside', () => {});`);
+
+    expect(results.map(({ status, message }) => [status, message])).toEqual([
+      ['failed', 'Error: no connection'],
+      ['passed', undefined],
+    ]);
+  });
+});
+
+describe('expect', () => {
+  it('compares values from the sandbox realm', () => {
+    const value = vm.runInNewContext(
+      '({ list: [1, 2], when: new Date(0), extra: undefined })'
+    );
+
+    sandboxExpect(value).toEqual({ list: [1, 2], when: new Date(0) });
+    sandboxExpect(value).toMatchObject({ list: [1, 2] });
+    sandboxExpect(value).toHaveProperty('list.1', 2);
+    sandboxExpect(value.list).toContain(2);
+  });
+
+  it('shows a line diff for multi-line values', () => {
+    const tags = ['a', 'b', 'c', 'd', 'e', 'f'];
+    const failure = getFailure(() =>
+      sandboxExpect({ name: 'brie', tags }).toEqual({ name: 'cheddar', tags })
+    );
+
+    expect(failure.message).toBe('expect(received).toEqual(expected)');
+    expect(failure.diff).toContain("- Expected\n+ Received\n\n  {\n- ");
+    expect(failure.diff).toContain("-   name: 'cheddar',\n+   name: 'brie',");
+  });
+
+  it('supports negation, thrown errors and settled promises', async () => {
+    sandboxExpect(1).not.toBe(2);
+    sandboxExpect(() => JSON.parse('{')).toThrow(SyntaxError);
+    await sandboxExpect(Promise.resolve(3)).resolves.toBeGreaterThan(2);
+    await sandboxExpect(Promise.reject(new Error('boom'))).rejects.toThrow(
+      'boom'
+    );
+
+    expect(getFailure(() => sandboxExpect(1).not.toBe(1)).diff).toBe(
+      'Expected: not 1\nReceived: 1'
+    );
+  });
+});
@@ -0,0 +1,100 @@
+/**
+ * @vitest-environment node
+ */
+import vm from 'vm';
+import { describe, it, expect } from 'vitest';
+import { ValueSerializer } from '../valueSerializer';
+
+describe('ValueSerializer', () => {
+  it('sends the first level and expands deeper ones by handle', () => {
+    const serializer = new ValueSerializer();
+    const value = serializer.serialize({ name: 'Ada', tags: ['a', 'b'] });
+
+    expect(value.kind).toBe('object');
+    expect(value.children?.map((entry) => entry.key)).toEqual([
+      'name',
+      'tags',
+    ]);
+    const tags = value.children![1].value;
+    expect(tags).toMatchObject({ kind: 'array', className: 'Array' });
+    expect(tags.children).toBeUndefined();
+
+    const expanded = serializer.expand(tags.handle!);
+    expect(expanded?.children).toEqual([
+      { key: '0', value: { kind: 'string', preview: "'a'" } },
+      { key: '1', value: { kind: 'string', preview: "'b'" } },
+    ]);
+  });
+
+  it('reuses the handle of objects it has seen, so cycles end', () => {
+    const serializer = new ValueSerializer();
+    const node: Record<string, unknown> = { id: 1 };
+    node.self = node;
+
+    const value = serializer.serialize(node);
+    const self = value.children!.find((entry) => entry.key === 'self')!;
+    expect(self.value.handle).toBe(value.handle);
+    expect(serializer.expand(self.value.handle!)?.children).toHaveLength(2);
+  });
+
+  it('describes maps, sets, errors and class instances', () => {
+    const values = vm.runInNewContext(`
+      class User { constructor() { this.name = 'Ada'; } }
+      const error = new TypeError('bad');
+      error.code = 42;
+      [new Map([[{ k: 1 }, 'v']]), new Set([1]), error, new User(),
+        new Uint8Array([7]), Promise.resolve(1)];
+    `) as unknown[];
+    const serializer = new ValueSerializer();
+    const [map, set, error, user, bytes, promise] = values.map((value) =>
+      serializer.serialize(value)
+    );
+
+    expect(map.kind).toBe('map');
+    expect(map.children![0].keyValue?.kind).toBe('object');
+    expect(map.children![0].value.preview).toBe("'v'");
+    expect(set).toMatchObject({ kind: 'set', className: 'Set' });
+    expect(error.preview).toBe('TypeError: bad');
+    expect(error.children?.map((entry) => entry.key)).toEqual([
+      'message',
+      'stack',
+      'code',
+    ]);
+    expect(user).toMatchObject({ kind: 'object', className: 'User' });
+    expect(bytes.children).toEqual([
+      { key: '0', value: { kind: 'number', preview: '7' } },
+    ]);
+    expect(promise.kind).toBe('promise');
+    expect(promise.handle).toBeUndefined();
+  });
+
+  it('leaves getters unevaluated', () => {
+    let calls = 0;
+    const serializer = new ValueSerializer();
+    const value = serializer.serialize({
+      get expensive() {
+        return ++calls;
+      },
+    });
+
+    expect(value.children![0].value).toEqual({
+      kind: 'accessor',
+      preview: '[Getter]',
+    });
+    expect(calls).toBe(0);
+  });
+
+  it('caps children and counts the rest', () => {
+    const serializer = new ValueSerializer();
+    const value = serializer.serialize(
+      Array.from({ length: 150 }, (_, i) => i)
+    );
+
+    expect(value.children).toHaveLength(100);
+    expect(value.omitted).toBe(50);
+  });
+
+  it('returns null for handles it never gave out', () => {
+    expect(new ValueSerializer().expand(3)).toBeNull();
+  });
+});
@@ -0,0 +1,33 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect } from 'vitest';
+import { InteractiveStdin } from '../wasiModuleRunner';
+
+function readAll(stdin: InteractiveStdin, maxLength = 4): string {
+  const decoder = new TextDecoder();
+  let text = '';
+  for (let chunk = stdin.read(maxLength); chunk.length > 0; ) {
+    text += decoder.decode(chunk);
+    chunk = stdin.read(maxLength);
+  }
+  return text;
+}
+
+describe('InteractiveStdin', () => {
+  it('reads each answer as a line and a dismissed prompt as end-of-file', () => {
+    const answers = ['12345', '', null];
+    const stdin = new InteractiveStdin(() => answers.shift() ?? null);
+
+    expect(readAll(stdin)).toBe('12345\n\n');
+    expect(answers).toEqual([]);
+  });
+
+  it('prompts again when read after end-of-file', () => {
+    const answers = [null, 'again'];
+    const stdin = new InteractiveStdin(() => answers.shift() ?? null);
+
+    expect(stdin.read(16)).toHaveLength(0);
+    expect(new TextDecoder().decode(stdin.read(16))).toBe('again\n');
+  });
+});
@@ -0,0 +1,117 @@
+/**
+ * @vitest-environment node
+ */
+import { describe, it, expect, beforeEach, afterEach } from 'vitest';
+import fs from 'fs/promises';
+import os from 'os';
+import path from 'path';
+import { resolveProjectSources } from '../wasiProject';
+
+describe('resolveProjectSources', () => {
+  let tempDir: string;
+
+  beforeEach(async () => {
+    tempDir = await fs.mkdtemp(path.join(os.tmpdir(), 'wasi-project-test-'));
+  });
+
+  afterEach(async () => {
+    await fs.rm(tempDir, { recursive: true, force: true });
+  });
+
+  async function writeFiles(files: string[]): Promise<void> {
+    for (const file of files) {
+      const filePath = path.join(tempDir, file);
+      await fs.mkdir(path.dirname(filePath), { recursive: true });
+      await fs.writeFile(filePath, '');
+    }
+  }
+
+  it('compiles only the editor program in single-file mode', async () => {
+    await writeFiles(['helper.c']);
+
+    await expect(
+      resolveProjectSources('c', 'single', tempDir)
+    ).resolves.toEqual({ sources: [], includeDirs: [] });
+  });
+
+  it('reads files, directories and include dirs from the manifest', async () => {
+    await writeFiles(['src/b.c', 'src/a.c', 'src/notes.txt', 'lib/util.c']);
+    await fs.writeFile(
+      path.join(tempDir, 'cheesejs.json'),
+      JSON.stringify({
+        sources: ['src', 'lib/util.c', 'src/a.c'],
+        includeDirs: ['include'],
+      })
+    );
+
+    const project = await resolveProjectSources('c', 'manifest', tempDir);
+
+    expect(project.sources).toEqual(
+      ['src/a.c', 'src/b.c', 'lib/util.c'].map((file) =>
+        path.join(tempDir, file)
+      )
+    );
+    expect(project.includeDirs).toEqual([path.join(tempDir, 'include')]);
+  });
+
+  it('rejects manifest entries outside the working directory', async () => {
+    await fs.writeFile(
+      path.join(tempDir, 'cheesejs.json'),
+      JSON.stringify({ sources: ['../elsewhere.c'] })
+    );
+
+    await expect(
+      resolveProjectSources('c', 'manifest', tempDir)
+    ).rejects.toThrow('outside the working directory');
+  });
+
+  it('reports a missing manifest', async () => {
+    await expect(
+      resolveProjectSources('cpp', 'manifest', tempDir)
+    ).rejects.toThrow('cheesejs.json not found');
+  });
+
+  it('collects workspace sources, skipping hidden and build folders', async () => {
+    await writeFiles([
+      'geometry.cpp',
+      'math/vec.cc',
+      'legacy.c',
+      '.cache/gen.cpp',
+      'node_modules/pkg/addon.cpp',
+      'build/out.cpp',
+    ]);
+
+    const project = await resolveProjectSources('cpp', 'workspace', tempDir);
+
+    expect(project.sources).toEqual(
+      ['geometry.cpp', 'math/vec.cc'].map((file) => path.join(tempDir, file))
+    );
+  });
+
+  it('leaves out workspace sources that define main', async () => {
+    await writeFiles(['main.cpp', 'tools/cli.cc', 'math/vec.cc']);
+    // Such as the editor program, saved in the workspace
+    await fs.writeFile(
+      path.join(tempDir, 'main.cpp'),
+      '#include <cstdio>\nint main(int argc, char **argv) {\n  return 0;\n}\n'
+    );
+    await fs.writeFile(
+      path.join(tempDir, 'tools/cli.cc'),
+      'auto main() -> int { return 0; }\n'
+    );
+    await fs.writeFile(
+      path.join(tempDir, 'math/vec.cc'),
+      '// Call from int main() { ... }\nint dot(int a, int b) { return a * b; }\n'
+    );
+
+    const project = await resolveProjectSources('cpp', 'workspace', tempDir);
+
+    expect(project.sources).toEqual([path.join(tempDir, 'math/vec.cc')]);
+  });
+
+  it('needs a working directory for project modes', async () => {
+    await expect(
+      resolveProjectSources('c', 'workspace', undefined)
+    ).rejects.toThrow('working directory');
+  });
+});
@@ -3,8 +3,14 @@
  */
 import { describe, it, expect } from 'vitest';
 import { Worker } from 'worker_threads';
+import fs from 'fs';
+import os from 'os';
 import path from 'path';
-import { isWasiToolchainAvailable } from '../wasiToolchain.js';
+import { SHARED_INPUT_DISMISSED } from '@cheesejs/core';
+import {
+  isRustWasiToolchainAvailable,
+  isWasiToolchainAvailable,
+} from '../wasiToolchain.js';
 
 const JS_WORKER_PATH = path.resolve(
   __dirname,
@@ -31,6 +37,7 @@ function runInWorker(
       workerData: {
         nodeModulesPath: path.resolve(__dirname, '../../../node_modules'),
       },
+      execArgv: ['--experimental-vm-modules'],
     });
 
     const results: any[] = [];
@@ -39,6 +46,7 @@ function runInWorker(
     worker.on('message', (msg) => {
       if (msg.id === id) {
         if (msg.type === 'complete') {
+          results.push(msg);
           worker.terminate();
           resolve(results);
         } else if (msg.type === 'error') {
@@ -106,6 +114,171 @@ describe('JS/TS Worker Integration (dist-electron)', () => {
     expect(errors[0].data.message).toMatch(/Time limit exceeded|timed out/i);
   }, 10000);
 
+  it('should complete only after scheduled timers have fired', async () => {
+    const code = `
+      setTimeout(() => console.log('later'), 50);
+      Promise.resolve().then(() => setTimeout(() => console.log('last'), 80));
+      console.log('now');
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code);
+
+    const logs = results.filter((r) => r.type === 'console');
+    expect(logs.map((r) => r.data.content)).toEqual(['now', 'later', 'last']);
+    const complete = results.find((r) => r.type === 'complete');
+    expect(complete.data.pending).toBeUndefined();
+  });
+
+  it('should report async work still pending at the timeout', async () => {
+    const code = `
+      setInterval(() => {}, 10);
+      new Promise(() => {});
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code, { timeout: 300 });
+
+    const complete = results.find((r) => r.type === 'complete');
+    expect(complete.data.pending).toEqual({
+      timeouts: 0,
+      intervals: 1,
+      promises: 1,
+      requests: 0,
+    });
+  });
+
+  it('should implement counters, timers and groups like Node', async () => {
+    const code = `
+      console.count();
+      console.count();
+      console.countReset();
+      console.count();
+      console.groupCollapsed('outer');
+      console.log('%s has %d items', 'cart', 2);
+      console.groupEnd();
+      console.time('t');
+      console.timeEnd('t');
+      console.timeEnd('missing');
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code);
+
+    const logs = results.filter((r) => r.type === 'console');
+    expect(logs.slice(0, 5).map((r) => r.data)).toEqual([
+      { content: 'default: 1' },
+      { content: 'default: 2' },
+      { content: 'default: 1' },
+      { content: 'outer', groupStart: 'collapsed' },
+      { content: 'cart has 2 items', groupDepth: 1 },
+    ]);
+    expect(logs[5].data.content).toMatch(/^t: \d+\.\d{3}ms$/);
+    expect(logs[6].consoleType).toBe('warn');
+    expect(logs[6].data.content).toBe(
+      "No such label 'missing' for console.timeEnd()"
+    );
+  });
+
+  it('should run import/export code as an ES module', async () => {
+    const code = `
+      import path from 'node:path';
+      import { format } from 'node:util';
+      export const joined = path.posix.join('a', 'b');
+      console.log(format('%s!', joined), import.meta.url.endsWith('.mjs'));
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code, {
+      moduleFormat: 'esm',
+    });
+
+    const logs = results.filter((r) => r.type === 'console');
+    expect(logs.map((r) => r.data.content)).toEqual(['a/b! true']);
+    expect(results.some((r) => r.type === 'complete')).toBe(true);
+  });
+
+  it('should block sandboxed builtins in ES module imports', async () => {
+    const code = `import { Worker } from 'node:worker_threads';`;
+    const results = await runInWorker(JS_WORKER_PATH, code, {
+      moduleFormat: 'esm',
+    });
+
+    const errors = results.filter((r) => r.type === 'error');
+    expect(errors[0].data.message).toContain('not available');
+  });
+
+  it('should deny child processes under the standard profile', async () => {
+    const code = `import { exec } from 'node:child_process';`;
+    const results = await runInWorker(JS_WORKER_PATH, code, {
+      moduleFormat: 'esm',
+    });
+
+    const errors = results.filter((r) => r.type === 'error');
+    expect(errors[0].data.message).toContain('Permission denied');
+  });
+
+  it('should enforce the permissions of the sandbox profile', async () => {
+    const code = `
+      const fs = require('fs');
+      console.log(typeof fs.readFileSync, Object.keys(process.env).length);
+      try {
+        fs.writeFileSync('never.txt', 'x');
+      } catch (error) {
+        console.log(error.message.startsWith('Permission denied'));
+      }
+      await fetch('http://localhost:1').catch((error) =>
+        console.log(error.message.startsWith('Permission denied'))
+      );
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code, {
+      permissions: {
+        fs: 'read',
+        net: 'deny',
+        fetch: 'deny',
+        env: 'deny',
+        childProcess: 'deny',
+      },
+    });
+
+    const logs = results.filter((r) => r.type === 'console');
+    expect(logs.map((r) => r.data.content)).toEqual([
+      'function 0',
+      'true',
+      'true',
+    ]);
+  });
+
+  it('should import open tabs and workspace files', async () => {
+    const workspaceRoot = fs.mkdtempSync(path.join(os.tmpdir(), 'esm-tabs-'));
+    fs.writeFileSync(
+      path.join(workspaceRoot, 'greeting.js'),
+      `export const greet = (name) => 'hello ' + name;`
+    );
+    const code = `
+      import { twice, fail } from './math';
+      import { greet } from './greeting.js';
+      console.log(twice(21), greet('cheese'));
+      fail();
+    `;
+
+    try {
+      const results = await runInWorker(JS_WORKER_PATH, code, {
+        moduleFormat: 'esm',
+        workspaceRoot,
+        entryFile: 'main.ts',
+        moduleFiles: {
+          'math.ts': [
+            'export const twice = (n: number): number => n * 2;',
+            'export function fail(): never {',
+            "  throw new Error('from math');",
+            '}',
+          ].join('\n'),
+        },
+      });
+
+      const logs = results.filter((r) => r.type === 'console');
+      expect(logs.map((r) => r.data.content)).toEqual(['42 hello cheese']);
+      const error = results.find((r) => r.type === 'error');
+      expect(error.data.message).toBe('from math');
+      expect(error.data.location).toMatchObject({ file: 'math.ts' });
+    } finally {
+      fs.rmSync(workspaceRoot, { recursive: true, force: true });
+    }
+  });
+
   it('should support debug() function', async () => {
     // The worker exposes a global debug(line, value)
     const code = `debug(1, 'test-debug');`;
@@ -115,6 +288,56 @@ describe('JS/TS Worker Integration (dist-electron)', () => {
     expect(debugs.length).toBe(1);
     expect(debugs[0].data.content).toBe('test-debug');
   });
+
+  it('should give tests their own timeout and keep finished ones', async () => {
+    const code = `
+      setTimeout(() => {}, 600);
+      test('slow', () => new Promise((resolve) => setTimeout(resolve, 600)));
+      test('stuck', () => new Promise(() => {}));
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code, { timeout: 1000 });
+
+    const tests = results.filter((r) => r.type === 'test');
+    expect(tests.map((r) => [r.data.name, r.data.status])).toEqual([
+      ['slow', 'passed'],
+    ]);
+    const complete = results.find((r) => r.type === 'complete');
+    expect(complete.data.tests).toMatchObject({
+      passed: 1,
+      failed: 0,
+      skipped: 0,
+      timedOut: true,
+    });
+  });
+
+  it('should give benchmarks their own timeout and keep finished ones', async () => {
+    const code = `
+      setTimeout(() => {}, 600);
+      bench('sum', () => 1 + 1, { time: 300, warmup: 10 });
+      bench('stuck', () => new Promise(() => {}));
+    `;
+    const results = await runInWorker(JS_WORKER_PATH, code, { timeout: 1000 });
+
+    const benchmarks = results.filter((r) => r.type === 'benchmark');
+    expect(benchmarks.map((r) => r.data.name)).toEqual(['sum']);
+    const complete = results.find((r) => r.type === 'complete');
+    expect(complete.data.benchmarksTimedOut).toBe(true);
+  });
+
+  it('should print objects as their value tree preview', async () => {
+    const code = `debug(1, 'user', { name: 'Ada', tags: { admin: true } });`;
+    const results = await runInWorker(JS_WORKER_PATH, code);
+
+    const debug = results.find((r) => r.type === 'debug');
+    expect(debug.data.content).toBe("user { name: 'Ada', tags: [Object] }");
+    expect(debug.data.values[1].children).toEqual([
+      { key: 'name', value: { kind: 'string', preview: "'Ada'" } },
+      {
+        key: 'tags',
+        value: expect.objectContaining({ kind: 'object', handle: 1 }),
+      },
+    ]);
+  });
 });
 
 describe('Python Worker Integration (dist-electron)', () => {
@@ -189,5 +412,271 @@ describe.skipIf(!isWasiToolchainAvailable())(
       expect(logs.length).toBeGreaterThan(0);
       expect(logs[0].data.content).toContain('42');
     });
+
+    it('should apply compiler options from the request', async () => {
+      const code =
+        '#include <stdio.h>\nint main(){ printf("%d %ld\\n", ANSWER, __STDC_VERSION__); return 0; }';
+      const results = await runInWorker(
+        WASI_WORKER_PATH,
+        code,
+        { compilerOptions: { cStandard: 'c17', defines: ['ANSWER=42'] } },
+        'c'
+      );
+
+      const logs = results.filter((r) => r.type === 'console');
+      expect(logs[0].data.content).toBe('42 201710');
+    });
+
+    it('should reject compiler flags outside the allowlist', async () => {
+      const code = 'int main(){ return 0; }';
+      const results = await runInWorker(
+        WASI_WORKER_PATH,
+        code,
+        { compilerOptions: { warnings: ['-Wl,--export-all'] } },
+        'c'
+      );
+
+      const errors = results.filter((r) => r.type === 'error');
+      expect(errors[0].data.name).toBe('CompileError');
+      expect(errors[0].data.message).toContain('-Wl,--export-all');
+    });
+
+    it('should post an assembly listing mapped to source lines', async () => {
+      const code = 'int main(){\n  return 7 * 6;\n}';
+      const results = await runInWorker(
+        WASI_WORKER_PATH,
+        code,
+        { emitCompiledOutput: true },
+        'c'
+      );
+
+      const output = results.find((r) => r.type === 'compiled-output');
+      const assembly = output.data.listings.find(
+        (listing: { format: string }) => listing.format === 'assembly'
+      );
+      expect(assembly.text).toContain('main:');
+      expect(assembly.sourceLines).toContain(2);
+    });
+
+    it('should link the sources listed in the project manifest', async () => {
+      const projectDir = fs.mkdtempSync(
+        path.join(os.tmpdir(), 'wasi-project-')
+      );
+      fs.mkdirSync(path.join(projectDir, 'src'));
+      fs.writeFileSync(
+        path.join(projectDir, 'src/answer.c'),
+        'int answer(void) { return 42; }\n'
+      );
+      fs.writeFileSync(
+        path.join(projectDir, 'cheesejs.json'),
+        JSON.stringify({ sources: ['src'] })
+      );
+
+      try {
+        const code =
+          '#include <stdio.h>\nint answer(void);\nint main(){ printf("%d", answer()); return 0; }';
+        const results = await runInWorker(
+          WASI_WORKER_PATH,
+          code,
+          { projectMode: 'manifest', workingDirectory: projectDir },
+          'c'
+        );
+
+        const logs = results.filter((r) => r.type === 'console');
+        expect(logs[0].data.content).toBe('42');
+      } finally {
+        fs.rmSync(projectDir, { recursive: true, force: true });
+      }
+    });
+
+    it('should reuse the compiled module while included headers are unchanged', async () => {
+      const workingDirectory = fs.mkdtempSync(
+        path.join(os.tmpdir(), 'wasi-cache-workdir-')
+      );
+      const compileCacheDir = path.join(workingDirectory, '.cache');
+      const header = path.join(workingDirectory, 'answer.h');
+      const worker = new Worker(WASI_WORKER_PATH, {
+        workerData: { compileCacheDir },
+      });
+      const code =
+        '#include <stdio.h>\n#include <answer.h>\nint main(){ printf("%d\\n", ANSWER); return 0; }';
+      let runs = 0;
+      const run = () =>
+        new Promise<string[]>((resolve) => {
+          const id = `test-cache-${runs++}`;
+          const logs: string[] = [];
+          const onMessage = (msg: any) => {
+            if (msg.id !== id) return;
+            if (msg.type === 'console') logs.push(msg.data.content);
+            if (msg.type === 'complete' || msg.type === 'error') {
+              worker.off('message', onMessage);
+              resolve(logs);
+            }
+          };
+          worker.on('message', onMessage);
+          worker.postMessage({
+            type: 'execute',
+            id,
+            code,
+            language: 'c',
+            options: { timeout: 10000, workingDirectory },
+          });
+        });
+      const accessCounts = () =>
+        JSON.parse(
+          fs.readFileSync(path.join(compileCacheDir, 'manifest.json'), 'utf8')
+        ).entries.map(([, entry]: [string, any]) => entry.accessCount);
+
+      try {
+        fs.writeFileSync(header, '#define ANSWER 42\n');
+        expect(await run()).toEqual(['42']);
+        expect(await run()).toEqual(['42']);
+        expect(accessCounts()).toEqual([2]);
+
+        fs.writeFileSync(header, '#define ANSWER 43\n');
+        expect(await run()).toEqual(['43']);
+      } finally {
+        await worker.terminate();
+        fs.rmSync(workingDirectory, { recursive: true, force: true });
+      }
+    }, 30000);
+
+    it('should stream stdout and stderr as separate console lines', async () => {
+      const code =
+        '#include <stdio.h>\nint main(){ printf("a\\n"); fprintf(stderr, "warn\\n"); printf("b"); return 0; }';
+      const results = await runInWorker(WASI_WORKER_PATH, code, {}, 'c');
+
+      const logs = results.filter(
+        (r) => r.type === 'console' && r.consoleType === 'log'
+      );
+      const errors = results.filter(
+        (r) => r.type === 'console' && r.consoleType === 'error'
+      );
+      expect(logs.map((r) => r.data.content)).toEqual(['a', 'b']);
+      expect(errors.map((r) => r.data.content)).toEqual(['warn']);
+    });
+
+    it('should stop an infinite loop with a TimeoutError', async () => {
+      const code =
+        '#include <stdio.h>\nint main(){ printf("start\\n"); for(;;){} return 0; }';
+      const results = await runInWorker(
+        WASI_WORKER_PATH,
+        code,
+        { timeout: 3000 },
+        'c'
+      );
+
+      const logs = results.filter((r) => r.type === 'console');
+      const errors = results.filter((r) => r.type === 'error');
+      expect(logs[0].data.content).toBe('start');
+      expect(errors[0].data.name).toBe('TimeoutError');
+    }, 15000);
+
+    it('should prompt for stdin and resume with the answer', async () => {
+      const stdinBuffer = new SharedArrayBuffer(1024);
+      const stdinLock = new SharedArrayBuffer(4);
+      const worker = new Worker(WASI_WORKER_PATH, {
+        workerData: { stdinBuffer, stdinLock },
+      });
+      const id = 'test-stdin';
+      const code =
+        '#include <stdio.h>\nint main(){ int n; printf("Number: "); scanf("%d", &n); printf("%d\\n", n * 2); return 0; }';
+
+      const results = await new Promise<any[]>((resolve) => {
+        const messages: any[] = [];
+        worker.on('message', (msg) => {
+          if (msg.id !== id) return;
+          messages.push(msg);
+          if (msg.type === 'prompt-request') {
+            new Uint8Array(stdinBuffer).set(new TextEncoder().encode('21'));
+            const lock = new Int32Array(stdinLock);
+            Atomics.store(lock, 0, 1);
+            Atomics.notify(lock, 0);
+          } else if (msg.type === 'complete' || msg.type === 'error') {
+            resolve(messages);
+          }
+        });
+        worker.postMessage({
+          type: 'execute',
+          id,
+          code,
+          language: 'c',
+          options: { timeout: 5000 },
+        });
+      });
+      await worker.terminate();
+
+      const prompts = results.filter((r) => r.type === 'prompt-request');
+      const logs = results.filter((r) => r.type === 'console');
+      expect(prompts[0].message).toBe('Number:');
+      expect(logs.map((r) => r.data.content)).toEqual(['Number: ', '42']);
+      expect(results[results.length - 1].type).toBe('complete');
+    }, 15000);
+
+    it('should end stdin when a prompt is dismissed', async () => {
+      const stdinBuffer = new SharedArrayBuffer(1024);
+      const stdinLock = new SharedArrayBuffer(4);
+      const worker = new Worker(WASI_WORKER_PATH, {
+        workerData: { stdinBuffer, stdinLock },
+      });
+      const id = 'test-stdin-eof';
+      const code =
+        '#include <cstdio>\nint main(){ int n, sum = 0; printf("Numbers: "); while (scanf("%d", &n) == 1) sum += n; printf("%d\\n", sum); return 0; }';
+      // null stands for the prompt being dismissed
+      const answers = ['1', '2', null];
+
+      const results = await new Promise<any[]>((resolve) => {
+        const messages: any[] = [];
+        worker.on('message', (msg) => {
+          if (msg.id !== id) return;
+          messages.push(msg);
+          if (msg.type === 'prompt-request') {
+            const answer = answers.shift();
+            new Uint8Array(stdinBuffer).fill(0);
+            if (answer != null) {
+              new Uint8Array(stdinBuffer).set(
+                new TextEncoder().encode(answer)
+              );
+            }
+            const lock = new Int32Array(stdinLock);
+            Atomics.store(lock, 0, answer == null ? SHARED_INPUT_DISMISSED : 1);
+            Atomics.notify(lock, 0);
+          } else if (msg.type === 'complete' || msg.type === 'error') {
+            resolve(messages);
+          }
+        });
+        worker.postMessage({
+          type: 'execute',
+          id,
+          code,
+          language: 'cpp',
+          options: { timeout: 5000 },
+        });
+      });
+      await worker.terminate();
+
+      const prompts = results.filter((r) => r.type === 'prompt-request');
+      const logs = results.filter((r) => r.type === 'console');
+      // The C++ program's prompt is shown before it blocks on input too
+      expect(prompts[0].message).toBe('Numbers:');
+      expect(prompts).toHaveLength(3);
+      expect(logs[logs.length - 1].data.content).toBe('3');
+      expect(results[results.length - 1].type).toBe('complete');
+    }, 15000);
+  }
+);
+
+describe.skipIf(!isRustWasiToolchainAvailable())(
+  'WASI Rust Worker Integration (dist-electron)',
+  () => {
+    it('should compile and execute simple Rust', async () => {
+      const code =
+        'fn main() { let v: Vec<i32> = (1..=3).collect(); println!("{}", v.iter().sum::<i32>()); }';
+      const results = await runInWorker(WASI_WORKER_PATH, code, {}, 'rust');
+
+      const logs = results.filter((r) => r.type === 'console');
+      expect(logs.length).toBeGreaterThan(0);
+      expect(logs[0].data.content).toContain('6');
+    }, 30000);
   }
 );
@@ -0,0 +1,195 @@
+/**
+ * Micro-benchmarks for JS/TS programs
+ *
+ * `bench(name, fn)` queues a function to time once the program has
+ * finished. Each benchmark warms up, then takes timed samples until its
+ * time budget is spent, and the statistics of the samples are reported at
+ * the line `bench()` was called on. The timing loops live here, outside
+ * the transformed program, so loop protection never counts them.
+ */
+
+import type { BenchmarkResult } from '@cheesejs/core';
+
+/** Options of one `bench()` call, in milliseconds */
+export interface BenchOptions {
+  /** Time spent taking samples */
+  time?: number;
+  /** Time spent running the function before sampling starts */
+  warmup?: number;
+}
+
+interface Benchmark {
+  name: string;
+  fn: () => unknown;
+  line?: number;
+  time: number;
+  warmup: number;
+}
+
+const DEFAULT_TIME = 500;
+const DEFAULT_WARMUP = 100;
+const MIN_SAMPLES = 10;
+/**
+ * Fast functions are timed in batches lasting at least this many
+ * milliseconds, so the clock's own cost does not dominate a sample
+ */
+const MIN_BATCH_TIME = 0.5;
+
+/** Student's t at 95%, two-sided, for 1 to 30 degrees of freedom */
+const T_CRITICAL_VALUES = [
+  12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+  2.201, 2.179, 2.16, 2.145, 2.131, 2.12, 2.11, 2.101, 2.093, 2.086, 2.08,
+  2.074, 2.069, 2.064, 2.06, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+/** The normal distribution's, used beyond the table */
+const Z_CRITICAL_VALUE = 1.96;
+
+function percentile(sorted: number[], p: number): number {
+  const rank = Math.ceil((p / 100) * sorted.length) - 1;
+  return sorted[Math.min(sorted.length - 1, Math.max(0, rank))];
+}
+
+/**
+ * Statistics of a benchmark from its samples, each the milliseconds one
+ * call took on average within its batch
+ */
+export function summarizeSamples(
+  name: string,
+  samples: number[],
+  iterations: number
+): BenchmarkResult {
+  const count = samples.length;
+  const sorted = [...samples].sort((a, b) => a - b);
+  const mean = samples.reduce((sum, sample) => sum + sample, 0) / count;
+  const variance =
+    count > 1
+      ? samples.reduce((sum, sample) => sum + (sample - mean) ** 2, 0) /
+        (count - 1)
+      : 0;
+  const critical = T_CRITICAL_VALUES[count - 2] ?? Z_CRITICAL_VALUE;
+  const margin = critical * Math.sqrt(variance / count);
+
+  return {
+    name,
+    opsPerSecond: 1000 / mean,
+    mean,
+    p50: percentile(sorted, 50),
+    p99: percentile(sorted, 99),
+    marginOfError: (margin / mean) * 100,
+    samples: count,
+    iterations,
+  };
+}
+
+function isThenable(value: unknown): value is PromiseLike<unknown> {
+  return typeof (value as PromiseLike<unknown> | null)?.then === 'function';
+}
+
+function timeBatch(fn: () => unknown, size: number): number {
+  const start = performance.now();
+  for (let i = 0; i < size; i++) {
+    fn();
+  }
+  return performance.now() - start;
+}
+
+async function timeAsyncBatch(
+  fn: () => unknown,
+  size: number
+): Promise<number> {
+  const start = performance.now();
+  for (let i = 0; i < size; i++) {
+    await fn();
+  }
+  return performance.now() - start;
+}
+
+/** Lets timers and worker messages, such as a cancel, through */
+function yieldToEventLoop(): Promise<void> {
+  return new Promise((resolve) => setImmediate(resolve));
+}
+
+/**
+ * The benchmarks one run declares. `locate` finds the editor line of a
+ * call from its stack, for showing results inline.
+ */
+export class BenchmarkRunner {
+  private readonly benchmarks: Benchmark[] = [];
+
+  constructor(
+    private readonly locate: (stack: string) => number | undefined
+  ) {}
+
+  /** Number of benchmarks declared so far */
+  get size(): number {
+    return this.benchmarks.length;
+  }
+
+  /** `bench(name, fn, options?)` for the sandbox */
+  createBench() {
+    return (name: unknown, fn: unknown, options: BenchOptions = {}) => {
+      if (typeof fn !== 'function') {
+        throw new TypeError('bench() needs a function to time');
+      }
+      this.benchmarks.push({
+        name: String(name),
+        fn: fn as () => unknown,
+        line: this.locate(new Error().stack ?? ''),
+        time: options.time ?? DEFAULT_TIME,
+        warmup: options.warmup ?? DEFAULT_WARMUP,
+      });
+    };
+  }
+
+  /**
+   * Times the declared benchmarks one after the other, reporting each as
+   * it finishes. An error thrown by a benchmarked function ends the run.
+   */
+  async run(
+    report: (result: BenchmarkResult) => void,
+    isCancelled: () => boolean = () => false
+  ): Promise<void> {
+    for (const benchmark of this.benchmarks) {
+      const result = await this.measure(benchmark, isCancelled);
+      if (!result) return;
+      report({ ...result, line: benchmark.line });
+    }
+  }
+
+  private async measure(
+    { name, fn, time, warmup }: Benchmark,
+    isCancelled: () => boolean
+  ): Promise<BenchmarkResult | null> {
+    // The first call tells functions returning promises apart
+    const first = fn();
+    const isAsync = isThenable(first);
+    if (isAsync) await first;
+    const timeCalls = isAsync
+      ? (size: number) => timeAsyncBatch(fn, size)
+      : (size: number) => Promise.resolve(timeBatch(fn, size));
+
+    // Warm up, doubling the batch until one lasts long enough to time
+    let batchSize = 1;
+    const warmupEnd = performance.now() + warmup;
+    for (;;) {
+      const elapsed = await timeCalls(batchSize);
+      if (elapsed < MIN_BATCH_TIME) {
+        batchSize *= 2;
+      } else if (performance.now() >= warmupEnd) {
+        break;
+      }
+      await yieldToEventLoop();
+      if (isCancelled()) return null;
+    }
+
+    const samples: number[] = [];
+    const end = performance.now() + time;
+    while (samples.length < MIN_SAMPLES || performance.now() < end) {
+      samples.push((await timeCalls(batchSize)) / batchSize);
+      await yieldToEventLoop();
+      if (isCancelled()) return null;
+    }
+
+    return summarizeSamples(name, samples, samples.length * batchSize);
+  }
+}
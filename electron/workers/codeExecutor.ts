@@ -4,9 +4,18 @@
  * Executes user code in a sandboxed vm context with:
  * - Custom console interception
  * - Debug function for line-numbered output
+ * - Printed values as value trees, expanded on request
  * - Timeout protection
+ * - Completion once timers, intervals and requests have drained
  * - Safe globals whitelist
  * - Package require support
+ * - Native ES modules (`import`/`export`) via vm.SourceTextModule, including
+ *   relative imports of other open tabs and workspace files
+ * - Per-tab sandbox profiles gating fs, network, fetch, env and processes
+ * - REPL sessions that keep their context and skip unchanged statements
+ * - Jest-style `describe`/`it`/`expect`, run once the program has finished
+ * - `bench()` micro-benchmarks, timed after the tests
+ * - CPU profiles of runs, mapped back to editor lines
  */
 
 import { parentPort, workerData } from 'worker_threads';
@@ -15,8 +24,21 @@ import util from 'util';
 import path from 'path';
 import Module from 'module';
 import { createRequire } from 'module';
+import { Console } from 'console';
+import { Writable } from 'stream';
+import { fileURLToPath } from 'url';
 import { config as dotenvConfig } from 'dotenv';
 import { expand as dotenvExpand } from 'dotenv-expand';
+import type {
+  ConsoleOutputData,
+  ExecutionCompletion,
+  SandboxCapability,
+  SandboxDecision,
+  SandboxPermissions,
+  SerializedValue,
+  TestRunSummary,
+} from '@cheesejs/core';
+import { SHARED_INPUT_DISMISSED } from '@cheesejs/core';
 
 const require = createRequire(import.meta.url);
 
@@ -43,16 +65,57 @@ interface ClearCacheMessage {
   packageName?: string;
 }
 
-type WorkerMessage = ExecuteMessage | CancelMessage | ClearCacheMessage;
+interface ExpandValueMessage {
+  type: 'expand-value';
+  id: string;
+  handle: number;
+  requestId: string;
+}
+
+interface ResetSessionMessage {
+  type: 'reset-session';
+  sessionId: string;
+}
+
+type WorkerMessage =
+  | ExecuteMessage
+  | CancelMessage
+  | ClearCacheMessage
+  | ExpandValueMessage
+  | ResetSessionMessage;
 
 interface ExecuteOptions {
   timeout?: number;
   showUndefined?: boolean;
   workingDirectory?: string;
+  /** 'esm' runs the code as a native ES module instead of a script */
+  moduleFormat?: 'commonjs' | 'esm';
+  /** Open tabs by title, importable from the ES module path */
+  moduleFiles?: Record<string, string>;
+  entryFile?: string;
+  /** Base for relative imports when no working directory is set */
+  workspaceRoot?: string;
+  /** What the run may access; unset means the standard profile */
+  permissions?: SandboxPermissions;
+  /** REPL session whose context the run shares (scripts only) */
+  sessionId?: string;
+  /** Run every statement of the session run, reusing none */
+  runAllStatements?: boolean;
+  /** Run under V8's CPU profiler and send the profile */
+  profile?: boolean;
 }
 
 interface ResultMessage {
-  type: 'result' | 'console' | 'debug' | 'error' | 'complete';
+  type:
+    | 'result'
+    | 'console'
+    | 'debug'
+    | 'error'
+    | 'complete'
+    | 'test'
+    | 'benchmark'
+    | 'profile'
+    | 'phase';
   id: string;
   data: unknown;
   line?: number;
@@ -64,13 +127,50 @@ interface ResultMessage {
 let currentExecutionId: string | null = null;
 let isExecuting = false;
 let cancellationRequested = false;
+let activeAsyncWork: AsyncWorkTracker | null = null;
+
+// Permissions of the latest execution, which installed packages, project
+// files loaded through require() and the worker's own fetch are checked
+// against as well
+let activePermissions = new SandboxPermissionGate();
+let activeProjectDirectory: string | undefined;
+
+// Profile the cached packages were loaded under. Packages hold on to the
+// built-ins they required, so they are loaded again when the profile
+// changes, and on every run that may ask.
+let packageCacheProfile: string | null = null;
+
+// Values printed by the latest execution, kept so the renderer can expand
+// them after it completes
+let printedValues: { id: string; serializer: ValueSerializer } | null = null;
+
+// REPL sessions by id, each keeping the context its runs share
+const replSessions = new Map<string, ReplSession>();
 
 // ============================================================================
 // SCRIPT CACHE - Using SmartScriptCache for intelligent memory management
 // ============================================================================
 
-import { getScriptCache } from './SmartScriptCache.js';
+import {
+  SCRIPT_FILENAME,
+  SCRIPT_LINE_OFFSET,
+  getScriptCache,
+} from './SmartScriptCache.js';
 import { isBlockedSandboxModule } from './sandboxRequirePolicy.js';
+import { AsyncWorkTracker } from './AsyncWorkTracker.js';
+import {
+  SandboxPermissionGate,
+  gatePackageBuiltins,
+} from './sandboxPermissions.js';
+import { ValueSerializer } from './valueSerializer.js';
+import { ReplSession, type ReplSessionRun } from './replSession.js';
+import { TestCollector } from './testRunner.js';
+import { BenchmarkRunner } from './benchmark.js';
+import { CpuProfiler, toCpuProfile, type FrameLocator } from './cpuProfile.js';
+import { EsmModuleLoader } from './esmModuleLoader.js';
+import { transpileWithSWC } from '../transpiler/swcTranspiler.js';
+import { BENCH_FUNCTION_NAME } from '../transpiler/codeTransforms.js';
+import { SourceMapper } from '../transpiler/sourceMaps.js';
 
 // Get singleton script cache instance
 const scriptCache = getScriptCache({
@@ -147,72 +247,197 @@ function serializeValue(val: unknown): { content: string; jsType: string } {
 }
 
 /**
- * Create a sandboxed console that forwards to parent
+ * Printed arguments as text plus value trees. Objects are walked once, by
+ * the serializer, and their one-line preview stands in for them in the
+ * text. The trees are only sent when one of them has something to expand.
  */
-function createSandboxConsole(executionId: string): typeof console {
-  const sendConsole = (
+function serializeArgs(
+  serializer: ValueSerializer,
+  args: unknown[]
+): { content: string; values?: SerializedValue[] } {
+  const values = args.map((arg) => serializer.serialize(arg));
+  const content = args
+    .map((arg, index) =>
+      typeof arg === 'object' && arg !== null
+        ? values[index].preview
+        : customInspect(arg)
+    )
+    .join(' ');
+  return ValueSerializer.isExpandable(values)
+    ? { content, values }
+    : { content };
+}
+
+/**
+ * Format `console.time()` durations the way Node does
+ */
+function formatDuration(ms: number): string {
+  if (ms >= 60_000) {
+    const minutes = Math.floor(ms / 60_000);
+    const seconds = ((ms % 60_000) / 1000).toFixed(3).padStart(6, '0');
+    return `${minutes}:${seconds} (m:ss.mmm)`;
+  }
+  if (ms >= 1000) {
+    return `${(ms / 1000).toFixed(3)}s`;
+  }
+  return `${ms.toFixed(3)}ms`;
+}
+
+/**
+ * Render `console.table()` with Node's own implementation
+ */
+function formatTable(data: unknown, properties?: readonly string[]): string {
+  let output = '';
+  const capture = new Writable({
+    write(chunk, _encoding, callback) {
+      output += chunk.toString();
+      callback();
+    },
+  });
+  new Console({ stdout: capture, stderr: capture }).table(data, properties);
+  return output.replace(/\n$/, '');
+}
+
+/**
+ * Create a sandboxed console that forwards to parent. Counters, timers and
+ * group nesting live here, so each execution starts from a clean slate.
+ */
+function createSandboxConsole(
+  executionId: string,
+  serializer: ValueSerializer
+): typeof console {
+  const counts = new Map<string, number>();
+  const timers = new Map<string, number>();
+  let groupDepth = 0;
+
+  const post = (
     type: 'log' | 'warn' | 'error' | 'info' | 'table' | 'dir',
-    args: unknown[]
+    data: ConsoleOutputData
   ) => {
-    const content = args.map((arg) => customInspect(arg)).join(' ');
     parentPort?.postMessage({
       type: 'console',
       id: executionId,
       consoleType: type,
-      data: { content },
+      data: groupDepth > 0 ? { groupDepth, ...data } : data,
     } as ResultMessage);
   };
 
+  // Honour printf-style format strings like Node, e.g. '%s has %d items'
+  const isFormatString = (args: unknown[]): boolean =>
+    typeof args[0] === 'string' && args[0].includes('%');
+  const format = (args: unknown[]): string =>
+    isFormatString(args)
+      ? util.format(...args)
+      : args.map((arg) => customInspect(arg)).join(' ');
+
+  const sendConsole = (
+    type: 'log' | 'warn' | 'error' | 'info' | 'table' | 'dir',
+    args: unknown[]
+  ) => {
+    const data = serializeArgs(serializer, args);
+    if (isFormatString(args)) data.content = util.format(...args);
+    post(type, data);
+  };
+
+  const startGroup = (
+    state: NonNullable<ConsoleOutputData['groupStart']>,
+    labels: unknown[]
+  ) => {
+    if (labels.length > 0) {
+      post('log', { content: format(labels), groupStart: state });
+    }
+    groupDepth++;
+  };
+
+  const elapsed = (label: string, method: string): number | null => {
+    const start = timers.get(label);
+    if (start === undefined) {
+      sendConsole('warn', [`No such label '${label}' for console.${method}()`]);
+      return null;
+    }
+    return performance.now() - start;
+  };
+
   return {
     log: (...args: unknown[]) => sendConsole('log', args),
     warn: (...args: unknown[]) => sendConsole('warn', args),
     error: (...args: unknown[]) => sendConsole('error', args),
     info: (...args: unknown[]) => sendConsole('info', args),
     debug: (...args: unknown[]) => sendConsole('log', args),
-    table: (data: unknown) => {
-      // Format table as ASCII
-      if (Array.isArray(data) || (typeof data === 'object' && data !== null)) {
-        sendConsole('table', [customInspect(data)]);
+    table: (data: unknown, properties?: readonly string[]) => {
+      if (typeof data === 'object' && data !== null) {
+        post('table', { content: formatTable(data, properties) });
       } else {
         sendConsole('log', [data]);
       }
     },
-    dir: (obj: unknown) => sendConsole('dir', [obj]),
+    dir: (obj: unknown, options?: util.InspectOptions) =>
+      post('dir', {
+        content: util.inspect(obj, { customInspect: false, ...options }),
+      }),
     trace: (...args: unknown[]) => {
-      const err = new Error();
-      sendConsole('log', [...args, err.stack]);
+      // Drop the frame for trace() itself, like Node
+      const frames = (new Error().stack ?? '').split('\n').slice(2);
+      const message = args.length > 0 ? `Trace: ${format(args)}` : 'Trace';
+      post('error', { content: [message, ...frames].join('\n') });
     },
-    assert: (condition: boolean, ...args: unknown[]) => {
-      if (!condition) {
-        sendConsole('error', ['Assertion failed:', ...args]);
+    assert: (condition?: boolean, ...args: unknown[]) => {
+      if (condition) return;
+      if (typeof args[0] === 'string') {
+        args[0] = `Assertion failed: ${args[0]}`;
+      } else {
+        args.unshift('Assertion failed');
       }
+      sendConsole('error', args);
     },
     clear: () => {
-      /* no-op */
-    },
-    count: () => {
-      /* simplified */
+      groupDepth = 0;
+      post('log', { content: '', clear: true });
     },
-    countReset: () => {
-      /* simplified */
-    },
-    group: () => {
-      /* no-op */
+    count: (label: unknown = 'default') => {
+      const key = String(label);
+      const count = (counts.get(key) ?? 0) + 1;
+      counts.set(key, count);
+      sendConsole('log', [`${key}: ${count}`]);
     },
-    groupCollapsed: () => {
-      /* no-op */
+    countReset: (label: unknown = 'default') => {
+      const key = String(label);
+      if (counts.has(key)) {
+        counts.set(key, 0);
+      } else {
+        sendConsole('warn', [`Count for '${key}' does not exist`]);
+      }
     },
+    group: (...labels: unknown[]) => startGroup('expanded', labels),
+    groupCollapsed: (...labels: unknown[]) => startGroup('collapsed', labels),
     groupEnd: () => {
-      /* no-op */
+      groupDepth = Math.max(0, groupDepth - 1);
     },
-    time: () => {
-      /* simplified */
+    time: (label: unknown = 'default') => {
+      const key = String(label);
+      if (timers.has(key)) {
+        sendConsole('warn', [
+          `Label '${key}' already exists for console.time()`,
+        ]);
+        return;
+      }
+      timers.set(key, performance.now());
     },
-    timeEnd: () => {
-      /* simplified */
+    timeEnd: (label: unknown = 'default') => {
+      const key = String(label);
+      const duration = elapsed(key, 'timeEnd');
+      if (duration === null) return;
+      timers.delete(key);
+      sendConsole('log', [`${key}: ${formatDuration(duration)}`]);
     },
-    timeLog: () => {
-      /* simplified */
+    timeLog: (label: unknown = 'default', ...data: unknown[]) => {
+      const key = String(label);
+      const duration = elapsed(key, 'timeLog');
+      if (duration === null) return;
+      const prefix = `${key}: ${formatDuration(duration)}`;
+      post('log', {
+        content: data.length > 0 ? `${prefix} ${format(data)}` : prefix,
+      });
     },
     timeStamp: () => {
       /* no-op */
@@ -229,7 +454,11 @@ function createSandboxConsole(executionId: string): typeof console {
 /**
  * Create the debug function for line-numbered output
  */
-function createDebugFunction(executionId: string, showUndefined: boolean) {
+function createDebugFunction(
+  executionId: string,
+  showUndefined: boolean,
+  serializer: ValueSerializer
+) {
   return (line: number, ...args: unknown[]) => {
     // Filter undefined if not showing
     if (!showUndefined && args.length === 1 && args[0] === undefined) {
@@ -244,15 +473,15 @@ function createDebugFunction(executionId: string, showUndefined: boolean) {
       }
     }
 
-    const serialized = args.map((arg) => serializeValue(arg));
-    const content = serialized.map((s) => s.content).join(' ');
-    const jsType = args.length === 1 ? serialized[0].jsType : 'string';
+    const value = args[0];
+    const jsType =
+      args.length !== 1 ? 'string' : value === null ? 'null' : typeof value;
 
     parentPort?.postMessage({
       type: 'debug',
       id: executionId,
       line,
-      data: { content },
+      data: serializeArgs(serializer, args),
       jsType,
     } as ResultMessage);
 
@@ -264,7 +493,7 @@ function createDebugFunction(executionId: string, showUndefined: boolean) {
 /**
  * Create a require function for installed packages
  */
-function createRequireFunction() {
+function createRequireFunction(permissions: SandboxPermissionGate) {
   if (!nodeModulesPath) {
     // Return a function that throws if no packages directory
     return (moduleName: string) => {
@@ -287,14 +516,16 @@ function createRequireFunction() {
       );
     }
 
-    try {
-      return customRequire(moduleName);
-    } catch {
-      // SECURITY: Don't expose system paths in error messages
-      throw new Error(
-        `Cannot find module '${moduleName}'. Please install it first.`
-      );
-    }
+    return permissions.requireModule(moduleName, () => {
+      try {
+        return customRequire(moduleName);
+      } catch {
+        // SECURITY: Don't expose system paths in error messages
+        throw new Error(
+          `Cannot find module '${moduleName}'. Please install it first.`
+        );
+      }
+    });
   };
 }
 
@@ -321,6 +552,18 @@ function clearRequireCache(packageName?: string): void {
   }
 }
 
+/**
+ * Drops cached packages whose built-ins were checked under another profile
+ */
+function preparePackageCache(permissions?: SandboxPermissions): void {
+  const profile = JSON.stringify(permissions ?? null);
+  const prompts = Object.values(permissions ?? {}).includes('prompt');
+  if (profile !== packageCacheProfile || prompts) {
+    clearRequireCache();
+    packageCacheProfile = profile;
+  }
+}
+
 /**
  * Create cancellation check function for cooperative cancellation
  * This is called periodically by loop-protected code to check if execution should stop
@@ -331,6 +574,21 @@ function createCancellationCheckFunction(): () => boolean {
   };
 }
 
+/**
+ * Read the answer the main process wrote to the shared input buffer
+ */
+function readJSInput(sharedBuffer: SharedArrayBuffer): string {
+  const buffer = new Uint8Array(sharedBuffer);
+  // Find first null byte
+  let end = 0;
+  while (end < buffer.length && buffer[end] !== 0) {
+    end++;
+  }
+
+  const decoder = new TextDecoder();
+  return decoder.decode(buffer.slice(0, end));
+}
+
 /**
  * Synchronous prompt implementation using SharedArrayBuffer and Atomics
  */
@@ -354,16 +612,9 @@ function promptImplementation(message?: string): string | null {
   // This blocks the thread until the main process sets lock[0] to 1 and notifies
   Atomics.wait(lock, 0, 0);
 
-  // 4. Read result
-  const buffer = new Uint8Array(jsInputBuffer);
-  // Find first null byte
-  let end = 0;
-  while (end < buffer.length && buffer[end] !== 0) {
-    end++;
-  }
-
-  const decoder = new TextDecoder();
-  return decoder.decode(buffer.slice(0, end));
+  // 4. Read result; a dismissed prompt returns null, as in browsers
+  if (Atomics.load(lock, 0) === SHARED_INPUT_DISMISSED) return null;
+  return readJSInput(jsInputBuffer);
 }
 
 /**
@@ -390,59 +641,133 @@ function alertImplementation(message?: unknown): void {
 }
 
 /**
- * Create the sandboxed context with safe globals
- *
- * SECURITY: The following dangerous globals have been removed:
- * - eval: Allows arbitrary code execution from strings
- * - Function: Constructor can execute arbitrary code
- * - Proxy: Can be used to intercept and modify behavior
- * - Reflect: Low-level operations can bypass security
- *
- * These removalals prevent common VM escape vectors.
+ * Ask whether the run may use a capability its sandbox profile prompts for,
+ * blocking like prompt() until the user answers in the console
  */
-function createSandboxContext(
+function permissionImplementation(
+  capability: SandboxCapability,
+  detail: string
+): SandboxDecision {
+  if (!jsInputBuffer || !jsInputLock || !parentPort) {
+    throw new Error('Permission prompts are not supported in this environment');
+  }
+
+  const lock = new Int32Array(jsInputLock);
+  Atomics.store(lock, 0, 0);
+
+  parentPort.postMessage({
+    type: 'permission-request',
+    id: currentExecutionId,
+    message: detail,
+    capability,
+  });
+
+  Atomics.wait(lock, 0, 0);
+  return readJSInput(jsInputBuffer) === 'allow' ? 'allow' : 'deny';
+}
+
+/**
+ * Read process.env plus the `.env` of the working directory, if any
+ */
+function loadProcessEnv(workingDirectory?: string): NodeJS.ProcessEnv {
+  let processEnv = { ...process.env };
+  if (workingDirectory) {
+    try {
+      const parsedEnv = dotenvConfig({
+        path: path.join(workingDirectory, '.env'),
+      });
+      if (parsedEnv.parsed) {
+        dotenvExpand(parsedEnv);
+        processEnv = { ...processEnv, ...parsedEnv.parsed };
+      }
+    } catch (err) {
+      console.warn('Failed to parse .env from working directory:', err);
+    }
+  }
+  return processEnv;
+}
+
+/**
+ * Globals bound to one execution: output, permissions and the async work
+ * it starts. A REPL session swaps these into its context on every run.
+ */
+function createExecutionGlobals(
   executionId: string,
-  options: ExecuteOptions
-): vm.Context {
-  const sandboxConsole = createSandboxConsole(executionId);
+  options: ExecuteOptions,
+  asyncWork: AsyncWorkTracker,
+  serializer: ValueSerializer,
+  tests: TestCollector,
+  benchmarks: BenchmarkRunner,
+  permissions: SandboxPermissionGate
+): Record<string, unknown> {
   const debugFunc = createDebugFunction(
     executionId,
-    options.showUndefined ?? false
+    options.showUndefined ?? false,
+    serializer
   );
-  const require = createRequireFunction();
-  const checkCancellation = createCancellationCheckFunction();
 
   // CommonJS module support
   const moduleExports = {};
-  const moduleObj = { exports: moduleExports };
 
-  // Safe globals whitelist
-  // NOTE: eval, Function, Proxy, and Reflect are intentionally excluded for security
   const globals: Record<string, unknown> = {
     // Console and debug functions
-    console: sandboxConsole,
+    console: createSandboxConsole(executionId, serializer),
     debug: debugFunc,
     __jsDebug: debugFunc,
 
     // Cancellation checkpoint for cooperative cancellation
-    __checkCancellation__: checkCancellation,
-
-    // Synchronous prompt
-    prompt: promptImplementation,
-    alert: alertImplementation,
+    __checkCancellation__: createCancellationCheckFunction(),
 
     // Package require and CommonJS module support
-    require,
+    require: createRequireFunction(permissions),
     exports: moduleExports,
-    module: moduleObj,
+    module: { exports: moduleExports },
+
+    // Timing functions, Promise and fetch, tracked so completion waits
+    ...asyncWork.createGlobals(),
+
+    // describe/it/test/expect and the lifecycle hooks
+    ...tests.createGlobals(),
 
-    // Timing functions
-    setTimeout,
-    setInterval,
-    clearTimeout,
-    clearInterval,
-    setImmediate,
-    clearImmediate,
+    // bench(), also called by //bench lines
+    bench: benchmarks.createBench(),
+    [BENCH_FUNCTION_NAME]: benchmarks.createBench(),
+
+    // Provide a rudimentary process object for env access
+    process: {
+      env: permissions.createEnv(loadProcessEnv(options.workingDirectory)),
+    },
+  };
+
+  if (typeof globals.fetch === 'function') {
+    globals.fetch = permissions.wrapFetch(globals.fetch as typeof fetch);
+  }
+  return globals;
+}
+
+/**
+ * Create the sandboxed context with safe globals
+ *
+ * SECURITY: The following dangerous globals have been removed:
+ * - eval: Allows arbitrary code execution from strings
+ * - Function: Constructor can execute arbitrary code
+ * - Proxy: Can be used to intercept and modify behavior
+ * - Reflect: Low-level operations can bypass security
+ *
+ * These removalals prevent common VM escape vectors.
+ */
+function createSandboxContext(
+  executionGlobals: Record<string, unknown>
+): vm.Context {
+  // Safe globals whitelist
+  // NOTE: eval, Function, Proxy, and Reflect are intentionally excluded for security
+  const globals: Record<string, unknown> = {
+    // Console, debug, require, timers and the rest of this execution
+    ...executionGlobals,
+
+    // Synchronous prompt
+    prompt: promptImplementation,
+    alert: alertImplementation,
 
     // ========================================================================
     // STANDARD CONSTRUCTORS (ES5-ES2021) - Safe to expose
@@ -466,7 +791,7 @@ function createSandboxContext(
     Map,
     Number,
     Object,
-    Promise,
+    // Promise is provided by the async work tracker
     // Proxy REMOVED - can be used for VM escape
     RangeError,
     ReferenceError,
@@ -543,8 +868,7 @@ function createSandboxContext(
     decodeURIComponent,
     // escape/unescape REMOVED - deprecated and rarely needed
 
-    // Fetch API (if available in Node.js)
-    fetch: typeof fetch !== 'undefined' ? fetch : undefined,
+    // Fetch API (if available in Node.js); fetch itself is tracked above
     Headers: typeof Headers !== 'undefined' ? Headers : undefined,
     Request: typeof Request !== 'undefined' ? Request : undefined,
     Response: typeof Response !== 'undefined' ? Response : undefined,
@@ -586,26 +910,206 @@ function createSandboxContext(
   context.global = context;
   context.self = context;
 
-  // Dotenv loading feature when working directory is provided
-  let processEnv = { ...process.env };
-  if (options.workingDirectory) {
-    try {
-      const parsedEnv = dotenvConfig({
-        path: path.join(options.workingDirectory, '.env'),
-      });
-      if (parsedEnv.parsed) {
-        dotenvExpand(parsedEnv);
-        processEnv = { ...processEnv, ...parsedEnv.parsed };
+  return context;
+}
+
+const SCRIPT_WRAPPER_START = '(async () => {\n';
+
+/**
+ * Run code as a classic script, wrapped in an async IIFE so top-level await
+ * works
+ */
+function runAsScript(
+  code: string,
+  context: vm.Context,
+  timeout: number
+): Promise<unknown> {
+  const wrappedCode = SCRIPT_WRAPPER_START + code + '\n})()';
+
+  // Get cached or create new compiled script
+  const script = getOrCreateScript(wrappedCode);
+
+  return script.runInContext(context, {
+    timeout,
+    displayErrors: true,
+    breakOnSigint: true,
+  });
+}
+
+/**
+ * Strip TypeScript/JSX from a project file imported on the ES module path
+ */
+function transpileModuleFile(code: string, filePath: string): string {
+  const extension = path.extname(filePath);
+  return transpileWithSWC(code, {
+    moduleFormat: 'esm',
+    jsx: extension === '.tsx' || extension === '.jsx',
+    filename: path.basename(filePath),
+  });
+}
+
+function createModuleLoader(
+  context: vm.Context,
+  options: ExecuteOptions
+): EsmModuleLoader {
+  if (typeof vm.SourceTextModule !== 'function') {
+    throw new Error(
+      'ES modules need the worker to run with --experimental-vm-modules'
+    );
+  }
+
+  return new EsmModuleLoader({
+    context,
+    nodeModulesPath,
+    workingDirectory: options.workingDirectory,
+    workspaceRoot: options.workspaceRoot,
+    moduleFiles: options.moduleFiles,
+    entryFile: options.entryFile,
+    requireModule: context.require,
+    transpile: transpileModuleFile,
+    permissions: activePermissions,
+  });
+}
+
+/**
+ * Run code as a native ES module; modules have no completion value
+ */
+async function runAsModule(
+  code: string,
+  loader: EsmModuleLoader,
+  timeout: number
+): Promise<undefined> {
+  await loader.run(code, timeout);
+  return undefined;
+}
+
+/** Source map SWC appended to the editor program, if any */
+function getProgramSourceMap(
+  code: string,
+  moduleLoader: EsmModuleLoader | null
+): SourceMapper | null {
+  if (moduleLoader) {
+    return SourceMapper.fromInlineComment(code, moduleLoader.mainIdentifier);
+  }
+  // Script frames count from the wrapper line, shifted by the script cache
+  const wrapperLines = SCRIPT_WRAPPER_START.split('\n').length - 1;
+  return SourceMapper.fromInlineComment(
+    code,
+    SCRIPT_FILENAME,
+    wrapperLines + SCRIPT_LINE_OFFSET
+  );
+}
+
+// Frames of the worker's own code are folded into the program's in profiles
+const WORKER_DIRECTORY = path.dirname(fileURLToPath(import.meta.url));
+
+/**
+ * Places the frames of a CPU profile: those of the program at editor lines,
+ * those of imported files and packages by file. Frames of the worker, of
+ * Node and of built-ins count towards their callers.
+ */
+function createFrameLocator(
+  code: string,
+  options: ExecuteOptions,
+  moduleLoader: EsmModuleLoader | null
+): FrameLocator {
+  const programFile = moduleLoader?.mainIdentifier ?? SCRIPT_FILENAME;
+  const sourceMap = getProgramSourceMap(code, moduleLoader);
+  const projectDirectory = options.workingDirectory ?? options.workspaceRoot;
+
+  return ({ url, functionName, lineNumber, columnNumber }) => {
+    const name = functionName || '(anonymous)';
+    if (url === programFile) {
+      const position = sourceMap?.originalPosition(
+        lineNumber + 1,
+        columnNumber + 1
+      );
+      // The script wrapper and the module body are the top level
+      if (
+        !position ||
+        (!functionName && lineNumber === 0 && columnNumber === 0)
+      ) {
+        return { name: '(top level)' };
       }
-    } catch (err) {
-      console.warn('Failed to parse .env from working directory:', err);
+      return { name, line: position.line };
+    }
+
+    const file = url.startsWith('file:') ? fileURLToPath(url) : url;
+    if (!path.isAbsolute(file) || file.startsWith(WORKER_DIRECTORY)) {
+      return null;
     }
+    const relative = projectDirectory && path.relative(projectDirectory, file);
+    return {
+      name,
+      line: lineNumber + 1,
+      file:
+        relative && !relative.startsWith('..') ? relative : path.basename(file),
+    };
+  };
+}
+
+/**
+ * The REPL session of a run, created with a fresh context on its first
+ * run. ES modules get a new module graph every run, so they never share.
+ */
+function getReplSession(
+  id: string,
+  options: ExecuteOptions,
+  executionGlobals: Record<string, unknown>
+): ReplSession | null {
+  if (!options.sessionId) return null;
+  if (options.moduleFormat === 'esm') {
+    parentPort?.postMessage({
+      type: 'console',
+      id,
+      consoleType: 'warn',
+      data: {
+        content:
+          'Session state is only kept for scripts; code with import/export starts fresh on every run',
+      },
+    } as ResultMessage);
+    return null;
+  }
+
+  let session = replSessions.get(options.sessionId);
+  if (!session) {
+    session = new ReplSession(createSandboxContext(executionGlobals));
+    replSessions.set(options.sessionId, session);
   }
+  return session;
+}
 
-  // Provide a rudimentary process object for env access
-  context.process = { env: processEnv };
+/** Time left for the tests or benchmarks of a run */
+interface PhaseBudget {
+  /** Resolves once the budget is spent */
+  expired: Promise<undefined>;
+  isExpired: () => boolean;
+  clear: () => void;
+}
 
-  return context;
+/**
+ * Gives a phase that runs after the program its own `timeout`, counted
+ * from now. The main process restarts its fallback timer along with it.
+ */
+function startPhase(
+  id: string,
+  phase: 'tests' | 'benchmarks',
+  timeout: number
+): PhaseBudget {
+  parentPort?.postMessage({ type: 'phase', id, data: phase } as ResultMessage);
+
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  let expired = false;
+  return {
+    expired: new Promise<undefined>((resolve) => {
+      timer = setTimeout(() => {
+        expired = true;
+        resolve(undefined);
+      }, timeout);
+    }),
+    isExpired: () => expired,
+    clear: () => clearTimeout(timer),
+  };
 }
 
 /**
@@ -615,39 +1119,161 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
   const { id, code, options } = message;
   const timeout = options.timeout ?? 30000;
 
+  const startTime = Date.now();
+  const asyncWork = new AsyncWorkTracker();
+
   currentExecutionId = id;
   isExecuting = true;
   cancellationRequested = false; // Reset cancellation flag
+  activeAsyncWork = asyncWork;
+  let moduleLoader: EsmModuleLoader | null = null;
+  const serializer = new ValueSerializer();
+  printedValues = { id, serializer };
+  let sessionRun: ReplSessionRun | null = null;
+  // Tests and benchmarks are located lazily, as the source map is parsed
+  // on first use
+  let programSourceMap: SourceMapper | null | undefined;
+  const locateLine = (stack: string) => {
+    programSourceMap ??= getProgramSourceMap(code, moduleLoader);
+    return programSourceMap?.locate(stack)?.line;
+  };
+  const tests = new TestCollector(locateLine);
+  const benchmarks = new BenchmarkRunner(locateLine);
+  let profiler: CpuProfiler | null = null;
 
   try {
-    const context = createSandboxContext(id, options);
+    activePermissions = new SandboxPermissionGate(
+      options.permissions,
+      permissionImplementation
+    );
+    activeProjectDirectory = options.workingDirectory ?? options.workspaceRoot;
+    preparePackageCache(options.permissions);
+    const executionGlobals = createExecutionGlobals(
+      id,
+      options,
+      asyncWork,
+      serializer,
+      tests,
+      benchmarks,
+      activePermissions
+    );
+    const session = getReplSession(id, options, executionGlobals);
+    sessionRun =
+      session?.startRun(executionGlobals, !options.runAllStatements) ?? null;
+    const context = session?.context ?? createSandboxContext(executionGlobals);
+    if (options.moduleFormat === 'esm') {
+      moduleLoader = createModuleLoader(context, options);
+    }
 
-    // Wrap code in async IIFE to support top-level await
-    const wrappedCode = '(async () => {\n' + code + '\n})()';
+    // The profile covers the program and the callbacks it leaves behind,
+    // but not its tests and benchmarks
+    if (options.profile) {
+      profiler = new CpuProfiler();
+      await profiler.start();
+    }
 
-    // Get cached or create new compiled script
-    const script = getOrCreateScript(wrappedCode);
+    const execution = moduleLoader
+      ? runAsModule(code, moduleLoader, timeout)
+      : runAsScript(code, context, timeout);
 
     // Run with timeout
-    const result = await Promise.race([
-      script.runInContext(context, {
-        timeout,
-        displayErrors: true,
-        breakOnSigint: true,
-      }),
-      new Promise((_, reject) => {
-        setTimeout(
-          () => reject(new Error(`Execution timeout(${timeout}ms)`)),
-          timeout + 100
-        );
-      }),
-    ]);
+    const timedOut = new Promise<never>((_, reject) => {
+      setTimeout(
+        () => reject(new Error(`Execution timeout(${timeout}ms)`)),
+        timeout + 100
+      );
+    });
+    const result = await Promise.race([execution, timedOut]);
+
+    // Callbacks scheduled by the snippet may still log, so only complete once
+    // they have drained or the time budget is spent
+    await asyncWork.waitForIdle(timeout - (Date.now() - startTime));
+    if (asyncWork.isDisposed) {
+      // Cancelled while waiting; the cancel handler already reported it
+      return;
+    }
+
+    if (profiler) {
+      const profile = await profiler.stop();
+      profiler = null;
+      parentPort?.postMessage({
+        type: 'profile',
+        id,
+        data: toCpuProfile(
+          profile,
+          createFrameLocator(code, options, moduleLoader)
+        ),
+      } as ResultMessage);
+    }
+
+    const completion: ExecutionCompletion = {
+      ...(result !== undefined ? serializeValue(result) : {}),
+      pending: asyncWork.getPending(),
+    };
+    if (sessionRun) completion.session = sessionRun.finish();
+    sessionRun = null;
+
+    // Tests declared by the program run now, each reported at its line.
+    // Out of time, the summary covers the tests that finished.
+    if (tests.size > 0) {
+      const testsStart = performance.now();
+      const budget = startPhase(id, 'tests', timeout);
+      const finished: TestRunSummary = {
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        duration: 0,
+      };
+      const testRun = tests.run(
+        (test) => {
+          if (budget.isExpired()) return;
+          finished[test.status]++;
+          parentPort?.postMessage({
+            type: 'test',
+            id,
+            line: test.line,
+            data: test,
+          } as ResultMessage);
+        },
+        () => asyncWork.isDisposed || budget.isExpired()
+      );
+      const summary = await Promise.race([testRun, budget.expired]);
+      budget.clear();
+      if (asyncWork.isDisposed) return;
+      completion.tests = summary ?? {
+        ...finished,
+        duration: Math.round(performance.now() - testsStart),
+        timedOut: true,
+      };
+    }
+
+    // Benchmarks run last, so nothing else competes with their timing.
+    // Out of time, those already reported are kept.
+    if (benchmarks.size > 0) {
+      const budget = startPhase(id, 'benchmarks', timeout);
+      const benchmarkRun = benchmarks.run(
+        (benchmark) => {
+          if (budget.isExpired()) return;
+          parentPort?.postMessage({
+            type: 'benchmark',
+            id,
+            line: benchmark.line,
+            data: benchmark,
+          } as ResultMessage);
+        },
+        () => asyncWork.isDisposed || budget.isExpired()
+      );
+      await Promise.race([benchmarkRun, budget.expired]);
+      budget.clear();
+      if (asyncWork.isDisposed) return;
+      if (budget.isExpired()) completion.benchmarksTimedOut = true;
+    }
 
     // Send completion
     parentPort?.postMessage({
       type: 'complete',
       id,
-      data: result !== undefined ? serializeValue(result) : null,
+      data: completion,
     } as ResultMessage);
   } catch (error) {
     // Enhance SyntaxError messages that might be caused by the wrapper
@@ -661,9 +1287,20 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
       }
     }
 
+    // Errors raised in an imported tab or workspace file point at it,
+    // those in the editor program at the line and column it was written on
+    const stack = error instanceof Error ? error.stack : undefined;
+    const sourceMap = stack ? getProgramSourceMap(code, moduleLoader) : null;
     const errorMessage =
       error instanceof Error
-        ? { name: error.name, message: error.message, stack: error.stack }
+        ? {
+            name: error.name,
+            message: error.message,
+            stack: stack && sourceMap ? sourceMap.remapStack(stack) : stack,
+            location:
+              moduleLoader?.locateError(stack) ??
+              (stack ? sourceMap?.locate(stack) : undefined),
+          }
         : { name: 'Error', message: String(error) };
 
     parentPort?.postMessage({
@@ -672,11 +1309,37 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
       data: errorMessage,
     } as ResultMessage);
   } finally {
-    currentExecutionId = null;
-    isExecuting = false;
+    sessionRun?.finish();
+    profiler?.dispose();
+    asyncWork.dispose();
+    if (activeAsyncWork === asyncWork) {
+      activeAsyncWork = null;
+      currentExecutionId = null;
+      isExecuting = false;
+    }
   }
 }
 
+// Installed packages and project files loaded through require() run outside
+// the sandbox context, so the built-ins they require and the fetch they
+// call are checked here. A project holding the worker itself is left out.
+gatePackageBuiltins(
+  () =>
+    [
+      nodeModulesPath && path.dirname(nodeModulesPath),
+      activeProjectDirectory,
+    ].filter(
+      (directory): directory is string =>
+        !!directory && !WORKER_DIRECTORY.startsWith(directory)
+    ),
+  () => activePermissions
+);
+if (typeof globalThis.fetch === 'function') {
+  const workerFetch = globalThis.fetch;
+  globalThis.fetch = ((...args: Parameters<typeof fetch>) =>
+    activePermissions.wrapFetch(workerFetch)(...args)) as typeof fetch;
+}
+
 // Message handler
 parentPort?.on('message', async (message: WorkerMessage) => {
   if (message.type === 'execute') {
@@ -694,12 +1357,25 @@ parentPort?.on('message', async (message: WorkerMessage) => {
         data: { name: 'CancelError', message: 'Execution cancelled by user' },
       } as ResultMessage);
 
-      // Reset state
+      // Reset state and stop timers the snippet left behind
+      activeAsyncWork?.dispose();
+      activeAsyncWork = null;
       currentExecutionId = null;
       isExecuting = false;
     }
   } else if (message.type === 'clear-cache') {
     clearRequireCache(message.packageName);
+  } else if (message.type === 'reset-session') {
+    replSessions.delete(message.sessionId);
+  } else if (message.type === 'expand-value') {
+    const serializer =
+      printedValues?.id === message.id ? printedValues.serializer : null;
+    parentPort?.postMessage({
+      type: 'value-children',
+      id: message.id,
+      requestId: message.requestId,
+      data: serializer?.expand(message.handle) ?? null,
+    });
   }
 });
 
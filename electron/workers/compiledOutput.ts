@@ -0,0 +1,83 @@
+/**
+ * Compiled Output Listings
+ *
+ * Prepares WAT and LLVM assembly text for the "show compiled output" pane.
+ * Assembly is compiled with `-g` so `.loc` directives map each instruction
+ * back to a source line; the directives themselves and the DWARF sections
+ * are dropped to keep the listing readable.
+ */
+
+import type { CompiledOutputListing } from '@cheesejs/core';
+
+/** Listings beyond this are cut; a linked libc can produce huge WAT. */
+export const MAX_LISTING_LINES = 20000;
+
+// .file "program.c"   .file 1 "program.c"   .file 1 "/tmp/dir" "program.c"
+const FILE_DIRECTIVE_PATTERN =
+  /^\s*\.file\s+(?:(\d+)\s+)?"([^"]*)"(?:\s+"([^"]*)")?/;
+// .loc 1 4 3 prologue_end
+const LOC_DIRECTIVE_PATTERN = /^\s*\.loc\s+(\d+)\s+(\d+)/;
+const DEBUG_SECTION_PATTERN = /^\s*\.section\s+\.debug_/;
+const DEBUG_LABEL_PATTERN = /^\.Ltmp\d+:/;
+
+export function truncateListing(text: string): string {
+  const lines = text.split('\n');
+  if (lines.length <= MAX_LISTING_LINES) {
+    return text;
+  }
+
+  return [
+    ...lines.slice(0, MAX_LISTING_LINES),
+    `;; ... ${lines.length - MAX_LISTING_LINES} more lines not shown`,
+  ].join('\n');
+}
+
+function isSourceFile(file: string, sourceFileName: string): boolean {
+  return file === sourceFileName || file.endsWith(`/${sourceFileName}`);
+}
+
+export function parseAssemblyListing(
+  assembly: string,
+  sourceFileName: string
+): CompiledOutputListing {
+  const sourceFileIds = new Set<string>();
+  const lines: string[] = [];
+  const sourceLines: (number | null)[] = [];
+  let currentLine: number | null = null;
+
+  for (const line of assembly.split('\n')) {
+    // DWARF sections follow the code and are not meant to be read
+    if (DEBUG_SECTION_PATTERN.test(line)) break;
+    if (DEBUG_LABEL_PATTERN.test(line)) continue;
+
+    const file = FILE_DIRECTIVE_PATTERN.exec(line);
+    if (file) {
+      if (file[1] && isSourceFile(file[3] ?? file[2], sourceFileName)) {
+        sourceFileIds.add(file[1]);
+      }
+      continue;
+    }
+
+    const loc = LOC_DIRECTIVE_PATTERN.exec(line);
+    if (loc) {
+      const lineNumber = Number(loc[2]);
+      // Line 0 marks compiler-generated code with no source position
+      currentLine =
+        sourceFileIds.has(loc[1]) && lineNumber > 0 ? lineNumber : null;
+      continue;
+    }
+
+    lines.push(line);
+    sourceLines.push(line.trim() ? currentLine : null);
+  }
+
+  const text = lines.join('\n').trimEnd();
+  const lineCount = Math.min(text.split('\n').length, MAX_LISTING_LINES);
+  return {
+    format: 'assembly',
+    text: truncateListing(text),
+    ...(sourceFileIds.size > 0
+      ? { sourceLines: sourceLines.slice(0, lineCount) }
+      : {}),
+  };
+}
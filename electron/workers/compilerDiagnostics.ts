@@ -0,0 +1,218 @@
+/**
+ * Compiler Diagnostics Parser
+ *
+ * Turns clang and rustc output into structured diagnostics for the editor.
+ * clang is run with `-fdiagnostics-print-source-range-info` and
+ * `-fdiagnostics-parseable-fixits` and parsed from text (its JSON format is
+ * not available in release builds); rustc emits JSON with
+ * `--error-format=json`.
+ *
+ * Callers strip the temp and working directories from the output first, so
+ * the editor program is reported by its bare file name, other project files
+ * by their relative path, and system headers keep absolute paths.
+ */
+
+import path from 'node:path';
+
+import type {
+  CompilerDiagnostic,
+  CompilerDiagnosticSeverity,
+  CompilerFixIt,
+} from '@cheesejs/core';
+
+export interface ParsedCompilerOutput {
+  /** Diagnostics located in the editor program or other project files */
+  diagnostics: CompilerDiagnostic[];
+  /** Human-readable output, as the compiler would print it to a terminal */
+  text: string;
+}
+
+// program.c:3:5:{3:5-3:9}: error: message [-Wflag]
+const CLANG_DIAGNOSTIC_PATTERN =
+  /^(.+?):(\d+):(\d+):((?:\{\d+:\d+-\d+:\d+\})*):? (fatal error|error|warning|note): (.*?)(?: \[([^\]]+)\])?$/;
+const CLANG_RANGE_PATTERN = /\{(\d+):(\d+)-(\d+):(\d+)\}/;
+// fix-it:"program.c":{3:5-3:5}:";"
+const CLANG_FIXIT_PATTERN =
+  /^fix-it:"(.+?)":\{(\d+):(\d+)-(\d+):(\d+)\}:"(.*)"$/;
+
+/**
+ * Where a diagnostic belongs: `{}` for the editor program, `{ file }` for
+ * another project file, or null for toolchain headers and built-ins.
+ */
+function locateFile(
+  file: string,
+  sourceFileName: string
+): { file?: string } | null {
+  if (file === sourceFileName) return {};
+  if (path.isAbsolute(file) || file.startsWith('<')) return null;
+  return { file };
+}
+
+function unescapeClangString(value: string): string {
+  return value.replace(/\\(.)/g, (_match, char: string) =>
+    char === 'n' ? '\n' : char === 't' ? '\t' : char
+  );
+}
+
+function toClangSeverity(level: string): CompilerDiagnosticSeverity {
+  if (level === 'warning' || level === 'note') return level;
+  return 'error';
+}
+
+export function parseClangDiagnostics(
+  output: string,
+  sourceFileName: string
+): ParsedCompilerOutput {
+  const diagnostics: CompilerDiagnostic[] = [];
+  const textLines: string[] = [];
+  let current: CompilerDiagnostic | null = null;
+
+  for (const line of output.split('\n')) {
+    const fixIt = CLANG_FIXIT_PATTERN.exec(line);
+    if (fixIt) {
+      // Fix-its can only be applied to the program open in the editor
+      if (current && !current.file && fixIt[1] === sourceFileName) {
+        current.fixIts = [
+          ...(current.fixIts ?? []),
+          {
+            line: Number(fixIt[2]),
+            column: Number(fixIt[3]),
+            endLine: Number(fixIt[4]),
+            endColumn: Number(fixIt[5]),
+            replacement: unescapeClangString(fixIt[6]),
+          },
+        ];
+      }
+      continue;
+    }
+
+    const match = CLANG_DIAGNOSTIC_PATTERN.exec(line);
+    if (!match) {
+      textLines.push(line);
+      continue;
+    }
+
+    const [, file, lineNo, column, ranges, level, message, code] = match;
+    // The range info is for tooling; keep the terminal text readable
+    textLines.push(ranges ? line.replace(`:${ranges}:`, ':') : line);
+
+    const location = locateFile(file, sourceFileName);
+    if (!location) {
+      current = null;
+      continue;
+    }
+
+    current = {
+      severity: toClangSeverity(level),
+      message,
+      ...location,
+      line: Number(lineNo),
+      column: Number(column),
+      ...(code ? { code } : {}),
+    };
+
+    const range = CLANG_RANGE_PATTERN.exec(ranges);
+    if (range) {
+      current.endLine = Number(range[3]);
+      // clang ranges end on the last character; markers end after it
+      current.endColumn = Number(range[4]) + 1;
+    }
+
+    diagnostics.push(current);
+  }
+
+  return { diagnostics, text: textLines.join('\n').trim() };
+}
+
+interface RustSpan {
+  file_name: string;
+  line_start: number;
+  line_end: number;
+  column_start: number;
+  column_end: number;
+  is_primary: boolean;
+  suggested_replacement: string | null;
+}
+
+interface RustDiagnostic {
+  $message_type?: string;
+  message: string;
+  code: { code: string } | null;
+  level: string;
+  spans: RustSpan[];
+  children: RustDiagnostic[];
+  rendered: string | null;
+}
+
+function toRustSeverity(level: string): CompilerDiagnosticSeverity {
+  if (level === 'warning') return 'warning';
+  if (level.startsWith('error')) return 'error';
+  return 'note';
+}
+
+function collectRustFixIts(
+  diagnostic: RustDiagnostic,
+  sourceFileName: string
+): CompilerFixIt[] {
+  return diagnostic.children.flatMap((child) =>
+    child.spans
+      .filter(
+        (span) =>
+          span.suggested_replacement !== null &&
+          span.file_name === sourceFileName
+      )
+      .map((span) => ({
+        line: span.line_start,
+        column: span.column_start,
+        endLine: span.line_end,
+        endColumn: span.column_end,
+        replacement: span.suggested_replacement ?? '',
+      }))
+  );
+}
+
+export function parseRustcDiagnostics(
+  output: string,
+  sourceFileName: string
+): ParsedCompilerOutput {
+  const diagnostics: CompilerDiagnostic[] = [];
+  const textParts: string[] = [];
+
+  for (const line of output.split('\n')) {
+    if (!line.trim()) continue;
+
+    let diagnostic: RustDiagnostic;
+    try {
+      diagnostic = JSON.parse(line) as RustDiagnostic;
+    } catch {
+      // Linker errors and other tool output are not JSON
+      textParts.push(line);
+      continue;
+    }
+
+    if (diagnostic.rendered) {
+      textParts.push(diagnostic.rendered.trimEnd());
+    }
+
+    const primary = diagnostic.spans.find((span) => span.is_primary);
+    const location = primary && locateFile(primary.file_name, sourceFileName);
+    if (!primary || !location) continue;
+
+    const fixIts = location.file
+      ? []
+      : collectRustFixIts(diagnostic, sourceFileName);
+    diagnostics.push({
+      severity: toRustSeverity(diagnostic.level),
+      message: diagnostic.message,
+      ...location,
+      line: primary.line_start,
+      column: primary.column_start,
+      endLine: primary.line_end,
+      endColumn: primary.column_end,
+      ...(diagnostic.code ? { code: diagnostic.code.code } : {}),
+      ...(fixIts.length > 0 ? { fixIts } : {}),
+    });
+  }
+
+  return { diagnostics, text: textParts.join('\n').trim() };
+}
@@ -0,0 +1,174 @@
+/**
+ * CPU profiles of JS/TS runs
+ *
+ * The worker profiles itself through a `node:inspector` session it owns;
+ * the sandbox cannot require `inspector`, so the program never sees it.
+ * V8's call tree is reduced to the frames the caller can place, such as
+ * those of the program and the files it imported. Frames of the worker
+ * and of Node itself are folded into their callers.
+ */
+
+import inspector from 'inspector';
+import type { CpuProfile, ProfileNode } from '@cheesejs/core';
+
+/** Where a frame is shown, or null to fold it into its caller */
+export type FrameLocator = (
+  frame: inspector.Runtime.CallFrame
+) => Pick<ProfileNode, 'name' | 'line' | 'file'> | null;
+
+/** Microseconds between samples; V8's default of 1 ms misses short runs */
+const SAMPLING_INTERVAL = 100;
+
+// V8's own entries: time outside any function, garbage collection, and
+// waiting for work, which is not CPU time at all
+const PROGRAM_FRAME = '(program)';
+const GC_FRAME = '(garbage collector)';
+const IDLE_FRAME = '(idle)';
+
+/** Milliseconds each node was sampled in, from the gaps between samples */
+function getSelfTimes(
+  profile: inspector.Profiler.Profile
+): Map<number, number> {
+  const selfTimes = new Map<number, number>();
+  const { samples = [], timeDeltas = [] } = profile;
+  let timestamp = profile.startTime;
+  for (let i = 0; i < samples.length; i++) {
+    timestamp += timeDeltas[i] ?? 0;
+    // A sample lasts until the next one, the last until the profile ends
+    const next =
+      i + 1 < samples.length
+        ? timestamp + (timeDeltas[i + 1] ?? 0)
+        : profile.endTime;
+    const duration = Math.max(0, next - timestamp) / 1000;
+    selfTimes.set(samples[i], (selfTimes.get(samples[i]) ?? 0) + duration);
+  }
+  return selfTimes;
+}
+
+/** Merges siblings for the same function, e.g. across folded frames */
+function mergeSiblings(nodes: ProfileNode[]): ProfileNode[] {
+  const merged = new Map<string, ProfileNode>();
+  for (const node of nodes) {
+    const key = `${node.name}\0${node.file ?? ''}\0${node.line ?? ''}`;
+    const existing = merged.get(key);
+    if (!existing) {
+      merged.set(key, node);
+      continue;
+    }
+    existing.selfTime += node.selfTime;
+    existing.totalTime += node.totalTime;
+    existing.children = mergeSiblings([
+      ...existing.children,
+      ...node.children,
+    ]);
+  }
+  return [...merged.values()].sort((a, b) => b.totalTime - a.totalTime);
+}
+
+function createNode(
+  frame: Pick<ProfileNode, 'name' | 'line' | 'file'>,
+  selfTime: number,
+  children: ProfileNode[]
+): ProfileNode {
+  return {
+    ...frame,
+    selfTime,
+    totalTime: children.reduce((sum, child) => sum + child.totalTime, selfTime),
+    children: mergeSiblings(children),
+  };
+}
+
+/**
+ * The call tree of a V8 profile with the frames `locate` places. Folded
+ * frames add their own time to their caller's and hand it their callees.
+ */
+export function toCpuProfile(
+  profile: inspector.Profiler.Profile,
+  locate: FrameLocator
+): CpuProfile {
+  const selfTimes = getSelfTimes(profile);
+  const nodes = new Map(profile.nodes.map((node) => [node.id, node]));
+  const hasParent = new Set(
+    profile.nodes.flatMap((node) => node.children ?? [])
+  );
+
+  const convert = (
+    node: inspector.Profiler.ProfileNode
+  ): { nodes: ProfileNode[]; selfTime: number } => {
+    const { functionName } = node.callFrame;
+    if (functionName === IDLE_FRAME) return { nodes: [], selfTime: 0 };
+
+    let selfTime = selfTimes.get(node.id) ?? 0;
+    const children: ProfileNode[] = [];
+    for (const childId of node.children ?? []) {
+      const child = nodes.get(childId);
+      if (!child) continue;
+      const converted = convert(child);
+      selfTime += converted.selfTime;
+      children.push(...converted.nodes);
+    }
+
+    const frame =
+      functionName === GC_FRAME
+        ? { name: functionName }
+        : functionName === PROGRAM_FRAME
+          ? null
+          : locate(node.callFrame);
+    return frame
+      ? { nodes: [createNode(frame, selfTime, children)], selfTime: 0 }
+      : { nodes: children, selfTime };
+  };
+
+  const roots = profile.nodes.filter((node) => !hasParent.has(node.id));
+  const converted = roots.map(convert);
+  return {
+    profiler: 'v8',
+    root: createNode(
+      { name: '(root)' },
+      converted.reduce((sum, { selfTime }) => sum + selfTime, 0),
+      converted.flatMap(({ nodes }) => nodes)
+    ),
+  };
+}
+
+/**
+ * V8's sampling profiler for the worker's own isolate. One profiler covers
+ * one run; `stop()` disconnects it.
+ */
+export class CpuProfiler {
+  private readonly session = new inspector.Session();
+
+  async start(): Promise<void> {
+    this.session.connect();
+    await this.post('Profiler.enable');
+    await this.post('Profiler.setSamplingInterval', {
+      interval: SAMPLING_INTERVAL,
+    });
+    await this.post('Profiler.start');
+  }
+
+  /** Ends profiling, returning V8's profile of the time since `start()` */
+  async stop(): Promise<inspector.Profiler.Profile> {
+    try {
+      const { profile } = await this.post<inspector.Profiler.StopReturnType>(
+        'Profiler.stop'
+      );
+      return profile;
+    } finally {
+      this.dispose();
+    }
+  }
+
+  /** Drops the session, e.g. when the run failed */
+  dispose(): void {
+    this.session.disconnect();
+  }
+
+  private post<T = unknown>(method: string, params: object = {}): Promise<T> {
+    return new Promise((resolve, reject) => {
+      this.session.post(method, params, (error, result) =>
+        error ? reject(error) : resolve(result as T)
+      );
+    });
+  }
+}
@@ -0,0 +1,578 @@
+/**
+ * ESM Module Loader
+ *
+ * Linker for the sandbox's native ES module path (`vm.SourceTextModule`,
+ * which needs the worker to run with `--experimental-vm-modules`). Resolves:
+ * - `node:` builtins, subject to the sandbox require policy
+ * - installed packages, honouring `exports` conditions so ESM-only packages
+ *   load as modules while CommonJS ones are wrapped in synthetic modules
+ * - files relative to the importing module. The editor program sits in the
+ *   project directory (the working directory, else the workspace root), where
+ *   the other open tabs shadow files of the same name. TypeScript and JSX
+ *   files are transpiled as they are loaded.
+ *
+ * Files on disk are only imported from the project directory and installed
+ * packages, and project files need the run's `fs` permission.
+ */
+
+import fs from 'fs';
+import path from 'path';
+import vm from 'vm';
+import { isBuiltin } from 'module';
+import { fileURLToPath, pathToFileURL } from 'url';
+import { isBlockedSandboxModule } from './sandboxRequirePolicy.js';
+import type { SandboxPermissionGate } from './sandboxPermissions.js';
+import { usesModuleSyntax } from '../transpiler/codeTransforms.js';
+
+/** Conditions matched in package `exports`, as Node does for `import` */
+const IMPORT_CONDITIONS = new Set(['import', 'node', 'default']);
+
+const RESOLVE_EXTENSIONS = [
+  '.js',
+  '.mjs',
+  '.cjs',
+  '.ts',
+  '.tsx',
+  '.mts',
+  '.cts',
+  '.jsx',
+  '.json',
+];
+
+/** Files that go through `transpile` before they are evaluated */
+const TRANSPILED_EXTENSIONS = new Set(['.ts', '.tsx', '.mts', '.cts', '.jsx']);
+
+/** TypeScript sources an emitted-extension import (`./utils.js`) points to */
+const TYPESCRIPT_SOURCE_EXTENSIONS: Record<string, string[]> = {
+  '.js': ['.ts', '.tsx'],
+  '.jsx': ['.tsx'],
+  '.mjs': ['.mts'],
+  '.cjs': ['.cts'],
+};
+
+/** File name the editor program is reported under in stacks and import.meta */
+export const MAIN_MODULE_NAME = 'main.mjs';
+
+export interface EsmModuleLoaderOptions {
+  context: vm.Context;
+  /** Installed packages directory (`<packages>/node_modules`) */
+  nodeModulesPath?: string;
+  /** Base for relative imports in the editor program */
+  workingDirectory?: string;
+  /** Used as the base instead when no working directory is set */
+  workspaceRoot?: string;
+  /** Open tabs by file name, resolved as if saved in the project directory */
+  moduleFiles?: Record<string, string>;
+  /** File name the editor program runs as (default `main.mjs`) */
+  entryFile?: string;
+  /** Loads builtins and CommonJS files the way the sandbox `require` does */
+  requireModule: (specifier: string) => unknown;
+  /** Strips TypeScript and JSX from a project file */
+  transpile?: (code: string, filePath: string) => string;
+  /** Checks reads of project files; without it they are allowed */
+  permissions?: SandboxPermissionGate;
+}
+
+/** Where an error was thrown, relative to the project directory */
+export interface ModuleErrorLocation {
+  file: string;
+  line: number;
+  column: number;
+}
+
+interface PackageManifest {
+  type?: string;
+  main?: string;
+  exports?: unknown;
+}
+
+function isFile(filePath: string): boolean {
+  return fs.statSync(filePath, { throwIfNoEntry: false })?.isFile() ?? false;
+}
+
+/** `directory` and each of its parents up to the filesystem root */
+function ancestors(directory: string): string[] {
+  const directories = [directory];
+  let parent = path.dirname(directory);
+  while (parent !== directories[directories.length - 1]) {
+    directories.push(parent);
+    parent = path.dirname(parent);
+  }
+  return directories;
+}
+
+function isInside(directory: string, filePath: string): boolean {
+  const relative = path.relative(directory, filePath);
+  return !relative.startsWith('..') && !path.isAbsolute(relative);
+}
+
+function isInstalledPackageFile(filePath: string): boolean {
+  return filePath.split(path.sep).includes('node_modules');
+}
+
+function readManifest(directory: string): PackageManifest | null {
+  try {
+    return JSON.parse(
+      fs.readFileSync(path.join(directory, 'package.json'), 'utf8')
+    );
+  } catch {
+    return null;
+  }
+}
+
+/** Splits `@scope/pkg/sub/path` into `@scope/pkg` and `./sub/path` */
+export function splitPackageSpecifier(specifier: string): {
+  name: string;
+  subpath: string;
+} {
+  const parts = specifier.split('/');
+  const nameLength = specifier.startsWith('@') ? 2 : 1;
+  const rest = parts.slice(nameLength).join('/');
+  return {
+    name: parts.slice(0, nameLength).join('/'),
+    subpath: rest ? `./${rest}` : '.',
+  };
+}
+
+function resolveExportTarget(target: unknown): string | null {
+  if (typeof target === 'string') {
+    return target;
+  }
+  if (Array.isArray(target)) {
+    for (const entry of target) {
+      const resolved = resolveExportTarget(entry);
+      if (resolved) return resolved;
+    }
+    return null;
+  }
+  if (target && typeof target === 'object') {
+    // Key order decides between matching conditions
+    for (const [condition, value] of Object.entries(target)) {
+      if (IMPORT_CONDITIONS.has(condition)) {
+        const resolved = resolveExportTarget(value);
+        if (resolved) return resolved;
+      }
+    }
+  }
+  return null;
+}
+
+/**
+ * Resolves a subpath against a package's `exports` field, including `*`
+ * patterns. Returns null when the subpath is not exported.
+ */
+export function resolvePackageExports(
+  exportsField: unknown,
+  subpath: string
+): string | null {
+  const isSubpathMap =
+    exportsField !== null &&
+    typeof exportsField === 'object' &&
+    !Array.isArray(exportsField) &&
+    Object.keys(exportsField).some((key) => key.startsWith('.'));
+  const exportsMap = (
+    isSubpathMap ? exportsField : { '.': exportsField }
+  ) as Record<string, unknown>;
+
+  if (subpath in exportsMap) {
+    return resolveExportTarget(exportsMap[subpath]);
+  }
+
+  for (const [pattern, target] of Object.entries(exportsMap)) {
+    const star = pattern.indexOf('*');
+    if (star === -1) continue;
+    const prefix = pattern.slice(0, star);
+    const suffix = pattern.slice(star + 1);
+    if (
+      subpath.length >= pattern.length - 1 &&
+      subpath.startsWith(prefix) &&
+      subpath.endsWith(suffix)
+    ) {
+      const match = subpath.slice(
+        prefix.length,
+        subpath.length - suffix.length
+      );
+      return resolveExportTarget(target)?.replaceAll('*', match) ?? null;
+    }
+  }
+  return null;
+}
+
+/** Whether Node would load the file as an ES module */
+export function isEsmFile(filePath: string): boolean {
+  const extension = path.extname(filePath);
+  if (extension === '.mjs') return true;
+  if (extension !== '.js') return false;
+
+  for (const directory of ancestors(path.dirname(filePath))) {
+    const manifest = readManifest(directory);
+    if (manifest) return manifest.type === 'module';
+  }
+  return false;
+}
+
+// `    at fn (/path/file.ts:3:9)` or `    at /path/file.ts:3:9`
+const STACK_FRAME_PATTERN = /^\s+at (?:.*\()?(.+?):(\d+):(\d+)\)?$/;
+
+export class EsmModuleLoader {
+  private readonly modules = new Map<string, vm.Module>();
+  private readonly moduleFiles = new Map<string, string>();
+  private readonly projectDirectory?: string;
+  /** Path the editor program is evaluated as */
+  readonly mainIdentifier: string;
+
+  constructor(private readonly options: EsmModuleLoaderOptions) {
+    this.projectDirectory = options.workingDirectory ?? options.workspaceRoot;
+    const mainDirectory =
+      this.projectDirectory ??
+      (options.nodeModulesPath
+        ? path.dirname(options.nodeModulesPath)
+        : process.cwd());
+    this.mainIdentifier = path.join(
+      mainDirectory,
+      path.basename(options.entryFile || MAIN_MODULE_NAME)
+    );
+
+    const { projectDirectory } = this;
+    if (projectDirectory) {
+      for (const [name, code] of Object.entries(options.moduleFiles ?? {})) {
+        const filePath = path.resolve(projectDirectory, name);
+        if (
+          filePath !== this.mainIdentifier &&
+          isInside(projectDirectory, filePath)
+        ) {
+          this.moduleFiles.set(filePath, code);
+        }
+      }
+    }
+  }
+
+  /** Links and evaluates the editor program; resolves once it settles */
+  async run(code: string, timeout: number): Promise<void> {
+    const main = this.createSourceModule(code, this.mainIdentifier);
+    await main.link(this.link);
+    await main.evaluate({ timeout, breakOnSigint: true });
+  }
+
+  /**
+   * Locates an error in the project files the program imported, or returns
+   * undefined when it was thrown in the editor program itself.
+   */
+  locateError(stack: string | undefined): ModuleErrorLocation | undefined {
+    const { projectDirectory } = this;
+    if (!stack || !projectDirectory) return undefined;
+
+    for (const frame of stack.split('\n')) {
+      const match = frame.match(STACK_FRAME_PATTERN);
+      if (!match) continue;
+
+      const [, file, line, column] = match;
+      if (file === this.mainIdentifier) return undefined;
+      if (
+        isInside(projectDirectory, file) &&
+        !isInstalledPackageFile(file) &&
+        this.exists(file)
+      ) {
+        return {
+          file: path.relative(projectDirectory, file),
+          line: Number(line),
+          column: Number(column),
+        };
+      }
+    }
+    return undefined;
+  }
+
+  readonly link = (
+    specifier: string,
+    referencingModule: vm.Module
+  ): Promise<vm.Module> => this.load(specifier, referencingModule.identifier);
+
+  private async load(specifier: string, referrer: string): Promise<vm.Module> {
+    if (isBuiltin(specifier)) {
+      return this.loadBuiltin(specifier);
+    }
+
+    const filePath = this.resolve(specifier, referrer);
+    const cached = this.modules.get(filePath);
+    if (cached) return cached;
+
+    this.assertReadable(filePath, specifier);
+    const module = this.loadFile(filePath);
+    this.modules.set(filePath, module);
+    return module;
+  }
+
+  private loadBuiltin(specifier: string): vm.Module {
+    if (isBlockedSandboxModule(specifier)) {
+      throw new Error(
+        `Module '${specifier}' is not available in this sandboxed runtime.`
+      );
+    }
+
+    const key = specifier.startsWith('node:') ? specifier : `node:${specifier}`;
+    const cached = this.modules.get(key);
+    if (cached) return cached;
+
+    const module = this.createCommonJsModule(
+      this.options.requireModule(key),
+      key
+    );
+    this.modules.set(key, module);
+    return module;
+  }
+
+  private resolve(specifier: string, referrer: string): string {
+    if (specifier.startsWith('file:')) {
+      return this.resolveFile(fileURLToPath(specifier), specifier);
+    }
+
+    if (path.isAbsolute(specifier)) {
+      return this.resolveFile(specifier, specifier);
+    }
+
+    if (/^\.{1,2}\//.test(specifier)) {
+      if (referrer === this.mainIdentifier && !this.projectDirectory) {
+        throw new Error(
+          `Cannot import '${specifier}': relative imports need a working directory (Settings → General)`
+        );
+      }
+      return this.resolveFile(
+        path.resolve(path.dirname(referrer), specifier),
+        specifier
+      );
+    }
+
+    return this.resolvePackage(specifier, path.dirname(referrer));
+  }
+
+  /**
+   * Rejects files on disk outside the project directory and installed
+   * packages, and project files the run may not read
+   */
+  private assertReadable(filePath: string, specifier: string): void {
+    if (this.moduleFiles.has(filePath)) return;
+
+    const { projectDirectory } = this;
+    const packageDirectories = this.nodeModulesDirectories(
+      projectDirectory ?? path.dirname(this.mainIdentifier)
+    );
+    if (packageDirectories.some((directory) => isInside(directory, filePath))) {
+      return;
+    }
+
+    if (!projectDirectory || !isInside(projectDirectory, filePath)) {
+      throw new Error(
+        `Cannot import '${specifier}': only files in the project directory can be imported`
+      );
+    }
+    this.options.permissions?.assertAllowed('fs', `import '${specifier}'`);
+  }
+
+  /** Open tabs count as files, ahead of what is saved on disk */
+  private exists(filePath: string): boolean {
+    return this.moduleFiles.has(filePath) || isFile(filePath);
+  }
+
+  private readSource(filePath: string): string {
+    return this.moduleFiles.get(filePath) ?? fs.readFileSync(filePath, 'utf8');
+  }
+
+  private resolveFile(candidate: string, specifier: string): string {
+    if (this.exists(candidate)) return candidate;
+
+    for (const extension of RESOLVE_EXTENSIONS) {
+      if (this.exists(candidate + extension)) return candidate + extension;
+    }
+
+    const emitted = path.extname(candidate);
+    for (const extension of TYPESCRIPT_SOURCE_EXTENSIONS[emitted] ?? []) {
+      const source = candidate.slice(0, -emitted.length) + extension;
+      if (this.exists(source)) return source;
+    }
+
+    const main = readManifest(candidate)?.main;
+    if (main && isFile(path.join(candidate, main))) {
+      return path.join(candidate, main);
+    }
+    for (const extension of RESOLVE_EXTENSIONS) {
+      const index = path.join(candidate, `index${extension}`);
+      if (this.exists(index)) return index;
+    }
+
+    throw new Error(`Cannot find module '${specifier}'`);
+  }
+
+  /** `node_modules` folders from `directory` upwards, then the packages dir */
+  private nodeModulesDirectories(directory: string): string[] {
+    const directories = ancestors(directory)
+      .filter((current) => path.basename(current) !== 'node_modules')
+      .map((current) => path.join(current, 'node_modules'));
+
+    const { nodeModulesPath } = this.options;
+    if (nodeModulesPath && !directories.includes(nodeModulesPath)) {
+      directories.push(nodeModulesPath);
+    }
+    return directories;
+  }
+
+  private resolvePackage(specifier: string, fromDirectory: string): string {
+    if (isBlockedSandboxModule(specifier)) {
+      throw new Error(
+        `Module '${specifier}' is not available in this sandboxed runtime.`
+      );
+    }
+
+    const { name, subpath } = splitPackageSpecifier(specifier);
+    for (const directory of this.nodeModulesDirectories(fromDirectory)) {
+      const packageDirectory = path.join(directory, name);
+      const manifest = readManifest(packageDirectory);
+      if (!manifest) continue;
+
+      if (manifest.exports !== undefined) {
+        const target = resolvePackageExports(manifest.exports, subpath);
+        if (!target) {
+          throw new Error(
+            `Package subpath '${subpath}' is not exported by '${name}'`
+          );
+        }
+        return this.resolveFile(path.join(packageDirectory, target), specifier);
+      }
+
+      return this.resolveFile(
+        path.join(packageDirectory, subpath === '.' ? '' : subpath),
+        specifier
+      );
+    }
+
+    throw new Error(
+      `Cannot find module '${specifier}'. Please install it first.`
+    );
+  }
+
+  private loadFile(filePath: string): vm.Module {
+    const extension = path.extname(filePath);
+    if (extension === '.json') {
+      const data = JSON.parse(this.readSource(filePath));
+      return this.createSyntheticModule(data, {}, filePath);
+    }
+
+    if (TRANSPILED_EXTENSIONS.has(extension)) {
+      const { transpile } = this.options;
+      if (!transpile) {
+        throw new Error(`Cannot import '${filePath}': no transpiler available`);
+      }
+      return this.createSourceModule(
+        transpile(this.readSource(filePath), filePath),
+        filePath
+      );
+    }
+
+    if (this.moduleFiles.has(filePath) || isEsmFile(filePath)) {
+      return this.createSourceModule(this.readSource(filePath), filePath);
+    }
+
+    // Project files written with import/export run as modules even without
+    // `"type": "module"`, as the editor program does
+    if (!isInstalledPackageFile(filePath)) {
+      const source = fs.readFileSync(filePath, 'utf8');
+      if (usesModuleSyntax(source)) {
+        return this.createSourceModule(source, filePath);
+      }
+    }
+
+    return this.createCommonJsModule(
+      this.options.requireModule(filePath),
+      filePath
+    );
+  }
+
+  private createSourceModule(
+    code: string,
+    identifier: string
+  ): vm.SourceTextModule {
+    try {
+      return new vm.SourceTextModule(code, {
+        context: this.options.context,
+        identifier,
+        initializeImportMeta: (meta) => {
+          meta.url = pathToFileURL(identifier).href;
+        },
+        importModuleDynamically: (specifier) =>
+          this.importDynamically(specifier, identifier),
+      });
+    } catch (error) {
+      // V8 leaves the file out of module syntax errors. The error comes from
+      // the sandbox realm, so `instanceof SyntaxError` does not hold
+      const syntaxError = error as Error;
+      if (
+        syntaxError?.name === 'SyntaxError' &&
+        identifier !== this.mainIdentifier
+      ) {
+        syntaxError.message += ` (in ${this.describeFile(identifier)})`;
+      }
+      throw error;
+    }
+  }
+
+  private describeFile(filePath: string): string {
+    const { projectDirectory } = this;
+    return projectDirectory && isInside(projectDirectory, filePath)
+      ? path.relative(projectDirectory, filePath)
+      : filePath;
+  }
+
+  /** Exposes a CommonJS value the way Node's ESM/CJS interop does */
+  private createCommonJsModule(
+    value: unknown,
+    identifier: string
+  ): vm.SyntheticModule {
+    if (
+      value === null ||
+      (typeof value !== 'object' && typeof value !== 'function')
+    ) {
+      return this.createSyntheticModule(value, {}, identifier);
+    }
+
+    const exports = value as Record<string, unknown>;
+    const named = Object.fromEntries(
+      Object.keys(exports)
+        .filter((name) => name !== 'default')
+        .map((name) => [name, exports[name]])
+    );
+    const defaultExport =
+      exports.__esModule && 'default' in exports ? exports.default : value;
+    return this.createSyntheticModule(defaultExport, named, identifier);
+  }
+
+  private createSyntheticModule(
+    defaultExport: unknown,
+    named: Record<string, unknown>,
+    identifier: string
+  ): vm.SyntheticModule {
+    return new vm.SyntheticModule(
+      ['default', ...Object.keys(named)],
+      function () {
+        this.setExport('default', defaultExport);
+        for (const [name, value] of Object.entries(named)) {
+          this.setExport(name, value);
+        }
+      },
+      { context: this.options.context, identifier }
+    );
+  }
+
+  private async importDynamically(
+    specifier: string,
+    referrer: string
+  ): Promise<vm.Module> {
+    const module = await this.load(specifier, referrer);
+    if (module.status === 'unlinked') {
+      await module.link(this.link);
+    }
+    if (module.status === 'linked') {
+      await module.evaluate();
+    }
+    return module;
+  }
+}
@@ -6,12 +6,24 @@
  * - Debug function for line-numbered output
  * - Timeout protection
  * - Package installation via micropip
+ * - REPL sessions with their own namespace, skipping unchanged statements
+ * - pytest-style test functions, run once the program has finished
+ * - cProfile profiles of runs, as call trees mapped to editor lines
  */
 
 import { parentPort } from 'worker_threads';
 import path from 'path';
 import { createRequire } from 'module';
 import { loadPyodide, type PyodideInterface } from 'pyodide';
+import type { PyProxy } from 'pyodide/ffi';
+import type {
+  CpuProfile,
+  ExecutionCompletion,
+  ProfileNode,
+  ReplSessionStats,
+  TestCaseResult,
+  TestRunSummary,
+} from '@cheesejs/core';
 import { config as dotenvConfig } from 'dotenv';
 import { expand as dotenvExpand } from 'dotenv-expand';
 
@@ -68,6 +80,11 @@ interface CleanupNamespaceMessage {
   id: string;
 }
 
+interface ResetSessionMessage {
+  type: 'reset-session';
+  sessionId: string;
+}
+
 type WorkerMessage =
   | ExecuteMessage
   | CancelMessage
@@ -77,16 +94,33 @@ type WorkerMessage =
   | SetInterruptBufferMessage
   | ResetRuntimeMessage
   | GetMemoryStatsMessage
-  | CleanupNamespaceMessage;
+  | CleanupNamespaceMessage
+  | ResetSessionMessage;
 
 interface ExecuteOptions {
   timeout?: number;
   showUndefined?: boolean;
   workingDirectory?: string;
+  /** REPL session whose namespace the run shares */
+  sessionId?: string;
+  /** Run every statement of the session run, reusing none */
+  runAllStatements?: boolean;
+  /** Run under cProfile and send the profile */
+  profile?: boolean;
+  /** Run the test* functions and Test* classes after the program */
+  runTests?: boolean;
 }
 
 interface ResultMessage {
-  type: 'result' | 'console' | 'debug' | 'error' | 'complete' | 'status';
+  type:
+    | 'result'
+    | 'console'
+    | 'debug'
+    | 'error'
+    | 'complete'
+    | 'status'
+    | 'test'
+    | 'profile';
   id: string;
   data: unknown;
   line?: number;
@@ -206,6 +240,145 @@ async function withTimeout<T>(
   }
 }
 
+// ============================================================================
+// REPL SESSIONS
+// ============================================================================
+
+/**
+ * A REPL session runs in its own globals dict, which the periodic namespace
+ * cleanup leaves alone, and remembers the top-level statements that
+ * completed in it so those before the first edit are skipped next run.
+ */
+interface PythonReplSession {
+  namespace: PyProxy;
+  completed: string[];
+}
+
+/** A run of a session: the code left to execute and its statement keys */
+interface SessionRunPlan {
+  code: string;
+  keys: string[];
+  /** Keys of the statements the run skips */
+  reused: string[];
+  stats: ReplSessionStats;
+}
+
+const replSessions = new Map<string, PythonReplSession>();
+
+function getReplSession(
+  py: PyodideInterface,
+  sessionId: string
+): PythonReplSession {
+  let session = replSessions.get(sessionId);
+  if (!session) {
+    session = {
+      namespace: py.runPython('_session_namespace()') as PyProxy,
+      completed: [],
+    };
+    replSessions.set(sessionId, session);
+  }
+  return session;
+}
+
+function dropReplSession(sessionId: string): void {
+  replSessions.get(sessionId)?.namespace.destroy();
+  replSessions.delete(sessionId);
+}
+
+/**
+ * Blanks the statements the session can reuse, unless `reuse` is off.
+ * Returns null for code that does not parse, which then runs as is and
+ * reports the syntax error.
+ */
+function planSessionRun(
+  py: PyodideInterface,
+  session: PythonReplSession,
+  code: string,
+  reuse: boolean
+): SessionRunPlan | null {
+  const plan = py.globals.get('_session_plan');
+  try {
+    const result = plan(code, reuse ? session.completed : []);
+    const [planned, keys, reused] = result.toJs() as [
+      string,
+      string[],
+      string[],
+    ];
+    result.destroy();
+    return {
+      code: planned,
+      keys,
+      reused,
+      stats: { ran: keys.length - reused.length, reused: reused.length },
+    };
+  } catch {
+    return null;
+  } finally {
+    plan.destroy();
+  }
+}
+
+/**
+ * Runs the tests `code` defines, collected the way pytest does, in the
+ * namespace the program ran in. `ranCode` is the code that actually ran.
+ */
+async function runPythonTests(
+  py: PyodideInterface,
+  code: string,
+  ranCode: string,
+  namespace: PyProxy
+): Promise<TestCaseResult[]> {
+  const runTests = py.globals.get('_run_tests');
+  try {
+    const results = await runTests(code, ranCode, namespace);
+    const tests = results.toJs({
+      dict_converter: Object.fromEntries,
+    }) as TestCaseResult[];
+    results.destroy();
+    return tests;
+  } finally {
+    runTests.destroy();
+  }
+}
+
+/** `cProfile.Profile` instance profiling a run */
+interface PythonProfiler extends PyProxy {
+  enable(): void;
+  disable(): void;
+}
+
+/** The call tree of a run profiled by `profiler`, once it is disabled */
+function getPythonProfile(
+  py: PyodideInterface,
+  profiler: PythonProfiler
+): CpuProfile {
+  const profileTree = py.globals.get('_profile_tree');
+  try {
+    const tree = profileTree(profiler);
+    const root = tree.toJs({
+      dict_converter: Object.fromEntries,
+    }) as ProfileNode;
+    tree.destroy();
+    return { profiler: 'cprofile', root };
+  } finally {
+    profileTree.destroy();
+  }
+}
+
+function summarizeTests(
+  tests: TestCaseResult[],
+  duration: number
+): TestRunSummary {
+  const count = (status: TestCaseResult['status']) =>
+    tests.filter((test) => test.status === status).length;
+  return {
+    passed: count('passed'),
+    failed: count('failed'),
+    skipped: count('skipped'),
+    duration,
+  };
+}
+
 // ============================================================================
 // MEMORY MANAGEMENT FUNCTIONS
 // ============================================================================
@@ -682,6 +855,254 @@ def _transform_for_async_input(code):
 setattr(builtins, '_transform_for_async_input', _transform_for_async_input)
 `);
 
+      // Helpers for REPL sessions, which run in namespaces of their own
+      instance.runPython(`
+import ast
+import hashlib
+
+def _session_namespace():
+    """Globals of a new REPL session, run as the main module"""
+    return {'__name__': '__main__', '_async_input': _async_input}
+
+def _is_output(node):
+    """print() calls and bare names, which only show something"""
+    if not isinstance(node, ast.Expr):
+        return False
+    value = node.value
+    if isinstance(value, ast.Call):
+        return isinstance(value.func, ast.Name) and value.func.id == 'print'
+    return isinstance(value, (ast.Name, ast.Attribute, ast.Constant))
+
+def _session_plan(code, completed):
+    """Blank the top-level statements that completed in an earlier run, up
+    to the first new or changed one, keeping every other line where it
+    was. Everything after that runs again, as it may read what changed,
+    and output runs every time as results are cleared between runs."""
+    tree = ast.parse(code)
+    lines = code.split('\\n')
+    completed = set(completed)
+    keys, reusable, needed, seen = [], [], set(), {}
+    rerun = False
+
+    for node in tree.body:
+        # ast.dump leaves out positions, comments and formatting
+        digest = hashlib.sha1(ast.dump(node).encode()).hexdigest()[:16]
+        occurrence = seen.get(digest, 0)
+        seen[digest] = occurrence + 1
+        key = f'{digest}-{occurrence}' if occurrence else digest
+
+        decorators = getattr(node, 'decorator_list', [])
+        first = min([node.lineno] + [d.lineno for d in decorators])
+        span = range(first, node.end_lineno + 1)
+        if _is_output(node):
+            needed.update(span)
+            continue
+        keys.append(key)
+        if not rerun and key in completed:
+            reusable.append((key, span))
+        else:
+            rerun = True
+            needed.update(span)
+
+    reused = []
+    for key, span in reusable:
+        # A statement sharing a line with one that runs has to run too
+        if needed.intersection(span):
+            continue
+        reused.append(key)
+        for line in span:
+            lines[line - 1] = ''
+
+    return '\\n'.join(lines), keys, reused
+`);
+
+      // pytest-style test collection, run after the program. Modules are
+      // imported inside the helpers, as namespace cleanup drops globals.
+      instance.runPython(`
+def _collect_tests(code, namespace):
+    """Top-level test* functions and the test* methods of Test* classes
+    that can be called without arguments, by name, with the line each is
+    defined on"""
+    import ast
+
+    def is_test(node, bound=0):
+        if not (isinstance(node, (ast.FunctionDef, ast.AsyncFunctionDef))
+                and node.name.startswith('test')):
+            return False
+        # Helpers like test_case(value) are not tests; self is bound
+        args = node.args
+        positional = len(args.posonlyargs) + len(args.args) - bound
+        return (positional <= len(args.defaults)
+                and None not in args.kw_defaults)
+
+    tests = {}
+    try:
+        tree = ast.parse(code)
+    except SyntaxError:
+        return tests
+    for node in tree.body:
+        if is_test(node) and callable(namespace.get(node.name)):
+            tests[node.name] = (node.lineno, namespace[node.name], None)
+        elif isinstance(node, ast.ClassDef) and node.name.startswith('Test'):
+            cls = namespace.get(node.name)
+            if not isinstance(cls, type):
+                continue
+            for item in node.body:
+                if is_test(item, bound=1):
+                    name = f'{node.name}::{item.name}'
+                    tests[name] = (item.lineno, cls, item.name)
+    return tests
+
+def _describe_assertion(error, code):
+    """The failed assert as written, with both sides of a failed == or !=
+    when they can be evaluated again without side effects"""
+    import ast
+
+    tb = error.__traceback__
+    while tb.tb_next:
+        tb = tb.tb_next
+    frame, line = tb.tb_frame, tb.tb_lineno
+    statement = None
+    if frame.f_code.co_filename == '<exec>':
+        try:
+            nodes = ast.walk(ast.parse(code))
+            statement = next(node for node in nodes
+                             if isinstance(node, ast.Assert)
+                             and node.lineno == line)
+        except (SyntaxError, StopIteration):
+            pass
+    if statement is None:
+        return {'message': f'AssertionError: {error}' if str(error)
+                else 'AssertionError'}
+
+    test = statement.test
+    described = {'message': str(error) if error.args
+                 else f'assert {ast.unparse(test)}'}
+    side_effects = (ast.Call, ast.Await, ast.NamedExpr, ast.Yield,
+                    ast.YieldFrom)
+    if (isinstance(test, ast.Compare) and len(test.ops) == 1
+            and isinstance(test.ops[0], (ast.Eq, ast.NotEq))
+            and not any(isinstance(node, side_effects)
+                        for node in ast.walk(test))):
+        try:
+            received, expected = (
+                eval(compile(ast.Expression(side), '<assert>', 'eval'),
+                     frame.f_globals, frame.f_locals)
+                for side in (test.left, test.comparators[0]))
+            negated = 'not ' if isinstance(test.ops[0], ast.NotEq) else ''
+            described['diff'] = (f'Expected: {negated}{expected!r}\\n'
+                                 f'Received: {received!r}')
+        except Exception:
+            pass
+    return described
+
+async def _run_tests(code, ran_code, namespace):
+    """Run the tests code defines in the namespace it ran in; failed
+    asserts are looked up in ran_code, the code that actually ran"""
+    import inspect
+    import time
+
+    results = []
+    for name, (line, target, method) in _collect_tests(code, namespace).items():
+        result = {'name': name, 'line': line}
+        start = time.perf_counter()
+        try:
+            outcome = getattr(target(), method)() if method else target()
+            if inspect.isawaitable(outcome):
+                await outcome
+            result['status'] = 'passed'
+        except AssertionError as error:
+            result.update(status='failed',
+                          **_describe_assertion(error, ran_code))
+        except Exception as error:
+            # unittest.SkipTest, or pytest.skip() where pytest is installed
+            if type(error).__name__ in ('SkipTest', 'Skipped'):
+                result['status'] = 'skipped'
+            else:
+                result.update(status='failed',
+                              message=f'{type(error).__name__}: {error}')
+        result['duration'] = round((time.perf_counter() - start) * 1000)
+        results.append(result)
+    return results
+`);
+
+      // cProfile call trees for profiled runs
+      instance.runPython(`
+# Frames of the helpers above that profiled code calls into
+_PROFILE_HOST_FRAMES = {(f.__code__.co_firstlineno, f.__name__)
+                        for f in (debug, _async_input)}
+
+def _profile_tree(profiler, max_nodes=2000, max_depth=64):
+    """The call tree of a cProfile run, as nested ProfileNode dicts.
+    cProfile keeps totals per caller and callee rather than stacks, so the
+    tree is rebuilt from those pairs, as snakeviz does. Functions outside
+    the program are leaves, except for the program functions they call
+    back into, e.g. the key function of sorted()."""
+    import os
+    import pstats
+
+    stats = pstats.Stats(profiler).stats
+    callees = {}
+    for func, (_, _, _, _, callers) in stats.items():
+        for caller, (_, _, own, cumulative) in callers.items():
+            callees.setdefault(caller, []).append((func, own, cumulative))
+
+    def is_host(func):
+        file, line, name = func
+        return file == '<exec>' and (line, name) in _PROFILE_HOST_FRAMES
+
+    def in_program(func):
+        return func[0] == '<exec>' and not is_host(func)
+
+    def called_from_program(func):
+        callers = stats[func][4] if func in stats else {}
+        return in_program(func) or any(map(in_program, callers))
+
+    remaining = [max_nodes]
+
+    def build(func, own, cumulative, path):
+        file, line, name = func
+        program = in_program(func)
+        children = []
+        for callee, callee_own, callee_cumulative in callees.get(func, ()):
+            # Recursive calls and helpers count as the function's own time
+            if callee == func:
+                own += callee_own
+            elif is_host(callee):
+                own += callee_cumulative
+            elif (callee not in path and len(path) < max_depth
+                    and remaining[0] > 0
+                    and (program or in_program(callee))):
+                remaining[0] -= 1
+                children.append(build(callee, callee_own, callee_cumulative,
+                                      path | {callee}))
+        children.sort(key=lambda child: child['totalTime'], reverse=True)
+
+        total = cumulative * 1000
+        node = {'name': name, 'totalTime': total, 'children': children}
+        if program and name == '<module>':
+            node['name'] = '(top level)'
+        elif program:
+            node['line'] = line
+        elif not file.startswith(('<', '~')):
+            node['file'] = os.path.basename(file)
+            node['line'] = line
+        # Callees of outside functions that are left out count as their own
+        node['selfTime'] = (own * 1000 if program else max(
+            0, total - sum(child['totalTime'] for child in children)))
+        return node
+
+    roots = [
+        build(func, entry[2], entry[3], {func})
+        for func, entry in stats.items()
+        if in_program(func)
+        and not any(map(called_from_program, entry[4]))
+    ]
+    roots.sort(key=lambda root: root['totalTime'], reverse=True)
+    return {'name': '(root)', 'selfTime': 0, 'children': roots,
+            'totalTime': sum(root['totalTime'] for root in roots)}
+`);
+
       // Set up interrupt buffer if available (P2)
       if (interruptBuffer) {
         instance.setInterruptBuffer(interruptBuffer);
@@ -957,6 +1378,11 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
   const timeout = hasInput ? 300000 : (options.timeout ?? 30000); // 5 min for input, 30s default
 
   currentExecutionId = id;
+  let sessionRun: {
+    session: PythonReplSession;
+    plan: SessionRunPlan | null;
+  } | null = null;
+  let profiler: PythonProfiler | null = null;
 
   try {
     // Initialize Pyodide if needed
@@ -969,6 +1395,20 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
 
     // Transform code to handle magic comments and async input (P1-A)
     const transformedCode = await transformPythonCode(py, code);
+    const session = options.sessionId
+      ? getReplSession(py, options.sessionId)
+      : null;
+    sessionRun = session
+      ? {
+          session,
+          plan: planSessionRun(
+            py,
+            session,
+            transformedCode,
+            !options.runAllStatements
+          ),
+        }
+      : null;
 
     // Load .env variables into Python's os.environ
     if (options.workingDirectory) {
@@ -1005,10 +1445,30 @@ except Exception as e:
     });
 
     // Execute code with timeout
+    const ranCode = sessionRun?.plan?.code ?? transformedCode;
+    const namespace = sessionRun?.session.namespace ?? py.globals;
+    // The profile covers the program, but not the tests run after it
+    if (options.profile) {
+      profiler = py.runPython(
+        '__import__("cProfile").Profile()'
+      ) as PythonProfiler;
+      profiler.enable();
+    }
     const result = await Promise.race([
-      py.runPythonAsync(transformedCode),
+      py.runPythonAsync(ranCode, { globals: namespace }),
       timeoutPromise,
     ]);
+    if (profiler) {
+      profiler.disable();
+      const profile = getPythonProfile(py, profiler);
+      profiler.destroy();
+      profiler = null;
+      parentPort?.postMessage({
+        type: 'profile',
+        id,
+        data: profile,
+      } as ResultMessage);
+    }
 
     // Flush Python stdout/stderr to ensure all output is captured
     // This is important because async operations might have buffered output
@@ -1048,16 +1508,52 @@ sys.stderr.flush()
       console.warn('[PythonExecutor] Debug outputs unavailable:', debugError);
     }
 
+    // Test functions the program defined run now, each reported at its line
+    const testStart = Date.now();
+    const tests = options.runTests
+      ? await Promise.race([
+          runPythonTests(py, code, ranCode, namespace),
+          timeoutPromise,
+        ])
+      : [];
+    for (const test of tests) {
+      parentPort?.postMessage({
+        type: 'test',
+        id,
+        line: test.line,
+        data: test,
+      } as ResultMessage);
+    }
+
     // Check and clean memory after execution
     await checkAndCleanMemory(py);
 
+    let completion: ExecutionCompletion | null =
+      result !== undefined ? serializeValue(result) : null;
+    if (sessionRun?.plan) {
+      sessionRun.session.completed = sessionRun.plan.keys;
+      completion = { ...completion, session: sessionRun.plan.stats };
+    }
+    if (tests.length > 0) {
+      completion = {
+        ...completion,
+        tests: summarizeTests(tests, Date.now() - testStart),
+      };
+    }
+
     // Send completion
     parentPort?.postMessage({
       type: 'complete',
       id,
-      data: result !== undefined ? serializeValue(result) : null,
+      data: completion,
     } as ResultMessage);
   } catch (error) {
+    // Where the run stopped is unknown, so only statements it skipped
+    // keep counting as done
+    if (sessionRun?.plan) {
+      sessionRun.session.completed = sessionRun.plan.reused;
+    }
+
     const errorMessage =
       error instanceof Error
         ? cleanPythonError(error)
@@ -1069,6 +1565,10 @@ sys.stderr.flush()
       data: errorMessage,
     } as ResultMessage);
   } finally {
+    if (profiler) {
+      profiler.disable();
+      profiler.destroy();
+    }
     currentExecutionId = null;
   }
 }
@@ -1187,7 +1687,11 @@ gc.collect()
       }
     }
 
-    // Full reset: null the instance and reinitialize
+    // Full reset: null the instance and reinitialize; session namespaces
+    // belong to the old interpreter
+    for (const sessionId of [...replSessions.keys()]) {
+      dropReplSession(sessionId);
+    }
     pyodide = null;
     pyodideLoadPromise = null;
     isLoading = false;
@@ -1280,6 +1784,8 @@ parentPort?.on('message', async (message: WorkerMessage) => {
         executionCount: executionCounter,
       },
     } as ResultMessage);
+  } else if (message.type === 'reset-session') {
+    dropReplSession(message.sessionId);
   } else if (message.type === 'cleanup-namespace') {
     // Manual namespace cleanup request
     try {
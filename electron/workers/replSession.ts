@@ -0,0 +1,98 @@
+/**
+ * REPL Sessions
+ *
+ * A session keeps the vm context of an editor tab between runs. Code run
+ * in one is instrumented by the `repl-session` transform pass, which
+ * guards every top-level statement with a key derived from its text and
+ * asks the session whether it still has to run. Statements that completed
+ * in an earlier run are skipped up to the first new or changed one, so
+ * expensive setup runs once instead of on every keystroke; everything
+ * after that runs again, since it may depend on what changed.
+ */
+
+import type vm from 'vm';
+import type { ReplSessionStats } from '@cheesejs/core';
+import { SESSION_RUNTIME_NAME } from '../transpiler/codeTransforms.js';
+
+/** What guarded code calls as `__session` */
+export interface ReplSessionRuntime {
+  /**
+   * Declares the names the program keeps and lists its statement keys,
+   * with the key of each function and the index of its first caller
+   */
+  begin(names: string[], keys: string[], functions?: [string, number][]): void;
+  /** Whether the statement has to run; false when it can be reused */
+  run(key: string): boolean;
+  /** The statement ran to the end */
+  done(key: string): void;
+}
+
+/** One run in a session */
+export interface ReplSessionRun {
+  /** Records what the run left behind; call once it has settled */
+  finish(): ReplSessionStats;
+}
+
+export class ReplSession {
+  /** Keys of statements whose effects the context still holds */
+  private completed = new Set<string>();
+  /** Keys of the functions the last run declared */
+  private functions = new Set<string>();
+
+  constructor(readonly context: vm.Context) {}
+
+  /**
+   * Swaps the globals of a new run into the context and starts tracking
+   * which statements it runs. Without `reuse` every statement runs again.
+   */
+  startRun(globals: Record<string, unknown>, reuse = true): ReplSessionRun {
+    const context = this.context;
+    let programKeys: string[] = [];
+    const reached = new Set<string>();
+    const finished = new Set<string>();
+    let reused = 0;
+    // Index of the first statement that has to run; all after it run too
+    let rerunFrom = Infinity;
+
+    const runtime: ReplSessionRuntime = {
+      begin: (names, keys, functions = []) => {
+        programKeys = keys;
+        for (const [key, caller] of functions) {
+          if (!this.functions.has(key)) rerunFrom = Math.min(rerunFrom, caller);
+        }
+        this.functions = new Set(functions.map(([key]) => key));
+        for (const name of names) {
+          if (!Object.prototype.hasOwnProperty.call(context, name)) {
+            context[name] = undefined;
+          }
+        }
+      },
+      run: (key) => {
+        reached.add(key);
+        const index = programKeys.indexOf(key);
+        if (reuse && index < rerunFrom && this.completed.has(key)) {
+          finished.add(key);
+          reused++;
+          return false;
+        }
+        rerunFrom = Math.min(rerunFrom, index);
+        return true;
+      },
+      done: (key) => {
+        finished.add(key);
+      },
+    };
+    Object.assign(context, globals, { [SESSION_RUNTIME_NAME]: runtime });
+
+    return {
+      finish: () => {
+        // Statements the run never got to come after one that had to run,
+        // so they have to run next time as well
+        this.completed = new Set(
+          programKeys.filter((key) => finished.has(key))
+        );
+        return { ran: reached.size - reused, reused };
+      },
+    };
+  }
+}
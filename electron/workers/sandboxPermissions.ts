@@ -0,0 +1,240 @@
+/**
+ * Sandbox Permissions
+ *
+ * Enforces a tab's sandbox profile inside the JS/TS worker. Each capability
+ * is allowed, denied, limited to reads (`fs` only) or decided by asking the
+ * user the first time a run touches it; answers last for the rest of the run.
+ * Installed packages (and project files imported as CommonJS) load through
+ * Node's own `require`, so the built-ins they use are checked by a hook on
+ * Node's module loader instead.
+ */
+
+import Module from 'module';
+import path from 'path';
+import type {
+  SandboxCapability,
+  SandboxDecision,
+  SandboxPermissions,
+} from '@cheesejs/core';
+import { SANDBOX_PROFILES, DEFAULT_SANDBOX_PROFILE } from '@cheesejs/core';
+import { getModuleCapability } from './sandboxRequirePolicy.js';
+
+export type PermissionPrompt = (
+  capability: SandboxCapability,
+  detail: string
+) => SandboxDecision;
+
+const CAPABILITY_LABELS: Record<SandboxCapability, string> = {
+  fs: 'file system access',
+  net: 'network access',
+  fetch: 'fetch',
+  env: 'environment variables',
+  childProcess: 'starting processes',
+};
+
+// fs functions that change the file system; `*Sync` variants are derived
+const FS_WRITE_METHODS = [
+  'appendFile',
+  'chmod',
+  'chown',
+  'copyFile',
+  'cp',
+  'createWriteStream',
+  'fchmod',
+  'fchown',
+  'fdatasync',
+  'fsync',
+  'ftruncate',
+  'futimes',
+  'lchmod',
+  'lchown',
+  'link',
+  'lutimes',
+  'mkdir',
+  'mkdtemp',
+  'rename',
+  'rm',
+  'rmdir',
+  'symlink',
+  'truncate',
+  'unlink',
+  'utimes',
+  'write',
+  'writeFile',
+  'writev',
+];
+
+function createPermissionError(
+  capability: SandboxCapability,
+  detail: string
+): Error {
+  return new Error(
+    `Permission denied: ${CAPABILITY_LABELS[capability]} (${detail}) is not allowed by this tab's sandbox profile`
+  );
+}
+
+// Opening for anything but reading counts as a write
+const READ_ONLY_FLAGS: unknown[] = [undefined, null, 0, 'r', 'rs', 'sr'];
+
+/**
+ * Copy of an `fs` or `fs/promises` module whose writing functions throw,
+ * including the ones reached through `fs.promises`
+ */
+export function createReadOnlyFs(
+  fsModule: Record<string, unknown>,
+  moduleName: string
+): Record<string, unknown> {
+  const readOnly: Record<string, unknown> = { ...fsModule };
+  const reject = (method: string): never => {
+    throw createPermissionError('fs', `${moduleName}.${method}`);
+  };
+
+  for (const method of FS_WRITE_METHODS) {
+    for (const name of [method, `${method}Sync`]) {
+      if (typeof fsModule[name] === 'function') {
+        readOnly[name] = () => reject(name);
+      }
+    }
+  }
+
+  for (const name of ['open', 'openSync']) {
+    const open = fsModule[name];
+    if (typeof open !== 'function') continue;
+    readOnly[name] = (file: unknown, flags?: unknown, ...rest: unknown[]) =>
+      READ_ONLY_FLAGS.includes(flags)
+        ? open(file, flags, ...rest)
+        : reject(name);
+  }
+
+  const promises = fsModule.promises;
+  if (promises && typeof promises === 'object') {
+    readOnly.promises = createReadOnlyFs(
+      promises as Record<string, unknown>,
+      `${moduleName}.promises`
+    );
+  }
+  return readOnly;
+}
+
+export class SandboxPermissionGate {
+  private readonly decisions = new Map<SandboxCapability, SandboxDecision>();
+
+  constructor(
+    private readonly permissions: SandboxPermissions = SANDBOX_PROFILES[
+      DEFAULT_SANDBOX_PROFILE
+    ],
+    private readonly prompt?: PermissionPrompt
+  ) {}
+
+  /** Whether the run may use `capability`, asking once if the profile says */
+  isAllowed(capability: SandboxCapability, detail: string): boolean {
+    const permission = this.permissions[capability];
+    if (permission !== 'prompt') return permission !== 'deny';
+
+    let decision = this.decisions.get(capability);
+    if (!decision) {
+      decision = this.prompt?.(capability, detail) ?? 'deny';
+      this.decisions.set(capability, decision);
+    }
+    return decision === 'allow';
+  }
+
+  assertAllowed(capability: SandboxCapability, detail: string): void {
+    if (!this.isAllowed(capability, detail)) {
+      throw createPermissionError(capability, detail);
+    }
+  }
+
+  /**
+   * Loads a module through `load` if the profile grants what it needs;
+   * `fs` under a read-only profile comes back without its writing functions
+   */
+  requireModule(moduleName: string, load: () => unknown): unknown {
+    const capability = getModuleCapability(moduleName);
+    if (!capability) return load();
+
+    this.assertAllowed(capability, `import '${moduleName}'`);
+    const loaded = load();
+    return capability === 'fs' && this.permissions.fs === 'read'
+      ? createReadOnlyFs(loaded as Record<string, unknown>, moduleName)
+      : loaded;
+  }
+
+  /** `fetch` that checks the profile before each request */
+  wrapFetch(fetchImpl: typeof fetch): typeof fetch {
+    return ((input: Parameters<typeof fetch>[0], init?: RequestInit) => {
+      const url =
+        typeof input === 'object' && 'url' in input ? input.url : String(input);
+      if (!this.isAllowed('fetch', `fetch ${url}`)) {
+        return Promise.reject(createPermissionError('fetch', `fetch ${url}`));
+      }
+      return fetchImpl(input, init);
+    }) as typeof fetch;
+  }
+
+  /**
+   * `process.env` for the sandbox: empty when denied, and asked for the
+   * first time a variable is read when the profile prompts
+   */
+  createEnv(
+    env: Record<string, string | undefined>
+  ): Record<string, string | undefined> {
+    const permission = this.permissions.env;
+    if (permission === 'deny') return {};
+    if (permission !== 'prompt') return env;
+
+    const visible = (key: string | symbol) =>
+      typeof key === 'string' && this.isAllowed('env', `process.env.${key}`);
+    return new Proxy(env, {
+      get: (target, key) =>
+        visible(key) ? Reflect.get(target, key) : undefined,
+      has: (target, key) => visible(key) && Reflect.has(target, key),
+      ownKeys: (target) =>
+        this.isAllowed('env', 'process.env') ? Reflect.ownKeys(target) : [],
+      getOwnPropertyDescriptor: (target, key) =>
+        visible(key)
+          ? Reflect.getOwnPropertyDescriptor(target, key)
+          : undefined,
+    });
+  }
+}
+
+type ModuleLoad = (
+  request: string,
+  parent: NodeJS.Module | undefined,
+  isMain: boolean
+) => unknown;
+
+/**
+ * Checks the built-ins that files under `getDirectories()` require against
+ * `getGate()`, the gate of the run in progress. Returns a function that
+ * removes the hook.
+ */
+export function gatePackageBuiltins(
+  getDirectories: () => string[],
+  getGate: () => SandboxPermissionGate
+): () => void {
+  const loader = Module as unknown as { _load: ModuleLoad };
+  const load = loader._load;
+  const isGated = (filename: string) =>
+    getDirectories().some((directory) => {
+      const relative = path.relative(directory, filename);
+      return !relative.startsWith('..') && !path.isAbsolute(relative);
+    });
+
+  loader._load = function (this: unknown, request, parent, isMain) {
+    if (
+      !parent?.filename ||
+      !Module.isBuiltin(request) ||
+      !isGated(parent.filename)
+    ) {
+      return load.call(this, request, parent, isMain);
+    }
+    return getGate().requireModule(request, () =>
+      load.call(this, request, parent, isMain)
+    );
+  };
+  return () => {
+    loader._load = load;
+  };
+}
@@ -2,13 +2,16 @@
  * Sandbox require policy for JS/TS execution worker.
  *
  * The goal is to block only high-risk Node.js built-ins while preserving
- * broad compatibility with user-installed npm packages.
+ * broad compatibility with user-installed npm packages. Built-ins that
+ * reach the file system, the network or other processes are gated by the
+ * tab's sandbox profile instead.
  */
 
+import type { SandboxCapability } from '@cheesejs/core';
+
 // High-risk Node.js built-ins that can break sandbox guarantees.
 // Kept intentionally small to reduce compatibility impact.
 export const BLOCKED_NODE_MODULES = new Set([
-  'child_process',
   'cluster',
   'inspector',
   'module',
@@ -17,6 +20,19 @@ export const BLOCKED_NODE_MODULES = new Set([
   'worker_threads',
 ]);
 
+// Built-ins that are only available when the profile grants the capability
+export const GATED_NODE_MODULES: Record<string, SandboxCapability> = {
+  fs: 'fs',
+  net: 'net',
+  http: 'net',
+  https: 'net',
+  http2: 'net',
+  dgram: 'net',
+  dns: 'net',
+  tls: 'net',
+  child_process: 'childProcess',
+};
+
 export function normalizeModuleSpecifier(moduleName: string): string {
   const withoutNodePrefix = moduleName.startsWith('node:')
     ? moduleName.slice(5)
@@ -29,3 +45,13 @@ export function normalizeModuleSpecifier(moduleName: string): string {
 export function isBlockedSandboxModule(moduleName: string): boolean {
   return BLOCKED_NODE_MODULES.has(normalizeModuleSpecifier(moduleName));
 }
+
+/** The capability a built-in needs, or undefined if it is not gated */
+export function getModuleCapability(
+  moduleName: string
+): SandboxCapability | undefined {
+  const name = normalizeModuleSpecifier(moduleName);
+  return Object.hasOwn(GATED_NODE_MODULES, name)
+    ? GATED_NODE_MODULES[name]
+    : undefined;
+}
@@ -12,46 +12,33 @@
  */
 
 import { parentPort } from 'worker_threads';
-import { transformSync, type Options, type JscTarget } from '@swc/core';
+import {
+  transformCode,
+  type TransformOptions,
+} from '../transpiler/swcTranspiler.js';
 
 // ============================================================================
 // TYPES
 // ============================================================================
 
-export interface TranspileOptions {
-  /** Wrap top-level expressions with debug() for inline results */
-  showTopLevelResults?: boolean;
-  /** Add loop protection to prevent infinite loops */
-  loopProtection?: boolean;
-  /** Process //? magic comments for debug output */
-  magicComments?: boolean;
-  /** Show undefined values in output */
-  showUndefined?: boolean;
-  /** Target ECMAScript version */
-  targetVersion?: 'ES2022' | 'ES2024' | 'ESNext';
-  /** Enable experimental decorators */
-  experimentalDecorators?: boolean;
-  /** Parse JSX syntax */
-  jsx?: boolean;
-  /** Debug function name (default: 'debug', can be '__jsDebug' for JS-specific) */
-  debugFunctionName?: string;
-}
+/** Same options as the main-process pipeline, which this worker shares */
+export type TranspileOptions = TransformOptions;
 
-interface TranspileRequest {
+export interface TranspileRequest {
   type: 'transpile';
   id: string;
   code: string;
   options: TranspileOptions;
 }
 
-interface TranspileResult {
+export interface TranspileResult {
   type: 'result';
   id: string;
   code: string;
   timing: number;
 }
 
-interface TranspileError {
+export interface TranspileError {
   type: 'error';
   id: string;
   error: string;
@@ -71,12 +58,8 @@ const CACHE_MAX_SIZE = 100;
 const CACHE_TTL_MS = 5 * 60 * 1000; // 5 minutes
 
 function getCacheKey(code: string, options: TranspileOptions): string {
-  const optKey = JSON.stringify({
-    t: options.targetVersion,
-    d: options.experimentalDecorators,
-    j: options.jsx,
-    fn: options.debugFunctionName,
-  });
+  // Every option can change the output, so all of them go in the key
+  const optKey = JSON.stringify(options);
   // Simple hash for cache key
   let hash = 0;
   const str = code + optKey;
@@ -108,282 +91,45 @@ function setCache(key: string, code: string): void {
 }
 
 // ============================================================================
-// SWC TRANSPILATION
-// ============================================================================
-
-function getSwcTarget(options: TranspileOptions): JscTarget {
-  switch (options.targetVersion) {
-    case 'ESNext':
-      return 'esnext';
-    case 'ES2024':
-      return 'es2022'; // SWC uses es2022 as highest stable
-    default:
-      return 'es2022';
-  }
-}
-
-function transpileWithSWC(code: string, options: TranspileOptions): string {
-  const target = getSwcTarget(options);
-
-  const swcOptions: Options = {
-    filename: 'index.tsx',
-    jsc: {
-      parser: {
-        syntax: 'typescript',
-        tsx: options.jsx !== false,
-        decorators: options.experimentalDecorators ?? true,
-        dynamicImport: true,
-      },
-      target,
-      transform: {
-        decoratorVersion: '2022-03',
-        react: {
-          runtime: 'classic',
-          pragma: 'React.createElement',
-          pragmaFrag: 'React.Fragment',
-        },
-      },
-      loose: false,
-      externalHelpers: false,
-      keepClassNames: true,
-    },
-    module: {
-      type: 'commonjs',
-      strict: false,
-      strictMode: false,
-      noInterop: false,
-      importInterop: 'swc',
-    },
-    sourceMaps: false,
-    isModule: true,
-  };
-
-  const result = transformSync(code, swcOptions);
-  return result.code;
-}
-
-// ============================================================================
-// CODE TRANSFORMATIONS
+// WORKER MESSAGE HANDLER
 // ============================================================================
 
-function findMatchingParen(code: string, startIndex: number): number {
-  let depth = 1;
-  let i = startIndex;
-
-  while (i < code.length && depth > 0) {
-    const char = code[i];
-    if (char === '(') depth++;
-    else if (char === ')') depth--;
-
-    if (char === '"' || char === "'" || char === '`') {
-      const quote = char;
-      i++;
-      while (i < code.length && code[i] !== quote) {
-        if (code[i] === '\\') i++;
-        i++;
-      }
-    }
-    i++;
-  }
-
-  return depth === 0 ? i - 1 : -1;
-}
-
-function transformConsoleToDebug(code: string, debugFn: string): string {
-  const lines = code.split('\n');
-  const result: string[] = [];
-
-  for (let i = 0; i < lines.length; i++) {
-    let line = lines[i];
-    const lineNumber = i + 1;
-
-    const consoleStartRegex = /\bconsole\.(log|warn|error|info|debug)\s*\(/g;
-    let match;
-    let offset = 0;
-
-    while ((match = consoleStartRegex.exec(lines[i])) !== null) {
-      const fullMatchStart = match.index + offset;
-      const openParenPos = fullMatchStart + match[0].length - 1;
-      const closeParenPos = findMatchingParen(line, openParenPos + 1);
-
-      if (closeParenPos !== -1) {
-        const args = line.slice(openParenPos + 1, closeParenPos).trim();
-        const before = line.slice(0, fullMatchStart);
-        const after = line.slice(closeParenPos + 1);
-
-        const replacement =
-          args === ''
-            ? `${debugFn}(${lineNumber})`
-            : `${debugFn}(${lineNumber}, ${args})`;
-
-        line = before + replacement + after;
-        offset += replacement.length - (closeParenPos + 1 - fullMatchStart);
-      }
-    }
-
-    result.push(line);
-  }
-
-  return result.join('\n');
-}
-
-function addLoopProtection(code: string): string {
-  const MAX_ITERATIONS = 10000;
-  let loopCounter = 0;
-
-  const loopPattern = /(while\s*\([^)]*\)\s*\{|for\s*\([^)]*\)\s*\{|do\s*\{)/g;
-
-  return code.replace(loopPattern, (match) => {
-    const counterVar = `__loopCounter${loopCounter++}`;
-    const checkCode = `let ${counterVar} = 0; if (++${counterVar} > ${MAX_ITERATIONS}) throw new Error("Loop limit exceeded (${MAX_ITERATIONS} iterations)"); `;
-
-    const braceIndex = match.lastIndexOf('{');
-    return match.slice(0, braceIndex + 1) + '\n' + checkCode + '\n';
-  });
-}
-
-function wrapTopLevelExpressions(code: string, debugFn: string): string {
-  const lines = code.split('\n');
-  const result: string[] = [];
-
-  const skipPatterns = [
-    /^$/,
-    /^\/\//,
-    /^\/\*/,
-    /^\*/,
-    /^import\s/,
-    /^export\s/,
-    /^(const|let|var)\s/,
-    /^(function|async\s+function)\s/,
-    /^class\s/,
-    /^(interface|type|enum)\s/,
-    /^(if|else|for|while|switch|try|catch|finally|do)\s*[({]/,
-    /^(return|throw|break|continue|yield)[\s;]/,
-    /^[{}()[\]]/,
-    /^[,\]})]+ ?;?$/,
-    new RegExp(`^${debugFn}\\(`),
-    new RegExp(`^await\\s+${debugFn}\\(`),
-    /^\./,
-    /^using\s/,
-    /^await\s+using\s/,
-  ];
-
-  for (let i = 0; i < lines.length; i++) {
-    const line = lines[i];
-    const trimmed = line.trim();
-    const lineNumber = i + 1;
-
-    // Check skip patterns
-    if (skipPatterns.some((p) => p.test(trimmed))) {
-      result.push(line);
-      continue;
-    }
-
-    // Skip assignments
-    if (
-      /^[a-zA-Z_$][a-zA-Z0-9_$]*\s*=\s*/.test(trimmed) &&
-      !trimmed.startsWith('==')
-    ) {
-      result.push(line);
-      continue;
-    }
-
-    // Skip if next line is method chaining
-    const nextLine = i + 1 < lines.length ? lines[i + 1].trim() : '';
-    if (nextLine.startsWith('.')) {
-      result.push(line);
-      continue;
+/**
+ * Transpile one request through the shared pipeline, served from the cache
+ * when the same code and options were seen recently
+ */
+export function transpileRequest(
+  request: TranspileRequest
+): TranspileResult | TranspileError {
+  const startTime = performance.now();
+  const { id, code, options } = request;
+
+  try {
+    // Check cache first
+    const cacheKey = getCacheKey(code, options);
+    let transpiledCode = getFromCache(cacheKey);
+
+    if (transpiledCode === null) {
+      transpiledCode = transformCode(code, options);
+      setCache(cacheKey, transpiledCode);
     }
 
-    // Wrap expressions ending with semicolon
-    if (
-      trimmed.endsWith(';') &&
-      !trimmed.includes('.then(') &&
-      !trimmed.includes('.catch(')
-    ) {
-      const expr = trimmed.slice(0, -1);
-      const indent = line.match(/^\s*/)?.[0] || '';
-      result.push(`${indent}${debugFn}(${lineNumber}, ${expr});`);
-    } else {
-      result.push(line);
-    }
+    return {
+      type: 'result',
+      id,
+      code: transpiledCode,
+      timing: performance.now() - startTime,
+    };
+  } catch (error) {
+    return {
+      type: 'error',
+      id,
+      error: error instanceof Error ? error.message : String(error),
+      timing: performance.now() - startTime,
+    };
   }
-
-  return result.join('\n');
 }
 
-function applyMagicComments(code: string, debugFn: string): string {
-  const lines = code.split('\n');
-  const result: string[] = [];
-
-  for (let i = 0; i < lines.length; i++) {
-    const line = lines[i];
-    const lineNumber = i + 1;
-
-    const magicMatch = line.match(/(.+?)\/\/\?\s*(.*)$/);
-
-    if (magicMatch) {
-      const codePart = magicMatch[1].trim();
-      const varMatch = codePart.match(
-        /^(const|let|var)\s+([a-zA-Z_$][a-zA-Z0-9_$]*)\s*=\s*(.+?);?\s*$/
-      );
-
-      if (varMatch) {
-        const [, keyword, varName, value] = varMatch;
-        result.push(`${keyword} ${varName} = ${value};`);
-        result.push(`${debugFn}(${lineNumber}, ${varName});`);
-      } else {
-        const exprMatch = codePart.match(/^(.+?);?\s*$/);
-        if (exprMatch) {
-          result.push(`${debugFn}(${lineNumber}, ${exprMatch[1]});`);
-        } else {
-          result.push(line);
-        }
-      }
-    } else {
-      result.push(line);
-    }
-  }
-
-  return result.join('\n');
-}
-
-// ============================================================================
-// FULL TRANSFORM PIPELINE
-// ============================================================================
-
-function transformCode(code: string, options: TranspileOptions): string {
-  if (!code.trim()) return '';
-
-  const debugFn = options.debugFunctionName || 'debug';
-
-  // Step 1: Apply magic comments BEFORE transpilation
-  let processedCode = code;
-  if (options.magicComments) {
-    processedCode = applyMagicComments(code, debugFn);
-  }
-
-  // Step 2: Transpile TS/JSX with SWC
-  const transpiled = transpileWithSWC(processedCode, options);
-
-  // Step 3: Apply code transformations
-  let transformed = transformConsoleToDebug(transpiled, debugFn);
-
-  if (options.loopProtection) {
-    transformed = addLoopProtection(transformed);
-  }
-
-  if (options.showTopLevelResults !== false) {
-    transformed = wrapTopLevelExpressions(transformed, debugFn);
-  }
-
-  return transformed;
-}
-
-// ============================================================================
-// WORKER MESSAGE HANDLER
-// ============================================================================
-
 function handleMessage(message: WorkerMessage): void {
   if (message.type === 'ping') {
     parentPort?.postMessage({ type: 'pong' });
@@ -391,39 +137,7 @@ function handleMessage(message: WorkerMessage): void {
   }
 
   if (message.type === 'transpile') {
-    const startTime = performance.now();
-    const { id, code, options } = message;
-
-    try {
-      // Check cache first
-      const cacheKey = getCacheKey(code, options);
-      let transpiledCode = getFromCache(cacheKey);
-
-      if (!transpiledCode) {
-        transpiledCode = transformCode(code, options);
-        setCache(cacheKey, transpiledCode);
-      }
-
-      const timing = performance.now() - startTime;
-
-      parentPort?.postMessage({
-        type: 'result',
-        id,
-        code: transpiledCode,
-        timing,
-      } as TranspileResult);
-    } catch (error) {
-      const timing = performance.now() - startTime;
-      const errorMessage =
-        error instanceof Error ? error.message : String(error);
-
-      parentPort?.postMessage({
-        type: 'error',
-        id,
-        error: errorMessage,
-        timing,
-      } as TranspileError);
-    }
+    parentPort?.postMessage(transpileRequest(message));
   }
 }
 
@@ -439,6 +153,3 @@ if (parentPort) {
     '[SWCTranspilerWorker] No parentPort available - not running as worker'
   );
 }
-
-// Export for testing
-export { transformCode, transpileWithSWC };
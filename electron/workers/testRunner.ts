@@ -0,0 +1,657 @@
+/**
+ * Test runner for JS/TS programs
+ *
+ * Gives the sandbox a Jest-compatible subset of `describe`/`it`/`test`,
+ * the lifecycle hooks and `expect`. Tests register while the program runs
+ * and execute one after the other once it has finished. Each remembers
+ * the editor line it was declared on, so its outcome can be shown there.
+ */
+
+import util from 'util';
+import type { TestCaseResult, TestRunSummary } from '@cheesejs/core';
+
+type TestFn = () => unknown;
+type RunMode = 'run' | 'skip' | 'only';
+
+interface TestCase {
+  kind: 'test';
+  name: string;
+  fn?: TestFn;
+  mode: RunMode;
+  line?: number;
+}
+
+interface TestSuite {
+  kind: 'suite';
+  name: string;
+  mode: RunMode;
+  parent?: TestSuite;
+  children: Array<TestCase | TestSuite>;
+  beforeAll: TestFn[];
+  afterAll: TestFn[];
+  beforeEach: TestFn[];
+  afterEach: TestFn[];
+}
+
+/** What a suite passes down to the tests and suites inside it */
+interface SuiteScope {
+  path: string[];
+  skipped: boolean;
+  focused: boolean;
+  beforeEach: TestFn[];
+  afterEach: TestFn[];
+  /** Set when a `beforeAll` hook of an enclosing suite threw */
+  hookError?: unknown;
+}
+
+/** Jest joins the names of nested blocks with this */
+const NAME_SEPARATOR = ' › ';
+
+const VALUE_OPTIONS: util.InspectOptions = {
+  depth: 6,
+  colors: false,
+  compact: 3,
+  breakLength: 60,
+  maxArrayLength: 50,
+  maxStringLength: 200,
+};
+
+// ============================================================================
+// EXPECT
+// ============================================================================
+
+/** A failed `expect`, carrying the values it compared */
+export class ExpectationError extends Error {
+  constructor(
+    message: string,
+    readonly diff?: string
+  ) {
+    super(message);
+    this.name = 'ExpectationError';
+  }
+}
+
+interface MatcherResult {
+  pass: boolean;
+  /** Shown as `Expected:`; leave out for matchers without an operand */
+  expected?: unknown;
+  /** Describes `expected` in words instead of printing it */
+  expectedLabel?: string;
+  /** Compare the printed values line by line */
+  diffable?: boolean;
+}
+
+type Matcher = (received: unknown, ...args: unknown[]) => MatcherResult;
+
+function formatValue(value: unknown): string {
+  return util.inspect(value, VALUE_OPTIONS);
+}
+
+function isObject(value: unknown): value is Record<PropertyKey, unknown> {
+  return typeof value === 'object' && value !== null;
+}
+
+function getTag(value: unknown): string {
+  return Object.prototype.toString.call(value);
+}
+
+function getDefinedKeys(value: Record<PropertyKey, unknown>): string[] {
+  return Object.keys(value).filter((key) => value[key] !== undefined);
+}
+
+/**
+ * `toEqual` equality: recursive, ignoring properties set to `undefined`
+ * and the classes of objects. Values come from the sandbox realm, so
+ * types are told apart by tag rather than `instanceof`.
+ */
+function equals(a: unknown, b: unknown): boolean {
+  if (Object.is(a, b)) return true;
+  if (!isObject(a) || !isObject(b) || getTag(a) !== getTag(b)) return false;
+  if (util.types.isDate(a)) return a.getTime() === (b as Date).getTime();
+  if (util.types.isRegExp(a)) return String(a) === String(b);
+  if (util.types.isMap(a) || util.types.isSet(a)) {
+    return util.isDeepStrictEqual(a, b);
+  }
+  if (Array.isArray(a) && a.length !== (b as unknown[]).length) return false;
+
+  const keys = getDefinedKeys(a);
+  return (
+    keys.length === getDefinedKeys(b).length &&
+    keys.every(
+      (key) =>
+        Object.prototype.hasOwnProperty.call(b, key) && equals(a[key], b[key])
+    )
+  );
+}
+
+/** Whether `received` has every property of `expected`, recursively */
+function matchesObject(received: unknown, expected: unknown): boolean {
+  if (!isObject(expected) || !isObject(received)) {
+    return equals(received, expected);
+  }
+  if (Array.isArray(expected)) {
+    return (
+      Array.isArray(received) &&
+      received.length === expected.length &&
+      expected.every((item, index) => matchesObject(received[index], item))
+    );
+  }
+  return Object.keys(expected).every(
+    (key) => key in received && matchesObject(received[key], expected[key])
+  );
+}
+
+function getPropertyPath(path: unknown): string[] {
+  return Array.isArray(path) ? path.map(String) : String(path).split('.');
+}
+
+function getNumber(value: unknown, matcher: string): number | bigint {
+  if (typeof value !== 'number' && typeof value !== 'bigint') {
+    throw new ExpectationError(
+      `${matcher}: received value must be a number, got ${formatValue(value)}`
+    );
+  }
+  return value;
+}
+
+function describeThrown(error: unknown): string {
+  return isObject(error) && 'message' in error
+    ? String(error.message)
+    : formatValue(error);
+}
+
+const MATCHERS: Record<string, Matcher> = {
+  toBe: (received, expected) => ({
+    pass: Object.is(received, expected),
+    expected,
+    diffable: true,
+  }),
+  toEqual: (received, expected) => ({
+    pass: equals(received, expected),
+    expected,
+    diffable: true,
+  }),
+  toStrictEqual: (received, expected) => ({
+    pass: util.isDeepStrictEqual(received, expected),
+    expected,
+    diffable: true,
+  }),
+  toMatchObject: (received, expected) => ({
+    pass: matchesObject(received, expected),
+    expected,
+    diffable: true,
+  }),
+  toBeTruthy: (received) => ({ pass: !!received }),
+  toBeFalsy: (received) => ({ pass: !received }),
+  toBeNull: (received) => ({ pass: received === null, expected: null }),
+  toBeUndefined: (received) => ({
+    pass: received === undefined,
+    expected: undefined,
+  }),
+  toBeDefined: (received) => ({ pass: received !== undefined }),
+  toBeNaN: (received) => ({ pass: Number.isNaN(received), expected: NaN }),
+  toBeGreaterThan: (received, expected) => ({
+    pass: getNumber(received, 'toBeGreaterThan') > (expected as number),
+    expectedLabel: `> ${formatValue(expected)}`,
+  }),
+  toBeGreaterThanOrEqual: (received, expected) => ({
+    pass: getNumber(received, 'toBeGreaterThanOrEqual') >= (expected as number),
+    expectedLabel: `>= ${formatValue(expected)}`,
+  }),
+  toBeLessThan: (received, expected) => ({
+    pass: getNumber(received, 'toBeLessThan') < (expected as number),
+    expectedLabel: `< ${formatValue(expected)}`,
+  }),
+  toBeLessThanOrEqual: (received, expected) => ({
+    pass: getNumber(received, 'toBeLessThanOrEqual') <= (expected as number),
+    expectedLabel: `<= ${formatValue(expected)}`,
+  }),
+  toBeCloseTo: (received, expected, digits = 2) => {
+    const actual = Number(getNumber(received, 'toBeCloseTo'));
+    const tolerance = 10 ** -(digits as number) / 2;
+    return {
+      pass: Math.abs(actual - (expected as number)) < tolerance,
+      expected,
+    };
+  },
+  toContain: (received, item) => {
+    const contains =
+      typeof received === 'string'
+        ? received.includes(String(item))
+        : isObject(received) && Symbol.iterator in received
+          ? Array.from(received as Iterable<unknown>).includes(item)
+          : false;
+    return { pass: contains, expectedLabel: `to contain ${formatValue(item)}` };
+  },
+  toHaveLength: (received, length) => ({
+    pass: (received as { length?: unknown } | null)?.length === length,
+    expectedLabel: `length ${formatValue(length)}`,
+  }),
+  toHaveProperty: (received, path, ...value) => {
+    let current: unknown = received;
+    for (const key of getPropertyPath(path)) {
+      if (!isObject(current) || !(key in current)) {
+        return { pass: false, expectedLabel: `property ${formatValue(path)}` };
+      }
+      current = current[key];
+    }
+    return value.length === 0
+      ? { pass: true, expectedLabel: `property ${formatValue(path)}` }
+      : { pass: equals(current, value[0]), expected: value[0] };
+  },
+  toMatch: (received, pattern) => ({
+    pass:
+      typeof received === 'string' &&
+      (util.types.isRegExp(pattern)
+        ? new RegExp(pattern).test(received)
+        : received.includes(String(pattern))),
+    expectedLabel: `to match ${formatValue(pattern)}`,
+  }),
+  toBeInstanceOf: (received, constructor) => ({
+    pass: received instanceof (constructor as new () => unknown),
+    expectedLabel: `instance of ${(constructor as { name?: string }).name}`,
+  }),
+  toThrow: (received, expected) => {
+    if (typeof received !== 'function') {
+      throw new ExpectationError(
+        `toThrow: received value must be a function, got ${formatValue(received)}`
+      );
+    }
+    try {
+      received();
+    } catch (error) {
+      const message = describeThrown(error);
+      const pass =
+        expected === undefined
+          ? true
+          : typeof expected === 'function'
+            ? error instanceof (expected as new () => unknown)
+            : util.types.isRegExp(expected)
+              ? expected.test(message)
+              : isObject(expected)
+                ? message === describeThrown(expected)
+                : message.includes(String(expected));
+      return {
+        pass,
+        expectedLabel:
+          expected === undefined
+            ? 'to throw'
+            : `to throw ${formatValue(expected)}`,
+      };
+    }
+    return { pass: false, expectedLabel: 'to throw' };
+  },
+};
+
+/** Jest's `-`/`+` line diff of two printed values */
+function diffLines(expected: string, received: string): string {
+  const a = expected.split('\n');
+  const b = received.split('\n');
+  // Longest common subsequence table, filled from the end
+  const lengths = Array.from({ length: a.length + 1 }, () =>
+    new Array<number>(b.length + 1).fill(0)
+  );
+  for (let i = a.length - 1; i >= 0; i--) {
+    for (let j = b.length - 1; j >= 0; j--) {
+      lengths[i][j] =
+        a[i] === b[j]
+          ? lengths[i + 1][j + 1] + 1
+          : Math.max(lengths[i + 1][j], lengths[i][j + 1]);
+    }
+  }
+
+  const lines = ['- Expected', '+ Received', ''];
+  let i = 0;
+  let j = 0;
+  while (i < a.length || j < b.length) {
+    if (i < a.length && j < b.length && a[i] === b[j]) {
+      lines.push(`  ${a[i++]}`);
+      j++;
+    } else if (
+      j >= b.length ||
+      (i < a.length && lengths[i + 1][j] >= lengths[i][j + 1])
+    ) {
+      lines.push(`- ${a[i++]}`);
+    } else {
+      lines.push(`+ ${b[j++]}`);
+    }
+  }
+  return lines.join('\n');
+}
+
+function describeFailure(
+  name: string,
+  received: unknown,
+  args: unknown[],
+  result: MatcherResult,
+  negated: boolean
+): ExpectationError {
+  const not = negated ? 'not ' : '';
+  const operand = args.length > 0 ? 'expected' : '';
+  const hint = `expect(received).${negated ? 'not.' : ''}${name}(${operand})`;
+  const receivedText = formatValue(received);
+
+  if (result.expectedLabel !== undefined) {
+    return new ExpectationError(
+      hint,
+      `Expected: ${not}${result.expectedLabel}\nReceived: ${receivedText}`
+    );
+  }
+  if (!('expected' in result)) {
+    return new ExpectationError(hint, `Received: ${receivedText}`);
+  }
+
+  const expectedText = formatValue(result.expected);
+  const diff =
+    result.diffable &&
+    !negated &&
+    (expectedText.includes('\n') || receivedText.includes('\n'))
+      ? diffLines(expectedText, receivedText)
+      : `Expected: ${not}${expectedText}\nReceived: ${receivedText}`;
+  return new ExpectationError(hint, diff);
+}
+
+type Matchers = Record<string, (...args: unknown[]) => unknown>;
+
+function createMatchers(
+  received: unknown,
+  negated: boolean,
+  settle?: 'resolves' | 'rejects'
+): Matchers {
+  const check = (name: string, value: unknown, args: unknown[]) => {
+    const result = MATCHERS[name](value, ...args);
+    if (result.pass === negated) {
+      throw describeFailure(name, value, args, result, negated);
+    }
+  };
+
+  return Object.fromEntries(
+    Object.keys(MATCHERS).map((name) => [
+      name,
+      (...args: unknown[]) => {
+        if (!settle) return check(name, received, args);
+
+        return Promise.resolve(received).then(
+          (value) => {
+            if (settle === 'rejects') {
+              throw new ExpectationError(
+                `expect(received).rejects.${name}()`,
+                `Received promise resolved instead of rejected\nResolved to: ${formatValue(value)}`
+              );
+            }
+            check(name, value, args);
+          },
+          (error: unknown) => {
+            if (settle === 'resolves') {
+              throw new ExpectationError(
+                `expect(received).resolves.${name}()`,
+                `Received promise rejected instead of resolved\nRejected with: ${formatValue(error)}`
+              );
+            }
+            // `rejects.toThrow()` checks the rejection reason itself
+            const value =
+              name === 'toThrow'
+                ? () => {
+                    throw error;
+                  }
+                : error;
+            check(name, value, args);
+          }
+        );
+      },
+    ])
+  );
+}
+
+/** Jest's `expect(value)`, with `.not`, `.resolves` and `.rejects` */
+export function expect(received: unknown) {
+  return {
+    ...createMatchers(received, false),
+    not: createMatchers(received, true),
+    resolves: {
+      ...createMatchers(received, false, 'resolves'),
+      not: createMatchers(received, true, 'resolves'),
+    },
+    rejects: {
+      ...createMatchers(received, false, 'rejects'),
+      not: createMatchers(received, true, 'rejects'),
+    },
+  };
+}
+
+// ============================================================================
+// COLLECTION AND RUNS
+// ============================================================================
+
+function createSuite(
+  name: string,
+  mode: RunMode,
+  parent?: TestSuite
+): TestSuite {
+  return {
+    kind: 'suite',
+    name,
+    mode,
+    parent,
+    children: [],
+    beforeAll: [],
+    afterAll: [],
+    beforeEach: [],
+    afterEach: [],
+  };
+}
+
+function describeError(
+  error: unknown
+): Pick<TestCaseResult, 'message' | 'diff'> {
+  if (error instanceof ExpectationError) {
+    return { message: error.message, diff: error.diff };
+  }
+  if (isObject(error) && 'message' in error) {
+    const name = 'name' in error ? String(error.name) : 'Error';
+    return { message: `${name}: ${String(error.message)}` };
+  }
+  return { message: `Thrown: ${formatValue(error)}` };
+}
+
+async function runHooks(hooks: TestFn[]): Promise<void> {
+  for (const hook of hooks) {
+    await hook();
+  }
+}
+
+/**
+ * The tests one run declares. `locate` finds the editor line of a call
+ * from its stack, for showing outcomes inline.
+ */
+export class TestCollector {
+  private readonly root = createSuite('', 'run');
+  private current = this.root;
+  private hasFocused = false;
+  private testCount = 0;
+
+  constructor(
+    private readonly locate: (stack: string) => number | undefined
+  ) {}
+
+  /** Number of tests declared so far */
+  get size(): number {
+    return this.testCount;
+  }
+
+  /** `describe`, `it`, `test`, hooks and `expect` for the sandbox */
+  createGlobals(): Record<string, unknown> {
+    const suite = (mode: RunMode) => (name: unknown, fn: TestFn) =>
+      this.addSuite(String(name), fn, mode);
+    const test = (mode: RunMode) => (name: unknown, fn?: TestFn) =>
+      this.addTest(String(name), fn, mode);
+    const hook =
+      (type: 'beforeAll' | 'afterAll' | 'beforeEach' | 'afterEach') =>
+      (fn: TestFn) => {
+        this.current[type].push(fn);
+      };
+
+    const describe = Object.assign(suite('run'), {
+      skip: suite('skip'),
+      only: suite('only'),
+    });
+    const it = Object.assign(test('run'), {
+      skip: test('skip'),
+      only: test('only'),
+      todo: (name: unknown) => this.addTest(String(name), undefined, 'skip'),
+    });
+
+    return {
+      describe,
+      it,
+      test: it,
+      expect,
+      beforeAll: hook('beforeAll'),
+      afterAll: hook('afterAll'),
+      beforeEach: hook('beforeEach'),
+      afterEach: hook('afterEach'),
+    };
+  }
+
+  /**
+   * Runs the declared tests in order, reporting each as it settles. Stops
+   * early once `isCancelled` returns true.
+   */
+  async run(
+    report: (result: TestCaseResult) => void,
+    isCancelled: () => boolean = () => false
+  ): Promise<TestRunSummary> {
+    const startTime = performance.now();
+    const summary: TestRunSummary = {
+      passed: 0,
+      failed: 0,
+      skipped: 0,
+      duration: 0,
+    };
+
+    const runSuite = async (
+      suite: TestSuite,
+      scope: SuiteScope
+    ): Promise<void> => {
+      let { hookError } = scope;
+      if (!scope.skipped && hookError === undefined) {
+        try {
+          await runHooks(suite.beforeAll);
+        } catch (error) {
+          hookError = error;
+        }
+      }
+
+      for (const child of suite.children) {
+        if (isCancelled()) return;
+        const focused = scope.focused || child.mode === 'only';
+        const skipped =
+          scope.skipped ||
+          child.mode === 'skip' ||
+          (this.hasFocused && child.kind === 'test' && !focused);
+
+        if (child.kind === 'suite') {
+          await runSuite(child, {
+            path: [...scope.path, child.name],
+            skipped,
+            focused,
+            beforeEach: [...scope.beforeEach, ...child.beforeEach],
+            afterEach: [...child.afterEach, ...scope.afterEach],
+            hookError,
+          });
+          continue;
+        }
+
+        const result = await runTest(child, skipped, scope, hookError);
+        summary[result.status]++;
+        report(result);
+      }
+
+      if (!scope.skipped) {
+        await runHooks(suite.afterAll).catch(() => undefined);
+      }
+    };
+
+    const runTest = async (
+      test: TestCase,
+      skipped: boolean,
+      scope: SuiteScope,
+      hookError: unknown
+    ): Promise<TestCaseResult> => {
+      const name = [...scope.path, test.name].join(NAME_SEPARATOR);
+      if (skipped || !test.fn) {
+        return { name, status: 'skipped', line: test.line, duration: 0 };
+      }
+
+      const testStart = performance.now();
+      const settle = (
+        outcome: Pick<TestCaseResult, 'status' | 'message' | 'diff'>
+      ): TestCaseResult => ({
+        name,
+        line: test.line,
+        duration: Math.round(performance.now() - testStart),
+        ...outcome,
+      });
+
+      if (hookError !== undefined) {
+        return settle({ status: 'failed', ...describeError(hookError) });
+      }
+
+      let failure: unknown;
+      let failed = false;
+      try {
+        await runHooks(scope.beforeEach);
+        await test.fn();
+      } catch (error) {
+        failure = error;
+        failed = true;
+      }
+      try {
+        await runHooks(scope.afterEach);
+      } catch (error) {
+        if (!failed) failure = error;
+        failed = true;
+      }
+
+      return failed
+        ? settle({ status: 'failed', ...describeError(failure) })
+        : settle({ status: 'passed' });
+    };
+
+    await runSuite(this.root, {
+      path: [],
+      skipped: false,
+      focused: false,
+      beforeEach: this.root.beforeEach,
+      afterEach: this.root.afterEach,
+    });
+
+    summary.duration = Math.round(performance.now() - startTime);
+    return summary;
+  }
+
+  private addSuite(name: string, fn: TestFn, mode: RunMode): void {
+    const suite = createSuite(name, mode, this.current);
+    this.current.children.push(suite);
+    if (mode === 'only') this.hasFocused = true;
+
+    this.current = suite;
+    try {
+      fn();
+    } finally {
+      this.current = suite.parent ?? this.root;
+    }
+  }
+
+  private addTest(name: string, fn: TestFn | undefined, mode: RunMode): void {
+    if (mode === 'only') this.hasFocused = true;
+    this.testCount++;
+    this.current.children.push({
+      kind: 'test',
+      name,
+      fn,
+      mode,
+      line: this.locate(new Error().stack ?? ''),
+    });
+  }
+}
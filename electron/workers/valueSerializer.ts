@@ -0,0 +1,238 @@
+/**
+ * Value trees for the result panel
+ *
+ * Turns values a run prints into `SerializedValue` trees: a one-line
+ * preview per value plus its first level of children. Objects further down
+ * stay in the worker under a numeric handle until the renderer expands
+ * them, so nothing is walked recursively and cycles cannot loop.
+ */
+
+import util from 'util';
+import type {
+  SerializedChildren,
+  SerializedEntry,
+  SerializedValue,
+  SerializedValueKind,
+} from '@cheesejs/core';
+
+/** Children sent per expansion; the rest are counted in `omitted` */
+const MAX_CHILDREN = 100;
+/** Objects kept for expansion per run, so huge graphs cannot pin memory */
+const MAX_HANDLES = 10_000;
+
+const PREVIEW_OPTIONS: util.InspectOptions = {
+  depth: 0,
+  colors: false,
+  compact: true,
+  breakLength: Infinity,
+  maxArrayLength: 10,
+  maxStringLength: 100,
+};
+
+function getKind(value: unknown): SerializedValueKind {
+  if (value === null) return 'null';
+  switch (typeof value) {
+    case 'undefined':
+    case 'boolean':
+    case 'number':
+    case 'bigint':
+    case 'string':
+    case 'symbol':
+    case 'function':
+      return typeof value as SerializedValueKind;
+  }
+
+  // Values come from the sandbox realm, so `instanceof` checks would fail
+  if (Array.isArray(value)) return 'array';
+  if (util.types.isTypedArray(value)) return 'typedarray';
+  if (util.types.isMap(value)) return 'map';
+  if (util.types.isSet(value)) return 'set';
+  if (util.types.isNativeError(value) || value instanceof Error) {
+    return 'error';
+  }
+  if (util.types.isPromise(value)) return 'promise';
+  if (util.types.isDate(value)) return 'date';
+  if (util.types.isRegExp(value)) return 'regexp';
+  return 'object';
+}
+
+function getClassName(value: object): string | undefined {
+  try {
+    const name = Object.getPrototypeOf(value)?.constructor?.name;
+    return typeof name === 'string' && name ? name : undefined;
+  } catch {
+    return undefined;
+  }
+}
+
+function preview(value: unknown, kind: SerializedValueKind): string {
+  try {
+    if (kind === 'error') {
+      const error = value as Error;
+      return `${error.name}: ${error.message}`;
+    }
+    return util.inspect(value, PREVIEW_OPTIONS);
+  } catch {
+    return kind === 'object' ? '[Object]' : `[${kind}]`;
+  }
+}
+
+function describeAccessor(descriptor: PropertyDescriptor): SerializedValue {
+  const label =
+    descriptor.get && descriptor.set
+      ? '[Getter/Setter]'
+      : descriptor.get
+        ? '[Getter]'
+        : '[Setter]';
+  return { kind: 'accessor', preview: label };
+}
+
+/** Whether expanding `value` would show anything */
+function hasChildren(value: object, kind: SerializedValueKind): boolean {
+  switch (kind) {
+    case 'map':
+    case 'set':
+      return (value as Map<unknown, unknown> | Set<unknown>).size > 0;
+    case 'error':
+      return true;
+    case 'promise':
+    case 'function':
+      return false;
+    default:
+      return Reflect.ownKeys(value).length > 0;
+  }
+}
+
+/**
+ * Serializes the values of one run and keeps what they refer to, so the
+ * renderer can come back for deeper levels.
+ */
+export class ValueSerializer {
+  private readonly objects: object[] = [];
+  private readonly handles = new WeakMap<object, number>();
+
+  /** The value with its first level of children */
+  serialize(value: unknown): SerializedValue {
+    const serialized = this.describe(value);
+    if (serialized.handle === undefined) return serialized;
+
+    return { ...serialized, ...this.collectChildren(serialized.handle) };
+  }
+
+  /** Children of a value sent earlier, or null for an unknown handle */
+  expand(handle: number): SerializedChildren | null {
+    return this.objects[handle] ? this.collectChildren(handle) : null;
+  }
+
+  /** Whether any of `values` can be expanded */
+  static isExpandable(values: SerializedValue[]): boolean {
+    return values.some((value) => value.handle !== undefined);
+  }
+
+  private describe(value: unknown): SerializedValue {
+    const kind = getKind(value);
+    const serialized: SerializedValue = {
+      kind,
+      preview: preview(value, kind),
+    };
+    if (typeof value !== 'object' || value === null) return serialized;
+
+    const className = getClassName(value);
+    if (className) serialized.className = className;
+
+    const handle = hasChildren(value, kind) ? this.retain(value) : undefined;
+    if (handle !== undefined) serialized.handle = handle;
+    return serialized;
+  }
+
+  private retain(value: object): number | undefined {
+    const existing = this.handles.get(value);
+    if (existing !== undefined) return existing;
+    if (this.objects.length >= MAX_HANDLES) return undefined;
+
+    const handle = this.objects.push(value) - 1;
+    this.handles.set(value, handle);
+    return handle;
+  }
+
+  private collectChildren(handle: number): SerializedChildren {
+    const value = this.objects[handle];
+    const entries: SerializedEntry[] = [];
+    let total = 0;
+    const add = (entry: () => SerializedEntry) => {
+      if (total++ < MAX_CHILDREN) entries.push(entry());
+    };
+
+    try {
+      switch (getKind(value)) {
+        case 'map':
+          for (const [key, item] of value as Map<unknown, unknown>) {
+            add(() => {
+              const keyValue = this.describe(key);
+              return {
+                key: keyValue.preview,
+                ...(keyValue.handle !== undefined ? { keyValue } : {}),
+                value: this.describe(item),
+              };
+            });
+          }
+          break;
+        case 'set': {
+          let index = 0;
+          for (const item of value as Set<unknown>) {
+            const key = String(index++);
+            add(() => ({ key, value: this.describe(item) }));
+          }
+          break;
+        }
+        case 'typedarray': {
+          const array = value as unknown as ArrayLike<unknown>;
+          for (let index = 0; index < array.length; index++) {
+            const key = String(index);
+            add(() => ({ key, value: this.describe(array[index]) }));
+          }
+          break;
+        }
+        case 'error':
+          // `message` and `stack` are own but not enumerable
+          for (const key of ['message', 'stack']) {
+            if (Object.prototype.hasOwnProperty.call(value, key)) {
+              add(() => this.describeProperty(value, key));
+            }
+          }
+          for (const key of Reflect.ownKeys(value)) {
+            if (key === 'message' || key === 'stack') continue;
+            add(() => this.describeProperty(value, key));
+          }
+          break;
+        default:
+          for (const key of Reflect.ownKeys(value)) {
+            // Arrays list their indexes, not `length`
+            if (Array.isArray(value) && key === 'length') continue;
+            add(() => this.describeProperty(value, key));
+          }
+      }
+    } catch (error) {
+      // Proxies and exotic objects can throw while being listed
+      entries.push({
+        key: '[[Error]]',
+        value: this.describe(error),
+      });
+    }
+
+    const omitted = total - entries.length;
+    return omitted > 0 ? { children: entries, omitted } : { children: entries };
+  }
+
+  private describeProperty(
+    value: object,
+    key: string | symbol
+  ): SerializedEntry {
+    const name = typeof key === 'symbol' ? `[${key.toString()}]` : key;
+    const descriptor = Reflect.getOwnPropertyDescriptor(value, key);
+    if (descriptor && (descriptor.get || descriptor.set)) {
+      return { key: name, value: describeAccessor(descriptor) };
+    }
+    return { key: name, value: this.describe(descriptor?.value) };
+  }
+}
@@ -1,31 +1,82 @@
 /**
- * WASI C/C++ Executor Worker Thread
+ * WASI C/C++/Rust Executor Worker Thread
  *
- * Compiles C/C++ source code to `wasm32-wasip1` and executes the result via
- * Node's WASI runtime in an isolated worker.
+ * Compiles C/C++ (clang) or Rust (rustc) source code to `wasm32-wasip1` and
+ * executes the result via Node's WASI runtime. The module itself runs on a
+ * nested runner thread (see wasiModuleRunner.ts) so that timeouts and
+ * cancellation can stop it without tearing down this worker. Compiled modules
+ * are kept in a content-addressed cache (see WasiCompileCache.ts) so unchanged
+ * code is not recompiled.
  */
 
 import { spawn, type ChildProcessByStdio } from 'node:child_process';
-import fs from 'node:fs';
 import { promises as fsp } from 'node:fs';
 import os from 'node:os';
 import path from 'node:path';
 import type { Readable } from 'node:stream';
-import { WASI } from 'node:wasi';
-import { parentPort } from 'worker_threads';
+import { fileURLToPath } from 'node:url';
+import { parentPort, Worker, workerData } from 'worker_threads';
 import { config as dotenvConfig } from 'dotenv';
 import { expand as dotenvExpand } from 'dotenv-expand';
+import type {
+  CompiledOutputListing,
+  CompilerDiagnostic,
+  NativeCompilerOptions,
+  NativeProjectMode,
+  OptimizationLevel,
+} from '@cheesejs/core';
 import {
+  C_STANDARDS,
+  CPP_STANDARDS,
+  DEFAULT_COMPILER_OPTIONS,
+  isValidDefine,
+  isValidLibraryName,
+  isValidWarningFlag,
+  OPTIMIZATION_LEVELS,
+} from '@cheesejs/languages/compilerFlags';
+import { parseDependencyFile, WasiCompileCache } from './WasiCompileCache.js';
+import {
+  parseClangDiagnostics,
+  parseRustcDiagnostics,
+  type ParsedCompilerOutput,
+} from './compilerDiagnostics.js';
+import { parseAssemblyListing, truncateListing } from './compiledOutput.js';
+import { resolveProjectSources, type ProjectSources } from './wasiProject.js';
+import {
+  getCompilerVersion,
+  getMissingRustWasiToolchainMessage,
   getMissingWasiToolchainMessage,
+  resolveRustWasiToolchain,
   resolveWasiToolchain,
+  resolveWasm2Wat,
+  RUST_WASI_TARGET,
 } from './wasiToolchain.js';
+import type {
+  RunModuleMessage,
+  RunnerResultMessage,
+} from './wasiModuleRunner.js';
 
-type WasiLanguage = 'c' | 'cpp';
+type WasiLanguage = 'c' | 'cpp' | 'rust';
 type ExecutionEnv = typeof process.env;
 
 interface ExecuteOptions {
   timeout?: number;
   workingDirectory?: string;
+  compilerOptions?: NativeCompilerOptions;
+  projectMode?: NativeProjectMode;
+  emitCompiledOutput?: boolean;
+}
+
+interface CompileRequest {
+  language: WasiLanguage;
+  code: string;
+  command: { command: string; args: string[] };
+  outputPath: string;
+  env: ExecutionEnv;
+  tempDirectory: string;
+  workingDirectory?: string;
+  /** Extra translation units from a multi-file project */
+  projectSources: string[];
 }
 
 interface ExecuteMessage {
@@ -44,37 +95,202 @@ interface CancelMessage {
 type WorkerMessage = ExecuteMessage | CancelMessage;
 
 interface ResultMessage {
-  type: 'console' | 'error' | 'complete' | 'ready';
+  type:
+    | 'console'
+    | 'error'
+    | 'complete'
+    | 'ready'
+    | 'prompt-request'
+    | 'diagnostics'
+    | 'compiled-output';
   id: string;
   data?: unknown;
+  message?: string;
   consoleType?: 'log' | 'warn' | 'error' | 'info' | 'table' | 'dir';
 }
 
-let currentExecutionId: string | null = null;
+interface ActiveExecution {
+  id: string;
+  abortReason: WasiExecutionError | null;
+  timeout: number;
+  remainingMs: number;
+  deadlineStartedAt: number;
+  deadlineHandle: ReturnType<typeof setTimeout> | null;
+}
+
+interface ActiveModuleRun {
+  id: string;
+  resolve: (result: { exitCode: number; stderr: string }) => void;
+  reject: (error: Error) => void;
+}
+
+const DEFAULT_TIMEOUT_MS = 30000;
+// Diagnostics of cached modules, replayed when compilation is skipped
+const MAX_CACHED_DIAGNOSTICS = 64;
+const MODULE_RUNNER_PATH = path.join(
+  path.dirname(fileURLToPath(import.meta.url)),
+  'wasiModuleRunner.js'
+);
+
+// musl only flushes a line-buffered stdout on newline, so a `printf` prompt
+// before `scanf` would stay hidden while the program waits for input. Valid
+// C and C++, so it is compiled as whichever language the program is in.
+const STDIO_PRELUDE = `#include <stdio.h>
+
+__attribute__((constructor)) static void cheesejs_unbuffer_stdout(void) {
+  setvbuf(stdout, NULL, _IONBF, 0);
+}
+`;
+
+// Provided by the worker pool; stdin buffers are forwarded to the runner
+const { stdinBuffer, stdinLock, compileCacheDir } = (workerData ?? {}) as {
+  stdinBuffer?: SharedArrayBuffer;
+  stdinLock?: SharedArrayBuffer;
+  compileCacheDir?: string;
+};
+
+const compileCache = compileCacheDir
+  ? new WasiCompileCache({ cacheDir: compileCacheDir })
+  : null;
+
+/** Error forwarded to the renderer with a stable `name`. */
+class WasiExecutionError extends Error {
+  constructor(
+    name: string,
+    message: string,
+    public readonly location?: { line: number; column: number }
+  ) {
+    super(message);
+    this.name = name;
+  }
+}
+
+const cachedDiagnostics = new Map<string, CompilerDiagnostic[]>();
+
+let activeExecution: ActiveExecution | null = null;
 let activeCompileProcess: ChildProcessByStdio<null, Readable, Readable> | null =
   null;
+let moduleRunner: Worker | null = null;
+let activeModuleRun: ActiveModuleRun | null = null;
 
 function postMessage(message: ResultMessage): void {
   parentPort?.postMessage(message);
 }
 
-function emitConsoleOutput(
-  executionId: string,
-  consoleType: 'log' | 'error',
-  content: string
-): void {
-  const normalized = content.replace(/\r\n/g, '\n');
-  for (const line of normalized.split('\n')) {
-    if (!line.trim()) {
-      continue;
+function handleModuleRunnerMessage(message: RunnerResultMessage): void {
+  const run = activeModuleRun;
+  if (!run || run.id !== message.id) {
+    return;
+  }
+
+  if (message.type === 'console') {
+    postMessage(message);
+    return;
+  }
+
+  // Time spent waiting for the user is not charged to the program
+  if (message.type === 'prompt-request') {
+    if (activeExecution) pauseDeadline(activeExecution);
+    postMessage(message);
+    return;
+  }
+
+  if (message.type === 'input-received') {
+    if (activeExecution) startDeadline(activeExecution);
+    return;
+  }
+
+  activeModuleRun = null;
+  if (message.type === 'exit') {
+    run.resolve({ exitCode: message.exitCode, stderr: message.stderr });
+  } else {
+    run.reject(
+      new WasiExecutionError(message.error.name, message.error.message)
+    );
+  }
+}
+
+function getModuleRunner(): Worker {
+  if (moduleRunner) {
+    return moduleRunner;
+  }
+
+  const runner = new Worker(MODULE_RUNNER_PATH, {
+    workerData: { stdinBuffer, stdinLock },
+  });
+  runner.on('message', handleModuleRunnerMessage);
+  // Events from a runner that was already stopped must not touch the run
+  // that replaced it.
+  runner.on('error', (error: Error) => {
+    if (moduleRunner !== runner) {
+      return;
     }
+    moduleRunner = null;
+    rejectModuleRun(new WasiExecutionError('RuntimeError', error.message));
+  });
+  runner.on('exit', (exitCode) => {
+    if (moduleRunner !== runner) {
+      return;
+    }
+    moduleRunner = null;
+    rejectModuleRun(
+      new WasiExecutionError(
+        'RuntimeError',
+        `WASI runner exited unexpectedly (code ${exitCode})`
+      )
+    );
+  });
 
-    postMessage({
-      type: 'console',
-      id: executionId,
-      consoleType,
-      data: { content: line },
-    });
+  moduleRunner = runner;
+  return runner;
+}
+
+function rejectModuleRun(error: Error): void {
+  const run = activeModuleRun;
+  activeModuleRun = null;
+  run?.reject(error);
+}
+
+/**
+ * Runs the module on the runner thread. Stopping a run terminates only the
+ * runner; a fresh one is spawned for the next execution.
+ */
+function runWasiModule(
+  run: Omit<RunModuleMessage, 'type'>
+): Promise<{ exitCode: number; stderr: string }> {
+  return new Promise((resolve, reject) => {
+    activeModuleRun = { id: run.id, resolve, reject };
+    const message: RunModuleMessage = { type: 'run', ...run };
+    getModuleRunner().postMessage(message);
+  });
+}
+
+function stopModuleRunner(reason: Error): void {
+  const runner = moduleRunner;
+  moduleRunner = null;
+  rejectModuleRun(reason);
+  void runner?.terminate().catch(() => undefined);
+}
+
+/**
+ * Stops whatever phase the active execution is in (compiling or running).
+ * The first reason wins so a timeout racing a cancel reports consistently.
+ */
+function abortExecution(reason: WasiExecutionError): void {
+  if (!activeExecution || activeExecution.abortReason) {
+    return;
+  }
+
+  activeExecution.abortReason = reason;
+  activeCompileProcess?.kill('SIGTERM');
+  if (activeModuleRun) {
+    stopModuleRunner(reason);
+  }
+}
+
+function throwIfAborted(): void {
+  if (activeExecution?.abortReason) {
+    throw activeExecution.abortReason;
   }
 }
 
@@ -104,7 +320,7 @@ async function runCommand(
   args: string[],
   env: ExecutionEnv,
   workingDirectory?: string
-): Promise<void> {
+): Promise<{ stdout: string; stderr: string }> {
   return new Promise((resolve, reject) => {
     const processHandle = spawn(command, args, {
       cwd: workingDirectory,
@@ -133,7 +349,8 @@ async function runCommand(
     processHandle.on('close', (exitCode: number | null) => {
       activeCompileProcess = null;
       if (exitCode === 0) {
-        resolve();
+        // Compiler warnings are reported on stderr even when it succeeds
+        resolve({ stdout, stderr: stderr.trim() });
         return;
       }
 
@@ -147,132 +364,531 @@ async function runCommand(
   });
 }
 
+function getSourceFileName(language: WasiLanguage): string {
+  switch (language) {
+    case 'cpp':
+      return 'program.cpp';
+    case 'rust':
+      return 'main.rs';
+    default:
+      return 'program.c';
+  }
+}
+
+/**
+ * Fills in defaults and rejects anything that is not a known standard,
+ * optimisation level, define, warning flag or library name, so settings and
+ * magic headers can never smuggle arbitrary arguments to the compiler.
+ */
+function resolveCompilerOptions(
+  options: NativeCompilerOptions | undefined
+): Required<NativeCompilerOptions> {
+  const resolved = { ...DEFAULT_COMPILER_OPTIONS, ...options };
+  const invalid = [
+    ...(C_STANDARDS.includes(resolved.cStandard)
+      ? []
+      : [`-std=${resolved.cStandard}`]),
+    ...(CPP_STANDARDS.includes(resolved.cppStandard)
+      ? []
+      : [`-std=${resolved.cppStandard}`]),
+    ...(OPTIMIZATION_LEVELS.includes(resolved.optimization)
+      ? []
+      : [`-${resolved.optimization}`]),
+    ...resolved.defines
+      .filter((define) => !isValidDefine(define))
+      .map((define) => `-D${define}`),
+    ...resolved.warnings.filter((flag) => !isValidWarningFlag(flag)),
+    ...resolved.libraries
+      .filter((library) => !isValidLibraryName(library))
+      .map((library) => `-l${library}`),
+  ];
+
+  if (invalid.length > 0) {
+    throw new WasiExecutionError(
+      'CompileError',
+      `Unsupported compiler option(s): ${invalid.join(' ')}`
+    );
+  }
+
+  return resolved;
+}
+
+function getRustOptLevel(optimization: OptimizationLevel): string {
+  return optimization === 'Os' || optimization === 'Oz'
+    ? optimization.slice(1).toLowerCase()
+    : optimization.slice(1);
+}
+
+function buildRustCompileCommand(
+  sourcePath: string,
+  outputPath: string,
+  compilerOptions: Required<NativeCompilerOptions>
+): { command: string; args: string[] } {
+  const toolchain = resolveRustWasiToolchain();
+  if (!toolchain) {
+    throw new Error(getMissingRustWasiToolchainMessage());
+  }
+
+  return {
+    command: toolchain.rustc,
+    args: [
+      `--target=${RUST_WASI_TARGET}`,
+      '--edition=2021',
+      '--crate-type=bin',
+      '--crate-name=program',
+      '--error-format=json',
+      '-C',
+      `opt-level=${getRustOptLevel(compilerOptions.optimization)}`,
+      sourcePath,
+      '-o',
+      outputPath,
+    ],
+  };
+}
+
 function buildCompileCommand(
   language: WasiLanguage,
-  sourcePath: string,
+  sourcePaths: string[],
   outputPath: string,
   workingDirectory: string | undefined,
-  env: ExecutionEnv
+  env: ExecutionEnv,
+  options: NativeCompilerOptions | undefined,
+  includeDirectories: string[] = []
 ): { command: string; args: string[] } {
+  const compilerOptions = resolveCompilerOptions(options);
+  if (language === 'rust') {
+    return buildRustCompileCommand(sourcePaths[0], outputPath, compilerOptions);
+  }
+
   const toolchain = resolveWasiToolchain();
   if (!toolchain) {
     throw new Error(getMissingWasiToolchainMessage());
   }
 
   const compiler = language === 'cpp' ? toolchain.clangxx : toolchain.clang;
+  const standard =
+    language === 'cpp'
+      ? compilerOptions.cppStandard
+      : compilerOptions.cStandard;
   const args = [
     '--target=wasm32-wasip1',
     `--sysroot=${toolchain.sysroot}`,
     `-fuse-ld=${toolchain.wasmLd}`,
-    language === 'cpp' ? '-std=c++17' : '-std=c11',
-    '-O0',
+    `-std=${standard}`,
+    `-${compilerOptions.optimization}`,
+    '-fno-color-diagnostics',
+    '-fdiagnostics-print-source-range-info',
+    '-fdiagnostics-parseable-fixits',
+    ...compilerOptions.defines.map((define) => `-D${define}`),
+    ...compilerOptions.warnings,
   ];
 
   const includeDirectory = env.CHEESEJS_WASI_INCLUDE_DIR ?? workingDirectory;
   if (includeDirectory) {
     args.push('-I', includeDirectory);
   }
+  for (const directory of includeDirectories) {
+    args.push('-I', directory);
+  }
 
-  args.push(sourcePath, '-o', outputPath);
+  // Libraries go after the sources so the linker resolves their symbols
+  args.push(
+    ...sourcePaths,
+    '-o',
+    outputPath,
+    ...compilerOptions.libraries.map((library) => `-l${library}`)
+  );
   return { command: compiler, args };
 }
 
-async function runWasiModule(
-  wasmPath: string,
+function buildAssemblyCommand(
+  language: WasiLanguage,
+  sourcePath: string,
+  outputPath: string,
+  workingDirectory: string | undefined,
   env: ExecutionEnv,
-  workingDirectory?: string
-): Promise<{ exitCode: number; stdout: string; stderr: string }> {
-  const stdoutPath = path.join(
-    os.tmpdir(),
-    `cheesejs-wasi-stdout-${Date.now()}.txt`
+  options: NativeCompilerOptions | undefined,
+  includeDirectories: string[]
+): { command: string; args: string[] } {
+  // Libraries only matter to the linker, which `-S` never runs
+  const command = buildCompileCommand(
+    language,
+    [sourcePath],
+    outputPath,
+    workingDirectory,
+    env,
+    { ...options, libraries: [] },
+    includeDirectories
   );
-  const stderrPath = path.join(
-    os.tmpdir(),
-    `cheesejs-wasi-stderr-${Date.now()}.txt`
+  const emitArgs = language === 'rust' ? ['--emit=asm', '-g'] : ['-S', '-g'];
+  return { command: command.command, args: [...command.args, ...emitArgs] };
+}
+
+function startDeadline(execution: ActiveExecution): void {
+  if (execution.deadlineHandle || execution.abortReason) {
+    return;
+  }
+
+  execution.deadlineStartedAt = Date.now();
+  execution.deadlineHandle = setTimeout(() => {
+    abortExecution(
+      new WasiExecutionError(
+        'TimeoutError',
+        `Execution timeout (${execution.timeout}ms)`
+      )
+    );
+  }, execution.remainingMs);
+}
+
+function pauseDeadline(execution: ActiveExecution): void {
+  if (!execution.deadlineHandle) {
+    return;
+  }
+
+  clearTimeout(execution.deadlineHandle);
+  execution.deadlineHandle = null;
+  execution.remainingMs = Math.max(
+    0,
+    execution.remainingMs - (Date.now() - execution.deadlineStartedAt)
   );
+}
+
+/**
+ * Cache key for a compilation, or null when it links project sources.
+ * Headers found through include paths, such as the working directory, are
+ * not part of the key: the cache checks them on lookup.
+ */
+function getCompileCacheKey({
+  language,
+  code,
+  command,
+  tempDirectory,
+  projectSources,
+}: CompileRequest): string | null {
+  if (projectSources.length > 0) {
+    return null;
+  }
+
+  return WasiCompileCache.computeKey({
+    language,
+    source: code,
+    compiler: command.command,
+    // Temp paths differ on every run and must not affect the key
+    flags: command.args.map((arg) => arg.split(tempDirectory).join('$TMP')),
+    toolchainVersion: getCompilerVersion(command.command),
+  });
+}
+
+function parseCompilerOutput(
+  language: WasiLanguage,
+  output: string,
+  directories: (string | undefined)[]
+): ParsedCompilerOutput {
+  // Report `program.c` and project files by name rather than absolute path
+  let relativeOutput = output;
+  for (const directory of directories) {
+    if (directory) {
+      relativeOutput = relativeOutput.split(`${directory}${path.sep}`).join('');
+    }
+  }
+  const sourceFileName = getSourceFileName(language);
+  return language === 'rust'
+    ? parseRustcDiagnostics(relativeOutput, sourceFileName)
+    : parseClangDiagnostics(relativeOutput, sourceFileName);
+}
+
+function postDiagnostics(id: string, diagnostics: CompilerDiagnostic[]): void {
+  if (diagnostics.length === 0) {
+    return;
+  }
+
+  postMessage({ type: 'diagnostics', id, data: diagnostics });
+}
+
+function rememberDiagnostics(
+  moduleKey: string,
+  diagnostics: CompilerDiagnostic[]
+): void {
+  cachedDiagnostics.delete(moduleKey);
+  cachedDiagnostics.set(moduleKey, diagnostics);
+  if (cachedDiagnostics.size > MAX_CACHED_DIAGNOSTICS) {
+    const oldestKey = cachedDiagnostics.keys().next().value;
+    if (oldestKey !== undefined) {
+      cachedDiagnostics.delete(oldestKey);
+    }
+  }
+}
 
-  const stdoutFd = fs.openSync(stdoutPath, 'w+');
-  const stderrFd = fs.openSync(stderrPath, 'w+');
+/** Files a C/C++ compilation read besides the ones written for the run */
+async function readDependencies(
+  dependencyPath: string,
+  tempDirectory: string
+): Promise<string[]> {
+  const files = parseDependencyFile(await fsp.readFile(dependencyPath, 'utf8'));
+  return files
+    .map((file) => path.resolve(tempDirectory, file))
+    .filter((file) => path.dirname(file) !== tempDirectory);
+}
 
+async function compileWithCache(
+  id: string,
+  request: CompileRequest
+): Promise<{ wasmPath: string; moduleKey?: string }> {
+  const { language, command, outputPath, env, tempDirectory } = request;
+  const outputDirectories = [tempDirectory, request.workingDirectory];
+  const moduleKey = compileCache ? getCompileCacheKey(request) : null;
+  // clang lists the headers the program included for the cache to check.
+  // Each input rewrites the file, so the program must be the last one.
+  const dependencyPath = path.join(tempDirectory, 'program.d');
+  const args =
+    moduleKey && language !== 'rust'
+      ? [...command.args, '-MD', '-MF', dependencyPath]
+      : command.args;
+
+  if (compileCache && moduleKey) {
+    const cachedPath = await compileCache.get(moduleKey);
+    if (cachedPath) {
+      postDiagnostics(id, cachedDiagnostics.get(moduleKey) ?? []);
+      return { wasmPath: cachedPath, moduleKey };
+    }
+  }
+
+  let output: ParsedCompilerOutput;
   try {
-    const preopens = workingDirectory ? { '/workspace': workingDirectory } : {};
-    const runtimeEnv = workingDirectory ? { ...env, PWD: '/workspace' } : env;
-
-    const wasi = new WASI({
-      version: 'preview1',
-      args: ['program'],
-      env: runtimeEnv,
-      preopens,
-      stdout: stdoutFd,
-      stderr: stderrFd,
-    });
+    const { stderr } = await runCommand(
+      command.command,
+      args,
+      env,
+      tempDirectory
+    );
+    output = parseCompilerOutput(language, stderr, outputDirectories);
+  } catch (error) {
+    throwIfAborted();
+    const errorMessage = error instanceof Error ? error.message : String(error);
+    const failure = parseCompilerOutput(
+      language,
+      errorMessage,
+      outputDirectories
+    );
+    postDiagnostics(id, failure.diagnostics);
 
-    const wasm = await WebAssembly.compile(await fsp.readFile(wasmPath));
-    const instance = await WebAssembly.instantiate(wasm, {
-      wasi_snapshot_preview1: wasi.wasiImport,
-    });
+    // Only errors in the editor program can be located in the editor
+    const firstError = failure.diagnostics.find(
+      (diagnostic) => diagnostic.severity === 'error' && !diagnostic.file
+    );
+    throw new WasiExecutionError(
+      'CompileError',
+      `Compilation failed:\n${failure.text || errorMessage}`,
+      firstError
+        ? { line: firstError.line, column: firstError.column - 1 }
+        : undefined
+    );
+  }
 
-    const exitCode = wasi.start(instance);
+  postDiagnostics(id, output.diagnostics);
+  // Linker warnings and other output that is not tied to a source line
+  if (output.diagnostics.length === 0) {
+    for (const line of output.text.split('\n')) {
+      if (!line.trim()) continue;
+      postMessage({
+        type: 'console',
+        id,
+        consoleType: 'warn',
+        data: { content: line },
+      });
+    }
+  }
+
+  if (!compileCache || !moduleKey) {
+    return { wasmPath: outputPath };
+  }
+
+  rememberDiagnostics(moduleKey, output.diagnostics);
+
+  try {
+    const dependencies =
+      language === 'rust'
+        ? []
+        : await readDependencies(dependencyPath, tempDirectory);
     return {
-      exitCode,
-      stdout: await fsp.readFile(stdoutPath, 'utf8'),
-      stderr: await fsp.readFile(stderrPath, 'utf8'),
+      wasmPath: await compileCache.put(moduleKey, outputPath, dependencies),
+      moduleKey,
     };
-  } finally {
-    fs.closeSync(stdoutFd);
-    fs.closeSync(stderrFd);
-    await fsp.rm(stdoutPath, { force: true }).catch(() => undefined);
-    await fsp.rm(stderrPath, { force: true }).catch(() => undefined);
+  } catch {
+    // A failed cache write must not fail the run
+    return { wasmPath: outputPath };
+  }
+}
+
+/**
+ * Posts WAT (when wabt is installed) and assembly listings for the module.
+ * Listings are best effort: a failure here never stops the program running.
+ */
+async function emitCompiledOutput(
+  id: string,
+  language: WasiLanguage,
+  sourcePath: string,
+  wasmPath: string,
+  tempDirectory: string,
+  env: ExecutionEnv,
+  options: ExecuteOptions,
+  includeDirectories: string[]
+): Promise<void> {
+  const listings: CompiledOutputListing[] = [];
+  const wasm2wat = resolveWasm2Wat();
+  if (wasm2wat) {
+    try {
+      const { stdout } = await runCommand(
+        wasm2wat,
+        [wasmPath],
+        env,
+        tempDirectory
+      );
+      listings.push({ format: 'wat', text: truncateListing(stdout.trimEnd()) });
+    } catch {
+      // Fall back to the assembly listing alone
+    }
+  }
+
+  try {
+    const assemblyPath = path.join(tempDirectory, 'program.s');
+    const command = buildAssemblyCommand(
+      language,
+      sourcePath,
+      assemblyPath,
+      options.workingDirectory,
+      env,
+      options.compilerOptions,
+      includeDirectories
+    );
+    await runCommand(command.command, command.args, env, tempDirectory);
+    const assembly = await fsp.readFile(assemblyPath, 'utf8');
+    listings.push(
+      parseAssemblyListing(
+        assembly.split(`${tempDirectory}${path.sep}`).join(''),
+        getSourceFileName(language)
+      )
+    );
+  } catch {
+    throwIfAborted();
+  }
+
+  postMessage({ type: 'compiled-output', id, data: { listings } });
+}
+
+async function resolveProject(
+  language: WasiLanguage,
+  options: ExecuteOptions
+): Promise<ProjectSources> {
+  if (language === 'rust') {
+    return { sources: [], includeDirs: [] };
+  }
+
+  try {
+    return await resolveProjectSources(
+      language,
+      options.projectMode,
+      options.workingDirectory
+    );
+  } catch (error) {
+    throw new WasiExecutionError(
+      'CompileError',
+      error instanceof Error ? error.message : String(error)
+    );
   }
 }
 
 async function executeCode(message: ExecuteMessage): Promise<void> {
   const { id, code, language, options } = message;
+  const timeout = options.timeout ?? DEFAULT_TIMEOUT_MS;
   const env = loadExecutionEnv(options.workingDirectory);
   const tempDirectory = await fsp.mkdtemp(
     path.join(os.tmpdir(), 'cheesejs-wasi-')
   );
 
-  currentExecutionId = id;
+  const execution: ActiveExecution = {
+    id,
+    abortReason: null,
+    timeout,
+    remainingMs: timeout,
+    deadlineStartedAt: 0,
+    deadlineHandle: null,
+  };
+  activeExecution = execution;
+
+  // The deadline covers compilation and execution so the pool's fallback
+  // termination only fires if this worker itself stops responding.
+  startDeadline(execution);
 
   try {
-    const sourceFileName = language === 'cpp' ? 'program.cpp' : 'program.c';
-    const sourcePath = path.join(tempDirectory, sourceFileName);
+    const sourcePath = path.join(tempDirectory, getSourceFileName(language));
     const outputPath = path.join(tempDirectory, 'program.wasm');
 
     await fsp.writeFile(sourcePath, code, 'utf8');
 
+    // The program goes after the prelude, as the compile cache reads its
+    // dependencies from the last input
+    const sourcePaths = [sourcePath];
+    if (language !== 'rust') {
+      const preludePath = path.join(
+        tempDirectory,
+        language === 'cpp' ? 'cheesejs_stdio.cpp' : 'cheesejs_stdio.c'
+      );
+      await fsp.writeFile(preludePath, STDIO_PRELUDE, 'utf8');
+      sourcePaths.unshift(preludePath);
+    }
+
+    const project = await resolveProject(language, options);
+    sourcePaths.push(...project.sources);
+
     const compileCommand = buildCompileCommand(
       language,
-      sourcePath,
+      sourcePaths,
       outputPath,
       options.workingDirectory,
-      env
-    );
-
-    await runCommand(
-      compileCommand.command,
-      compileCommand.args,
       env,
-      tempDirectory
+      options.compilerOptions,
+      project.includeDirs
     );
 
-    const result = await runWasiModule(
+    const compiled = await compileWithCache(id, {
+      language,
+      code,
+      command: compileCommand,
       outputPath,
       env,
-      options.workingDirectory
-    );
+      tempDirectory,
+      workingDirectory: options.workingDirectory,
+      projectSources: project.sources,
+    });
 
-    if (result.stdout) {
-      emitConsoleOutput(id, 'log', result.stdout);
+    throwIfAborted();
+
+    if (options.emitCompiledOutput) {
+      await emitCompiledOutput(
+        id,
+        language,
+        sourcePath,
+        compiled.wasmPath,
+        tempDirectory,
+        env,
+        options,
+        project.includeDirs
+      );
+      throwIfAborted();
     }
 
-    if (result.stderr) {
-      emitConsoleOutput(id, 'error', result.stderr);
-    }
+    const result = await runWasiModule({
+      id,
+      wasmPath: compiled.wasmPath,
+      env,
+      workingDirectory: options.workingDirectory,
+      moduleKey: compiled.moduleKey,
+    });
 
     if (result.exitCode !== 0) {
-      throw new Error(
+      throw new WasiExecutionError(
+        'RuntimeError',
         result.stderr.trim() || `Program exited with code ${result.exitCode}`
       );
     }
@@ -283,17 +899,24 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
       data: { exitCode: result.exitCode },
     });
   } catch (error) {
-    const errorMessage = error instanceof Error ? error.message : String(error);
+    const reason = execution.abortReason ?? error;
     postMessage({
       type: 'error',
       id,
-      data: {
-        name: 'CompileError',
-        message: `Compilation failed:\n${errorMessage}`,
-      },
+      data:
+        reason instanceof WasiExecutionError && reason.location
+          ? {
+              name: reason.name,
+              message: reason.message,
+              location: reason.location,
+            }
+          : reason instanceof Error
+            ? { name: reason.name, message: reason.message }
+            : { name: 'Error', message: String(reason) },
     });
   } finally {
-    currentExecutionId = null;
+    pauseDeadline(execution);
+    activeExecution = null;
     activeCompileProcess = null;
     await fsp
       .rm(tempDirectory, { recursive: true, force: true })
@@ -302,13 +925,13 @@ async function executeCode(message: ExecuteMessage): Promise<void> {
 }
 
 function cancelExecution(message: CancelMessage): void {
-  if (currentExecutionId !== message.id) {
+  if (activeExecution?.id !== message.id) {
     return;
   }
 
-  if (activeCompileProcess) {
-    activeCompileProcess.kill('SIGTERM');
-  }
+  abortExecution(
+    new WasiExecutionError('CancelError', 'Execution cancelled by user')
+  );
 }
 
 parentPort?.on('message', async (message: WorkerMessage) => {
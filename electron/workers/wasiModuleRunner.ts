@@ -0,0 +1,326 @@
+/**
+ * WASI Module Runner Thread
+ *
+ * Runs a compiled `wasm32-wasip1` module for the WASI executor. `wasi.start`
+ * blocks its thread until the program exits, so it runs here instead of in
+ * the executor: the executor stays responsive to timeouts and cancellation
+ * and can terminate this thread without being torn down itself.
+ */
+
+import { promises as fsp } from 'node:fs';
+import { WASI } from 'node:wasi';
+import { parentPort, workerData } from 'worker_threads';
+import { SHARED_INPUT_DISMISSED } from '@cheesejs/core';
+
+type ExecutionEnv = typeof process.env;
+
+export interface RunModuleMessage {
+  type: 'run';
+  id: string;
+  wasmPath: string;
+  env: ExecutionEnv;
+  workingDirectory?: string;
+  /** Compile cache key; lets the runner reuse an already compiled module */
+  moduleKey?: string;
+}
+
+export type RunnerResultMessage =
+  | {
+      type: 'console';
+      id: string;
+      consoleType: 'log' | 'error';
+      data: { content: string };
+    }
+  | { type: 'prompt-request'; id: string; message: string }
+  | { type: 'input-received'; id: string }
+  | { type: 'exit'; id: string; exitCode: number; stderr: string }
+  | { type: 'failed'; id: string; error: { name: string; message: string } };
+
+const WASI_ERRNO_SUCCESS = 0;
+const WASI_FILETYPE_CHARACTER_DEVICE = 2;
+const WASI_RIGHTS_FD_WRITE = BigInt(1 << 6);
+const MAX_STDERR_TAIL_LENGTH = 64 * 1024;
+const DEFAULT_STDIN_PROMPT = 'Program is waiting for input (stdin)';
+const MAX_COMPILED_MODULES = 16;
+
+// Shared with the WASI executor, which receives them from the worker pool
+const { stdinBuffer, stdinLock } = (workerData ?? {}) as {
+  stdinBuffer?: SharedArrayBuffer;
+  stdinLock?: SharedArrayBuffer;
+};
+
+type WasiImportFunction = (...args: number[]) => number;
+
+// In-memory tier over the disk compile cache, kept in LRU order
+const compiledModules = new Map<string, WebAssembly.Module>();
+
+function postMessage(message: RunnerResultMessage): void {
+  parentPort?.postMessage(message);
+}
+
+/**
+ * Splits a byte stream into lines as it arrives so program output reaches
+ * the renderer while the module is still running.
+ */
+class OutputLineStream {
+  private readonly decoder = new TextDecoder();
+  private pending = '';
+
+  constructor(private readonly onLine: (line: string) => void) {}
+
+  write(bytes: Uint8Array): void {
+    this.pending += this.decoder.decode(bytes, { stream: true });
+    const lines = this.pending.split('\n');
+    this.pending = lines.pop() ?? '';
+    for (const line of lines) {
+      this.onLine(line.replace(/\r$/, ''));
+    }
+  }
+
+  /** Emits the unterminated tail, if any, and returns it. */
+  flush(): string {
+    this.pending += this.decoder.decode();
+    const line = this.pending;
+    if (line) {
+      this.onLine(line);
+      this.pending = '';
+    }
+    return line;
+  }
+}
+
+/**
+ * Feeds fd 0 from the console input panel. Whenever the program reads with
+ * nothing queued, the user is prompted and the thread blocks until the
+ * answer is written to the shared buffer. A dismissed prompt (null) is read
+ * as end-of-file, so input loops end; a later read prompts again.
+ */
+export class InteractiveStdin {
+  private readonly encoder = new TextEncoder();
+  private queued = new Uint8Array(0);
+
+  constructor(private readonly requestLine: () => string | null) {}
+
+  read(maxLength: number): Uint8Array {
+    if (this.queued.length === 0) {
+      const line = this.requestLine();
+      if (line === null) {
+        return this.queued;
+      }
+      this.queued = this.encoder.encode(`${line}\n`);
+    }
+
+    const chunk = this.queued.subarray(0, maxLength);
+    this.queued = this.queued.subarray(chunk.length);
+    return chunk;
+  }
+}
+
+function requestStdinLine(
+  id: string,
+  prompt: string,
+  inputBuffer: SharedArrayBuffer,
+  inputLock: SharedArrayBuffer
+): string | null {
+  const lock = new Int32Array(inputLock);
+  Atomics.store(lock, 0, 0);
+  postMessage({ type: 'prompt-request', id, message: prompt });
+  Atomics.wait(lock, 0, 0);
+  postMessage({ type: 'input-received', id });
+  if (Atomics.load(lock, 0) === SHARED_INPUT_DISMISSED) {
+    return null;
+  }
+
+  const bytes = new Uint8Array(inputBuffer);
+  const end = bytes.indexOf(0);
+  // Copy out of shared memory: TextDecoder rejects SharedArrayBuffer views
+  return new TextDecoder().decode(
+    bytes.slice(0, end === -1 ? bytes.length : end)
+  );
+}
+
+/**
+ * Wraps the WASI imports so writes to stdout/stderr are forwarded to line
+ * streams instead of host file descriptors.
+ *
+ * Reads from stdin are answered through the console input panel.
+ *
+ * Both output descriptors are reported as character devices: libc then
+ * treats them as a terminal and line-buffers `printf`/`std::cout` output
+ * instead of holding it until exit.
+ */
+function createStreamingWasiImports(
+  wasi: WASI,
+  getMemory: () => WebAssembly.Memory | undefined,
+  outputs: Record<number, OutputLineStream>,
+  stdin: InteractiveStdin | null
+): Record<string, WasiImportFunction> {
+  const wasiImport = wasi.wasiImport as Record<string, WasiImportFunction>;
+
+  return {
+    ...wasiImport,
+    fd_read(fd, iovsPtr, iovsLength, nreadPtr) {
+      const memory = getMemory();
+      if (fd !== 0 || !memory) {
+        return wasiImport.fd_read(fd, iovsPtr, iovsLength, nreadPtr);
+      }
+
+      // Without a shared input buffer stdin behaves as an empty stream
+      let view = new DataView(memory.buffer);
+      let capacity = 0;
+      for (let index = 0; index < iovsLength; index++) {
+        capacity += view.getUint32(iovsPtr + index * 8 + 4, true);
+      }
+      const data = stdin && capacity > 0 ? stdin.read(capacity) : null;
+
+      // Re-read the view: memory may have grown while blocked on input
+      view = new DataView(memory.buffer);
+      let read = 0;
+      for (let index = 0; data && index < iovsLength; index++) {
+        const bufferPtr = view.getUint32(iovsPtr + index * 8, true);
+        const bufferLength = view.getUint32(iovsPtr + index * 8 + 4, true);
+        const chunk = data.subarray(read, read + bufferLength);
+        new Uint8Array(memory.buffer, bufferPtr, chunk.length).set(chunk);
+        read += chunk.length;
+      }
+
+      view.setUint32(nreadPtr, read, true);
+      return WASI_ERRNO_SUCCESS;
+    },
+    fd_write(fd, iovsPtr, iovsLength, nwrittenPtr) {
+      const output = outputs[fd];
+      const memory = getMemory();
+      if (!output || !memory) {
+        return wasiImport.fd_write(fd, iovsPtr, iovsLength, nwrittenPtr);
+      }
+
+      const view = new DataView(memory.buffer);
+      let written = 0;
+      for (let index = 0; index < iovsLength; index++) {
+        const bufferPtr = view.getUint32(iovsPtr + index * 8, true);
+        const bufferLength = view.getUint32(iovsPtr + index * 8 + 4, true);
+        output.write(new Uint8Array(memory.buffer, bufferPtr, bufferLength));
+        written += bufferLength;
+      }
+
+      view.setUint32(nwrittenPtr, written, true);
+      return WASI_ERRNO_SUCCESS;
+    },
+    fd_fdstat_get(fd, statPtr) {
+      const memory = getMemory();
+      if (!outputs[fd] || !memory) {
+        return wasiImport.fd_fdstat_get(fd, statPtr);
+      }
+
+      // __wasi_fdstat_t: filetype u8, flags u16, rights_base u64,
+      // rights_inheriting u64. No seek/tell rights, so isatty() succeeds.
+      const view = new DataView(memory.buffer);
+      view.setUint8(statPtr, WASI_FILETYPE_CHARACTER_DEVICE);
+      view.setUint16(statPtr + 2, 0, true);
+      view.setBigUint64(statPtr + 8, WASI_RIGHTS_FD_WRITE, true);
+      view.setBigUint64(statPtr + 16, BigInt(0), true);
+      return WASI_ERRNO_SUCCESS;
+    },
+  };
+}
+
+async function loadModule(
+  wasmPath: string,
+  moduleKey?: string
+): Promise<WebAssembly.Module> {
+  const cached = moduleKey ? compiledModules.get(moduleKey) : undefined;
+  if (moduleKey && cached) {
+    compiledModules.delete(moduleKey);
+    compiledModules.set(moduleKey, cached);
+    return cached;
+  }
+
+  const wasm = await WebAssembly.compile(await fsp.readFile(wasmPath));
+  if (moduleKey) {
+    compiledModules.set(moduleKey, wasm);
+    if (compiledModules.size > MAX_COMPILED_MODULES) {
+      const oldestKey = compiledModules.keys().next().value;
+      if (oldestKey) compiledModules.delete(oldestKey);
+    }
+  }
+  return wasm;
+}
+
+async function runWasiModule(
+  message: RunModuleMessage
+): Promise<{ exitCode: number; stderr: string }> {
+  const { id, wasmPath, env, workingDirectory, moduleKey } = message;
+  let stderrTail = '';
+
+  const emitLine = (consoleType: 'log' | 'error', line: string) => {
+    if (!line.trim()) {
+      return;
+    }
+
+    postMessage({ type: 'console', id, consoleType, data: { content: line } });
+  };
+
+  const stdout = new OutputLineStream((line) => emitLine('log', line));
+  const stderr = new OutputLineStream((line) => {
+    stderrTail = `${stderrTail}${line}\n`.slice(-MAX_STDERR_TAIL_LENGTH);
+    emitLine('error', line);
+  });
+
+  // A prompt printed without a newline (`printf("Name: ")`) is still pending
+  // in the stdout stream; show it and reuse it as the input label.
+  const stdin =
+    stdinBuffer && stdinLock
+      ? new InteractiveStdin(() => {
+          const prompt = stdout.flush().trim() || DEFAULT_STDIN_PROMPT;
+          return requestStdinLine(id, prompt, stdinBuffer, stdinLock);
+        })
+      : null;
+
+  const preopens = workingDirectory ? { '/workspace': workingDirectory } : {};
+  const runtimeEnv = workingDirectory ? { ...env, PWD: '/workspace' } : env;
+
+  const wasi = new WASI({
+    version: 'preview1',
+    args: ['program'],
+    env: runtimeEnv,
+    preopens,
+  });
+
+  let memory: WebAssembly.Memory | undefined;
+  const wasm = await loadModule(wasmPath, moduleKey);
+  const instance = await WebAssembly.instantiate(wasm, {
+    wasi_snapshot_preview1: createStreamingWasiImports(
+      wasi,
+      () => memory,
+      { 1: stdout, 2: stderr },
+      stdin
+    ),
+  });
+  memory = instance.exports.memory as WebAssembly.Memory | undefined;
+
+  let exitCode: number;
+  try {
+    exitCode = wasi.start(instance);
+  } finally {
+    stdout.flush();
+    stderr.flush();
+  }
+
+  return { exitCode, stderr: stderrTail };
+}
+
+parentPort?.on('message', async (message: RunModuleMessage) => {
+  try {
+    const result = await runWasiModule(message);
+    postMessage({ type: 'exit', id: message.id, ...result });
+  } catch (error) {
+    postMessage({
+      type: 'failed',
+      id: message.id,
+      error:
+        error instanceof Error
+          ? { name: error.name, message: error.message }
+          : { name: 'Error', message: String(error) },
+    });
+  }
+});
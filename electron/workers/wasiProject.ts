@@ -0,0 +1,199 @@
+/**
+ * WASI Project Sources
+ *
+ * Finds the extra C/C++ translation units compiled and linked alongside the
+ * editor program. Sources come either from a `cheesejs.json` manifest in the
+ * working directory or from every matching file in that directory tree:
+ *
+ *   { "sources": ["src", "lib/vector.cpp"], "includeDirs": ["include"] }
+ *
+ * Manifest entries are files or directories (searched recursively) relative
+ * to the working directory, and may not point outside of it. Workspace files
+ * that define `main` are programs of their own, like the saved copy of the
+ * editor program, and are left out.
+ */
+
+import { promises as fsp } from 'node:fs';
+import path from 'node:path';
+import type { NativeProjectMode } from '@cheesejs/core';
+
+export const PROJECT_MANIFEST_FILE = 'cheesejs.json';
+
+/** Upper bound so a mis-set working directory cannot compile a whole disk */
+export const MAX_PROJECT_SOURCES = 256;
+
+const SOURCE_EXTENSIONS: Record<'c' | 'cpp', readonly string[]> = {
+  c: ['.c'],
+  cpp: ['.cpp', '.cc', '.cxx', '.c++'],
+};
+
+// Never descended into when collecting workspace sources
+const IGNORED_DIRECTORIES = new Set([
+  'node_modules',
+  'build',
+  'target',
+  'dist',
+]);
+
+// A `main` definition, once comments are removed
+const MAIN_DEFINITION =
+  /\b(?:int|auto)\s+main\s*\([^)]*\)\s*(?:->\s*int\s*)?\{/;
+
+export interface ProjectSources {
+  /** Absolute paths of the extra translation units */
+  sources: string[];
+  /** Absolute include directories from the manifest */
+  includeDirs: string[];
+}
+
+interface ProjectManifest {
+  sources?: unknown;
+  includeDirs?: unknown;
+}
+
+export class ProjectSourceError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'ProjectSourceError';
+  }
+}
+
+function resolveInside(root: string, entry: string): string {
+  const resolved = path.resolve(root, entry);
+  const relative = path.relative(root, resolved);
+  if (relative.startsWith('..') || path.isAbsolute(relative)) {
+    throw new ProjectSourceError(
+      `${PROJECT_MANIFEST_FILE}: "${entry}" is outside the working directory`
+    );
+  }
+  return resolved;
+}
+
+async function definesMain(sourcePath: string): Promise<boolean> {
+  const source = await fsp.readFile(sourcePath, 'utf8');
+  return MAIN_DEFINITION.test(
+    source.replace(/\/\*[\s\S]*?\*\//g, '').replace(/\/\/.*$/gm, '')
+  );
+}
+
+function readStringList(value: unknown, field: string): string[] {
+  if (value === undefined) return [];
+  if (
+    !Array.isArray(value) ||
+    !value.every((entry) => typeof entry === 'string')
+  ) {
+    throw new ProjectSourceError(
+      `${PROJECT_MANIFEST_FILE}: "${field}" must be an array of paths`
+    );
+  }
+  return value;
+}
+
+async function collectSources(
+  directory: string,
+  extensions: readonly string[],
+  found: string[]
+): Promise<void> {
+  const entries = await fsp.readdir(directory, { withFileTypes: true });
+  entries.sort((a, b) => a.name.localeCompare(b.name));
+
+  for (const entry of entries) {
+    if (entry.name.startsWith('.')) continue;
+    const entryPath = path.join(directory, entry.name);
+
+    if (entry.isDirectory()) {
+      if (!IGNORED_DIRECTORIES.has(entry.name)) {
+        await collectSources(entryPath, extensions, found);
+      }
+    } else if (extensions.includes(path.extname(entry.name).toLowerCase())) {
+      found.push(entryPath);
+    }
+
+    if (found.length > MAX_PROJECT_SOURCES) {
+      throw new ProjectSourceError(
+        `Too many source files (more than ${MAX_PROJECT_SOURCES}) in ${directory}`
+      );
+    }
+  }
+}
+
+async function readManifest(
+  workingDirectory: string,
+  extensions: readonly string[]
+): Promise<ProjectSources> {
+  const manifestPath = path.join(workingDirectory, PROJECT_MANIFEST_FILE);
+  let manifest: ProjectManifest;
+  try {
+    manifest = JSON.parse(await fsp.readFile(manifestPath, 'utf8'));
+  } catch (error) {
+    const reason =
+      (error as NodeJS.ErrnoException).code === 'ENOENT'
+        ? 'not found'
+        : 'is not valid JSON';
+    throw new ProjectSourceError(
+      `${PROJECT_MANIFEST_FILE} ${reason} in ${workingDirectory}`
+    );
+  }
+
+  const sources: string[] = [];
+  for (const entry of readStringList(manifest.sources, 'sources')) {
+    const resolved = resolveInside(workingDirectory, entry);
+    const stats = await fsp.stat(resolved).catch(() => null);
+    if (!stats) {
+      throw new ProjectSourceError(
+        `${PROJECT_MANIFEST_FILE}: source "${entry}" does not exist`
+      );
+    }
+
+    if (stats.isDirectory()) {
+      await collectSources(resolved, extensions, sources);
+    } else if (extensions.includes(path.extname(resolved).toLowerCase())) {
+      sources.push(resolved);
+    } else {
+      throw new ProjectSourceError(
+        `${PROJECT_MANIFEST_FILE}: "${entry}" is not a ${extensions.join('/')} file`
+      );
+    }
+  }
+
+  return {
+    sources: [...new Set(sources)],
+    includeDirs: readStringList(manifest.includeDirs, 'includeDirs').map(
+      (entry) => resolveInside(workingDirectory, entry)
+    ),
+  };
+}
+
+/**
+ * Resolves the extra sources for a C/C++ run. Single-file mode (the default)
+ * returns nothing, so existing programs are compiled exactly as before.
+ */
+export async function resolveProjectSources(
+  language: 'c' | 'cpp',
+  mode: NativeProjectMode | undefined,
+  workingDirectory: string | undefined
+): Promise<ProjectSources> {
+  if (!mode || mode === 'single') {
+    return { sources: [], includeDirs: [] };
+  }
+
+  if (!workingDirectory) {
+    throw new ProjectSourceError(
+      'Multi-file projects need a working directory (Settings → General)'
+    );
+  }
+
+  const extensions = SOURCE_EXTENSIONS[language];
+  if (mode === 'manifest') {
+    return readManifest(workingDirectory, extensions);
+  }
+
+  const sources: string[] = [];
+  await collectSources(workingDirectory, extensions, sources);
+  // The editor program is the one `main`, so others would fail to link
+  const linked: string[] = [];
+  for (const source of sources) {
+    if (!(await definesMain(source))) linked.push(source);
+  }
+  return { sources: linked, includeDirs: [] };
+}
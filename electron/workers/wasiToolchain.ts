@@ -1,4 +1,7 @@
+import { execFileSync } from 'node:child_process';
 import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
 
 export interface WasiToolchain {
   clang: string;
@@ -7,10 +10,22 @@ export interface WasiToolchain {
   sysroot: string;
 }
 
+export interface RustWasiToolchain {
+  rustc: string;
+  targetLibDir: string;
+}
+
 interface WasiToolchainCandidate extends WasiToolchain {
   label: string;
 }
 
+interface RustToolchainCandidate {
+  label: string;
+  rustc: string;
+}
+
+export const RUST_WASI_TARGET = 'wasm32-wasip1';
+
 function isExecutableFile(filePath: string): boolean {
   try {
     fs.accessSync(filePath, fs.constants.X_OK);
@@ -105,3 +120,142 @@ export function getMissingWasiToolchainMessage(): string {
     'Install `llvm`, `lld`, `wasi-libc`, and `wasi-runtimes`, or set CHEESEJS_WASI_CLANG, CHEESEJS_WASI_CLANGXX, CHEESEJS_WASM_LD, and CHEESEJS_WASI_SYSROOT.',
   ].join(' ');
 }
+
+function getRustToolchainCandidates(): RustToolchainCandidate[] {
+  const candidates: RustToolchainCandidate[] = [];
+  const executableName = process.platform === 'win32' ? 'rustc.exe' : 'rustc';
+
+  if (process.env.CHEESEJS_RUSTC) {
+    candidates.push({
+      label: 'CHEESEJS_RUSTC environment variable',
+      rustc: process.env.CHEESEJS_RUSTC,
+    });
+  }
+
+  const cargoHome =
+    process.env.CARGO_HOME ?? path.join(os.homedir(), '.cargo');
+
+  candidates.push(
+    {
+      label: 'rustup (CARGO_HOME)',
+      rustc: path.join(cargoHome, 'bin', executableName),
+    },
+    {
+      label: 'Homebrew Apple Silicon',
+      rustc: '/opt/homebrew/bin/rustc',
+    },
+    {
+      label: 'Homebrew Intel macOS',
+      rustc: '/usr/local/bin/rustc',
+    },
+    {
+      label: 'Linux system toolchain',
+      rustc: '/usr/bin/rustc',
+    }
+  );
+
+  return candidates;
+}
+
+function resolveRustTargetLibDir(rustc: string): string | null {
+  try {
+    const targetLibDir = execFileSync(
+      rustc,
+      ['--print', 'target-libdir', '--target', RUST_WASI_TARGET],
+      { encoding: 'utf8', stdio: ['ignore', 'pipe', 'ignore'], timeout: 5000 }
+    ).trim();
+
+    return targetLibDir && isReadableDirectory(targetLibDir)
+      ? targetLibDir
+      : null;
+  } catch {
+    return null;
+  }
+}
+
+let cachedRustToolchain: RustWasiToolchain | null = null;
+
+export function resolveRustWasiToolchain(): RustWasiToolchain | null {
+  if (cachedRustToolchain && isExecutableFile(cachedRustToolchain.rustc)) {
+    return cachedRustToolchain;
+  }
+
+  for (const candidate of getRustToolchainCandidates()) {
+    if (!isExecutableFile(candidate.rustc)) {
+      continue;
+    }
+
+    // rustc alone is not enough: the wasm32-wasip1 std must be installed.
+    const targetLibDir = resolveRustTargetLibDir(candidate.rustc);
+    if (targetLibDir) {
+      cachedRustToolchain = { rustc: candidate.rustc, targetLibDir };
+      return cachedRustToolchain;
+    }
+  }
+
+  return null;
+}
+
+export function isRustWasiToolchainAvailable(): boolean {
+  return resolveRustWasiToolchain() !== null;
+}
+
+export function getMissingRustWasiToolchainMessage(): string {
+  return [
+    'Rust WebAssembly toolchain not found.',
+    `Install Rust (https://rustup.rs) and run \`rustup target add ${RUST_WASI_TARGET}\`, or set CHEESEJS_RUSTC to a rustc that has the ${RUST_WASI_TARGET} target installed.`,
+  ].join(' ');
+}
+
+const compilerVersions = new Map<string, string>();
+
+/**
+ * Identifies the exact compiler build behind an executable path so cached
+ * modules are invalidated when the toolchain is upgraded in place. Falls
+ * back to the file's mtime when the version cannot be queried.
+ */
+export function getCompilerVersion(compiler: string): string {
+  const cached = compilerVersions.get(compiler);
+  if (cached) {
+    return cached;
+  }
+
+  let version: string;
+  try {
+    version = execFileSync(compiler, ['--version', '--verbose'], {
+      encoding: 'utf8',
+      stdio: ['ignore', 'pipe', 'ignore'],
+      timeout: 5000,
+    }).trim();
+  } catch {
+    try {
+      version = `mtime:${fs.statSync(compiler).mtimeMs}`;
+    } catch {
+      version = 'unknown';
+    }
+  }
+
+  compilerVersions.set(compiler, version);
+  return version;
+}
+
+/**
+ * Locates wabt's `wasm2wat`, used to show compiled modules as WAT. It is
+ * optional; without it only the compiler's assembly listing is available.
+ */
+export function resolveWasm2Wat(): string | null {
+  const candidates = [
+    process.env.CHEESEJS_WASM2WAT,
+    '/opt/homebrew/bin/wasm2wat',
+    '/usr/local/bin/wasm2wat',
+    '/usr/bin/wasm2wat',
+  ];
+
+  for (const candidate of candidates) {
+    if (candidate && isExecutableFile(candidate)) {
+      return candidate;
+    }
+  }
+
+  return null;
+}
@@ -32,6 +32,8 @@ export function createMockCodeRunner() {
     onInputRequest: vi.fn().mockReturnValue(vi.fn()),
     onJSInputRequest: vi.fn().mockReturnValue(vi.fn()),
     sendInputResponse: vi.fn(),
+    expandValue: vi.fn().mockResolvedValue(null),
+    resetSession: vi.fn().mockResolvedValue(undefined),
   };
 }
 
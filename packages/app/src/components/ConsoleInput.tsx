@@ -1,24 +1,36 @@
 import { ConsoleInputPanel } from '@cheesejs/runtime-shell';
-import { useEditorTabsStore } from '../store/storeHooks';
+import { useEditorTabsStore, useSettingsStore } from '../store/storeHooks';
 
 export function ConsoleInput() {
   const { tabs, activeTabId, setTabPromptRequest } = useEditorTabsStore();
+  const { workingDirectory, rememberSandboxDecision } = useSettingsStore();
   const activeTab = tabs.find((t) => t.id === activeTabId);
   const promptRequest = activeTab?.promptRequest || null;
   const promptType = activeTab?.promptType || 'text';
   const promptExecutionId = activeTab?.promptExecutionId || null;
+  const promptCapability = activeTab?.promptCapability ?? null;
+
+  const respond = (value: string | null) => {
+    const targetExecutionId = promptExecutionId || activeTabId;
+    if (targetExecutionId) {
+      window.codeRunner.sendJSInputResponse(targetExecutionId, value);
+    }
+    if (activeTabId) setTabPromptRequest(activeTabId, null);
+  };
 
   return (
     <ConsoleInputPanel
       key={`${promptExecutionId ?? activeTabId ?? 'none'}:${promptType}:${promptRequest ?? ''}`}
       promptRequest={promptRequest}
       promptType={promptType}
-      onSubmit={(input) => {
-        const targetExecutionId = promptExecutionId || activeTabId;
-        if (targetExecutionId) {
-          window.codeRunner.sendJSInputResponse(targetExecutionId, input);
+      promptCapability={promptCapability}
+      onSubmit={respond}
+      onDismiss={() => respond(null)}
+      onPermissionDecision={(decision, remember) => {
+        if (remember && promptCapability) {
+          rememberSandboxDecision(workingDirectory, promptCapability, decision);
         }
-        if (activeTabId) setTabPromptRequest(activeTabId, null);
+        respond(decision);
       }}
     />
   );
@@ -60,12 +60,19 @@ export default function CodeEditorAdapter() {
         incrementDetectionVersion,
         getDetectionVersion,
       }}
-      runtime={{ runCode }}
+      runtime={{
+        runCode,
+        runCell: (mode, cellIndex) => runCode(undefined, { mode, cellIndex }),
+      }}
       commands={{
         subscribeToFormatRequested: (handler) =>
           appEventBus.subscribe('editor.format.requested', () => {
             handler();
           }),
+        subscribeToRevealLineRequested: (handler) =>
+          appEventBus.subscribe('editor.line.reveal.requested', ({ line }) => {
+            handler(line);
+          }),
       }}
       lifecycleServices={{
         configureMonaco,
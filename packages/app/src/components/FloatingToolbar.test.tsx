@@ -61,6 +61,10 @@ vi.mock('./SnippetsMenu', () => ({
   SnippetsMenu: () => <div data-testid="snippets-menu">Snippets</div>,
 }));
 
+vi.mock('./SandboxProfileMenu', () => ({
+  SandboxProfileMenu: () => <div data-testid="sandbox-menu">Sandbox</div>,
+}));
+
 // Mock framer-motion
 vi.mock('framer-motion', () => {
   const component = (props: React.ComponentPropsWithoutRef<'div'>) => (
@@ -99,4 +103,10 @@ describe('FloatingToolbar', () => {
     fireEvent.click(screen.getByTestId('run-button'));
     expect(mockRunCode).toHaveBeenCalled();
   });
+
+  it('should run the code under the profiler', () => {
+    render(<FloatingToolbar />);
+    fireEvent.click(screen.getByTestId('profile-button'));
+    expect(mockRunCode).toHaveBeenCalledWith(undefined, undefined, true);
+  });
 });
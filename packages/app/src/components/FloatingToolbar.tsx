@@ -8,8 +8,13 @@ import { useEditorTabsStore } from '../store/storeHooks';
 import { usePackagesStore } from '../store/storeHooks';
 import { usePythonPackagesStore } from '../store/storeHooks';
 import { SnippetsMenu } from './SnippetsMenu';
+import { SandboxProfileMenu } from './SandboxProfileMenu';
+import { SessionMenu } from './SessionMenu';
 import { appEventBus } from '../events/appEventBus';
 
+// Languages whose runs can be CPU-profiled
+const PROFILED_LANGUAGES = new Set(['javascript', 'typescript', 'python']);
+
 export default function FloatingToolbar() {
   const { runCode } = useCodeRunner();
   const toggleSettings = useSettingsStore((state) => state.toggleSettings);
@@ -50,7 +55,7 @@ export default function FloatingToolbar() {
     const unsubscribeRun = appEventBus.subscribe(
       'workbench.run.requested',
       (payload) => {
-        runCode(payload?.code);
+        runCode(payload?.code, undefined, payload?.profile);
       }
     );
     const unsubscribeSettings = appEventBus.subscribe(
@@ -71,7 +76,10 @@ export default function FloatingToolbar() {
       eventBus={appEventBus}
       isBusy={isBusy}
       busyMessage={busyMessage}
+      canProfile={PROFILED_LANGUAGES.has(currentLanguage)}
       snippetsMenu={<SnippetsMenu />}
+      sandboxMenu={<SandboxProfileMenu />}
+      sessionMenu={<SessionMenu />}
     />
   );
 }
@@ -72,8 +72,10 @@ const mockCodeStoreState = {
     lineNumber?: number;
     action?: { payload?: string };
     element?: { content?: string; consoleType?: string };
+    cell?: number;
   }>,
   code: '',
+  language: 'javascript',
 };
 
 const mockSettingsState = {
@@ -140,6 +142,7 @@ type MockEditorTabsState = {
   tabs: Array<{
     id: string;
     code: string;
+    language: string;
     result: typeof mockCodeStoreState.result;
   }>;
 };
@@ -164,6 +167,7 @@ vi.mock('../store/storeHooks', () => ({
           {
             id: 'test-tab',
             code: mockCodeStoreState.code,
+            language: mockCodeStoreState.language,
             result: mockCodeStoreState.result,
           },
         ],
@@ -177,6 +181,7 @@ vi.mock('../store/storeHooks', () => ({
           {
             id: 'test-tab',
             code: mockCodeStoreState.code,
+            language: mockCodeStoreState.language,
             result: mockCodeStoreState.result,
           },
         ],
@@ -506,6 +511,29 @@ describe('ResultDisplay', () => {
     expect(lines[2]).toBe('result on line 3');
   });
 
+  it('should group output under a header per code cell', () => {
+    mockCodeStoreState.code = '// %% Setup\nconst x = 1;\n// %%\nx';
+    mockCodeStoreState.result = [
+      {
+        type: 'execution',
+        lineNumber: 4,
+        cell: 1,
+        element: { content: '1', consoleType: 'log' },
+      },
+      {
+        type: 'execution',
+        cell: 0,
+        element: { content: 'setup done', consoleType: 'log' },
+      },
+    ];
+
+    render(<ResultDisplay />);
+    const editor = screen.getByTestId('monaco-editor');
+    expect(editor.textContent).toBe(
+      '// ── Cell 1: Setup ──\nsetup done\n// ── Cell 2 ──\n1'
+    );
+  });
+
   it('should filter out action elements from display', () => {
     mockCodeStoreState.result = [
       {
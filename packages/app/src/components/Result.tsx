@@ -1,10 +1,16 @@
-import { useMemo } from 'react';
+import { useEffect, useMemo, useState } from 'react';
 import {
   createNpmPackageBridge,
   createPythonPackageBridge,
   PackagePrompts,
 } from '@cheesejs/package-management';
-import { ResultPanel } from '@cheesejs/runtime-shell';
+import {
+  CompiledOutputPanel,
+  ProfilePanel,
+  ResultPanel,
+  type RuntimeResultEntry,
+} from '@cheesejs/runtime-shell';
+import { splitCodeCells } from '@cheesejs/languages';
 import { themes } from '@cheesejs/themes';
 import {
   useEditorTabsStore,
@@ -15,12 +21,28 @@ import {
 import { Monaco } from '@monaco-editor/react';
 import type { editor } from 'monaco-editor';
 import { ConsoleInput } from './ConsoleInput';
+import { appEventBus } from '../events/appEventBus';
+import clsx from 'clsx';
+
+// Languages compiled to WebAssembly, whose output can be inspected
+const NATIVE_LANGUAGES = new Set(['c', 'cpp', 'rust']);
 
 const npmBridge = createNpmPackageBridge(() => window.packageManager);
 const pythonBridge = createPythonPackageBridge(
   () => window.pythonPackageManager
 );
 
+function revealLine(line: number) {
+  appEventBus.emit('editor.line.reveal.requested', { line });
+}
+
+function expandValue(entry: RuntimeResultEntry, handle: number) {
+  if (!entry.executionId || !window.codeRunner?.expandValue) {
+    return Promise.resolve(null);
+  }
+  return window.codeRunner.expandValue(entry.executionId, handle);
+}
+
 function ResultDisplay() {
   const { tabs, activeTabId } = useEditorTabsStore();
   const activeTab = useMemo(
@@ -29,13 +51,20 @@ function ResultDisplay() {
   );
 
   const elements = useMemo(() => activeTab?.result || [], [activeTab?.result]);
+  const profile = activeTab?.profile ?? null;
+  const [showProfile, setShowProfile] = useState(false);
+  // A profile is shown as soon as its run sends it
+  useEffect(() => setShowProfile(!!profile), [profile]);
   const code = activeTab?.code || '';
+  const language = activeTab?.language ?? '';
+  const cells = useMemo(() => splitCodeCells(code, language), [code, language]);
   const {
     themeName,
     fontSize,
     alignResults,
     consoleFilters,
     setConsoleFilters,
+    showCompiledOutput,
   } = useSettingsStore();
   const npmStore = usePackagesStore();
   const pythonStore = usePythonPackagesStore();
@@ -66,9 +95,10 @@ function ResultDisplay() {
     }
   }
 
-  return (
+  const resultPanel = (
     <ResultPanel
       elements={elements}
+      cells={cells}
       code={code}
       themeName={themeName}
       fontSize={fontSize}
@@ -76,6 +106,7 @@ function ResultDisplay() {
       consoleFilters={filters}
       onToggleFilter={toggleFilter}
       onEditorWillMount={handleEditorWillMount}
+      onExpandValue={expandValue}
       consoleInput={<ConsoleInput />}
       packagePrompts={
         <PackagePrompts
@@ -88,6 +119,61 @@ function ResultDisplay() {
       }
     />
   );
+
+  const output =
+    !showCompiledOutput ||
+    !NATIVE_LANGUAGES.has(activeTab?.language ?? '') ? (
+      resultPanel
+    ) : (
+      <div className="h-full flex flex-col">
+        <div className="flex-1 min-h-0">{resultPanel}</div>
+        <div className="flex-1 min-h-0 border-t border-border">
+          <CompiledOutputPanel
+            output={activeTab?.compiledOutput ?? null}
+            themeName={themeName}
+            fontSize={fontSize}
+          />
+        </div>
+      </div>
+    );
+
+  // Profiled runs get a tab for their profile next to the output, which
+  // stays mounted so its editor keeps its state
+  return (
+    <div className="h-full flex flex-col">
+      {profile && (
+        <div className="flex items-center gap-1 px-2 border-b border-border bg-muted/20 shrink-0">
+          {[
+            { label: 'Output', active: !showProfile, select: false },
+            { label: 'Profile', active: showProfile, select: true },
+          ].map(({ label, active, select }) => (
+            <button
+              key={label}
+              onClick={() => setShowProfile(select)}
+              className={clsx(
+                'px-3 py-1.5 text-xs font-medium border-b-2 -mb-px select-none',
+                active
+                  ? 'border-primary text-foreground'
+                  : 'border-transparent text-muted-foreground hover:text-foreground'
+              )}
+            >
+              {label}
+            </button>
+          ))}
+        </div>
+      )}
+      <div
+        className={clsx('flex-1 min-h-0', profile && showProfile && 'hidden')}
+      >
+        {output}
+      </div>
+      {profile && showProfile && (
+        <div className="flex-1 min-h-0">
+          <ProfilePanel profile={profile} onRevealLine={revealLine} />
+        </div>
+      )}
+    </div>
+  );
 }
 
 export default ResultDisplay;
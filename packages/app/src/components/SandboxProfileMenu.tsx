@@ -0,0 +1,181 @@
+import React, { useState, useRef, useEffect } from 'react';
+import { createPortal } from 'react-dom';
+import { m, AnimatePresence } from 'framer-motion';
+import { Check, RotateCcw, Shield, X } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
+import {
+  DEFAULT_SANDBOX_PROFILE,
+  SANDBOX_PROFILES,
+  type SandboxProfileId,
+} from '@cheesejs/core';
+import { useEditorTabsStore, useSettingsStore } from '../store/storeHooks';
+import clsx from 'clsx';
+
+const PROFILE_IDS = Object.keys(SANDBOX_PROFILES) as SandboxProfileId[];
+
+/**
+ * Toolbar menu picking the sandbox profile JS/TS runs of the active tab
+ * use, and clearing the permission answers remembered for the workspace.
+ */
+export function SandboxProfileMenu() {
+  const { t } = useTranslation();
+  const [isOpen, setIsOpen] = useState(false);
+  const { tabs, activeTabId, setTabSandboxProfile } = useEditorTabsStore();
+  const { workingDirectory, sandboxDecisions, forgetSandboxDecisions } =
+    useSettingsStore();
+
+  const activeTab = tabs.find((t) => t.id === activeTabId);
+  const activeProfile = activeTab?.sandboxProfile ?? DEFAULT_SANDBOX_PROFILE;
+  const hasDecisions =
+    Object.keys(sandboxDecisions[workingDirectory] ?? {}).length > 0;
+
+  const buttonRef = useRef<HTMLButtonElement>(null);
+  const [menuStyle, setMenuStyle] = useState<React.CSSProperties>({});
+
+  // Update position when opening
+  useEffect(() => {
+    if (isOpen && buttonRef.current) {
+      const updatePosition = () => {
+        const rect = buttonRef.current!.getBoundingClientRect();
+        setMenuStyle({
+          position: 'fixed',
+          bottom: window.innerHeight - rect.top + 16,
+          left: rect.left + rect.width / 2,
+          transform: 'translateX(-50%)',
+          zIndex: 9999,
+          width: '20rem',
+        });
+      };
+
+      updatePosition();
+      window.addEventListener('resize', updatePosition);
+      window.addEventListener('scroll', updatePosition, true);
+
+      return () => {
+        window.removeEventListener('resize', updatePosition);
+        window.removeEventListener('scroll', updatePosition, true);
+      };
+    }
+  }, [isOpen]);
+
+  const handleSelect = (profile: SandboxProfileId) => {
+    if (activeTabId) setTabSandboxProfile(activeTabId, profile);
+    setIsOpen(false);
+  };
+
+  return (
+    <>
+      <m.button
+        ref={buttonRef}
+        whileHover={{ scale: 1.1 }}
+        whileTap={{ scale: 0.95 }}
+        onClick={() => setIsOpen(!isOpen)}
+        data-testid="sandbox-profile-button"
+        className={clsx(
+          'p-3 rounded-full text-muted-foreground hover:bg-accent hover:text-accent-foreground transition-colors relative group',
+          isOpen && 'bg-accent text-primary',
+          activeProfile !== DEFAULT_SANDBOX_PROFILE && 'text-primary'
+        )}
+        title={`${t('toolbar.sandbox', 'Sandbox permissions')}: ${t(`sandbox.profiles.${activeProfile}`, activeProfile)}`}
+      >
+        <Shield className="w-5 h-5" />
+      </m.button>
+
+      {createPortal(
+        <AnimatePresence>
+          {isOpen && (
+            <>
+              {/* Backdrop */}
+              <div
+                className="fixed inset-0 z-[9998]"
+                onClick={() => setIsOpen(false)}
+              />
+
+              {/* Menu */}
+              <m.div
+                initial={{ opacity: 0, y: 10, scale: 0.95 }}
+                animate={{ opacity: 1, y: 0, scale: 1 }}
+                exit={{ opacity: 0, y: 10, scale: 0.95 }}
+                transition={{ duration: 0.2 }}
+                style={menuStyle}
+                className="bg-popover/95 backdrop-blur-xl rounded-2xl shadow-[0_30px_60px_-15px_rgba(0,0,0,0.5)] border border-border/50 flex flex-col overflow-hidden ring-1 ring-white/5"
+                onClick={(e) => e.stopPropagation()}
+              >
+                {/* Header */}
+                <div className="p-4 border-b border-border/40 flex justify-between items-start bg-gradient-to-b from-white/5 to-transparent">
+                  <div>
+                    <h3 className="font-semibold text-foreground flex items-center gap-2">
+                      <Shield size={18} className="text-primary" />
+                      {t('sandbox.title', 'Sandbox permissions')}
+                    </h3>
+                    <p className="text-xs text-muted-foreground mt-1">
+                      {t(
+                        'sandbox.hint',
+                        'Applies to JavaScript and TypeScript runs of this tab and the packages they load'
+                      )}
+                    </p>
+                  </div>
+                  <button
+                    onClick={() => setIsOpen(false)}
+                    className="p-1.5 rounded-md text-muted-foreground hover:text-foreground hover:bg-white/10 transition-colors"
+                  >
+                    <X size={18} />
+                  </button>
+                </div>
+
+                {/* Profiles */}
+                <div className="p-2 space-y-1" role="radiogroup">
+                  {PROFILE_IDS.map((profile) => (
+                    <button
+                      key={profile}
+                      role="radio"
+                      aria-checked={profile === activeProfile}
+                      onClick={() => handleSelect(profile)}
+                      data-testid={`sandbox-profile-${profile}`}
+                      className={clsx(
+                        'w-full flex items-start gap-3 p-3 rounded-xl text-left transition-colors',
+                        profile === activeProfile
+                          ? 'bg-primary/10'
+                          : 'hover:bg-white/5'
+                      )}
+                    >
+                      <Check
+                        size={16}
+                        className={clsx(
+                          'mt-0.5 shrink-0 text-primary',
+                          profile !== activeProfile && 'invisible'
+                        )}
+                      />
+                      <span>
+                        <span className="block text-sm font-medium text-foreground">
+                          {t(`sandbox.profiles.${profile}`, profile)}
+                        </span>
+                        <span className="block text-xs text-muted-foreground">
+                          {t(`sandbox.descriptions.${profile}`, '')}
+                        </span>
+                      </span>
+                    </button>
+                  ))}
+                </div>
+
+                {hasDecisions && (
+                  <div className="p-2 border-t border-border/40">
+                    <button
+                      onClick={() => forgetSandboxDecisions(workingDirectory)}
+                      data-testid="sandbox-forget-decisions"
+                      className="w-full flex items-center justify-center gap-2 px-3 py-2 text-xs font-medium text-muted-foreground hover:text-foreground rounded-lg hover:bg-white/5 transition-colors"
+                    >
+                      <RotateCcw size={14} />
+                      {t('sandbox.forget', 'Forget remembered answers')}
+                    </button>
+                  </div>
+                )}
+              </m.div>
+            </>
+          )}
+        </AnimatePresence>,
+        document.body
+      )}
+    </>
+  );
+}
@@ -0,0 +1,178 @@
+import React, { useState, useRef, useEffect, useMemo } from 'react';
+import { createPortal } from 'react-dom';
+import { m, AnimatePresence } from 'framer-motion';
+import { Check, History, RotateCcw, X } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
+import { splitCodeCells } from '@cheesejs/languages';
+import { useEditorTabsStore } from '../store/storeHooks';
+import clsx from 'clsx';
+
+/**
+ * Toolbar menu turning the REPL session of the active tab on and off, and
+ * resetting the state it kept. Sessions are keyed by tab id; running code
+ * cells uses the session even when it is off, so it can be reset then too.
+ */
+export function SessionMenu() {
+  const { t } = useTranslation();
+  const [isOpen, setIsOpen] = useState(false);
+  const { tabs, activeTabId, setTabReplSession } = useEditorTabsStore();
+
+  const activeTab = tabs.find((t) => t.id === activeTabId);
+  const isEnabled = !!activeTab?.replSession;
+  const code = activeTab?.code ?? '';
+  const language = activeTab?.language ?? '';
+  const hasCells = useMemo(
+    () => splitCodeCells(code, language).length > 0,
+    [code, language]
+  );
+
+  const buttonRef = useRef<HTMLButtonElement>(null);
+  const [menuStyle, setMenuStyle] = useState<React.CSSProperties>({});
+
+  // Update position when opening
+  useEffect(() => {
+    if (isOpen && buttonRef.current) {
+      const updatePosition = () => {
+        const rect = buttonRef.current!.getBoundingClientRect();
+        setMenuStyle({
+          position: 'fixed',
+          bottom: window.innerHeight - rect.top + 16,
+          left: rect.left + rect.width / 2,
+          transform: 'translateX(-50%)',
+          zIndex: 9999,
+          width: '20rem',
+        });
+      };
+
+      updatePosition();
+      window.addEventListener('resize', updatePosition);
+      window.addEventListener('scroll', updatePosition, true);
+
+      return () => {
+        window.removeEventListener('resize', updatePosition);
+        window.removeEventListener('scroll', updatePosition, true);
+      };
+    }
+  }, [isOpen]);
+
+  const resetSession = () => {
+    if (activeTabId) void window.codeRunner?.resetSession(activeTabId);
+  };
+
+  const handleToggle = () => {
+    if (!activeTabId) return;
+    // Turning the session off drops what it kept
+    if (isEnabled) resetSession();
+    setTabReplSession(activeTabId, !isEnabled);
+  };
+
+  const handleReset = () => {
+    resetSession();
+    setIsOpen(false);
+  };
+
+  return (
+    <>
+      <m.button
+        ref={buttonRef}
+        whileHover={{ scale: 1.1 }}
+        whileTap={{ scale: 0.95 }}
+        onClick={() => setIsOpen(!isOpen)}
+        data-testid="session-button"
+        className={clsx(
+          'p-3 rounded-full text-muted-foreground hover:bg-accent hover:text-accent-foreground transition-colors relative group',
+          isOpen && 'bg-accent text-primary',
+          isEnabled && 'text-primary'
+        )}
+        title={t('toolbar.session', 'REPL session')}
+      >
+        <History className="w-5 h-5" />
+      </m.button>
+
+      {createPortal(
+        <AnimatePresence>
+          {isOpen && (
+            <>
+              {/* Backdrop */}
+              <div
+                className="fixed inset-0 z-[9998]"
+                onClick={() => setIsOpen(false)}
+              />
+
+              {/* Menu */}
+              <m.div
+                initial={{ opacity: 0, y: 10, scale: 0.95 }}
+                animate={{ opacity: 1, y: 0, scale: 1 }}
+                exit={{ opacity: 0, y: 10, scale: 0.95 }}
+                transition={{ duration: 0.2 }}
+                style={menuStyle}
+                className="bg-popover/95 backdrop-blur-xl rounded-2xl shadow-[0_30px_60px_-15px_rgba(0,0,0,0.5)] border border-border/50 flex flex-col overflow-hidden ring-1 ring-white/5"
+                onClick={(e) => e.stopPropagation()}
+              >
+                {/* Header */}
+                <div className="p-4 border-b border-border/40 flex justify-between items-start bg-gradient-to-b from-white/5 to-transparent">
+                  <div>
+                    <h3 className="font-semibold text-foreground flex items-center gap-2">
+                      <History size={18} className="text-primary" />
+                      {t('session.title', 'REPL session')}
+                    </h3>
+                    <p className="text-xs text-muted-foreground mt-1">
+                      {t(
+                        'session.hint',
+                        'JavaScript, TypeScript and Python runs of this tab keep their variables; top-level statements run again from the first new or changed one'
+                      )}
+                    </p>
+                  </div>
+                  <button
+                    onClick={() => setIsOpen(false)}
+                    className="p-1.5 rounded-md text-muted-foreground hover:text-foreground hover:bg-white/10 transition-colors"
+                  >
+                    <X size={18} />
+                  </button>
+                </div>
+
+                <div className="p-2">
+                  <button
+                    role="switch"
+                    aria-checked={isEnabled}
+                    onClick={handleToggle}
+                    data-testid="session-toggle"
+                    className={clsx(
+                      'w-full flex items-center gap-3 p-3 rounded-xl text-left transition-colors',
+                      isEnabled ? 'bg-primary/10' : 'hover:bg-white/5'
+                    )}
+                  >
+                    <Check
+                      size={16}
+                      className={clsx(
+                        'shrink-0 text-primary',
+                        !isEnabled && 'invisible'
+                      )}
+                    />
+                    <span className="text-sm font-medium text-foreground">
+                      {t('session.enable', 'Keep state between runs')}
+                    </span>
+                  </button>
+                </div>
+
+                {(isEnabled || hasCells) && (
+                  <div className="p-2 border-t border-border/40">
+                    <button
+                      onClick={handleReset}
+                      data-testid="session-reset"
+                      className="w-full flex items-center justify-center gap-2 px-3 py-2 text-xs font-medium text-muted-foreground hover:text-foreground rounded-lg hover:bg-white/5 transition-colors"
+                    >
+                      <RotateCcw size={14} />
+                      {t('session.reset', 'Reset session')}
+                    </button>
+                  </div>
+                )}
+              </m.div>
+            </>
+          )}
+        </AnimatePresence>,
+        document.body
+      )}
+    </>
+  );
+}
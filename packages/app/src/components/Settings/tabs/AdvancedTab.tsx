@@ -2,12 +2,19 @@ import { AdvancedTab as SettingsAdvancedTab } from '@cheesejs/settings';
 import { useSettingsStore } from '../../../store/storeHooks';
 
 export function AdvancedTab() {
-  const { internalLogLevel, setInternalLogLevel } = useSettingsStore();
+  const {
+    internalLogLevel,
+    setInternalLogLevel,
+    workerMemoryLimitMb,
+    setWorkerMemoryLimitMb,
+  } = useSettingsStore();
 
   return (
     <SettingsAdvancedTab
       internalLogLevel={internalLogLevel}
       onInternalLogLevelChange={setInternalLogLevel}
+      workerMemoryLimitMb={workerMemoryLimitMb}
+      onWorkerMemoryLimitChange={setWorkerMemoryLimitMb}
     />
   );
 }
@@ -9,16 +9,32 @@ export function CompilationTab() {
     setMagicComments,
     showTopLevelResults,
     setShowTopLevelResults,
+    pythonTests,
+    setPythonTests,
+    compilerOptions,
+    setCompilerOptions,
+    showCompiledOutput,
+    setShowCompiledOutput,
+    projectMode,
+    setProjectMode,
   } = useSettingsStore();
 
   return (
     <SettingsCompilationTab
+      compilerOptions={compilerOptions}
+      onCompilerOptionsChange={setCompilerOptions}
       loopProtection={loopProtection}
       onLoopProtectionChange={setLoopProtection}
       magicComments={magicComments}
       onMagicCommentsChange={setMagicComments}
       showTopLevelResults={showTopLevelResults}
       onShowTopLevelResultsChange={setShowTopLevelResults}
+      pythonTests={pythonTests}
+      onPythonTestsChange={setPythonTests}
+      showCompiledOutput={showCompiledOutput}
+      onShowCompiledOutputChange={setShowCompiledOutput}
+      projectMode={projectMode}
+      onProjectModeChange={setProjectMode}
     />
   );
 }
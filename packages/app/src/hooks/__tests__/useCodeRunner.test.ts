@@ -39,14 +39,24 @@ const mockAddToHistory = vi.fn();
 
 const buildEditorTabsState = () => ({
   activeTabId: 'test-tab',
-  tabs: [{ id: 'test-tab', code: 'console.log("hello")' }],
+  tabs: [
+    {
+      id: 'test-tab',
+      title: 'main.js',
+      code: 'console.log("hello")',
+      sandboxProfile: 'offline',
+    },
+    { id: 'utils-tab', title: 'utils.ts', code: 'export const one = 1;' },
+    { id: 'notes-tab', title: 'notes.md', code: '# notes' },
+  ],
   setTabPromptRequest: (
     id: string,
     message: string | null,
-    type: 'text' | 'alert' = 'text',
-    executionId: string | null = null
+    type: 'text' | 'alert' | 'permission' = 'text',
+    executionId: string | null = null,
+    capability: string | null = null
   ) => {
-    mockSetTabPromptRequest(id, message, type, executionId);
+    mockSetTabPromptRequest(id, message, type, executionId, capability);
     mockSetPromptRequest(message);
   },
   setTabExecuting: (id: string, isExecuting: boolean) => {
@@ -65,8 +75,8 @@ const buildEditorTabsState = () => ({
     mockSetTabResults(id, results);
     mockSetResult(results);
   },
-  clearTabResults: (id: string) => {
-    mockClearTabResults(id);
+  clearTabResults: (id: string, cells?: number[]) => {
+    mockClearTabResults(id, cells);
     mockClearResult();
   },
   appendTabResult: (
@@ -101,7 +111,11 @@ vi.mock('../../store/storeHooks', () => ({
     loopProtection: false,
     showUndefined: false,
     magicComments: false,
+    pythonTests: false,
     workingDirectory: undefined,
+    compilerOptions: { cStandard: 'c11', optimization: 'O0', defines: ['A'] },
+    workerMemoryLimitMb: 512,
+    sandboxDecisions: {},
   }),
   useLanguageStore: Object.assign(
     (selector: (s: any) => any) => selector({ currentLanguage: 'javascript' }),
@@ -285,6 +299,115 @@ describe('useCodeRunner', () => {
     );
   });
 
+  it('should pass the other open tabs as importable modules', async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode("import { one } from './utils';");
+    });
+    await flushDebounce();
+
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      "import { one } from './utils';",
+      expect.objectContaining({
+        moduleFiles: { 'utils.ts': 'export const one = 1;' },
+        entryFile: 'main.js',
+      })
+    );
+  });
+
+  it("should pass the permissions of the tab's sandbox profile", async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode('fetch("https://example.com")');
+    });
+    await flushDebounce();
+
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      expect.any(String),
+      expect.objectContaining({
+        permissions: {
+          fs: 'allow',
+          net: 'deny',
+          fetch: 'deny',
+          env: 'allow',
+          childProcess: 'deny',
+        },
+      })
+    );
+  });
+
+  it('should pass the worker memory limit for JS/TS runs', async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode('const big = [];');
+    });
+    await flushDebounce();
+
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      expect.any(String),
+      expect.objectContaining({ memoryLimitMb: 512 })
+    );
+  });
+
+  it('should run a cell in the tab session with other cells blanked', async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode('// %% Setup\nconst x = 1;\n// %% Use\nx * 2', {
+        mode: 'cell',
+        cellIndex: 1,
+      });
+    });
+    await flushDebounce();
+
+    expect(mockClearTabResults).toHaveBeenCalledWith('test-tab', [1]);
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      '\n\n// %% Use\nx * 2',
+      expect.objectContaining({ sessionId: 'test-tab', runAllStatements: true })
+    );
+  });
+
+  it('should run the cells above a cell of a module buffer', async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode(
+        "// %% Imports\nimport path from 'node:path';\n// %% Use\npath.sep",
+        { mode: 'cell', cellIndex: 1 }
+      );
+    });
+    await flushDebounce();
+
+    expect(mockClearTabResults).toHaveBeenCalledWith('test-tab', [0, 1]);
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      "// %% Imports\nimport path from 'node:path';\n// %% Use\npath.sep",
+      expect.objectContaining({ sessionId: undefined })
+    );
+  });
+
+  it('should ask for a CPU profile only when profiling the run', async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode('const x = 1', undefined, true);
+    });
+    await flushDebounce();
+
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      expect.any(String),
+      expect.objectContaining({ profile: true })
+    );
+  });
+
   it('should handle debug result type (line-numbered output)', async () => {
     let resultCallback: (data: Record<string, unknown>) => void;
     mockOnResult.mockImplementation(
@@ -657,7 +780,8 @@ describe('useCodeRunner', () => {
     expect(mockExecute).toHaveBeenCalledWith(
       expect.any(String),
       'print("hello")',
-      expect.objectContaining({ language: 'python' })
+      // Python tests run only once they are enabled in the settings
+      expect.objectContaining({ language: 'python', runTests: undefined })
     );
   });
 
@@ -727,6 +851,42 @@ describe('useCodeRunner', () => {
     );
   });
 
+  it('should merge a cheese: magic header into compiler options', async () => {
+    mockDetectLanguage.mockReturnValue({
+      monacoId: 'c',
+      confidence: 1,
+      isExecutable: true,
+    });
+
+    const code = '// cheese: -std=c17 -O2 -DB=1 -fplugin=x\nint main(){}';
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode(code);
+    });
+    await flushDebounce();
+
+    expect(mockExecute).toHaveBeenCalledWith(
+      expect.any(String),
+      code,
+      expect.objectContaining({
+        compilerOptions: expect.objectContaining({
+          cStandard: 'c17',
+          optimization: 'O2',
+          defines: ['A', 'B=1'],
+        }),
+      })
+    );
+    expect(mockAppendTabResult).toHaveBeenCalledWith(
+      'test-tab',
+      expect.objectContaining({
+        element: expect.objectContaining({
+          content: expect.stringContaining('-fplugin=x'),
+        }),
+      })
+    );
+  });
+
   it('should update code in store when codeToRun is provided', async () => {
     const { result } = renderHook(() => useCodeRunner());
 
@@ -771,7 +931,37 @@ describe('useCodeRunner', () => {
       'test-tab',
       'What is your name?',
       'text',
-      executionId
+      executionId,
+      null
+    );
+  });
+
+  it('should show permission requests as permission prompts', async () => {
+    const { result } = renderHook(() => useCodeRunner());
+
+    act(() => {
+      result.current.runCode("require('fs')");
+    });
+    await flushDebounce();
+
+    const executionId = mockExecute.mock.calls[0][0];
+    const inputListener = mockOnJSInputRequest.mock.calls[0][0];
+
+    act(() => {
+      inputListener({
+        id: executionId,
+        message: "import 'fs'",
+        type: 'permission-request',
+        capability: 'fs',
+      });
+    });
+
+    expect(mockSetTabPromptRequest).toHaveBeenCalledWith(
+      'test-tab',
+      "import 'fs'",
+      'permission',
+      executionId,
+      'fs'
     );
   });
 
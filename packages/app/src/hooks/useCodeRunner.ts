@@ -5,10 +5,62 @@ import {
   useEditorTabsStore,
   useSettingsStore,
 } from '../store/storeHooks';
+import {
+  findCellAtLine,
+  getCellSource,
+  mergeCompilerOptions,
+  parseCompilerMagicHeader,
+  selectCellsToRun,
+  splitCodeCells,
+  type CellRunMode,
+} from '@cheesejs/languages';
+import { resolveSandboxPermissions, usesModuleSyntax } from '@cheesejs/core';
 import { useAppStore } from '../store/index';
 import { executionEngine } from '../lib/execution/ExecutionEngine';
 import { useEffect, useCallback } from 'react';
 
+// Languages compiled by the WASI toolchain; these honour compiler options
+const NATIVE_EXECUTION_LANGUAGES = new Set(['c', 'cpp', 'rust']);
+
+// Languages whose programs can `import` the other open tabs
+const MODULE_EXECUTION_LANGUAGES = new Set(['javascript', 'typescript']);
+
+/** A run of the code cells of a buffer instead of the whole of it */
+export interface CellRunRequest {
+  mode: CellRunMode;
+  cellIndex: number;
+}
+
+// Languages whose runs can keep their state in a REPL session
+const SESSION_EXECUTION_LANGUAGES = new Set([
+  'javascript',
+  'typescript',
+  'python',
+]);
+
+// Languages whose runs can be CPU-profiled
+const PROFILED_EXECUTION_LANGUAGES = new Set([
+  'javascript',
+  'typescript',
+  'python',
+]);
+
+// Tab titles a relative import may resolve to
+const MODULE_FILE_PATTERN = /\.(?:[cm]?[jt]s|[jt]sx|json)$/;
+
+function collectModuleFiles(
+  tabs: Array<{ id: string; title: string; code: string }>,
+  callerTabId: string
+): Record<string, string> {
+  return Object.fromEntries(
+    tabs
+      .filter(
+        (tab) => tab.id !== callerTabId && MODULE_FILE_PATTERN.test(tab.title)
+      )
+      .map((tab) => [tab.title, tab.code])
+  );
+}
+
 let executionCounter = 0;
 const executionToTabMap = new Map<string, string>();
 
@@ -45,7 +97,13 @@ export function useCodeRunner() {
     loopProtection,
     showUndefined,
     magicComments,
+    pythonTests,
     workingDirectory,
+    compilerOptions,
+    showCompiledOutput,
+    projectMode,
+    workerMemoryLimitMb,
+    sandboxDecisions,
   } = useSettingsStore();
 
   // Remove global cancel on unmount to allow background tab execution
@@ -72,8 +130,13 @@ export function useCodeRunner() {
         setTabPromptRequest(
           targetTabId,
           request.message,
-          request.type === 'alert-request' ? 'alert' : 'text',
-          request.id
+          request.type === 'alert-request'
+            ? 'alert'
+            : request.type === 'permission-request'
+              ? 'permission'
+              : 'text',
+          request.id,
+          request.capability
         );
       }
     });
@@ -84,7 +147,7 @@ export function useCodeRunner() {
   }, [setTabPromptRequest]);
 
   const runCode = useCallback(
-    async (codeToRun?: string) => {
+    async (codeToRun?: string, cellRun?: CellRunRequest, profile = false) => {
       const callerTabId = useEditorTabsStore.getState().activeTabId;
       if (!callerTabId) return;
 
@@ -116,7 +179,7 @@ export function useCodeRunner() {
         setTabResults(callerTabId, [
           {
             element: {
-              content: `❌ Unsupported Language: ${getLanguageDisplayName(currentLang)} \n\nThis editor can execute JavaScript, TypeScript, Python, C, and C++ code.\n\nDetected language: ${currentLang} \nSupported languages: javascript, typescript, python, c, cpp`,
+              content: `❌ Unsupported Language: ${getLanguageDisplayName(currentLang)} \n\nThis editor can execute JavaScript, TypeScript, Python, C, C++, and Rust code.\n\nDetected language: ${currentLang} \nSupported languages: javascript, typescript, python, c, cpp, rust`,
             },
             type: 'error',
           },
@@ -124,9 +187,6 @@ export function useCodeRunner() {
         return;
       }
 
-      clearTabResults(callerTabId);
-      setTabExecuting(callerTabId, true);
-
       const execLanguage = getExecutionLanguage(currentLang);
       if (!execLanguage) {
         setTabExecuting(callerTabId, false);
@@ -141,13 +201,56 @@ export function useCodeRunner() {
         return;
       }
 
+      // Cells run in the tab's REPL session so they share state; languages
+      // without sessions run the cells above the selected one again, as do
+      // JS/TS buffers with import/export, which run as modules that never
+      // keep state
+      const cells = splitCodeCells(sourceCode, currentLang);
+      const runsAsModule = MODULE_EXECUTION_LANGUAGES.has(execLanguage);
+      const isStateful =
+        SESSION_EXECUTION_LANGUAGES.has(execLanguage) &&
+        !(runsAsModule && usesModuleSyntax(sourceCode));
+      const runCells =
+        cellRun && cells.length > 0
+          ? selectCellsToRun(cells, cellRun.mode, cellRun.cellIndex, isStateful)
+          : null;
+      if (runCells?.length === 0) return;
+
+      // Output goes to the cell of its line, or else to the last cell run
+      const fallbackCell = runCells?.[runCells.length - 1].index;
+      const getResultCell = (lineNumber?: number) =>
+        (lineNumber ? findCellAtLine(cells, lineNumber)?.index : undefined) ??
+        fallbackCell;
+
+      clearTabResults(callerTabId, runCells?.map((cell) => cell.index));
+      setTabExecuting(callerTabId, true);
+
+      // A `// cheese:` header overrides the settings for this file only
+      let tabCompilerOptions = compilerOptions;
+      if (NATIVE_EXECUTION_LANGUAGES.has(execLanguage)) {
+        const header = parseCompilerMagicHeader(sourceCode);
+        tabCompilerOptions = mergeCompilerOptions(
+          compilerOptions,
+          header.options
+        );
+        if (header.ignored.length > 0) {
+          useEditorTabsStore.getState().appendTabResult(callerTabId, {
+            element: {
+              content: `⚠️ Ignored unsupported compiler flags: ${header.ignored.join(' ')}`,
+              consoleType: 'warn',
+            },
+            type: 'execution',
+          });
+        }
+      }
+
       const executionId = createExecutionId(callerTabId);
       setMappedTabId(executionId, callerTabId);
 
       await executionEngine.run(
         callerTabId,
         executionId,
-        sourceCode,
+        runCells ? getCellSource(sourceCode, runCells) : sourceCode,
         execLanguage,
         {
           showUndefined,
@@ -155,11 +258,40 @@ export function useCodeRunner() {
           loopProtection,
           magicComments,
           workingDirectory,
+          compilerOptions: tabCompilerOptions,
+          emitCompiledOutput:
+            showCompiledOutput && NATIVE_EXECUTION_LANGUAGES.has(execLanguage),
+          projectMode,
+          moduleFiles: runsAsModule
+            ? collectModuleFiles(tabs, callerTabId)
+            : undefined,
+          entryFile: runsAsModule ? callerTab?.title : undefined,
+          permissions: runsAsModule
+            ? resolveSandboxPermissions(
+                callerTab?.sandboxProfile,
+                sandboxDecisions[workingDirectory]
+              )
+            : undefined,
+          memoryLimitMb: runsAsModule ? workerMemoryLimitMb : undefined,
+          // Sessions are keyed by tab, so each tab keeps its own state
+          sessionId:
+            (callerTab?.replSession || runCells) && isStateful
+              ? callerTabId
+              : undefined,
+          // Running a cell means running it again, even if it is unchanged
+          runAllStatements: runCells ? true : undefined,
+          profile:
+            profile && PROFILED_EXECUTION_LANGUAGES.has(execLanguage)
+              ? true
+              : undefined,
+          runTests: pythonTests && execLanguage === 'python' ? true : undefined,
         },
         {
           onOutput: (result) => {
             useEditorTabsStore.getState().appendTabResult(callerTabId, {
               lineNumber: result.lineNumber,
+              executionId: result.values ? executionId : undefined,
+              cell: getResultCell(result.lineNumber),
               element: {
                 content: result.content,
                 jsType: result.jsType,
@@ -170,14 +302,54 @@ export function useCodeRunner() {
                   | 'info'
                   | 'table'
                   | 'dir',
+                groupDepth: result.groupDepth,
+                groupStart: result.groupStart,
+                values: result.values,
               },
               type: result.type,
             });
           },
+          onClearOutput: () => {
+            useEditorTabsStore.getState().setTabResults(callerTabId, []);
+          },
+          onDiagnostics: (diagnostics) => {
+            const store = useEditorTabsStore.getState();
+            store.setTabDiagnostics(callerTabId, diagnostics);
+            // Surface each error and warning inline next to its line as well;
+            // those from other project files are listed with their location
+            for (const diagnostic of diagnostics) {
+              if (diagnostic.severity === 'note') continue;
+              const isError = diagnostic.severity === 'error';
+              const location = diagnostic.file
+                ? `${diagnostic.file}:${diagnostic.line}:${diagnostic.column}: `
+                : '';
+              const lineNumber = diagnostic.file ? undefined : diagnostic.line;
+              store.appendTabResult(callerTabId, {
+                lineNumber,
+                cell: getResultCell(lineNumber),
+                element: {
+                  content: `${isError ? '❌' : '⚠️'} ${location}${diagnostic.message}`,
+                  consoleType: isError ? 'error' : 'warn',
+                },
+                type: 'execution',
+              });
+            }
+          },
+          onCompiledOutput: (output) => {
+            useEditorTabsStore
+              .getState()
+              .setTabCompiledOutput(callerTabId, output);
+          },
+          onProfile: (cpuProfile) => {
+            useEditorTabsStore
+              .getState()
+              .setTabProfile(callerTabId, cpuProfile);
+          },
           onError: (errorMsg) => {
             useEditorTabsStore.getState().appendTabResult(callerTabId, {
               element: { content: errorMsg },
               type: 'error',
+              cell: fallbackCell,
             });
             clearMappedExecution(executionId);
             useEditorTabsStore.getState().setTabExecuting(callerTabId, false);
@@ -198,7 +370,13 @@ export function useCodeRunner() {
       loopProtection,
       showUndefined,
       magicComments,
+      pythonTests,
       workingDirectory,
+      compilerOptions,
+      showCompiledOutput,
+      projectMode,
+      workerMemoryLimitMb,
+      sandboxDecisions,
     ]
   );
 
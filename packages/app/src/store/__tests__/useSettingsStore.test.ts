@@ -38,4 +38,14 @@ describe('useSettingsStore', () => {
     setInternalLogLevel('debug');
     expect(useAppStore.getState().settings.internalLogLevel).toBe('debug');
   });
+
+  it('should merge partial compiler option updates', () => {
+    const { setCompilerOptions } = useAppStore.getState().settings;
+    setCompilerOptions({ cppStandard: 'c++20', defines: ['DEBUG'] });
+
+    const { compilerOptions } = useAppStore.getState().settings;
+    expect(compilerOptions.cppStandard).toBe('c++20');
+    expect(compilerOptions.defines).toEqual(['DEBUG']);
+    expect(compilerOptions.cStandard).toBe('c11');
+  });
 });
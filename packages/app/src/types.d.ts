@@ -2,9 +2,6 @@ import type { CodeRunner } from './types/runner';
 import type { PackageManager, PythonPackageManager } from './types/packages';
 import type { LspBridgeApi, LspConfigApi } from '@cheesejs/core/contracts/lsp';
 
-declare module 'json-cycle';
-declare module 'stringify-object';
-
 declare global {
   interface Window {
     electronAPI: {
@@ -17,6 +17,16 @@ export interface LspSaveResult {
   error?: string;
 }
 
+/**
+ * Result of starting a language server. Servers that need a real project on
+ * disk (e.g. rust-analyzer) also report the generated root and the file that
+ * stands in for the editor buffer.
+ */
+export interface LspStartResult extends LspSaveResult {
+  rootUri?: string;
+  documentUri?: string;
+}
+
 export interface LspBridgeMessage {
   langId: string;
   data: string;
@@ -30,7 +40,7 @@ export interface LspConfigApi {
 
 /** Typed preload API used by Monaco clients to talk to spawned LSP servers. */
 export interface LspBridgeApi {
-  start: (langId: string) => Promise<LspSaveResult>;
+  start: (langId: string) => Promise<LspStartResult>;
   stop: (langId: string) => void;
   sendMessage: (langId: string, message: string) => void;
   onMessage: (callback: (msg: LspBridgeMessage) => void) => () => void;
@@ -1,5 +1,360 @@
 import type { Language } from './workerTypes';
 
+export type CStandard = 'c99' | 'c11' | 'c17' | 'c23';
+export type CppStandard = 'c++14' | 'c++17' | 'c++20' | 'c++23';
+export type OptimizationLevel = 'O0' | 'O1' | 'O2' | 'O3' | 'Os' | 'Oz';
+
+/**
+ * Compiler settings for the WASI C/C++/Rust runtimes. Kept structured rather
+ * than as raw flags so the worker can validate every value it passes on.
+ */
+export interface NativeCompilerOptions {
+  cStandard?: CStandard;
+  cppStandard?: CppStandard;
+  optimization?: OptimizationLevel;
+  /** Preprocessor defines as `NAME` or `NAME=VALUE` */
+  defines?: string[];
+  /** Warning flags such as `-Wall` or `-Wno-unused-variable` */
+  warnings?: string[];
+  /** Libraries to link, without the `-l` prefix */
+  libraries?: string[];
+}
+
+/** Heap limits offered for JS/TS workers, in MB (0 keeps V8's default) */
+export const WORKER_MEMORY_LIMITS_MB = [0, 256, 512, 1024, 2048, 4096];
+
+export const DEFAULT_WORKER_MEMORY_LIMIT_MB = 1024;
+
+/**
+ * Stored in the lock of a worker's shared input buffer, instead of 1, when a
+ * prompt was dismissed: `prompt()` returns null and stdin reads end-of-file
+ */
+export const SHARED_INPUT_DISMISSED = 2;
+
+export const DEFAULT_COMPILER_OPTIONS: Required<NativeCompilerOptions> = {
+  cStandard: 'c11',
+  cppStandard: 'c++17',
+  optimization: 'O0',
+  defines: [],
+  warnings: [],
+  libraries: [],
+};
+
+/**
+ * Which sources a C/C++ run compiles: the editor program alone, the files
+ * listed in the working directory's `cheesejs.json`, or every C/C++ file in
+ * the working directory.
+ */
+export type NativeProjectMode = 'single' | 'manifest' | 'workspace';
+
+/** Capabilities a JS/TS run is granted or denied separately. */
+export type SandboxCapability = 'fs' | 'net' | 'fetch' | 'env' | 'childProcess';
+
+/**
+ * `prompt` asks in the console the first time a run uses the capability;
+ * `read` only applies to `fs` and allows reading but not writing.
+ */
+export type SandboxPermission = 'allow' | 'deny' | 'prompt' | 'read';
+
+export type SandboxPermissions = Record<SandboxCapability, SandboxPermission>;
+
+export type SandboxProfileId =
+  | 'standard'
+  | 'offline'
+  | 'read-only-fs'
+  | 'ask'
+  | 'full';
+
+export const SANDBOX_PROFILES: Record<SandboxProfileId, SandboxPermissions> = {
+  // What a snippet could always do: everything but spawning processes
+  standard: {
+    fs: 'allow',
+    net: 'allow',
+    fetch: 'allow',
+    env: 'allow',
+    childProcess: 'deny',
+  },
+  offline: {
+    fs: 'allow',
+    net: 'deny',
+    fetch: 'deny',
+    env: 'allow',
+    childProcess: 'deny',
+  },
+  'read-only-fs': {
+    fs: 'read',
+    net: 'allow',
+    fetch: 'allow',
+    env: 'allow',
+    childProcess: 'deny',
+  },
+  ask: {
+    fs: 'prompt',
+    net: 'prompt',
+    fetch: 'prompt',
+    env: 'prompt',
+    childProcess: 'prompt',
+  },
+  full: {
+    fs: 'allow',
+    net: 'allow',
+    fetch: 'allow',
+    env: 'allow',
+    childProcess: 'allow',
+  },
+};
+
+export const DEFAULT_SANDBOX_PROFILE: SandboxProfileId = 'standard';
+
+export type SandboxDecision = 'allow' | 'deny';
+
+/** Answers to permission prompts remembered for one workspace. */
+export type SandboxDecisions = Partial<
+  Record<SandboxCapability, SandboxDecision>
+>;
+
+/**
+ * Permissions a run gets under `profile`, with remembered decisions
+ * answering the capabilities the profile would prompt for.
+ */
+export function resolveSandboxPermissions(
+  profile: SandboxProfileId = DEFAULT_SANDBOX_PROFILE,
+  decisions: SandboxDecisions = {}
+): SandboxPermissions {
+  const permissions = {
+    ...(SANDBOX_PROFILES[profile] ?? SANDBOX_PROFILES[DEFAULT_SANDBOX_PROFILE]),
+  };
+  for (const capability of Object.keys(permissions) as SandboxCapability[]) {
+    const decision = decisions[capability];
+    if (permissions[capability] === 'prompt' && decision) {
+      permissions[capability] = decision;
+    }
+  }
+  return permissions;
+}
+
+// Static `import x from`, `import 'x'` or `export` at the start of a line;
+// `import(` and `import.meta` alone do not make a module
+const MODULE_SYNTAX_PATTERN =
+  /^[ \t]*(?:import(?:[ \t]+[\w$*{'"]|[ \t]*[*{'"])|export[ \t{*])/m;
+
+/**
+ * Whether a JS/TS snippet is written as an ES module and runs on the
+ * native ESM path rather than being rewritten to `require`.
+ */
+export function usesModuleSyntax(code: string): boolean {
+  return MODULE_SYNTAX_PATTERN.test(code);
+}
+
+export type CompilerDiagnosticSeverity = 'error' | 'warning' | 'note';
+
+/** Replacement suggested by the compiler; positions are 1-based. */
+export interface CompilerFixIt {
+  line: number;
+  column: number;
+  endLine: number;
+  endColumn: number;
+  replacement: string;
+}
+
+/** A single compiler message located in the user's source (1-based). */
+export interface CompilerDiagnostic {
+  severity: CompilerDiagnosticSeverity;
+  message: string;
+  /** Project file relative to the working directory; unset for the editor */
+  file?: string;
+  line: number;
+  column: number;
+  endLine?: number;
+  endColumn?: number;
+  /** Warning flag or error code, e.g. `-Wunused-variable` or `E0308` */
+  code?: string;
+  fixIts?: CompilerFixIt[];
+}
+
+export type CompiledOutputFormat = 'wat' | 'assembly';
+
+/** Text listing of a compiled module, e.g. WAT or clang `-S` output. */
+export interface CompiledOutputListing {
+  format: CompiledOutputFormat;
+  text: string;
+  /**
+   * Source line (1-based) for each listing line, from DWARF `.loc`
+   * directives; null where the line has no source mapping.
+   */
+  sourceLines?: (number | null)[];
+}
+
+/** Compiler output for a WASI module, sent before the program runs. */
+export interface CompiledOutput {
+  listings: CompiledOutputListing[];
+}
+
+/** What a serialized JS value is, deciding its preview and children. */
+export type SerializedValueKind =
+  | 'undefined'
+  | 'null'
+  | 'boolean'
+  | 'number'
+  | 'bigint'
+  | 'string'
+  | 'symbol'
+  | 'function'
+  | 'array'
+  | 'typedarray'
+  | 'map'
+  | 'set'
+  | 'error'
+  | 'promise'
+  | 'date'
+  | 'regexp'
+  | 'object'
+  /** Getter/setter property, left unevaluated */
+  | 'accessor';
+
+/**
+ * JS value sent from the worker as a tree. Children past the first level
+ * stay in the worker until they are asked for by `handle`.
+ */
+export interface SerializedValue {
+  kind: SerializedValueKind;
+  /** One-line rendering, e.g. `Map(2) { 'a' => 1, 'b' => 2 }` */
+  preview: string;
+  /** Constructor name of objects, e.g. `User` or `Uint8Array` */
+  className?: string;
+  /** Set when the value has children the worker can send */
+  handle?: number;
+  /** Children sent along with the value */
+  children?: SerializedEntry[];
+  /** Children left out after the first ones */
+  omitted?: number;
+}
+
+/** Property, index or entry of a serialized value. */
+export interface SerializedEntry {
+  /** Property name, index, or the preview of a Map key */
+  key: string;
+  /** Map keys that are objects, so they can be expanded too */
+  keyValue?: SerializedValue;
+  value: SerializedValue;
+}
+
+/** Children of a value fetched from the worker by handle. */
+export interface SerializedChildren {
+  children: SerializedEntry[];
+  omitted?: number;
+}
+
+/** `data` of a `console` result. */
+export interface ConsoleOutputData {
+  content: string;
+  /** Arguments as value trees, when one of them can be expanded */
+  values?: SerializedValue[];
+  /** `console.group()` nesting level; unset at the top level */
+  groupDepth?: number;
+  /** Set on the label line printed by `console.group()`/`groupCollapsed()` */
+  groupStart?: 'expanded' | 'collapsed';
+  /** `console.clear()`: drop the output printed so far */
+  clear?: boolean;
+}
+
+/** Async work a JS/TS run left outstanding when it completed. */
+export interface PendingAsyncWork {
+  /** `setTimeout`/`setImmediate` callbacks that had not fired */
+  timeouts: number;
+  intervals: number;
+  /** Promises created with `new Promise` that never settled */
+  promises: number;
+  /** `fetch` requests or body reads still in flight */
+  requests: number;
+}
+
+/** Top-level statements a REPL session run executed or kept from before. */
+export interface ReplSessionStats {
+  ran: number;
+  reused: number;
+}
+
+/** `data` of a `test` result: one test of a JS/TS or Python run. */
+export interface TestCaseResult {
+  /** Names of the enclosing `describe` blocks or class and of the test */
+  name: string;
+  status: 'passed' | 'failed' | 'skipped';
+  /** Editor line the test was declared on */
+  line?: number;
+  /** Milliseconds the test and its hooks took */
+  duration: number;
+  /** Why a failed test failed */
+  message?: string;
+  /** Expected and received values of a failed assertion */
+  diff?: string;
+}
+
+/** Tests a run declared and how they went. */
+export interface TestRunSummary {
+  passed: number;
+  failed: number;
+  skipped: number;
+  duration: number;
+  /** Set when the tests ran out of time; counts cover those that finished */
+  timedOut?: boolean;
+}
+
+/** `data` of a `benchmark` result: one `bench()` of a JS/TS run. */
+export interface BenchmarkResult {
+  name: string;
+  /** Editor line `bench()` was called on */
+  line?: number;
+  opsPerSecond: number;
+  /** Milliseconds per call: mean, median and 99th percentile */
+  mean: number;
+  p50: number;
+  p99: number;
+  /** 95% margin of error of the mean, in percent of it */
+  marginOfError: number;
+  /** Timed samples, and calls across all of them */
+  samples: number;
+  iterations: number;
+}
+
+/** Function in the call tree of a CPU profile. */
+export interface ProfileNode {
+  /** Function name; `(anonymous)` for unnamed ones */
+  name: string;
+  /** Line the function starts on: in the editor, or in `file` when set */
+  line?: number;
+  /** File of a function outside the program, e.g. an imported module */
+  file?: string;
+  /** Milliseconds spent in the function itself */
+  selfTime: number;
+  /** Milliseconds spent in the function and everything it called */
+  totalTime: number;
+  children: ProfileNode[];
+}
+
+/** `data` of a `profile` result: where a profiled run spent its time. */
+export interface CpuProfile {
+  /** Profiler that took it: V8's for JS/TS, cProfile for Python */
+  profiler: 'v8' | 'cprofile';
+  /** `(root)` node whose total is the time profiled */
+  root: ProfileNode;
+}
+
+/** `data` of a JS/TS or Python `complete` result. */
+export interface ExecutionCompletion {
+  /** Serialized value of the program, when it produced one */
+  content?: string;
+  jsType?: string;
+  /** Set when the run finished with async work outstanding */
+  pending?: PendingAsyncWork;
+  /** Set for runs in a REPL session */
+  session?: ReplSessionStats;
+  /** Set when the program declared tests, which ran after it */
+  tests?: TestRunSummary;
+  /** Set when the benchmarks ran out of time before all of them finished */
+  benchmarksTimedOut?: boolean;
+}
+
 /** Shared execution options for renderer/main worker orchestration. */
 export interface ExecutionOptions {
   timeout?: number;
@@ -9,11 +364,57 @@ export interface ExecutionOptions {
   magicComments?: boolean;
   workingDirectory?: string;
   language?: Language;
+  compilerOptions?: NativeCompilerOptions;
+  projectMode?: NativeProjectMode;
+  /** Also produce WAT/assembly listings for C/C++/Rust */
+  emitCompiledOutput?: boolean;
+  /**
+   * Other open JS/TS tabs by title, importable by relative path from the
+   * program as if they were saved next to it in the workspace
+   */
+  moduleFiles?: Record<string, string>;
+  /** Tab title the program is run as, for imports and stack traces */
+  entryFile?: string;
+  /** What a JS/TS run may access; unset means the standard profile */
+  permissions?: SandboxPermissions;
+  /** Heap limit for the JS/TS worker in MB; 0 or unset keeps V8's default */
+  memoryLimitMb?: number;
+  /**
+   * REPL session the run belongs to. Its JS/TS context or Python namespace
+   * outlives the run, and top-level statements execute from the first new
+   * or changed one on.
+   */
+  sessionId?: string;
+  /**
+   * Runs every top-level statement of a session run, even unchanged ones;
+   * the state kept by earlier runs is still shared. Used for code cells.
+   */
+  runAllStatements?: boolean;
+  /**
+   * Runs JS/TS under V8's CPU profiler and Python under cProfile, sending
+   * the profile of the program once it has finished
+   */
+  profile?: boolean;
+  /**
+   * Runs the pytest-style tests a Python program defines once it has
+   * finished; JS/TS tests always run, as they are registered by calls
+   */
+  runTests?: boolean;
 }
 
 /** Result payload emitted by JS/TS/Python workers through the preload bridge. */
 export interface ExecutionResult {
-  type: 'result' | 'console' | 'debug' | 'error' | 'complete';
+  type:
+    | 'result'
+    | 'console'
+    | 'debug'
+    | 'error'
+    | 'complete'
+    | 'diagnostics'
+    | 'compiled-output'
+    | 'test'
+    | 'benchmark'
+    | 'profile';
   id: string;
   data?: unknown;
   line?: number;
@@ -45,9 +446,22 @@ export interface CodeRunner {
   onJSInputRequest: (
     callback: (request: {
       id: string;
-      type: 'prompt-request' | 'alert-request';
+      type: 'prompt-request' | 'alert-request' | 'permission-request';
       message: string;
+      /** Set for permission requests */
+      capability?: SandboxCapability;
     }) => void
   ) => () => void;
-  sendJSInputResponse: (id: string, value: string) => void;
+  /** Answers a JS prompt or WASI stdin read; null dismisses it */
+  sendJSInputResponse: (id: string, value: string | null) => void;
+  /**
+   * Children of a value a JS/TS run printed; null once the worker no longer
+   * holds the values of that run
+   */
+  expandValue: (
+    id: string,
+    handle: number
+  ) => Promise<SerializedChildren | null>;
+  /** Drop the state a REPL session kept, so its next run starts over */
+  resetSession: (sessionId: string) => Promise<void>;
 }
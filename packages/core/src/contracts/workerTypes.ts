@@ -5,6 +5,8 @@
  * Ensures type safety between main process, workers, and renderer.
  */
 
+import type { NativeCompilerOptions, NativeProjectMode } from './runner';
+
 // ============================================================================
 // LANGUAGES
 // ============================================================================
@@ -17,6 +19,7 @@ export const SUPPORTED_LANGUAGES: Language[] = [
   'python',
   'c',
   'cpp',
+  'rust',
 ];
 
 export function isValidLanguage(lang: string): lang is Language {
@@ -34,6 +37,9 @@ export interface ExecutionOptions {
   loopProtection?: boolean;
   magicComments?: boolean;
   language?: Language;
+  compilerOptions?: NativeCompilerOptions;
+  projectMode?: NativeProjectMode;
+  emitCompiledOutput?: boolean;
 }
 
 export interface ExecutionRequest {
@@ -50,7 +56,9 @@ export type ResultType =
   | 'debug'
   | 'error'
   | 'complete'
-  | 'status';
+  | 'status'
+  | 'diagnostics'
+  | 'compiled-output';
 
 export type ConsoleType = 'log' | 'warn' | 'error' | 'info' | 'table' | 'dir';
 
@@ -2,9 +2,11 @@ import { createEventBus, type TypedEventBus } from './eventBus';
 
 export interface CheeseJsAppEvents {
   'editor.format.requested': undefined;
+  /** Scroll the editor to a line and put the cursor on it */
+  'editor.line.reveal.requested': { line: number };
   'packages.bootstrap.requested': undefined;
   'settings.magic-comments.toggle.requested': undefined;
-  'workbench.run.requested': { code?: string } | undefined;
+  'workbench.run.requested': { code?: string; profile?: boolean } | undefined;
   'workbench.settings.toggle.requested': undefined;
 }
 
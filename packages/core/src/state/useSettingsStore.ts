@@ -1,3 +1,13 @@
+import {
+  DEFAULT_COMPILER_OPTIONS,
+  DEFAULT_WORKER_MEMORY_LIMIT_MB,
+  type NativeCompilerOptions,
+  type NativeProjectMode,
+  type SandboxCapability,
+  type SandboxDecision,
+  type SandboxDecisions,
+} from '../contracts/runner';
+
 export interface Theme {
   name: string;
   type: 'vs-dark' | 'vs-light' | 'hc-black';
@@ -28,11 +38,20 @@ export interface SettingsState {
   internalLogLevel: 'none' | 'error' | 'warn' | 'info' | 'debug';
   npmRcContent: string;
   magicComments: boolean;
+  /** Run the pytest-style tests a Python program defines after it */
+  pythonTests: boolean;
   autoRunAfterInstall: boolean;
   autoInstallPackages: boolean;
   uiFontSize: number;
   fontLigatures: boolean;
   workingDirectory: string;
+  compilerOptions: NativeCompilerOptions;
+  showCompiledOutput: boolean;
+  projectMode: NativeProjectMode;
+  /** Heap limit for JS/TS workers in MB; 0 keeps V8's default */
+  workerMemoryLimitMb: number;
+  /** Remembered permission prompt answers, keyed by working directory */
+  sandboxDecisions: Record<string, SandboxDecisions>;
   setLanguage: (lang: string) => void;
   setThemeName: (theme: string) => void;
   setFontSize: (size: number) => void;
@@ -48,8 +67,19 @@ export interface SettingsState {
   ) => void;
   setNpmRcContent: (content: string) => void;
   setMagicComments: (enabled: boolean) => void;
+  setPythonTests: (enabled: boolean) => void;
   setFontLigatures: (enabled: boolean) => void;
   setWorkingDirectory: (dir: string) => void;
+  setCompilerOptions: (options: Partial<NativeCompilerOptions>) => void;
+  setShowCompiledOutput: (show: boolean) => void;
+  setProjectMode: (mode: NativeProjectMode) => void;
+  setWorkerMemoryLimitMb: (limitMb: number) => void;
+  rememberSandboxDecision: (
+    workspace: string,
+    capability: SandboxCapability,
+    decision: SandboxDecision
+  ) => void;
+  forgetSandboxDecisions: (workspace: string) => void;
   setAutoRunAfterInstall: (autoRun: boolean) => void;
   setAutoInstallPackages: (autoInstall: boolean) => void;
   setConsoleFilters: (
@@ -82,11 +112,17 @@ export const createSettingsSlice: import('zustand').StateCreator<
   internalLogLevel: 'none',
   npmRcContent: 'registry=https://registry.npmjs.org/\nstrict-ssl=true',
   magicComments: true,
+  pythonTests: false,
   autoRunAfterInstall: true,
   autoInstallPackages: true, // Default to auto-install
   uiFontSize: 14,
   fontLigatures: true,
   workingDirectory: '',
+  compilerOptions: DEFAULT_COMPILER_OPTIONS,
+  showCompiledOutput: false,
+  projectMode: 'single',
+  workerMemoryLimitMb: DEFAULT_WORKER_MEMORY_LIMIT_MB,
+  sandboxDecisions: {},
   setLanguage: (language) => set({ language }),
   setThemeName: (themeName) => set({ themeName }),
   setFontSize: (fontSize) => set({ fontSize }),
@@ -101,8 +137,32 @@ export const createSettingsSlice: import('zustand').StateCreator<
   setInternalLogLevel: (internalLogLevel) => set({ internalLogLevel }),
   setNpmRcContent: (npmRcContent) => set({ npmRcContent }),
   setMagicComments: (magicComments) => set({ magicComments }),
+  setPythonTests: (pythonTests) => set({ pythonTests }),
   setFontLigatures: (fontLigatures: boolean) => set({ fontLigatures }),
   setWorkingDirectory: (workingDirectory: string) => set({ workingDirectory }),
+  setCompilerOptions: (options) =>
+    set((state) => ({
+      compilerOptions: { ...state.compilerOptions, ...options },
+    })),
+  setShowCompiledOutput: (showCompiledOutput) => set({ showCompiledOutput }),
+  setProjectMode: (projectMode) => set({ projectMode }),
+  setWorkerMemoryLimitMb: (workerMemoryLimitMb) =>
+    set({ workerMemoryLimitMb }),
+  rememberSandboxDecision: (workspace, capability, decision) =>
+    set((state) => ({
+      sandboxDecisions: {
+        ...state.sandboxDecisions,
+        [workspace]: {
+          ...state.sandboxDecisions[workspace],
+          [capability]: decision,
+        },
+      },
+    })),
+  forgetSandboxDecisions: (workspace) =>
+    set((state) => {
+      const { [workspace]: _, ...sandboxDecisions } = state.sandboxDecisions;
+      return { sandboxDecisions };
+    }),
   setAutoRunAfterInstall: (autoRunAfterInstall) => set({ autoRunAfterInstall }),
   setAutoInstallPackages: (autoInstallPackages) => set({ autoInstallPackages }),
   setConsoleFilters: (filters) =>
@@ -122,9 +182,15 @@ export const partializeSettings = (state: SettingsState) => ({
   internalLogLevel: state.internalLogLevel,
   npmRcContent: state.npmRcContent,
   magicComments: state.magicComments,
+  pythonTests: state.pythonTests,
   uiFontSize: state.uiFontSize,
   fontLigatures: state.fontLigatures,
   workingDirectory: state.workingDirectory,
+  compilerOptions: state.compilerOptions,
+  showCompiledOutput: state.showCompiledOutput,
+  projectMode: state.projectMode,
+  workerMemoryLimitMb: state.workerMemoryLimitMb,
+  sandboxDecisions: state.sandboxDecisions,
   autoRunAfterInstall: state.autoRunAfterInstall,
   autoInstallPackages: state.autoInstallPackages,
   consoleFilters: state.consoleFilters,
@@ -8,6 +8,9 @@ import { EditorTabBar } from './EditorTabBar';
 import { CODE_RUNNER_DEBOUNCE_MS } from '../constants';
 import { useDebouncedFunction } from '../hooks/useDebounce';
 import { useEditorFormat } from '../hooks/useEditorFormat';
+import { useEditorRevealLine } from '../hooks/useEditorRevealLine';
+import { useCompilerDiagnostics } from '../hooks/useCompilerDiagnostics';
+import { useCodeCells, type RunCodeCell } from '../hooks/useCodeCells';
 import { useEditorCodeSync } from '../hooks/useEditorCodeSync';
 import { useEditorModels } from '../hooks/useEditorModels';
 import {
@@ -51,12 +54,17 @@ export interface CodeEditorLanguageServices {
 /** Runtime callbacks required by the packaged editor shell. */
 export interface CodeEditorRuntimeServices {
   runCode: (code?: string) => void;
+  /** Runs code cells; buffers get no cell controls without it */
+  runCell?: RunCodeCell;
   debounceMs?: number;
 }
 
 /** Optional command subscriptions injected by the host shell. */
 export interface CodeEditorCommandServices {
   subscribeToFormatRequested?: (handler: () => void) => () => void;
+  subscribeToRevealLineRequested?: (
+    handler: (line: number) => void
+  ) => () => void;
 }
 
 /** Settings consumed by the packaged editor shell. */
@@ -132,6 +140,7 @@ export default function CodeEditor({
     [activeTab.id, updateTabCode]
   );
   const code = activeTab.code;
+  const monacoPath = `inmemory://model/${activeTab.id}.ts`;
 
   const prevLanguageRef = useRef(activeTab.language);
   const currentLanguage = activeTab.language;
@@ -147,6 +156,8 @@ export default function CodeEditor({
   );
 
   useEditorFormat(monacoRef, commands?.subscribeToFormatRequested);
+  useEditorRevealLine(monacoRef, commands?.subscribeToRevealLineRequested);
+  const { attachCodeCells } = useCodeCells(monacoPath, runtime.runCell);
   useEditorCodeSync(monacoRef, code, lastLocalCodeRef);
 
   useLspIntegration({
@@ -216,9 +227,10 @@ export default function CodeEditor({
       editorInstance.onDidChangeCursorPosition((event) => {
         lastCursorPositionRef.current = event.position;
       });
+      attachCodeCells(editorInstance);
       lifecycleDidMount(editorInstance, monacoInstance);
     },
-    [lifecycleDidMount]
+    [attachCodeCells, lifecycleDidMount]
   );
 
   const handleChange = useEditorChangeHandler({
@@ -235,7 +247,7 @@ export default function CodeEditor({
     getDetectionVersion: language.getDetectionVersion,
   });
 
-  const monacoPath = `inmemory://model/${activeTab.id}.ts`;
+  useCompilerDiagnostics(monacoPath, activeTab.diagnostics);
 
   useEffect(() => {
     if (monacoRef.current && prevLanguageRef.current !== activeTab.language) {
@@ -0,0 +1,122 @@
+import { useCallback, useEffect, useRef } from 'react';
+import * as monaco from 'monaco-editor';
+import {
+  CODE_CELL_LANGUAGES,
+  findCellAtLine,
+  splitCodeCells,
+  type CellRunMode,
+} from '@cheesejs/languages';
+
+const RUN_CELL_COMMAND = 'cheeseJS.runCell';
+const HAS_CELLS_CONTEXT_KEY = 'cheeseJS.hasCodeCells';
+
+/** Runs the cells a code lens or keybinding selected */
+export type RunCodeCell = (mode: CellRunMode, cellIndex: number) => void;
+
+function getModelCells(model: monaco.editor.ITextModel) {
+  return splitCodeCells(model.getValue(), model.getLanguageId());
+}
+
+/**
+ * Adds "Run cell | Run above | Run all" code lenses over the `%%` cell
+ * markers of the given model. The returned `attachCodeCells` binds
+ * Shift+Enter to running the cell at the cursor; in buffers without cells
+ * the key keeps inserting a line. Nothing is added without `runCell`.
+ */
+export function useCodeCells(modelPath: string, runCell?: RunCodeCell) {
+  const runCellRef = useRef(runCell);
+  runCellRef.current = runCell;
+  const canRunCells = !!runCell;
+
+  useEffect(() => {
+    if (!canRunCells) {
+      return;
+    }
+
+    const modelUri = monaco.Uri.parse(modelPath).toString();
+    const command = monaco.editor.registerCommand(
+      RUN_CELL_COMMAND,
+      (_accessor, mode: CellRunMode, cellIndex: number) =>
+        runCellRef.current?.(mode, cellIndex)
+    );
+
+    const provider: monaco.languages.CodeLensProvider = {
+      provideCodeLenses: (model) => {
+        if (model.uri.toString() !== modelUri) {
+          return { lenses: [], dispose: () => undefined };
+        }
+
+        const lenses = getModelCells(model).flatMap((cell) => {
+          const range = new monaco.Range(cell.startLine, 1, cell.startLine, 1);
+          const lens = (mode: CellRunMode, title: string) => ({
+            range,
+            command: {
+              id: RUN_CELL_COMMAND,
+              title,
+              arguments: [mode, cell.index],
+            },
+          });
+
+          return [
+            lens('cell', '▶ Run cell'),
+            ...(cell.index > 0 ? [lens('above', 'Run above')] : []),
+            lens('all', 'Run all'),
+          ];
+        });
+
+        return { lenses, dispose: () => undefined };
+      },
+    };
+
+    const registrations = CODE_CELL_LANGUAGES.map((language) =>
+      monaco.languages.registerCodeLensProvider(language, provider)
+    );
+
+    return () => {
+      command.dispose();
+      registrations.forEach((registration) => registration.dispose());
+    };
+  }, [modelPath, canRunCells]);
+
+  const attachCodeCells = useCallback(
+    (editorInstance: monaco.editor.IStandaloneCodeEditor) => {
+      const hasCells = editorInstance.createContextKey(
+        HAS_CELLS_CONTEXT_KEY,
+        false
+      );
+      const updateHasCells = () => {
+        const model = editorInstance.getModel();
+        hasCells.set(
+          !!runCellRef.current && !!model && getModelCells(model).length > 0
+        );
+      };
+
+      updateHasCells();
+      editorInstance.onDidChangeModel(updateHasCells);
+      editorInstance.onDidChangeModelContent(updateHasCells);
+      editorInstance.onDidChangeModelLanguage(updateHasCells);
+
+      editorInstance.addAction({
+        id: 'cheeseJS.runCellAtCursor',
+        label: 'Run Cell',
+        keybindings: [monaco.KeyMod.Shift | monaco.KeyCode.Enter],
+        precondition: HAS_CELLS_CONTEXT_KEY,
+        contextMenuGroupId: 'navigation',
+        run: (target) => {
+          const model = target.getModel();
+          const position = target.getPosition();
+          if (!model || !position) return;
+
+          const cell = findCellAtLine(
+            getModelCells(model),
+            position.lineNumber
+          );
+          if (cell) runCellRef.current?.('cell', cell.index);
+        },
+      });
+    },
+    []
+  );
+
+  return { attachCodeCells };
+}
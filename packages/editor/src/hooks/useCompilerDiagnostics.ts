@@ -0,0 +1,120 @@
+import { useEffect, useMemo, useRef } from 'react';
+import * as monaco from 'monaco-editor';
+import type { CompilerDiagnostic } from '@cheesejs/core';
+
+const MARKER_OWNER = 'cheesejs-compiler';
+const NATIVE_LANGUAGES = ['c', 'cpp', 'rust'];
+
+function toMarkerSeverity(
+  severity: CompilerDiagnostic['severity']
+): monaco.MarkerSeverity {
+  switch (severity) {
+    case 'error':
+      return monaco.MarkerSeverity.Error;
+    case 'warning':
+      return monaco.MarkerSeverity.Warning;
+    default:
+      return monaco.MarkerSeverity.Info;
+  }
+}
+
+function toMarker(diagnostic: CompilerDiagnostic): monaco.editor.IMarkerData {
+  return {
+    severity: toMarkerSeverity(diagnostic.severity),
+    message: diagnostic.message,
+    code: diagnostic.code,
+    source: MARKER_OWNER,
+    startLineNumber: diagnostic.line,
+    startColumn: diagnostic.column,
+    endLineNumber: diagnostic.endLine ?? diagnostic.line,
+    // Without a range, underline up to the end of the word at the column
+    endColumn: diagnostic.endColumn ?? diagnostic.column + 1,
+  };
+}
+
+function intersects(
+  diagnostic: CompilerDiagnostic,
+  range: monaco.Range
+): boolean {
+  const endLine = diagnostic.endLine ?? diagnostic.line;
+  return (
+    diagnostic.line <= range.endLineNumber && endLine >= range.startLineNumber
+  );
+}
+
+/**
+ * Shows compiler diagnostics for the given model as Monaco markers and offers
+ * compiler fix-its as quick fixes.
+ */
+export function useCompilerDiagnostics(
+  modelPath: string,
+  diagnostics: CompilerDiagnostic[] | undefined
+) {
+  // Diagnostics from other project files have no place in this model
+  const editorDiagnostics = useMemo(
+    () => (diagnostics ?? []).filter((diagnostic) => !diagnostic.file),
+    [diagnostics]
+  );
+  const diagnosticsRef = useRef<CompilerDiagnostic[]>([]);
+  diagnosticsRef.current = editorDiagnostics;
+
+  useEffect(() => {
+    const model = monaco.editor.getModel(monaco.Uri.parse(modelPath));
+    if (!model) {
+      return;
+    }
+
+    monaco.editor.setModelMarkers(
+      model,
+      MARKER_OWNER,
+      editorDiagnostics.map(toMarker)
+    );
+  }, [modelPath, editorDiagnostics]);
+
+  useEffect(() => {
+    const provider: monaco.languages.CodeActionProvider = {
+      provideCodeActions: (model, range) => {
+        if (model.uri.toString() !== monaco.Uri.parse(modelPath).toString()) {
+          return { actions: [], dispose: () => undefined };
+        }
+
+        const actions = diagnosticsRef.current
+          .filter(
+            (diagnostic) =>
+              diagnostic.fixIts?.length && intersects(diagnostic, range)
+          )
+          .map((diagnostic) => ({
+            title: `Fix: ${diagnostic.message}`,
+            kind: 'quickfix',
+            diagnostics: [toMarker(diagnostic)],
+            isPreferred: true,
+            edit: {
+              edits: (diagnostic.fixIts ?? []).map((fixIt) => ({
+                resource: model.uri,
+                versionId: model.getVersionId(),
+                textEdit: {
+                  range: new monaco.Range(
+                    fixIt.line,
+                    fixIt.column,
+                    fixIt.endLine,
+                    fixIt.endColumn
+                  ),
+                  text: fixIt.replacement,
+                },
+              })),
+            },
+          }));
+
+        return { actions, dispose: () => undefined };
+      },
+    };
+
+    const registrations = NATIVE_LANGUAGES.map((language) =>
+      monaco.languages.registerCodeActionProvider(language, provider)
+    );
+
+    return () => {
+      registrations.forEach((registration) => registration.dispose());
+    };
+  }, [modelPath]);
+}
@@ -4,6 +4,7 @@ import type { editor } from 'monaco-editor';
 
 const PROTECTED_MODEL_URIS = [
   'result-output.js',
+  'compiled-output.wat',
   'code.txt',
   'ts:node-globals.d.ts',
   'inmemory://model/',
@@ -0,0 +1,29 @@
+import { useEffect, type MutableRefObject } from 'react';
+import type { editor } from 'monaco-editor';
+
+/**
+ * Subscribes the editor to a host-provided channel asking it to show a line,
+ * e.g. one a profile or result points at.
+ */
+export function useEditorRevealLine(
+  monacoRef: MutableRefObject<editor.IStandaloneCodeEditor | null>,
+  subscribeToRevealLineRequested?: (
+    handler: (line: number) => void
+  ) => () => void
+) {
+  useEffect(() => {
+    if (!subscribeToRevealLineRequested) {
+      return;
+    }
+
+    const handleRevealLine = (line: number) => {
+      const editorInstance = monacoRef.current;
+      if (!editorInstance) return;
+      editorInstance.revealLineInCenter(line);
+      editorInstance.setPosition({ lineNumber: line, column: 1 });
+      editorInstance.focus();
+    };
+
+    return subscribeToRevealLineRequested(handleRevealLine);
+  }, [monacoRef, subscribeToRevealLineRequested]);
+}
@@ -2,10 +2,13 @@ export { default as CodeEditor } from './components/CodeEditor';
 export * from './components/EditorTabBar';
 export { default as LoadingIndicator } from './components/LoadingIndicator';
 export * from './constants';
+export * from './hooks/useCodeCells';
+export * from './hooks/useCompilerDiagnostics';
 export * from './hooks/useEditorChangeHandler';
 export * from './hooks/useEditorCodeSync';
 export * from './hooks/useDebounce';
 export * from './hooks/useEditorFormat';
+export * from './hooks/useEditorRevealLine';
 export * from './hooks/useEditorLifecycle';
 export * from './hooks/useEditorModels';
 export * from './hooks/useLspIntegration';
@@ -108,6 +108,15 @@ export function createMonacoLspClient({
 
     connection.listen();
 
+    // Servers rooted at a generated project expose a single on-disk document;
+    // the active Monaco model is mirrored into it instead of its own URI.
+    const rootUri = res.rootUri ?? null;
+    const documentSync = createDocumentSync(
+      connection,
+      langId,
+      res.documentUri
+    );
+
     let initializationOptions: unknown = undefined;
     try {
       const fullConfig = (await getLspConfig()?.getConfig()) as
@@ -121,7 +130,8 @@ export function createMonacoLspClient({
 
     await connection.sendRequest('initialize', {
       processId: null,
-      rootUri: null,
+      rootUri,
+      workspaceFolders: rootUri ? [{ uri: rootUri, name: langId }] : null,
       capabilities: {
         textDocument: {
           completion: {
@@ -158,10 +168,7 @@ export function createMonacoLspClient({
     connection.onNotification(
       'textDocument/publishDiagnostics',
       (params: LspPublishDiagnosticsParams) => {
-        const uri = monaco.Uri.parse(params.uri);
-        const model = monaco.editor
-          .getModels()
-          .find((m) => m.uri.toString() === uri.toString());
+        const model = documentSync.resolveModel(params.uri);
         if (model) {
           const markers: monaco.editor.IMarkerData[] = (
             params.diagnostics || []
@@ -188,7 +195,7 @@ export function createMonacoLspClient({
           triggerCharacters: ['.', '/', '<', '"', "'", '`', '@'],
           provideCompletionItems: async (model, position) => {
             try {
-              sendDidOpen(connection, model, lspLangId);
+              documentSync.open(model);
               const wordUntil = model.getWordUntilPosition(position);
               const range = new monaco.Range(
                 position.lineNumber,
@@ -199,7 +206,7 @@ export function createMonacoLspClient({
               const result = (await connection.sendRequest(
                 'textDocument/completion',
                 {
-                  textDocument: { uri: model.uri.toString() },
+                  textDocument: { uri: documentSync.uriFor(model) },
                   position: {
                     line: position.lineNumber - 1,
                     character: position.column - 1,
@@ -237,9 +244,9 @@ export function createMonacoLspClient({
       const hoverProvider = monaco.languages.registerHoverProvider(lspLangId, {
         provideHover: async (model, position) => {
           try {
-            sendDidOpen(connection, model, lspLangId);
+            documentSync.open(model);
             const result = (await connection.sendRequest('textDocument/hover', {
-              textDocument: { uri: model.uri.toString() },
+              textDocument: { uri: documentSync.uriFor(model) },
               position: {
                 line: position.lineNumber - 1,
                 character: position.column - 1,
@@ -271,11 +278,11 @@ export function createMonacoLspClient({
           signatureHelpTriggerCharacters: ['(', ','],
           provideSignatureHelp: async (model, position) => {
             try {
-              sendDidOpen(connection, model, lspLangId);
+              documentSync.open(model);
               const result = (await connection.sendRequest(
                 'textDocument/signatureHelp',
                 {
-                  textDocument: { uri: model.uri.toString() },
+                  textDocument: { uri: documentSync.uriFor(model) },
                   position: {
                     line: position.lineNumber - 1,
                     character: position.column - 1,
@@ -317,20 +324,9 @@ export function createMonacoLspClient({
       disposables.push(signatureProvider);
     }
 
-    const openDocs = new Set<string>();
-
     monaco.editor.getModels().forEach((model) => {
       const modelDisposable = model.onDidChangeContent(() => {
-        const uri = model.uri.toString();
-        if (!openDocs.has(uri)) {
-          sendDidOpenNotification(connection, model, lspLangId);
-          openDocs.add(uri);
-        } else {
-          connection.sendNotification('textDocument/didChange', {
-            textDocument: { uri, version: model.getVersionId() },
-            contentChanges: [{ text: model.getValue() }],
-          });
-        }
+        documentSync.change(model);
       });
       disposables.push(modelDisposable);
     });
@@ -380,6 +376,113 @@ export function createMonacoLspClient({
 
 const sentDocs = new Set<string>();
 
+interface DocumentSync {
+  uriFor: (model: monaco.editor.ITextModel) => string;
+  open: (model: monaco.editor.ITextModel) => void;
+  change: (model: monaco.editor.ITextModel) => void;
+  resolveModel: (uri: string) => monaco.editor.ITextModel | undefined;
+}
+
+/**
+ * Keeps the server's view of open documents in sync with Monaco models.
+ *
+ * Without a `mirroredUri` every model is its own document. With one, the
+ * most recently used model of the language is mirrored into that single
+ * document, which is re-synced whenever a different model takes over.
+ */
+function createDocumentSync(
+  connection: jsonrpc.MessageConnection,
+  languageId: string,
+  mirroredUri?: string
+): DocumentSync {
+  if (!mirroredUri) {
+    const openDocs = new Set<string>();
+
+    return {
+      uriFor: (model) => model.uri.toString(),
+      open: (model) => sendDidOpen(connection, model, languageId),
+      change: (model) => {
+        const uri = model.uri.toString();
+        if (!openDocs.has(uri)) {
+          sendDidOpenNotification(connection, model, languageId);
+          openDocs.add(uri);
+        } else {
+          connection.sendNotification('textDocument/didChange', {
+            textDocument: { uri, version: model.getVersionId() },
+            contentChanges: [{ text: model.getValue() }],
+          });
+        }
+      },
+      resolveModel: (uri) => {
+        const target = monaco.Uri.parse(uri).toString();
+        return monaco.editor
+          .getModels()
+          .find((m) => m.uri.toString() === target);
+      },
+    };
+  }
+
+  let boundModelUri: string | null = null;
+  // Versions must increase monotonically even when the bound model changes.
+  let version = 0;
+
+  const sendFullText = (model: monaco.editor.ITextModel) => {
+    version += 1;
+    connection.sendNotification('textDocument/didChange', {
+      textDocument: { uri: mirroredUri, version },
+      contentChanges: [{ text: model.getValue() }],
+    });
+  };
+
+  const open = (model: monaco.editor.ITextModel) => {
+    const modelUri = model.uri.toString();
+    if (boundModelUri === modelUri) {
+      return;
+    }
+
+    if (boundModelUri === null) {
+      version += 1;
+      connection.sendNotification('textDocument/didOpen', {
+        textDocument: {
+          uri: mirroredUri,
+          languageId,
+          version,
+          text: model.getValue(),
+        },
+      });
+    } else {
+      sendFullText(model);
+    }
+    boundModelUri = modelUri;
+  };
+
+  return {
+    uriFor: () => mirroredUri,
+    open,
+    change: (model) => {
+      if (model.getLanguageId() !== languageId) {
+        return;
+      }
+
+      if (boundModelUri !== model.uri.toString()) {
+        open(model);
+        return;
+      }
+
+      sendFullText(model);
+    },
+    resolveModel: (uri) => {
+      if (uri !== mirroredUri || !boundModelUri) {
+        return undefined;
+      }
+
+      return monaco.editor
+        .getModels()
+        .find((m) => m.uri.toString() === boundModelUri);
+    },
+  };
+}
+
 function sendDidOpen(
   connection: jsonrpc.MessageConnection,
   model: monaco.editor.ITextModel,
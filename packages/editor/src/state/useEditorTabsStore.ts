@@ -1,3 +1,11 @@
+import type {
+  CompiledOutput,
+  CompilerDiagnostic,
+  CpuProfile,
+  SandboxCapability,
+  SandboxProfileId,
+  SerializedValue,
+} from '@cheesejs/core/contracts/runner';
 import {
   MAX_RESULTS,
   type ConsoleType,
@@ -11,12 +19,22 @@ export interface CodeResultElement {
   content: string | number | boolean | object | null;
   jsType?: string;
   consoleType?: ConsoleType;
+  /** `console.group()` nesting level */
+  groupDepth?: number;
+  /** Set on `console.group()` label lines; collapsed groups start folded */
+  groupStart?: 'expanded' | 'collapsed';
+  /** Printed JS/TS arguments as value trees, when one can be expanded */
+  values?: SerializedValue[];
 }
 
 export interface CodeResult {
   element: CodeResultElement;
   type: 'execution' | 'error';
   lineNumber?: number;
+  /** Run that printed `element.values`, which the worker can expand */
+  executionId?: string;
+  /** Index of the code cell the result belongs to, in buffers with cells */
+  cell?: number;
   action?: {
     type: 'install-package';
     payload: string;
@@ -29,11 +47,23 @@ export interface EditorTab {
   code: string;
   language: string;
   result: CodeResult[];
+  /** Diagnostics from the last native compilation, shown as markers */
+  diagnostics: CompilerDiagnostic[];
+  /** WAT/assembly listings from the last native compilation */
+  compiledOutput: CompiledOutput | null;
+  /** CPU profile of the last run, when it was profiled */
+  profile: CpuProfile | null;
   isExecuting: boolean;
   isPendingRun: boolean;
   promptRequest: string | null;
-  promptType: 'text' | 'alert';
+  promptType: 'text' | 'alert' | 'permission';
   promptExecutionId: string | null;
+  /** Capability a `permission` prompt asks for */
+  promptCapability?: SandboxCapability | null;
+  /** What JS/TS runs of this tab may access; unset means the default */
+  sandboxProfile?: SandboxProfileId;
+  /** JS/TS and Python runs keep their state between runs of this tab */
+  replSession?: boolean;
 }
 
 export interface EditorTabsState {
@@ -49,20 +79,27 @@ export interface EditorTabsState {
   updateTabCode: (id: string, code: string) => void;
   updateTabLanguage: (id: string, language: string) => void;
   updateTabTitle: (id: string, title: string) => void;
+  setTabSandboxProfile: (id: string, profile: SandboxProfileId) => void;
+  setTabReplSession: (id: string, enabled: boolean) => void;
 
   // Execution Context
   setTabExecuting: (id: string, isExecuting: boolean) => void;
   setTabPendingRun: (id: string, isPendingRun: boolean) => void;
   setTabResults: (id: string, results: CodeResult[]) => void;
   appendTabResult: (id: string, resultItem: CodeResult) => void;
-  clearTabResults: (id: string) => void;
+  /** With `cells`, results of other cells are kept */
+  clearTabResults: (id: string, cells?: number[]) => void;
+  setTabDiagnostics: (id: string, diagnostics: CompilerDiagnostic[]) => void;
+  setTabCompiledOutput: (id: string, output: CompiledOutput | null) => void;
+  setTabProfile: (id: string, profile: CpuProfile | null) => void;
 
   // Prompt Context
   setTabPromptRequest: (
     id: string,
     message: string | null,
-    type?: 'text' | 'alert',
-    executionId?: string | null
+    type?: 'text' | 'alert' | 'permission',
+    executionId?: string | null,
+    capability?: SandboxCapability | null
   ) => void;
   pruneTabResults: (id: string) => void;
 }
@@ -77,6 +114,9 @@ const createDefaultTab = (index: number): EditorTab => ({
   code: '',
   language: 'javascript', // Default, logic detects later
   result: [],
+  diagnostics: [],
+  compiledOutput: null,
+  profile: null,
   isExecuting: false,
   isPendingRun: false,
   promptRequest: null,
@@ -115,6 +155,9 @@ export const createEditorTabsSlice = (
         code,
         language,
         result: [],
+        diagnostics: [],
+        compiledOutput: null,
+        profile: null,
         isExecuting: false,
         isPendingRun: false,
         promptRequest: null,
@@ -177,6 +220,20 @@ export const createEditorTabsSlice = (
         ),
       })),
 
+    setTabSandboxProfile: (id, sandboxProfile) =>
+      set((state) => ({
+        tabs: state.tabs.map((tab) =>
+          tab.id === id ? { ...tab, sandboxProfile } : tab
+        ),
+      })),
+
+    setTabReplSession: (id, replSession) =>
+      set((state) => ({
+        tabs: state.tabs.map((tab) =>
+          tab.id === id ? { ...tab, replSession } : tab
+        ),
+      })),
+
     setTabExecuting: (id, isExecuting) =>
       set((state) => ({
         tabs: state.tabs.map((tab) =>
@@ -216,13 +273,21 @@ export const createEditorTabsSlice = (
         }),
       })),
 
-    clearTabResults: (id) =>
+    clearTabResults: (id, cells) =>
       set((state) => ({
         tabs: state.tabs.map((tab) =>
           tab.id === id
             ? {
                 ...tab,
-                result: [],
+                result: cells
+                  ? tab.result.filter(
+                      (item) =>
+                        item.cell !== undefined && !cells.includes(item.cell)
+                    )
+                  : [],
+                diagnostics: [],
+                compiledOutput: null,
+                profile: null,
                 promptRequest: null,
                 promptExecutionId: null,
               }
@@ -230,7 +295,34 @@ export const createEditorTabsSlice = (
         ),
       })),
 
-    setTabPromptRequest: (id, message, type = 'text', executionId = null) =>
+    setTabDiagnostics: (id, diagnostics) =>
+      set((state) => ({
+        tabs: state.tabs.map((tab) =>
+          tab.id === id ? { ...tab, diagnostics } : tab
+        ),
+      })),
+
+    setTabCompiledOutput: (id, compiledOutput) =>
+      set((state) => ({
+        tabs: state.tabs.map((tab) =>
+          tab.id === id ? { ...tab, compiledOutput } : tab
+        ),
+      })),
+
+    setTabProfile: (id, profile) =>
+      set((state) => ({
+        tabs: state.tabs.map((tab) =>
+          tab.id === id ? { ...tab, profile } : tab
+        ),
+      })),
+
+    setTabPromptRequest: (
+      id,
+      message,
+      type = 'text',
+      executionId = null,
+      capability = null
+    ) =>
       set((state) => ({
         tabs: state.tabs.map((tab) =>
           tab.id === id
@@ -241,6 +333,7 @@ export const createEditorTabsSlice = (
                 promptExecutionId: message
                   ? (executionId ?? tab.promptExecutionId ?? id)
                   : null,
+                promptCapability: message ? capability : null,
               }
             : tab
         ),
@@ -264,6 +357,8 @@ export const partializeEditorTabs = (state: EditorTabsState) => ({
     title: t.title,
     code: t.code,
     language: t.language,
+    sandboxProfile: t.sandboxProfile,
+    replSession: t.replSession,
   })),
   activeTabId: state.activeTabId,
 });
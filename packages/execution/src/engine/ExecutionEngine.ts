@@ -1,13 +1,24 @@
 import type {
+  BenchmarkResult,
   CodeRunner,
+  CompiledOutput,
+  CompilerDiagnostic,
+  ConsoleOutputData,
+  CpuProfile,
+  ExecutionCompletion,
   ExecutionOptions,
   ExecutionResult,
+  PendingAsyncWork,
+  SerializedValue,
+  TestCaseResult,
+  TestRunSummary,
 } from '@cheesejs/core/contracts/runner';
 import type { Language } from '@cheesejs/core/contracts/workerTypes';
 import { getMetrics } from '@cheesejs/execution/metrics';
 import {
   createExecutionError,
   shouldDisplayError,
+  type SourceLocation,
 } from '@cheesejs/execution/errors';
 
 export interface ExecutionCallbacks {
@@ -17,6 +28,10 @@ export interface ExecutionCallbacks {
     consoleType?: string;
     jsType?: string;
     lineNumber?: number;
+    /** Printed arguments as value trees, for expandable JS/TS output */
+    values?: SerializedValue[];
+    groupDepth?: number;
+    groupStart?: ConsoleOutputData['groupStart'];
   }) => void;
   onComplete: (historyData: {
     code: string;
@@ -25,6 +40,14 @@ export interface ExecutionCallbacks {
     executionTime: number;
   }) => void;
   onError: (errorMsg: string) => void;
+  /** `console.clear()` was called; drop the output shown so far */
+  onClearOutput?: () => void;
+  /** Structured compiler output for C/C++/Rust, sent before the run starts */
+  onDiagnostics?: (diagnostics: CompilerDiagnostic[]) => void;
+  /** WAT/assembly listings, when `emitCompiledOutput` was requested */
+  onCompiledOutput?: (output: CompiledOutput) => void;
+  /** Call tree of the program, when `profile` was requested */
+  onProfile?: (profile: CpuProfile) => void;
 }
 
 export interface ExecutionEngineDependencies {
@@ -32,6 +55,97 @@ export interface ExecutionEngineDependencies {
   defaultTimeout?: number;
 }
 
+function pluralize(count: number, label: string): string {
+  return `${count} ${label}${count === 1 ? '' : 's'}`;
+}
+
+function describePendingWork(pending: PendingAsyncWork): string {
+  const parts = [
+    [pending.timeouts, 'timer'],
+    [pending.intervals, 'interval'],
+    [pending.promises, 'unsettled promise'],
+    [pending.requests, 'request'],
+  ] as const;
+  return parts
+    .filter(([count]) => count > 0)
+    .map(([count, label]) => pluralize(count, label))
+    .join(', ');
+}
+
+const TEST_STATUS_ICONS: Record<TestCaseResult['status'], string> = {
+  passed: '✅',
+  failed: '❌',
+  skipped: '⏭️',
+};
+
+/** One line per test; a failed one adds its message and diff below */
+function describeTestResult(test: TestCaseResult): string {
+  const icon = TEST_STATUS_ICONS[test.status];
+  if (test.status === 'skipped') return `${icon} ${test.name} (skipped)`;
+
+  const header = `${icon} ${test.name} (${test.duration} ms)`;
+  const details = [test.message, test.diff]
+    .filter((detail): detail is string => !!detail)
+    .flatMap((detail) => detail.split('\n'))
+    .map((line) => `   ${line}`);
+  return [header, ...details].join('\n');
+}
+
+function describeTestSummary(summary: TestRunSummary): string {
+  const parts = [
+    `${summary.passed} passed`,
+    ...(summary.failed > 0 ? [`${summary.failed} failed`] : []),
+    ...(summary.skipped > 0 ? [`${summary.skipped} skipped`] : []),
+    ...(summary.timedOut ? ['timed out'] : []),
+  ];
+  return `🧪 Tests: ${parts.join(', ')} (${summary.duration} ms)`;
+}
+
+/** `12.34 µs`, `5.67 ms` and the like, from milliseconds */
+function formatDuration(ms: number): string {
+  if (ms >= 1000) return `${(ms / 1000).toFixed(2)} s`;
+  if (ms >= 1) return `${ms.toFixed(2)} ms`;
+  if (ms >= 0.001) return `${(ms * 1000).toFixed(2)} µs`;
+  return `${(ms * 1_000_000).toFixed(2)} ns`;
+}
+
+/** Change from the last run, or `≈` when within the margins of error */
+function describeBenchmarkChange(
+  current: BenchmarkResult,
+  previous: BenchmarkResult
+): string {
+  const change = (current.opsPerSecond / previous.opsPerSecond - 1) * 100;
+  const noise = Math.max(current.marginOfError, previous.marginOfError);
+  if (Math.abs(change) <= noise) return '≈ last run';
+  const direction = change > 0 ? 'faster' : 'slower';
+  return `${Math.abs(change).toFixed(1)}% ${direction} than last run`;
+}
+
+function describeBenchmark(
+  result: BenchmarkResult,
+  previous?: BenchmarkResult
+): string {
+  const ops = Math.round(result.opsPerSecond).toLocaleString('en-US');
+  const stats = [
+    `mean ${formatDuration(result.mean)}`,
+    `p50 ${formatDuration(result.p50)}`,
+    `p99 ${formatDuration(result.p99)}`,
+    pluralize(result.samples, 'sample'),
+  ].join(', ');
+  const summary = `⏱️ ${result.name}: ${ops} ops/sec ±${result.marginOfError.toFixed(2)}% (${stats})`;
+  return previous
+    ? `${summary} · ${describeBenchmarkChange(result, previous)}`
+    : summary;
+}
+
+/** ` (at utils.ts:3:9)` for errors the worker traced to an imported file */
+function describeErrorFile(error: unknown): string {
+  const location = (error as { location?: SourceLocation } | null)?.location;
+  if (!location?.file) return '';
+  const column = location.column ? `:${location.column}` : '';
+  return ` (at ${location.file}:${location.line}${column})`;
+}
+
 class WorkerUnavailableError extends Error {
   constructor() {
     super(
@@ -74,20 +188,45 @@ export class ExecutionSession {
         if (result.id !== this.executionId) return;
 
         if (result.type === 'debug') {
+          const data = result.data as
+            | Pick<ConsoleOutputData, 'content' | 'values'>
+            | undefined;
           this.callbacks.onOutput({
-            content:
-              (result.data as { content: string })?.content ??
-              String(result.data),
+            content: data?.content ?? String(result.data),
             type: 'execution',
             jsType: result.jsType,
             lineNumber: result.line,
+            values: data?.values,
+          });
+        } else if (result.type === 'test') {
+          const test = result.data as TestCaseResult;
+          this.callbacks.onOutput({
+            content: describeTestResult(test),
+            type: 'execution',
+            consoleType:
+              test.status === 'failed'
+                ? 'error'
+                : test.status === 'skipped'
+                  ? 'warn'
+                  : 'info',
+            lineNumber: test.line,
+          });
+        } else if (result.type === 'benchmark') {
+          const benchmark = result.data as BenchmarkResult;
+          const previous = metrics.recordBenchmark(benchmark);
+          this.callbacks.onOutput({
+            content: describeBenchmark(benchmark, previous),
+            type: 'execution',
+            consoleType: 'info',
+            lineNumber: benchmark.line,
           });
         } else if (result.type === 'console') {
-          const content =
-            (result.data as { content: string })?.content ??
-            String(result.data);
+          const data = result.data as ConsoleOutputData | undefined;
+          const content = data?.content ?? String(result.data);
 
-          if (!this.isCancellationMessage(content)) {
+          if (data?.clear) {
+            this.callbacks.onClearOutput?.();
+          } else if (!this.isCancellationMessage(content)) {
             const prefix =
               result.consoleType === 'error'
                 ? '❌ '
@@ -98,14 +237,67 @@ export class ExecutionSession {
               content: prefix + content,
               type: 'execution',
               consoleType: result.consoleType,
+              values: data?.values,
+              groupDepth: data?.groupDepth,
+              groupStart: data?.groupStart,
             });
           }
+        } else if (result.type === 'diagnostics') {
+          this.callbacks.onDiagnostics?.(
+            (result.data as CompilerDiagnostic[] | undefined) ?? []
+          );
+        } else if (result.type === 'compiled-output') {
+          this.callbacks.onCompiledOutput?.(result.data as CompiledOutput);
+        } else if (result.type === 'profile') {
+          this.callbacks.onProfile?.(result.data as CpuProfile);
         } else if (result.type === 'error') {
-          const { message, shouldDisplay } = this.formatError(result.data);
+          const { message, shouldDisplay, lineNumber } = this.formatError(
+            result.data
+          );
           if (shouldDisplay) {
-            this.callbacks.onOutput({ content: message, type: 'error' });
+            this.callbacks.onOutput({
+              content: message,
+              type: 'error',
+              lineNumber,
+            });
           }
         } else if (result.type === 'complete') {
+          const completion = result.data as ExecutionCompletion | null;
+          const pending = completion?.pending;
+          if (pending) {
+            this.callbacks.onOutput({
+              content: `⚠️ Finished with async work still pending: ${describePendingWork(pending)}`,
+              type: 'execution',
+              consoleType: 'warn',
+            });
+          }
+          // Output of statements the session skipped is not printed again
+          const session = completion?.session;
+          if (session && session.reused > 0) {
+            this.callbacks.onOutput({
+              content: `ℹ️ Session: reused ${pluralize(session.reused, 'unchanged statement')}, ran ${session.ran}`,
+              type: 'execution',
+              consoleType: 'info',
+            });
+          }
+          const tests = completion?.tests;
+          if (tests) {
+            this.callbacks.onOutput({
+              content: describeTestSummary(tests),
+              type: 'execution',
+              consoleType:
+                tests.failed > 0 || tests.timedOut ? 'error' : 'info',
+            });
+          }
+          if (completion?.benchmarksTimedOut) {
+            this.callbacks.onOutput({
+              content:
+                '⏱️ Benchmarks ran out of time; the ones left were not run',
+              type: 'execution',
+              consoleType: 'warn',
+            });
+          }
+
           metrics.recordExecution({
             language: this.language,
             duration: Date.now() - startTime,
@@ -182,9 +374,12 @@ export class ExecutionSession {
     if (!shouldDisplayError(execError)) {
       return { message: '', shouldDisplay: false };
     }
+    // Errors the worker traced to the editor program sit next to their line
+    const location = (error as { location?: SourceLocation } | null)?.location;
     return {
-      message: execError.getFormattedMessage(),
+      message: execError.getFormattedMessage() + describeErrorFile(error),
       shouldDisplay: true,
+      lineNumber: location && !location.file ? location.line : undefined,
     };
   }
 }
@@ -20,12 +20,16 @@ export enum ErrorCategory {
   TIMEOUT = 'timeout',
   /** Memory limit exceeded */
   MEMORY = 'memory',
+  /** Worker heap limit reached; the worker was restarted */
+  OUT_OF_MEMORY = 'out_of_memory',
   /** Execution cancelled by user */
   CANCELLED = 'cancelled',
   /** Module/package not found */
   MODULE_NOT_FOUND = 'module_not_found',
   /** Transpilation errors */
   TRANSPILATION = 'transpilation',
+  /** Native (C/C++/Rust) compiler errors */
+  COMPILATION = 'compilation',
   /** Worker communication errors */
   WORKER = 'worker',
   /** Unknown errors */
@@ -146,6 +150,12 @@ export class ExecutionError extends Error {
       case ErrorCategory.MEMORY:
         return `Memory limit exceeded. Your code may be creating too many objects or infinite structures.`;
 
+      case ErrorCategory.OUT_OF_MEMORY: {
+        const limitMatch = message.match(/(\d+) MB/);
+        const limit = limitMatch ? ` of ${limitMatch[1]} MB` : '';
+        return `Out of memory: your code used up the worker's heap limit${limit}. The worker was restarted, so the next run starts fresh.`;
+      }
+
       case ErrorCategory.CANCELLED:
         return `Execution was cancelled.`;
 
@@ -166,6 +176,10 @@ export class ExecutionError extends Error {
       case ErrorCategory.TRANSPILATION:
         return `Transpilation failed: ${ExecutionError.simplifyMessage(message)}`;
 
+      case ErrorCategory.COMPILATION:
+        // Compiler output is already concise and every line matters
+        return message;
+
       case ErrorCategory.WORKER:
         return `Code runner error. Please restart the application if this persists.`;
 
@@ -212,6 +226,7 @@ export class ExecutionError extends Error {
       case ErrorCategory.TIMEOUT:
         return '⏱️';
       case ErrorCategory.MEMORY:
+      case ErrorCategory.OUT_OF_MEMORY:
         return '💾';
       case ErrorCategory.CANCELLED:
         return '🛑';
@@ -219,6 +234,8 @@ export class ExecutionError extends Error {
         return '📦';
       case ErrorCategory.TRANSPILATION:
         return '🔧';
+      case ErrorCategory.COMPILATION:
+        return '🛠️';
       case ErrorCategory.WORKER:
         return '🔌';
       default:
@@ -284,6 +301,11 @@ export function detectErrorCategory(
   const lowerMessage = errorMessage.toLowerCase();
   const lowerName = errorName.toLowerCase();
 
+  // Compiler errors (their text may mention anything, so check the name first)
+  if (lowerName === 'compileerror') {
+    return ErrorCategory.COMPILATION;
+  }
+
   // Timeout errors
   if (
     lowerMessage.includes('timeout') ||
@@ -304,6 +326,15 @@ export function detectErrorCategory(
     return ErrorCategory.CANCELLED;
   }
 
+  // Worker killed for reaching its heap limit
+  if (
+    lowerName === 'outofmemoryerror' ||
+    lowerMessage.includes('out of memory') ||
+    lowerMessage.includes('err_worker_out_of_memory')
+  ) {
+    return ErrorCategory.OUT_OF_MEMORY;
+  }
+
   // Memory errors
   if (
     lowerMessage.includes('memory') ||
@@ -399,6 +430,14 @@ export function parseErrorLocation(
   return undefined;
 }
 
+function isSourceLocation(value: unknown): value is SourceLocation {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as SourceLocation).line === 'number'
+  );
+}
+
 /**
  * Generate suggestions based on error type
  */
@@ -458,6 +497,19 @@ export function generateSuggestions(
       });
       break;
 
+    case ErrorCategory.OUT_OF_MEMORY:
+      suggestions.push({
+        title: 'Raise the memory limit',
+        description:
+          'Increase "Worker memory limit" in Settings → Advanced if your code legitimately needs more memory.',
+      });
+      suggestions.push({
+        title: 'Process data in smaller chunks',
+        description:
+          'Stream or batch large inputs and release references you no longer need.',
+      });
+      break;
+
     default:
       break;
   }
@@ -502,7 +554,9 @@ export function createExecutionError(
     const stack = typeof errObj.stack === 'string' ? errObj.stack : undefined;
 
     const category = detectErrorCategory(name, message, language);
-    const location = parseErrorLocation(message, stack);
+    const location = isSourceLocation(errObj.location)
+      ? errObj.location
+      : parseErrorLocation(message, stack);
     const suggestions = generateSuggestions(category, message, language);
 
     return new ExecutionError({
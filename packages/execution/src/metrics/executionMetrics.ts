@@ -5,6 +5,7 @@
  * transpilation, and worker performance monitoring.
  */
 
+import type { BenchmarkResult } from '@cheesejs/core/contracts/runner';
 import type { Language } from '@cheesejs/core/contracts/workerTypes';
 
 // ============================================================================
@@ -18,6 +19,7 @@ export enum MetricType {
   PACKAGE = 'package',
   CACHE = 'cache',
   ERROR = 'error',
+  BENCHMARK = 'benchmark',
 }
 
 export enum LogLevel {
@@ -111,6 +113,7 @@ export interface LogEntry {
 
 const MAX_EVENTS = 1000;
 const MAX_LOGS = 500;
+const MAX_BENCHMARKS = 200;
 
 class MetricsCollector {
   private events: MetricEvent[] = [];
@@ -124,6 +127,9 @@ class MetricsCollector {
   private transpileTimes: number[] = [];
   private queueWaitTimes: number[] = [];
 
+  // Latest result of each benchmark by name, oldest first
+  private benchmarks = new Map<string, BenchmarkResult>();
+
   private metrics: AggregatedMetrics = {
     execution: {
       totalExecutions: 0,
@@ -369,6 +375,41 @@ class MetricsCollector {
     });
   }
 
+  /**
+   * Record a benchmark result, returning the previous result of the same
+   * name so consecutive runs can be compared
+   */
+  recordBenchmark(result: BenchmarkResult): BenchmarkResult | undefined {
+    const previous = this.benchmarks.get(result.name);
+    this.benchmarks.delete(result.name);
+    this.benchmarks.set(result.name, result);
+
+    // Forget the benchmark that ran longest ago
+    if (this.benchmarks.size > MAX_BENCHMARKS) {
+      const [oldest] = this.benchmarks.keys();
+      this.benchmarks.delete(oldest);
+    }
+
+    this.recordEvent({
+      type: MetricType.BENCHMARK,
+      name: result.name,
+      duration: result.mean,
+      metadata: {
+        opsPerSecond: result.opsPerSecond,
+        marginOfError: result.marginOfError,
+        samples: result.samples,
+      },
+    });
+    return previous;
+  }
+
+  /**
+   * Get the latest result of a benchmark
+   */
+  getBenchmark(name: string): BenchmarkResult | undefined {
+    return this.benchmarks.get(name);
+  }
+
   // ============================================================================
   // LOGGING
   // ============================================================================
@@ -538,6 +579,7 @@ class MetricsCollector {
     this.executionTimes = [];
     this.transpileTimes = [];
     this.queueWaitTimes = [];
+    this.benchmarks.clear();
     this.startTime = Date.now();
 
     this.metrics = {
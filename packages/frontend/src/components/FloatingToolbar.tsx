@@ -1,5 +1,5 @@
 import { m, AnimatePresence } from 'framer-motion';
-import { Play, Settings, Brush, Loader2 } from 'lucide-react';
+import { Play, Flame, Settings, Brush, Loader2 } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
 import clsx from 'clsx';
 import type { ReactNode } from 'react';
@@ -9,7 +9,11 @@ export interface FloatingToolbarProps {
   busyMessage?: string;
   eventBus: CheeseJsEventBus;
   isBusy: boolean;
+  /** Show the button running the code under the CPU profiler */
+  canProfile?: boolean;
   snippetsMenu?: ReactNode;
+  sandboxMenu?: ReactNode;
+  sessionMenu?: ReactNode;
 }
 
 /**
@@ -19,7 +23,10 @@ export function FloatingToolbar({
   busyMessage = '',
   eventBus,
   isBusy,
+  canProfile = false,
   snippetsMenu,
+  sandboxMenu,
+  sessionMenu,
 }: FloatingToolbarProps) {
   const { t } = useTranslation();
 
@@ -56,7 +63,20 @@ export function FloatingToolbar({
           disabled={isBusy}
           testId="run-button"
         />
+        {canProfile && (
+          <ToolbarButton
+            icon={<Flame className="w-5 h-5" />}
+            onClick={() =>
+              eventBus.emit('workbench.run.requested', { profile: true })
+            }
+            label={t('toolbar.profile')}
+            disabled={isBusy}
+            testId="profile-button"
+          />
+        )}
         {snippetsMenu}
+        {sandboxMenu}
+        {sessionMenu}
         <ToolbarButton
           icon={<Brush className="w-5 h-5" />}
           onClick={() => eventBus.emit('editor.format.requested')}
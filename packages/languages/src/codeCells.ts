@@ -0,0 +1,123 @@
+/**
+ * A buffer split into notebook-style cells by `// %%` (JS/TS/C/C++/Rust) or
+ * `# %%` (Python) lines. Text after the marker is the cell title.
+ */
+export interface CodeCell {
+  /** Position among the cells of the buffer, from 0 */
+  index: number;
+  /** 1-based line of the marker; 1 for code above the first marker */
+  startLine: number;
+  /** Last line of the cell, inclusive */
+  endLine: number;
+  title: string;
+}
+
+/**
+ * `cell` runs one cell, `above` the cells before it and `all` every cell
+ */
+export type CellRunMode = 'cell' | 'above' | 'all';
+
+const CELL_MARKERS: Record<string, '//' | '#'> = {
+  javascript: '//',
+  typescript: '//',
+  c: '//',
+  cpp: '//',
+  rust: '//',
+  python: '#',
+};
+
+/** Languages whose buffers can be split into cells */
+export const CODE_CELL_LANGUAGES: readonly string[] = Object.keys(CELL_MARKERS);
+
+const MARKER_PATTERNS = {
+  '//': /^\s*\/\/\s*%%(.*)$/,
+  '#': /^\s*#\s*%%(.*)$/,
+};
+
+/** Comment prefix of cell markers in `language`, or null without cells */
+export function getCellMarker(language: string): '//' | '#' | null {
+  return CELL_MARKERS[language] ?? null;
+}
+
+/**
+ * Finds the cells of a buffer. A buffer without markers has no cells and
+ * runs as a whole; code above the first marker is a cell of its own when
+ * it is not blank.
+ */
+export function splitCodeCells(code: string, language: string): CodeCell[] {
+  const marker = getCellMarker(language);
+  if (!marker) return [];
+
+  const pattern = MARKER_PATTERNS[marker];
+  const lines = code.split('\n');
+  const cells: CodeCell[] = [];
+  let leadingCode = false;
+
+  lines.forEach((line, index) => {
+    const match = pattern.exec(line);
+    if (!match) {
+      if (cells.length === 0 && line.trim()) leadingCode = true;
+      return;
+    }
+
+    const lineNumber = index + 1;
+    if (cells.length === 0 && leadingCode) {
+      cells.push({ index: 0, startLine: 1, endLine: 0, title: '' });
+    }
+    if (cells.length > 0) {
+      cells[cells.length - 1].endLine = lineNumber - 1;
+    }
+    cells.push({
+      index: cells.length,
+      startLine: lineNumber,
+      endLine: 0,
+      title: match[1].trim(),
+    });
+  });
+
+  if (cells.length > 0) {
+    cells[cells.length - 1].endLine = lines.length;
+  }
+  return cells;
+}
+
+/** The cell `line` belongs to, if it is in one */
+export function findCellAtLine(
+  cells: CodeCell[],
+  line: number
+): CodeCell | undefined {
+  return cells.find((cell) => line >= cell.startLine && line <= cell.endLine);
+}
+
+/**
+ * Cells a run of `cellIndex` executes. Without shared state (`stateful`
+ * off) a cell cannot build on earlier runs, so the cells above it run too.
+ */
+export function selectCellsToRun(
+  cells: CodeCell[],
+  mode: CellRunMode,
+  cellIndex: number,
+  stateful: boolean
+): CodeCell[] {
+  switch (mode) {
+    case 'all':
+      return cells;
+    case 'above':
+      return cells.slice(0, cellIndex);
+    default:
+      return stateful
+        ? cells.slice(cellIndex, cellIndex + 1)
+        : cells.slice(0, cellIndex + 1);
+  }
+}
+
+/**
+ * The buffer with every line outside `selected` blanked, so line numbers
+ * in output and errors still point at the editor.
+ */
+export function getCellSource(code: string, selected: CodeCell[]): string {
+  return code
+    .split('\n')
+    .map((line, index) => (findCellAtLine(selected, index + 1) ? line : ''))
+    .join('\n');
+}
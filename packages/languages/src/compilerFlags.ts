@@ -0,0 +1,140 @@
+import type {
+  CppStandard,
+  CStandard,
+  NativeCompilerOptions,
+  OptimizationLevel,
+} from '@cheesejs/core';
+
+export { DEFAULT_COMPILER_OPTIONS } from '@cheesejs/core/contracts/runner';
+
+export const C_STANDARDS: readonly CStandard[] = ['c99', 'c11', 'c17', 'c23'];
+export const CPP_STANDARDS: readonly CppStandard[] = [
+  'c++14',
+  'c++17',
+  'c++20',
+  'c++23',
+];
+export const OPTIMIZATION_LEVELS: readonly OptimizationLevel[] = [
+  'O0',
+  'O1',
+  'O2',
+  'O3',
+  'Os',
+  'Oz',
+];
+
+const MAGIC_HEADER_PATTERN = /^\s*\/\/\s*cheese:(.*)$/;
+const DEFINE_PATTERN = /^[A-Za-z_]\w*(=\S*)?$/;
+// `-Wl,`/`-Wa,`/`-Wp,` forward arguments to other tools and never match
+const WARNING_PATTERN = /^-W[\w=+-]+$/;
+const LIBRARY_PATTERN = /^[\w+-]+$/;
+
+export interface CompilerMagicHeader {
+  options: NativeCompilerOptions;
+  /** Flags that were not recognised and will not reach the compiler */
+  ignored: string[];
+}
+
+export function isValidDefine(define: string): boolean {
+  return DEFINE_PATTERN.test(define);
+}
+
+export function isValidWarningFlag(flag: string): boolean {
+  return WARNING_PATTERN.test(flag);
+}
+
+export function isValidLibraryName(library: string): boolean {
+  return LIBRARY_PATTERN.test(library);
+}
+
+/**
+ * Splits a free-form flag list (as typed in settings) on whitespace.
+ */
+export function splitFlagList(value: string): string[] {
+  return value.split(/\s+/).filter(Boolean);
+}
+
+/**
+ * Reads `// cheese: -std=c++20 -O2 -DDEBUG -Wall -lm` lines from the top of
+ * a source file. Only the leading comment block is scanned, so flags cannot
+ * be injected from the middle of a program.
+ */
+export function parseCompilerMagicHeader(code: string): CompilerMagicHeader {
+  const options: NativeCompilerOptions = {};
+  const ignored: string[] = [];
+
+  for (const line of code.split('\n')) {
+    const trimmed = line.trim();
+    if (!trimmed) continue;
+    if (!trimmed.startsWith('//')) break;
+
+    const match = MAGIC_HEADER_PATTERN.exec(trimmed);
+    if (!match) continue;
+
+    for (const flag of splitFlagList(match[1])) {
+      if (!applyFlag(options, flag)) {
+        ignored.push(flag);
+      }
+    }
+  }
+
+  return { options, ignored };
+}
+
+function applyFlag(options: NativeCompilerOptions, flag: string): boolean {
+  if (flag.startsWith('-std=')) {
+    const standard = flag.slice('-std='.length).toLowerCase();
+    if ((C_STANDARDS as readonly string[]).includes(standard)) {
+      options.cStandard = standard as CStandard;
+      return true;
+    }
+    if ((CPP_STANDARDS as readonly string[]).includes(standard)) {
+      options.cppStandard = standard as CppStandard;
+      return true;
+    }
+    return false;
+  }
+
+  if (flag.startsWith('-O')) {
+    const level = flag.slice(1);
+    if ((OPTIMIZATION_LEVELS as readonly string[]).includes(level)) {
+      options.optimization = level as OptimizationLevel;
+      return true;
+    }
+    return false;
+  }
+
+  if (flag.startsWith('-D') && isValidDefine(flag.slice(2))) {
+    options.defines = [...(options.defines ?? []), flag.slice(2)];
+    return true;
+  }
+
+  if (isValidWarningFlag(flag)) {
+    options.warnings = [...(options.warnings ?? []), flag];
+    return true;
+  }
+
+  if (flag.startsWith('-l') && isValidLibraryName(flag.slice(2))) {
+    options.libraries = [...(options.libraries ?? []), flag.slice(2)];
+    return true;
+  }
+
+  return false;
+}
+
+/**
+ * Layers per-file options over the defaults from settings. Scalars are
+ * replaced; defines, warnings and libraries accumulate.
+ */
+export function mergeCompilerOptions(
+  base: NativeCompilerOptions,
+  override: NativeCompilerOptions
+): NativeCompilerOptions {
+  return {
+    ...base,
+    ...override,
+    defines: [...(base.defines ?? []), ...(override.defines ?? [])],
+    warnings: [...(base.warnings ?? []), ...(override.warnings ?? [])],
+    libraries: [...(base.libraries ?? []), ...(override.libraries ?? [])],
+  };
+}
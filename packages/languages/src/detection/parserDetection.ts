@@ -64,6 +64,21 @@ const CPP_SEMANTIC_PATTERNS: Array<[RegExp, number]> = [
   [/\bint\s+main\s*\(/, 35],
 ];
 
+const RUST_SEMANTIC_PATTERNS: Array<[RegExp, number]> = [
+  [/\bfn\s+main\s*\(\s*\)/, 80],
+  [/\b(println|print|eprintln|format|vec|panic|assert_eq)!\s*[([]/, 70],
+  [/\bfn\s+[A-Za-z_]\w*\s*(<[^>]*>)?\s*\(/, 40],
+  [/\blet\s+mut\b/, 50],
+  [/^\s*use\s+(std|core|crate)::/m, 60],
+  [/\bimpl\b[^{]*\{/, 50],
+  [/\b(struct|enum|trait)\s+[A-Za-z_]\w*/, 20],
+  [/&mut\s+|&self\b/, 40],
+  [/\)\s*->\s*[A-Za-z_&(]/, 25],
+  [/\bmatch\s+[^{]+\{/, 25],
+  [/\b(Some|None|Ok|Err)\s*\(/, 20],
+  [/\b(i32|i64|u8|u32|u64|usize|f64|String|Vec<)/, 20],
+];
+
 function scoreSemanticPatterns(
   content: string,
   patterns: Array<[RegExp, number]>
@@ -244,6 +259,19 @@ function analyzeCpp(content: string): ParserDetectionCandidate {
   };
 }
 
+function analyzeRust(content: string): ParserDetectionCandidate {
+  const score = scoreSemanticPatterns(content, RUST_SEMANTIC_PATTERNS);
+  const errors = score > 0 ? 0 : 3;
+
+  return {
+    monacoId: 'rust',
+    confidence: Math.min(0.99, score / 100),
+    score,
+    errors,
+    executable: isExecutableLanguage('rust'),
+  };
+}
+
 function getStickyScriptLanguage(
   context?: DetectionContext
 ): 'javascript' | 'typescript' | null {
@@ -275,6 +303,7 @@ export function detectWithParsers(
 
   if (trimmed.length === 0) {
     const sticky =
+      (context?.currentLanguage === 'rust' ? 'rust' : null) ??
       getStickyNativeLanguage(context) ??
       getStickyScriptLanguage(context) ??
       'typescript';
@@ -286,6 +315,7 @@ export function detectWithParsers(
   const typescript = analyzeTypeScript(content);
   const c = analyzeC(content);
   const cpp = analyzeCpp(content);
+  const rust = analyzeRust(content);
 
   if (typescript.errors === 0 && typescript.tsSpecificSyntax) {
     return toDetectionResult(
@@ -307,6 +337,21 @@ export function detectWithParsers(
     );
   }
 
+  if (
+    rust.errors === 0 &&
+    rust.score > c.score + 10 &&
+    rust.score > cpp.score + 10 &&
+    rust.score > python.score + 15 &&
+    rust.score > javascript.score + 15 &&
+    rust.score > typescript.score + 15
+  ) {
+    return toDetectionResult(
+      'rust',
+      Math.max(0.92, rust.confidence),
+      'parser'
+    );
+  }
+
   if (
     cpp.errors === 0 &&
     cpp.score > 0 &&
@@ -391,7 +436,7 @@ export function detectWithParsers(
     );
   }
 
-  const candidates = [python, typescript, javascript, cpp, c].sort(
+  const candidates = [python, typescript, javascript, cpp, c, rust].sort(
     (a, b) => b.score - a.score
   );
   const winner = candidates[0];
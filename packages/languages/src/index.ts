@@ -3,3 +3,5 @@ export * from './registry';
 export * from './detection/cache';
 export * from './detection/mlDetection';
 export * from './detection/parserDetection';
+export * from './compilerFlags';
+export * from './codeCells';
@@ -13,6 +13,7 @@ const NPX =
     : 'npx';
 
 const CLANGD = 'clangd';
+const RUST_ANALYZER = 'rust-analyzer';
 
 export const LANGUAGE_DESCRIPTORS: Record<LanguageId, LanguageDescriptor> = {
   javascript: {
@@ -110,6 +111,22 @@ export const LANGUAGE_DESCRIPTORS: Record<LanguageId, LanguageDescriptor> = {
       enabled: true,
     },
   },
+  rust: {
+    id: 'rust',
+    monacoId: 'rust',
+    displayName: 'Rust',
+    extensions: ['.rs'],
+    executable: true,
+    executionLanguage: 'rust',
+    runtimeProvider: 'wasi-rustc',
+    lsp: {
+      name: 'Rust (rust-analyzer)',
+      command: RUST_ANALYZER,
+      args: [],
+      fileExtensions: ['.rs'],
+      enabled: true,
+    },
+  },
   html: {
     id: 'html',
     monacoId: 'html',
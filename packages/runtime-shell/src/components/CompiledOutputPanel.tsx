@@ -0,0 +1,83 @@
+import { useState } from 'react';
+import Editor from '@monaco-editor/react';
+import type { CompiledOutput, CompiledOutputFormat } from '@cheesejs/core';
+import clsx from 'clsx';
+
+export interface CompiledOutputPanelProps {
+  fontSize: number;
+  output: CompiledOutput | null;
+  themeName: string;
+  waitingMessage?: string;
+}
+
+const FORMAT_LABELS: Record<CompiledOutputFormat, string> = {
+  wat: 'WAT',
+  assembly: 'Assembly',
+};
+
+/**
+ * Read-only view of the WAT/assembly produced for a C/C++/Rust program. When
+ * the listing carries DWARF line info, the gutter shows source line numbers.
+ */
+export function CompiledOutputPanel({
+  fontSize,
+  output,
+  themeName,
+  waitingMessage = ';; Run the program to see its compiled output',
+}: CompiledOutputPanelProps) {
+  const listings = output?.listings ?? [];
+  const [format, setFormat] = useState<CompiledOutputFormat | null>(null);
+  // Keep the chosen view across runs, falling back to the first listing
+  const listing =
+    listings.find((entry) => entry.format === format) ?? listings[0];
+  const sourceLines = listing?.sourceLines;
+
+  return (
+    <div
+      className="h-full flex flex-col text-foreground bg-background overflow-hidden"
+      data-testid="compiled-output-panel"
+    >
+      <div className="flex items-center gap-1.5 px-3 py-2 border-b border-border bg-muted/20 shrink-0">
+        {listings.map((entry) => (
+          <button
+            key={entry.format}
+            onClick={() => setFormat(entry.format)}
+            className={clsx(
+              'px-2 py-1 rounded text-xs font-medium border select-none',
+              entry.format === listing?.format
+                ? 'bg-background border-border shadow-sm text-foreground'
+                : 'bg-muted/50 border-transparent text-muted-foreground'
+            )}
+          >
+            {FORMAT_LABELS[entry.format]}
+          </button>
+        ))}
+        {output && listings.length === 0 && (
+          <span className="text-xs text-muted-foreground">
+            No compiled output available for this program.
+          </span>
+        )}
+      </div>
+
+      <div className="flex-1 relative min-h-0">
+        <Editor
+          theme={themeName}
+          path="compiled-output.wat"
+          options={{
+            automaticLayout: true,
+            minimap: { enabled: false },
+            fontSize,
+            readOnly: true,
+            wordWrap: 'off',
+            renderLineHighlight: 'none',
+            lineNumbers: sourceLines
+              ? (lineNumber) => String(sourceLines[lineNumber - 1] ?? '')
+              : 'on',
+          }}
+          defaultLanguage="plaintext"
+          value={listing?.text ?? waitingMessage}
+        />
+      </div>
+    </div>
+  );
+}
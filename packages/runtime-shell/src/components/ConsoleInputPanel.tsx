@@ -1,11 +1,25 @@
-import { useEffect, useRef, useState, type FormEvent } from 'react';
-import { Send, Terminal, AlertTriangle } from 'lucide-react';
+import {
+  useEffect,
+  useRef,
+  useState,
+  type FormEvent,
+  type RefObject,
+} from 'react';
+import { Send, Terminal, AlertTriangle, ShieldAlert, X } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
 import clsx from 'clsx';
+import type { SandboxCapability, SandboxDecision } from '@cheesejs/core';
 
 export interface ConsoleInputPanelProps {
   promptRequest: string | null;
-  promptType: 'text' | 'alert';
+  promptType: 'text' | 'alert' | 'permission';
+  /** Capability a `permission` prompt asks for */
+  promptCapability?: SandboxCapability | null;
   onSubmit: (value: string) => void;
+  /** Closes a text prompt unanswered, read by programs as end of input */
+  onDismiss?: () => void;
+  /** Answers a `permission` prompt; `remember` keeps it for the workspace */
+  onPermissionDecision?: (decision: SandboxDecision, remember: boolean) => void;
 }
 
 /**
@@ -14,8 +28,12 @@ export interface ConsoleInputPanelProps {
 export function ConsoleInputPanel({
   promptRequest,
   promptType,
+  promptCapability,
   onSubmit,
+  onDismiss,
+  onPermissionDecision,
 }: ConsoleInputPanelProps) {
+  const { t } = useTranslation();
   const [input, setInput] = useState('');
   const inputRef = useRef<HTMLInputElement>(null);
   const buttonRef = useRef<HTMLButtonElement>(null);
@@ -26,7 +44,7 @@ export function ConsoleInputPanel({
     }
 
     const timer = setTimeout(() => {
-      if (promptType === 'alert') {
+      if (promptType !== 'text') {
         buttonRef.current?.focus();
       } else {
         inputRef.current?.focus();
@@ -46,6 +64,28 @@ export function ConsoleInputPanel({
     setInput('');
   };
 
+  const handleDismiss = () => {
+    onDismiss?.();
+    setInput('');
+  };
+
+  if (promptType === 'permission') {
+    return (
+      <PermissionPrompt
+        capability={promptCapability ?? null}
+        detail={promptRequest}
+        buttonRef={buttonRef}
+        onDecision={(decision, remember) => {
+          if (onPermissionDecision) {
+            onPermissionDecision(decision, remember);
+          } else {
+            onSubmit(decision);
+          }
+        }}
+      />
+    );
+  }
+
   return (
     <div
       className={clsx(
@@ -98,6 +138,13 @@ export function ConsoleInputPanel({
                   if (event.key === 'Enter' && !event.shiftKey) {
                     event.preventDefault();
                     handleSubmit();
+                  } else if (
+                    event.key === 'Escape' &&
+                    promptType === 'text' &&
+                    onDismiss
+                  ) {
+                    event.preventDefault();
+                    handleDismiss();
                   }
                 }}
                 className="w-full bg-transparent border-none text-sm font-mono focus:ring-0 pl-6 pr-8 py-1.5 placeholder:text-muted-foreground/40 text-foreground"
@@ -114,6 +161,96 @@ export function ConsoleInputPanel({
                 <Send className="w-3.5 h-3.5" />
               </button>
             </div>
+            {promptType === 'text' && onDismiss && (
+              <button
+                onClick={handleDismiss}
+                data-testid="console-dismiss"
+                title={t('input.endOfInput', 'End of input (Escape)')}
+                className="p-1 text-muted-foreground hover:text-foreground transition-colors rounded-sm hover:bg-muted"
+              >
+                <X className="w-3.5 h-3.5" />
+              </button>
+            )}
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
+
+function PermissionPrompt({
+  capability,
+  detail,
+  buttonRef,
+  onDecision,
+}: {
+  capability: SandboxCapability | null;
+  detail: string;
+  buttonRef: RefObject<HTMLButtonElement | null>;
+  onDecision: (decision: SandboxDecision, remember: boolean) => void;
+}) {
+  const { t } = useTranslation();
+  const choices: Array<{
+    decision: SandboxDecision;
+    remember: boolean;
+    label: string;
+  }> = [
+    { decision: 'allow', remember: false, label: t('sandbox.allow', 'Allow') },
+    {
+      decision: 'allow',
+      remember: true,
+      label: t('sandbox.allowAlways', 'Always allow'),
+    },
+    { decision: 'deny', remember: false, label: t('sandbox.deny', 'Deny') },
+    {
+      decision: 'deny',
+      remember: true,
+      label: t('sandbox.denyAlways', 'Always deny'),
+    },
+  ];
+
+  return (
+    <div
+      className={clsx(
+        'shrink-0 border-t border-border bg-background/95 backdrop-blur supports-backdrop-filter:bg-background/60',
+        'p-3 pr-16 animate-in slide-in-from-bottom-2 duration-200'
+      )}
+    >
+      <div className="max-w-3xl mx-auto w-full rounded-md overflow-hidden border shadow-sm border-sky-500/30 bg-sky-500/5">
+        <div className="flex flex-col sm:flex-row sm:items-center p-2 gap-3">
+          <div className="flex items-center gap-2 text-sm font-medium min-w-0 shrink text-sky-600 dark:text-sky-400">
+            <ShieldAlert className="w-4 h-4 shrink-0" />
+            <span
+              className="wrap-break-word leading-tight"
+              data-testid="prompt-message"
+            >
+              {t('sandbox.request', {
+                capability: capability
+                  ? t(`sandbox.capabilities.${capability}`, capability)
+                  : '',
+                detail,
+                defaultValue: 'Allow {{capability}} access? ({{detail}})',
+              })}
+            </span>
+          </div>
+
+          <div className="flex-1 flex items-center justify-end gap-2">
+            {choices.map(({ decision, remember, label }, index) => (
+              <button
+                key={label}
+                ref={index === 0 ? buttonRef : undefined}
+                onClick={() => onDecision(decision, remember)}
+                data-testid={`permission-${decision}${remember ? '-always' : ''}`}
+                className={clsx(
+                  'px-2.5 py-1 text-xs font-medium rounded-sm transition-colors',
+                  decision === 'allow'
+                    ? 'text-primary hover:bg-primary/10'
+                    : 'text-muted-foreground hover:text-destructive hover:bg-muted'
+                )}
+              >
+                {label}
+              </button>
+            ))}
           </div>
         </div>
       </div>
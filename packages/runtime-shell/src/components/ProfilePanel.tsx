@@ -0,0 +1,336 @@
+import { useMemo, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import type { CpuProfile, ProfileNode } from '@cheesejs/core';
+import clsx from 'clsx';
+
+export interface ProfilePanelProps {
+  profile: CpuProfile;
+  /** Shows the editor line a function of the program starts on */
+  onRevealLine?: (line: number) => void;
+}
+
+type ProfileView = 'flame' | 'bottom-up';
+
+const PROFILE_VIEWS: ProfileView[] = ['flame', 'bottom-up'];
+
+const PROFILER_LABELS: Record<CpuProfile['profiler'], string> = {
+  v8: 'V8',
+  cprofile: 'cProfile',
+};
+
+/** Frames narrower than this share of the zoomed frame are left out */
+const MIN_FRAME_SHARE = 0.005;
+
+/** A function of a profile with its time across all of its calls */
+export interface ProfileFunctionSummary {
+  name: string;
+  line?: number;
+  file?: string;
+  selfTime: number;
+  /** Time in the function and its callees; recursive calls count once */
+  totalTime: number;
+}
+
+/** Rows of the bottom-up table, the functions with most self time first */
+export function summarizeProfileFunctions(
+  root: ProfileNode
+): ProfileFunctionSummary[] {
+  const summaries = new Map<string, ProfileFunctionSummary>();
+  // Calls of each function on the path being visited
+  const active = new Map<string, number>();
+
+  const visit = (node: ProfileNode) => {
+    const key = `${node.name}\0${node.file ?? ''}\0${node.line ?? ''}`;
+    let summary = summaries.get(key);
+    if (!summary) {
+      const { name, line, file } = node;
+      summary = { name, line, file, selfTime: 0, totalTime: 0 };
+      summaries.set(key, summary);
+    }
+    summary.selfTime += node.selfTime;
+
+    const depth = active.get(key) ?? 0;
+    if (depth === 0) summary.totalTime += node.totalTime;
+    active.set(key, depth + 1);
+    node.children.forEach(visit);
+    active.set(key, depth);
+  };
+  root.children.forEach(visit);
+
+  return [...summaries.values()].sort((a, b) => b.selfTime - a.selfTime);
+}
+
+function formatTime(milliseconds: number): string {
+  if (milliseconds >= 1000) return `${(milliseconds / 1000).toFixed(2)} s`;
+  return `${milliseconds.toFixed(milliseconds < 10 ? 2 : 1)} ms`;
+}
+
+function formatShare(time: number, total: number): string {
+  return `${total > 0 ? ((time / total) * 100).toFixed(1) : '0.0'}%`;
+}
+
+/** Formats `line 3` for the program, `utils.ts:3` for other files */
+function useFormatLocation() {
+  const { t } = useTranslation();
+  return ({ line, file }: Pick<ProfileNode, 'line' | 'file'>) => {
+    if (file) return line ? `${file}:${line}` : file;
+    if (!line) return '';
+    return t('profile.line', { line, defaultValue: 'line {{line}}' });
+  };
+}
+
+/** Editor line of a function of the program; other files have none */
+function getEditorLine(node: Pick<ProfileNode, 'line' | 'file'>) {
+  return node.file ? undefined : node.line;
+}
+
+function FlameFrame({
+  node,
+  width,
+  minTime,
+  profileTime,
+  onSelect,
+}: {
+  node: ProfileNode;
+  /** Share of the parent's width, in percent */
+  width: number;
+  minTime: number;
+  profileTime: number;
+  onSelect: (node: ProfileNode) => void;
+}) {
+  const { t } = useTranslation();
+  const formatLocation = useFormatLocation();
+  const location = formatLocation(node);
+  const children = node.children.filter(
+    (child) => child.totalTime >= minTime
+  );
+
+  return (
+    <div className="flex flex-col min-w-0" style={{ width: `${width}%` }}>
+      <button
+        onClick={() => onSelect(node)}
+        title={`${node.name}${location ? ` (${location})` : ''}\n${t('profile.total', 'Total')}: ${formatTime(node.totalTime)} (${formatShare(node.totalTime, profileTime)})\n${t('profile.self', 'Self')}: ${formatTime(node.selfTime)}`}
+        className={clsx(
+          'h-5 px-1 mb-px mr-px rounded-sm text-[11px] leading-5 text-left truncate hover:brightness-125',
+          getEditorLine(node) !== undefined
+            ? 'bg-orange-500/70 text-white'
+            : node.file
+              ? 'bg-sky-600/60 text-white'
+              : 'bg-muted-foreground/30 text-foreground'
+        )}
+      >
+        {node.name}
+      </button>
+      {children.length > 0 && (
+        <div className="flex">
+          {children.map((child, index) => (
+            <FlameFrame
+              key={index}
+              node={child}
+              width={(child.totalTime / node.totalTime) * 100}
+              minTime={minTime}
+              profileTime={profileTime}
+              onSelect={onSelect}
+            />
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
+
+function FlameGraph({
+  root,
+  onRevealLine,
+}: {
+  root: ProfileNode;
+  onRevealLine?: (line: number) => void;
+}) {
+  // Clicking a frame zooms into it; the path leads back out. A new
+  // profile starts zoomed out.
+  const [zoom, setZoom] = useState<{
+    root: ProfileNode;
+    path: ProfileNode[];
+  } | null>(null);
+  const path = zoom?.root === root ? zoom.path : [];
+  const setPath = (nodes: ProfileNode[]) => setZoom({ root, path: nodes });
+  const focus = path[path.length - 1] ?? root;
+
+  const select = (node: ProfileNode) => {
+    const line = getEditorLine(node);
+    if (line !== undefined) onRevealLine?.(line);
+    if (node === focus) return;
+    // Frames are only reachable through the focused one
+    const chain: ProfileNode[] = [];
+    const find = (current: ProfileNode): boolean => {
+      if (current === node) return true;
+      for (const child of current.children) {
+        if (find(child)) {
+          chain.unshift(child);
+          return true;
+        }
+      }
+      return false;
+    };
+    find(focus);
+    setPath([...path, ...chain]);
+  };
+
+  return (
+    <div className="h-full flex flex-col">
+      {path.length > 0 && (
+        <div className="flex items-center gap-1 px-3 py-1 text-xs text-muted-foreground border-b border-border shrink-0 overflow-x-auto">
+          {[root, ...path].map((node, index) => (
+            <button
+              key={index}
+              onClick={() => setPath(path.slice(0, index))}
+              className="whitespace-nowrap hover:text-foreground"
+            >
+              {index > 0 && '› '}
+              {node.name}
+            </button>
+          ))}
+        </div>
+      )}
+      <div className="flex-1 min-h-0 overflow-auto p-2">
+        <FlameFrame
+          node={focus}
+          width={100}
+          minTime={focus.totalTime * MIN_FRAME_SHARE}
+          profileTime={root.totalTime}
+          onSelect={select}
+        />
+      </div>
+    </div>
+  );
+}
+
+function BottomUpTable({
+  root,
+  onRevealLine,
+}: {
+  root: ProfileNode;
+  onRevealLine?: (line: number) => void;
+}) {
+  const { t } = useTranslation();
+  const formatLocation = useFormatLocation();
+  const rows = useMemo(() => summarizeProfileFunctions(root), [root]);
+
+  return (
+    <div className="h-full overflow-auto">
+      <table className="w-full text-xs">
+        <thead className="sticky top-0 bg-background text-muted-foreground">
+          <tr className="text-left border-b border-border">
+            <th className="px-3 py-1.5 font-medium text-right">
+              {t('profile.self', 'Self')}
+            </th>
+            <th className="px-3 py-1.5 font-medium text-right">
+              {t('profile.total', 'Total')}
+            </th>
+            <th className="px-3 py-1.5 font-medium">
+              {t('profile.function', 'Function')}
+            </th>
+            <th className="px-3 py-1.5 font-medium">
+              {t('profile.location', 'Location')}
+            </th>
+          </tr>
+        </thead>
+        <tbody>
+          {rows.map((row, index) => {
+            const line = getEditorLine(row);
+            return (
+              <tr
+                key={index}
+                onClick={() => line !== undefined && onRevealLine?.(line)}
+                className={clsx(
+                  'border-b border-border/40',
+                  line !== undefined && 'cursor-pointer hover:bg-muted/40'
+                )}
+              >
+                <td className="px-3 py-1 text-right tabular-nums whitespace-nowrap">
+                  {formatTime(row.selfTime)}{' '}
+                  <span className="text-muted-foreground">
+                    {formatShare(row.selfTime, root.totalTime)}
+                  </span>
+                </td>
+                <td className="px-3 py-1 text-right tabular-nums whitespace-nowrap">
+                  {formatTime(row.totalTime)}{' '}
+                  <span className="text-muted-foreground">
+                    {formatShare(row.totalTime, root.totalTime)}
+                  </span>
+                </td>
+                <td className="px-3 py-1 font-mono truncate max-w-[16rem]">
+                  {row.name}
+                </td>
+                <td className="px-3 py-1 text-muted-foreground whitespace-nowrap">
+                  {formatLocation(row)}
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </table>
+    </div>
+  );
+}
+
+/**
+ * Where a profiled run spent its time, as a flame graph of its call tree or
+ * a bottom-up table of its functions. Functions of the program link to the
+ * editor line they start on.
+ */
+export function ProfilePanel({ profile, onRevealLine }: ProfilePanelProps) {
+  const { t } = useTranslation();
+  const [view, setView] = useState<ProfileView>('flame');
+  const { root } = profile;
+  const viewLabels: Record<ProfileView, string> = {
+    flame: t('profile.flame', 'Flame graph'),
+    'bottom-up': t('profile.bottomUp', 'Bottom-up'),
+  };
+
+  return (
+    <div
+      className="h-full flex flex-col text-foreground bg-background overflow-hidden"
+      data-testid="profile-panel"
+    >
+      <div className="flex items-center gap-1.5 px-3 py-2 border-b border-border bg-muted/20 shrink-0">
+        {PROFILE_VIEWS.map((option) => (
+          <button
+            key={option}
+            onClick={() => setView(option)}
+            className={clsx(
+              'px-2 py-1 rounded text-xs font-medium border select-none',
+              option === view
+                ? 'bg-background border-border shadow-sm text-foreground'
+                : 'bg-muted/50 border-transparent text-muted-foreground'
+            )}
+          >
+            {viewLabels[option]}
+          </button>
+        ))}
+        <span className="ml-auto text-xs text-muted-foreground">
+          {t('profile.summary', {
+            time: formatTime(root.totalTime),
+            profiler: PROFILER_LABELS[profile.profiler],
+            defaultValue: '{{time}} profiled with {{profiler}}',
+          })}
+        </span>
+      </div>
+
+      <div className="flex-1 min-h-0">
+        {root.children.length === 0 ? (
+          <div className="p-3 text-xs text-muted-foreground">
+            {t(
+              'profile.noSamples',
+              'The run finished before the profiler took any samples.'
+            )}
+          </div>
+        ) : view === 'flame' ? (
+          <FlameGraph root={root} onRevealLine={onRevealLine} />
+        ) : (
+          <BottomUpTable root={root} onRevealLine={onRevealLine} />
+        )}
+      </div>
+    </div>
+  );
+}
@@ -1,7 +1,10 @@
-import { useMemo, type ReactNode } from 'react';
-import Editor, { type Monaco } from '@monaco-editor/react';
+import { useEffect, useMemo, useState, type ReactNode } from 'react';
+import { createPortal } from 'react-dom';
+import Editor, { type Monaco, type OnMount } from '@monaco-editor/react';
 import { Filter } from 'lucide-react';
 import clsx from 'clsx';
+import type { SerializedChildren, SerializedValue } from '@cheesejs/core';
+import { ValueTree } from './ValueTree';
 
 export interface RuntimeConsoleFilters {
   error: boolean;
@@ -18,44 +21,133 @@ export interface RuntimeResultEntry {
   element?: {
     consoleType?: 'dir' | 'error' | 'info' | 'log' | 'table' | 'warn';
     content?: string | number | boolean | object | null;
+    groupDepth?: number;
+    groupStart?: 'collapsed' | 'expanded';
+    /** Printed arguments, expandable under the entry's output line */
+    values?: SerializedValue[];
   };
+  /** Run that printed the entry, for fetching more of its values */
+  executionId?: string;
   lineNumber?: number;
+  /** Index of the code cell that printed the entry */
+  cell?: number;
   type: 'error' | 'execution';
 }
 
+/** A code cell of the buffer, which output is grouped by */
+export interface RuntimeResultCell {
+  index: number;
+  title: string;
+  /** Last line of the cell, where aligned output without a line goes */
+  endLine: number;
+}
+
 export interface ResultPanelProps {
   alignResults: boolean;
+  /** Cells of a buffer split by `%%` markers; output is grouped by cell */
+  cells?: RuntimeResultCell[];
   code: string;
   consoleFilters: RuntimeConsoleFilters;
   consoleInput?: ReactNode;
   elements: RuntimeResultEntry[];
   fontSize: number;
   onEditorWillMount?: (monaco: Monaco) => void;
+  /** Fetches children of an entry's values; without it none can expand */
+  onExpandValue?: (
+    entry: RuntimeResultEntry,
+    handle: number
+  ) => Promise<SerializedChildren | null>;
   onToggleFilter: (type: keyof RuntimeConsoleFilters) => void;
   packagePrompts?: ReactNode;
   themeName: string;
   waitingMessage?: string;
 }
 
+type ResultEditor = Parameters<OnMount>[0];
+
+interface MountedEditor {
+  editor: ResultEditor;
+  monaco: Monaco;
+}
+
+/** Output line whose entries printed values that can be expanded */
+interface ValueLine {
+  /** Position of the line's first entry in `elements`, a stable React key */
+  key: number;
+  lineNumber: number;
+  entries: RuntimeResultEntry[];
+}
+
+const GROUP_INDENT = '  ';
+
+// Monaco recomputes folding ranges on a short delay after content changes
+const FOLD_DELAY_MS = 300;
+
+/** Entry text, indented by its `console.group()` depth like Node does */
+function formatEntry(entry: RuntimeResultEntry): string {
+  const content = String(entry.element?.content || '');
+  const depth = entry.element?.groupDepth ?? 0;
+  if (depth === 0) {
+    return content;
+  }
+
+  const indent = GROUP_INDENT.repeat(depth);
+  return content
+    .split('\n')
+    .map((line) => indent + line)
+    .join('\n');
+}
+
+/** `// ── Cell 2: Load data ──`, shown above the output of a cell */
+function formatCellHeader(cell: RuntimeResultCell): string {
+  const title = cell.title ? `: ${cell.title}` : '';
+  return `// ── Cell ${cell.index + 1}${title} ──`;
+}
+
+/** Values the entry printed that have contents to expand */
+function getExpandableValues(entry: RuntimeResultEntry): SerializedValue[] {
+  return (entry.element?.values ?? []).filter(
+    (value) => value.handle !== undefined
+  );
+}
+
+/** Output of no cell sorts after that of every cell */
+function getCellOrder(entry: RuntimeResultEntry): number {
+  return entry.cell ?? Number.MAX_SAFE_INTEGER;
+}
+
 /**
  * Read-only runtime output panel with filter controls and prompt slots.
+ * Console groups are indented and foldable; `groupCollapsed()` groups start
+ * folded. Lines that printed objects, arrays and other values with contents
+ * get a chevron in the gutter, which opens their tree right under the line.
+ * In buffers with code cells, output is listed cell by cell, or next to the
+ * last line of its cell when aligned.
  */
 export function ResultPanel({
   alignResults,
+  cells,
   code,
   consoleFilters,
   consoleInput,
   elements,
   fontSize,
   onEditorWillMount,
+  onExpandValue,
   onToggleFilter,
   packagePrompts,
   themeName,
   waitingMessage = '// Waiting for output...',
 }: ResultPanelProps) {
-  const displayValue = useMemo(() => {
+  const [mounted, setMounted] = useState<MountedEditor | null>(null);
+  // Keyed by the first entry of a line, which stays put as output grows
+  const [openEntries, setOpenEntries] = useState<
+    ReadonlySet<RuntimeResultEntry>
+  >(() => new Set());
+
+  const { displayValue, collapsedLines, valueLines } = useMemo(() => {
     if (elements.length === 0) {
-      return '';
+      return { displayValue: '', collapsedLines: [], valueLines: [] };
     }
 
     const filteredElements = elements.filter((entry) => {
@@ -81,35 +173,161 @@ export function ResultPanel({
       return consoleFilters.log;
     });
 
+    // Indexes into `lines` of collapsed group labels
+    const collapsedIndexes = new Set<number>();
+    // Indexes into `lines` of entries with values to expand
+    const valueIndexes = new Map<number, RuntimeResultEntry[]>();
+    const addValues = (index: number, entry: RuntimeResultEntry) => {
+      if (getExpandableValues(entry).length > 0) {
+        valueIndexes.set(index, [...(valueIndexes.get(index) ?? []), entry]);
+      }
+    };
+    let lines: string[];
+
+    const findCell = (entry: RuntimeResultEntry) =>
+      cells?.find((cell) => cell.index === entry.cell);
+
     if (!alignResults) {
-      return filteredElements
-        .map((entry) => String(entry.element?.content || ''))
-        .join('\n');
+      // Sorting is stable, so output keeps its order within each cell
+      const orderedElements = cells?.length
+        ? [...filteredElements].sort(
+            (a, b) => getCellOrder(a) - getCellOrder(b)
+          )
+        : filteredElements;
+      let previousCell: number | undefined;
+      lines = [];
+
+      orderedElements.forEach((entry) => {
+        const cell = findCell(entry);
+        if (cell && cell.index !== previousCell) {
+          lines.push(formatCellHeader(cell));
+        }
+        previousCell = entry.cell;
+
+        if (entry.element?.groupStart === 'collapsed') {
+          collapsedIndexes.add(lines.length);
+        }
+        addValues(lines.length, entry);
+        lines.push(formatEntry(entry));
+      });
+    } else {
+      const sourceLineCount = code.split('\n').length;
+      const maxLine = Math.max(
+        sourceLineCount,
+        ...filteredElements.map((entry) => entry.lineNumber || 0)
+      );
+      lines = new Array(maxLine).fill('');
+
+      filteredElements.forEach((entry) => {
+        const content = formatEntry(entry);
+        const lineNumber = entry.lineNumber || findCell(entry)?.endLine;
+
+        if (lineNumber && lineNumber > 0 && lineNumber <= lines.length) {
+          const current = lines[lineNumber - 1];
+          lines[lineNumber - 1] = current ? `${current} ${content}` : content;
+          addValues(lineNumber - 1, entry);
+          return;
+        }
+
+        if (entry.element?.groupStart === 'collapsed') {
+          collapsedIndexes.add(lines.length);
+        }
+        addValues(lines.length, entry);
+        lines.push(content);
+      });
     }
 
-    const sourceLineCount = code.split('\n').length;
-    const maxLine = Math.max(
-      sourceLineCount,
-      ...filteredElements.map((entry) => entry.lineNumber || 0)
-    );
-    const lines = new Array(maxLine).fill('');
+    // Entries may span several lines, so count them to find the labels
+    // and the last line of entries with values
+    const collapsedLines: number[] = [];
+    const valueLines: ValueLine[] = [];
+    const positions = new Map<RuntimeResultEntry, number>();
+    if (valueIndexes.size > 0) {
+      elements.forEach((entry, index) => positions.set(entry, index));
+    }
+    let lineNumber = 1;
+    lines.forEach((line, index) => {
+      if (collapsedIndexes.has(index)) {
+        collapsedLines.push(lineNumber);
+      }
+      lineNumber += line.split('\n').length;
+      const entries = valueIndexes.get(index);
+      if (entries) {
+        const key = positions.get(entries[0])!;
+        valueLines.push({ key, lineNumber: lineNumber - 1, entries });
+      }
+    });
+
+    return {
+      displayValue: lines.join('\n'),
+      collapsedLines,
+      valueLines: onExpandValue ? valueLines : [],
+    };
+  }, [alignResults, cells, code, consoleFilters, elements, onExpandValue]);
+
+  // Keyed by value so appending output does not refold groups the user opened
+  const collapsedKey = collapsedLines.join(',');
 
-    filteredElements.forEach((entry) => {
-      const content = String(entry.element?.content || '');
+  useEffect(() => {
+    if (!mounted || !collapsedKey) {
+      return;
+    }
+
+    const timer = setTimeout(() => {
+      mounted.editor.trigger('result-panel', 'editor.fold', {
+        levels: 1,
+        selectionLines: collapsedKey.split(',').map((line) => Number(line) - 1),
+      });
+    }, FOLD_DELAY_MS);
+    return () => clearTimeout(timer);
+  }, [collapsedKey, mounted]);
+
+  // Chevrons in the gutter of lines with values, which toggle their trees
+  useEffect(() => {
+    if (!mounted || valueLines.length === 0) {
+      return;
+    }
 
-      if (entry.lineNumber && entry.lineNumber > 0) {
-        const current = lines[entry.lineNumber - 1];
-        lines[entry.lineNumber - 1] = current
-          ? `${current} ${content}`
-          : content;
+    const { editor, monaco } = mounted;
+    const decorations = editor.createDecorationsCollection(
+      valueLines.map(({ lineNumber, entries }) => ({
+        range: new monaco.Range(lineNumber, 1, lineNumber, 1),
+        options: {
+          glyphMarginClassName: clsx(
+            'codicon cursor-pointer',
+            openEntries.has(entries[0])
+              ? 'codicon-chevron-down'
+              : 'codicon-chevron-right'
+          ),
+        },
+      }))
+    );
+    const listener = editor.onMouseDown((event) => {
+      const { type, position } = event.target;
+      if (type !== monaco.editor.MouseTargetType.GUTTER_GLYPH_MARGIN) {
+        return;
+      }
+      const line = valueLines.find(
+        ({ lineNumber }) => lineNumber === position?.lineNumber
+      );
+      if (!line) {
         return;
       }
 
-      lines.push(content);
+      setOpenEntries((open) => {
+        const next = new Set(open);
+        if (!next.delete(line.entries[0])) {
+          next.add(line.entries[0]);
+        }
+        return next;
+      });
     });
 
-    return lines.join('\n');
-  }, [alignResults, code, consoleFilters, elements]);
+    return () => {
+      listener.dispose();
+      decorations.clear();
+    };
+  }, [mounted, openEntries, valueLines]);
 
   return (
     <div
@@ -188,6 +406,9 @@ export function ResultPanel({
             wordWrap: 'on',
             readOnly: true,
             lineNumbers: 'off',
+            glyphMargin: valueLines.length > 0,
+            folding: true,
+            foldingStrategy: 'indentation',
             renderLineHighlight: 'none',
             showUnused: false,
             suggest: {
@@ -198,9 +419,32 @@ export function ResultPanel({
           defaultLanguage="javascript"
           value={displayValue || waitingMessage}
           beforeMount={onEditorWillMount}
+          onMount={(editor, monaco) => setMounted({ editor, monaco })}
         />
       </div>
 
+      {mounted &&
+        onExpandValue &&
+        valueLines
+          .filter(({ entries }) => openEntries.has(entries[0]))
+          .map(({ key, lineNumber, entries }) => (
+            <ValueZone
+              key={key}
+              editor={mounted.editor}
+              afterLineNumber={lineNumber}
+            >
+              {entries.map((entry, entryIndex) =>
+                getExpandableValues(entry).map((value, valueIndex) => (
+                  <ValueTree
+                    key={`${entryIndex}-${valueIndex}`}
+                    value={value}
+                    onExpand={(handle) => onExpandValue(entry, handle)}
+                  />
+                ))
+              )}
+            </ValueZone>
+          ))}
+
       {consoleInput}
 
       {packagePrompts}
@@ -208,6 +452,54 @@ export function ResultPanel({
   );
 }
 
+/**
+ * Monaco view zone under an output line, holding `children`. The zone is
+ * resized along with them as trees are opened and closed.
+ */
+function ValueZone({
+  editor,
+  afterLineNumber,
+  children,
+}: {
+  editor: ResultEditor;
+  afterLineNumber: number;
+  children: ReactNode;
+}) {
+  const [content] = useState(() => document.createElement('div'));
+
+  useEffect(() => {
+    const domNode = document.createElement('div');
+    domNode.appendChild(content);
+    const zone = {
+      afterLineNumber,
+      heightInPx: content.offsetHeight,
+      domNode,
+      // Let clicks reach the tree instead of moving the cursor
+      suppressMouseDown: true,
+    };
+    let zoneId = '';
+    editor.changeViewZones((accessor) => {
+      zoneId = accessor.addZone(zone);
+    });
+
+    const observer = new ResizeObserver(() => {
+      zone.heightInPx = content.offsetHeight;
+      editor.changeViewZones((accessor) => accessor.layoutZone(zoneId));
+    });
+    observer.observe(content);
+
+    return () => {
+      observer.disconnect();
+      editor.changeViewZones((accessor) => accessor.removeZone(zoneId));
+    };
+  }, [afterLineNumber, content, editor]);
+
+  return createPortal(
+    <div className="px-3 py-1 font-mono text-xs">{children}</div>,
+    content
+  );
+}
+
 function FilterToggle({
   active,
   count,
@@ -0,0 +1,184 @@
+import { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { ChevronDown, ChevronRight } from 'lucide-react';
+import type {
+  SerializedChildren,
+  SerializedValue,
+  SerializedValueKind,
+} from '@cheesejs/core';
+import clsx from 'clsx';
+
+export interface ValueTreeProps {
+  value: SerializedValue;
+  /** Fetches children the worker did not send with the value */
+  onExpand: (handle: number) => Promise<SerializedChildren | null>;
+}
+
+const KIND_COLORS: Partial<Record<SerializedValueKind, string>> = {
+  string: 'text-emerald-600 dark:text-emerald-400',
+  number: 'text-sky-600 dark:text-sky-400',
+  bigint: 'text-sky-600 dark:text-sky-400',
+  boolean: 'text-violet-600 dark:text-violet-400',
+  undefined: 'text-muted-foreground',
+  null: 'text-muted-foreground',
+  symbol: 'text-amber-600 dark:text-amber-400',
+  error: 'text-destructive',
+  accessor: 'text-muted-foreground italic',
+};
+
+type ChildrenState =
+  | { status: 'idle' }
+  | { status: 'loading' }
+  | { status: 'loaded'; children: SerializedChildren }
+  | { status: 'unavailable' };
+
+/** Children of `value`, fetched from the worker the first time `load` runs */
+function useChildren(
+  value: SerializedValue,
+  onExpand: ValueTreeProps['onExpand']
+): [ChildrenState, () => void] {
+  const [state, setState] = useState<ChildrenState>(() =>
+    value.children
+      ? {
+          status: 'loaded',
+          children: { children: value.children, omitted: value.omitted },
+        }
+      : { status: 'idle' }
+  );
+
+  const load = () => {
+    if (state.status !== 'idle' || value.handle === undefined) return;
+
+    setState({ status: 'loading' });
+    onExpand(value.handle)
+      .then((children) =>
+        setState(
+          children ? { status: 'loaded', children } : { status: 'unavailable' }
+        )
+      )
+      .catch(() => setState({ status: 'unavailable' }));
+  };
+
+  return [state, load];
+}
+
+function ValueChildren({
+  state,
+  onExpand,
+}: {
+  state: ChildrenState;
+  onExpand: ValueTreeProps['onExpand'];
+}) {
+  const { t } = useTranslation();
+
+  if (state.status === 'loading') {
+    return (
+      <div className="text-muted-foreground pl-3.5">
+        {t('valueTree.loading', 'Loading…')}
+      </div>
+    );
+  }
+  if (state.status === 'unavailable') {
+    return (
+      <div className="text-muted-foreground pl-3.5">
+        {t(
+          'valueTree.unavailable',
+          'No longer available, run the code again to inspect it'
+        )}
+      </div>
+    );
+  }
+  if (state.status !== 'loaded') {
+    return null;
+  }
+
+  return (
+    <>
+      {state.children.children.map((entry, index) => (
+        <div key={`${entry.key}-${index}`}>
+          {entry.keyValue && (
+            <ValueNode label="key" value={entry.keyValue} onExpand={onExpand} />
+          )}
+          <ValueNode
+            label={entry.keyValue ? 'value' : entry.key}
+            value={entry.value}
+            onExpand={onExpand}
+          />
+        </div>
+      ))}
+      {!!state.children.omitted && (
+        <div className="text-muted-foreground pl-3.5">
+          {t('valueTree.more', {
+            count: state.children.omitted,
+            defaultValue: '… {{count}} more',
+          })}
+        </div>
+      )}
+    </>
+  );
+}
+
+function ValueNode({
+  label,
+  value,
+  onExpand,
+}: ValueTreeProps & { label: string }) {
+  const [expanded, setExpanded] = useState(false);
+  const [state, load] = useChildren(value, onExpand);
+  const expandable = value.handle !== undefined;
+
+  const toggle = () => {
+    if (!expandable) return;
+    setExpanded((open) => !open);
+    load();
+  };
+
+  const Chevron = expanded ? ChevronDown : ChevronRight;
+
+  return (
+    <div>
+      <button
+        type="button"
+        onClick={toggle}
+        disabled={!expandable}
+        aria-expanded={expandable ? expanded : undefined}
+        className="flex items-start gap-0.5 text-left w-full hover:bg-muted/50 rounded-sm disabled:hover:bg-transparent disabled:cursor-text"
+      >
+        {expandable ? (
+          <Chevron className="w-3 h-3 mt-0.5 shrink-0 text-muted-foreground" />
+        ) : (
+          <span className="w-3 shrink-0" />
+        )}
+        <span className="text-rose-600 dark:text-rose-400 shrink-0">
+          {label}:&nbsp;
+        </span>
+        <span
+          className={clsx(
+            'whitespace-pre-wrap break-all',
+            KIND_COLORS[value.kind]
+          )}
+        >
+          {value.preview}
+        </span>
+      </button>
+
+      {expanded && (
+        <div className="pl-4 border-l border-border/60 ml-1.5">
+          <ValueChildren state={state} onExpand={onExpand} />
+        </div>
+      )}
+    </div>
+  );
+}
+
+/**
+ * Contents of a value printed by a JS/TS run, shown under its output line
+ * and expanded one level at a time. Levels the worker did not send are
+ * fetched on first expansion and kept, so collapsing and reopening does
+ * not refetch them.
+ */
+export function ValueTree({ value, onExpand }: ValueTreeProps) {
+  // Printed values come with their first level of children
+  const [state] = useChildren(value, onExpand);
+  return <ValueChildren state={state} onExpand={onExpand} />;
+}
@@ -34,6 +34,7 @@ export const useRuntimeStatusStore = create<RuntimeStatusState>((set, get) => ({
     ['python', defaultStatus('python')],
     ['c', defaultStatus('c')],
     ['cpp', defaultStatus('cpp')],
+    ['rust', defaultStatus('rust')],
   ]),
 
   getStatus: (lang) => get().statuses.get(lang) || defaultStatus(lang),
@@ -1,4 +1,7 @@
+export * from './components/CompiledOutputPanel';
 export * from './components/ConsoleInputPanel';
 export * from './components/InputTooltipOverlay';
+export * from './components/ProfilePanel';
 export * from './components/ResultPanel';
+export * from './components/ValueTree';
 export * from './hooks/useRuntimeStatus';
@@ -4,17 +4,22 @@ import { useTranslation } from 'react-i18next';
 import clsx from 'clsx';
 import type { ReactNode } from 'react';
 import { Select, Tooltip } from '@cheesejs/ui';
+import { WORKER_MEMORY_LIMITS_MB } from '@cheesejs/core';
 
 export interface AdvancedTabProps {
   internalLogLevel: 'debug' | 'error' | 'info' | 'none' | 'warn';
   onInternalLogLevelChange: (
     level: 'debug' | 'error' | 'info' | 'none' | 'warn'
   ) => void;
+  workerMemoryLimitMb: number;
+  onWorkerMemoryLimitChange: (limitMb: number) => void;
 }
 
 export function AdvancedTab({
   internalLogLevel,
   onInternalLogLevelChange,
+  workerMemoryLimitMb,
+  onWorkerMemoryLimitChange,
 }: AdvancedTabProps) {
   const { t } = useTranslation();
 
@@ -50,6 +55,36 @@ export function AdvancedTab({
           </AdvancedRow>
         </div>
       </div>
+
+      <div>
+        <h4 className="text-sm font-semibold mb-6 text-muted-foreground">
+          {t('settings.advanced.resources', 'Resources')}
+        </h4>
+
+        <div className="space-y-6">
+          <AdvancedRow
+            label={t('settings.advanced.workerMemoryLimit')}
+            helpContent={t('settings.advanced.workerMemoryLimitTooltip')}
+          >
+            <Select
+              value={workerMemoryLimitMb}
+              onChange={(event) =>
+                onWorkerMemoryLimitChange(Number(event.target.value))
+              }
+              className="w-32"
+              data-testid="worker-memory-limit"
+            >
+              {WORKER_MEMORY_LIMITS_MB.map((limitMb) => (
+                <option key={limitMb} value={limitMb}>
+                  {limitMb === 0
+                    ? t('settings.advanced.unlimited', 'Unlimited')
+                    : `${limitMb} MB`}
+                </option>
+              ))}
+            </Select>
+          </AdvancedRow>
+        </div>
+      </div>
     </m.div>
   );
 }
@@ -2,24 +2,61 @@ import { m } from 'framer-motion';
 import { HelpCircle } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
 import clsx from 'clsx';
-import type { ReactNode } from 'react';
-import { Toggle, Tooltip } from '@cheesejs/ui';
+import { useEffect, useState, type ReactNode } from 'react';
+import type {
+  CppStandard,
+  CStandard,
+  NativeCompilerOptions,
+  NativeProjectMode,
+  OptimizationLevel,
+} from '@cheesejs/core';
+import {
+  C_STANDARDS,
+  CPP_STANDARDS,
+  OPTIMIZATION_LEVELS,
+  splitFlagList,
+} from '@cheesejs/languages/compilerFlags';
+import { Select, Toggle, Tooltip } from '@cheesejs/ui';
 
 export interface CompilationTabProps {
+  compilerOptions: NativeCompilerOptions;
   loopProtection: boolean;
   magicComments: boolean;
+  onCompilerOptionsChange: (options: Partial<NativeCompilerOptions>) => void;
   onLoopProtectionChange: (value: boolean) => void;
   onMagicCommentsChange: (value: boolean) => void;
+  onProjectModeChange: (mode: NativeProjectMode) => void;
+  onPythonTestsChange: (value: boolean) => void;
+  onShowCompiledOutputChange: (value: boolean) => void;
   onShowTopLevelResultsChange: (value: boolean) => void;
+  projectMode: NativeProjectMode;
+  pythonTests: boolean;
+  showCompiledOutput: boolean;
   showTopLevelResults: boolean;
 }
 
+const PROJECT_MODES: NativeProjectMode[] = ['single', 'manifest', 'workspace'];
+
+const PROJECT_MODE_LABELS: Record<NativeProjectMode, string> = {
+  single: 'settings.compilation.projectModeSingle',
+  manifest: 'settings.compilation.projectModeManifest',
+  workspace: 'settings.compilation.projectModeWorkspace',
+};
+
 export function CompilationTab({
+  compilerOptions,
   loopProtection,
   magicComments,
+  onCompilerOptionsChange,
   onLoopProtectionChange,
   onMagicCommentsChange,
+  onProjectModeChange,
+  onPythonTestsChange,
+  onShowCompiledOutputChange,
   onShowTopLevelResultsChange,
+  projectMode,
+  pythonTests,
+  showCompiledOutput,
   showTopLevelResults,
 }: CompilationTabProps) {
   const { t } = useTranslation();
@@ -62,12 +99,178 @@ export function CompilationTab({
               onChange={onShowTopLevelResultsChange}
             />
           </Row>
+
+          <Row
+            label={t('settings.advanced.pythonTests')}
+            helpContent={t('settings.advanced.pythonTestsTooltip')}
+          >
+            <Toggle checked={pythonTests} onChange={onPythonTestsChange} />
+          </Row>
+        </div>
+      </div>
+
+      <div>
+        <h4 className="text-sm font-semibold mb-6 text-muted-foreground">
+          {t('settings.compilation.nativeCompiler')}
+        </h4>
+
+        <div className="space-y-6">
+          <Row label={t('settings.compilation.cStandard')}>
+            <Select
+              value={compilerOptions.cStandard}
+              onChange={(event) =>
+                onCompilerOptionsChange({
+                  cStandard: event.target.value as CStandard,
+                })
+              }
+              className="w-32"
+            >
+              {C_STANDARDS.map((standard) => (
+                <option key={standard} value={standard}>
+                  {standard.toUpperCase()}
+                </option>
+              ))}
+            </Select>
+          </Row>
+
+          <Row label={t('settings.compilation.cppStandard')}>
+            <Select
+              value={compilerOptions.cppStandard}
+              onChange={(event) =>
+                onCompilerOptionsChange({
+                  cppStandard: event.target.value as CppStandard,
+                })
+              }
+              className="w-32"
+            >
+              {CPP_STANDARDS.map((standard) => (
+                <option key={standard} value={standard}>
+                  {standard.toUpperCase()}
+                </option>
+              ))}
+            </Select>
+          </Row>
+
+          <Row
+            label={t('settings.compilation.optimization')}
+            helpContent={t('settings.compilation.optimizationTooltip')}
+          >
+            <Select
+              value={compilerOptions.optimization}
+              onChange={(event) =>
+                onCompilerOptionsChange({
+                  optimization: event.target.value as OptimizationLevel,
+                })
+              }
+              className="w-32"
+            >
+              {OPTIMIZATION_LEVELS.map((level) => (
+                <option key={level} value={level}>
+                  -{level}
+                </option>
+              ))}
+            </Select>
+          </Row>
+
+          <FlagListField
+            label={t('settings.compilation.defines')}
+            placeholder="DEBUG VERSION=2"
+            values={compilerOptions.defines ?? []}
+            onCommit={(defines) => onCompilerOptionsChange({ defines })}
+          />
+
+          <FlagListField
+            label={t('settings.compilation.warnings')}
+            placeholder="-Wall -Wextra"
+            values={compilerOptions.warnings ?? []}
+            onCommit={(warnings) => onCompilerOptionsChange({ warnings })}
+          />
+
+          <FlagListField
+            label={t('settings.compilation.libraries')}
+            placeholder="m"
+            values={compilerOptions.libraries ?? []}
+            onCommit={(libraries) => onCompilerOptionsChange({ libraries })}
+          />
+
+          <Row
+            label={t('settings.compilation.projectMode')}
+            helpContent={t('settings.compilation.projectModeTooltip')}
+          >
+            <Select
+              value={projectMode}
+              onChange={(event) =>
+                onProjectModeChange(event.target.value as NativeProjectMode)
+              }
+              className="w-40"
+            >
+              {PROJECT_MODES.map((mode) => (
+                <option key={mode} value={mode}>
+                  {t(PROJECT_MODE_LABELS[mode])}
+                </option>
+              ))}
+            </Select>
+          </Row>
+
+          <Row
+            label={t('settings.compilation.showCompiledOutput')}
+            helpContent={t('settings.compilation.showCompiledOutputTooltip')}
+          >
+            <Toggle
+              checked={showCompiledOutput}
+              onChange={onShowCompiledOutputChange}
+            />
+          </Row>
+
+          <p className="text-xs text-muted-foreground">
+            {t('settings.compilation.magicHeaderHint')}
+          </p>
         </div>
       </div>
     </m.div>
   );
 }
 
+/**
+ * Space-separated list input. Edits are kept locally and committed on blur so
+ * typing a separator does not immediately get normalised away.
+ */
+function FlagListField({
+  label,
+  placeholder,
+  values,
+  onCommit,
+}: {
+  label: string;
+  onCommit: (values: string[]) => void;
+  placeholder: string;
+  values: string[];
+}) {
+  const joined = values.join(' ');
+  const [draft, setDraft] = useState(joined);
+
+  useEffect(() => {
+    setDraft(joined);
+  }, [joined]);
+
+  return (
+    <div className="flex flex-col space-y-2">
+      <label className="text-sm text-foreground">{label}</label>
+      <input
+        type="text"
+        className="w-full rounded-md border border-input bg-background px-3 py-2 text-sm font-mono text-foreground"
+        placeholder={placeholder}
+        value={draft}
+        onChange={(event) => setDraft(event.target.value)}
+        onBlur={() => onCommit(splitFlagList(draft))}
+        onKeyDown={(event) => {
+          if (event.key === 'Enter') onCommit(splitFlagList(draft));
+        }}
+      />
+    </div>
+  );
+}
+
 function HelpIcon({ content }: { content: string }) {
   return (
     <Tooltip content={content}>
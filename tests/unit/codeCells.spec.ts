@@ -0,0 +1,78 @@
+import { describe, expect, it } from 'vitest';
+import {
+  findCellAtLine,
+  getCellSource,
+  selectCellsToRun,
+  splitCodeCells,
+} from '../../packages/languages/src/codeCells';
+
+const NOTEBOOK = [
+  "import fs from 'fs';",
+  '// %% Load data',
+  'const data = [1, 2, 3];',
+  '',
+  '//%%',
+  'console.log(data.length);',
+].join('\n');
+
+describe('splitCodeCells', () => {
+  it('splits on markers and keeps code above the first as a cell', () => {
+    expect(splitCodeCells(NOTEBOOK, 'typescript')).toEqual([
+      { index: 0, startLine: 1, endLine: 1, title: '' },
+      { index: 1, startLine: 2, endLine: 4, title: 'Load data' },
+      { index: 2, startLine: 5, endLine: 6, title: '' },
+    ]);
+  });
+
+  it('uses `#` markers for Python and skips blank leading lines', () => {
+    const cells = splitCodeCells('\n# %% Setup\nx = 1\n# %%\nx', 'python');
+
+    expect(cells.map(({ startLine, title }) => [startLine, title])).toEqual([
+      [2, 'Setup'],
+      [4, ''],
+    ]);
+  });
+
+  it('finds no cells without markers or in other languages', () => {
+    expect(splitCodeCells('const x = 1;', 'javascript')).toEqual([]);
+    expect(splitCodeCells('# %%\nx = 1', 'javascript')).toEqual([]);
+    expect(splitCodeCells('// %%\nx', 'markdown')).toEqual([]);
+  });
+});
+
+describe('selectCellsToRun', () => {
+  const cells = splitCodeCells(NOTEBOOK, 'typescript');
+
+  it('runs one cell when state is shared, and the cells above otherwise', () => {
+    expect(selectCellsToRun(cells, 'cell', 1, true)).toEqual([cells[1]]);
+    expect(selectCellsToRun(cells, 'cell', 1, false)).toEqual([
+      cells[0],
+      cells[1],
+    ]);
+  });
+
+  it('runs the cells before one, or all of them', () => {
+    expect(selectCellsToRun(cells, 'above', 2, true)).toEqual([
+      cells[0],
+      cells[1],
+    ]);
+    expect(selectCellsToRun(cells, 'all', 0, true)).toEqual(cells);
+  });
+});
+
+describe('getCellSource', () => {
+  it('blanks other cells so lines keep their numbers', () => {
+    const cells = splitCodeCells(NOTEBOOK, 'typescript');
+    const source = getCellSource(NOTEBOOK, [cells[2]]);
+
+    expect(source.split('\n')).toEqual([
+      '',
+      '',
+      '',
+      '',
+      '//%%',
+      'console.log(data.length);',
+    ]);
+    expect(findCellAtLine(cells, 6)?.index).toBe(2);
+  });
+});
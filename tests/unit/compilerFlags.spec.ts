@@ -0,0 +1,75 @@
+import { describe, expect, it } from 'vitest';
+import {
+  mergeCompilerOptions,
+  parseCompilerMagicHeader,
+} from '../../packages/languages/src/compilerFlags';
+
+describe('parseCompilerMagicHeader', () => {
+  it('reads standards, optimisation, defines, warnings and libraries', () => {
+    const { options, ignored } = parseCompilerMagicHeader(
+      '// cheese: -std=c++20 -O2 -DDEBUG -DLEVEL=3 -Wall -lm\nint main() {}'
+    );
+
+    expect(options).toEqual({
+      cppStandard: 'c++20',
+      optimization: 'O2',
+      defines: ['DEBUG', 'LEVEL=3'],
+      warnings: ['-Wall'],
+      libraries: ['m'],
+    });
+    expect(ignored).toEqual([]);
+  });
+
+  it('accumulates several header lines and skips ordinary comments', () => {
+    const { options } = parseCompilerMagicHeader(
+      [
+        '',
+        '// Solution for exercise 4',
+        '// cheese: -std=c17',
+        '// cheese: -O3',
+        'int main() {}',
+      ].join('\n')
+    );
+
+    expect(options).toEqual({ cStandard: 'c17', optimization: 'O3' });
+  });
+
+  it('stops at the first line of code', () => {
+    const { options } = parseCompilerMagicHeader(
+      'int x;\n// cheese: -O2\nint main() {}'
+    );
+
+    expect(options).toEqual({});
+  });
+
+  it('reports flags that would pass arbitrary arguments through', () => {
+    const { options, ignored } = parseCompilerMagicHeader(
+      '// cheese: -Wl,--export-all -fplugin=evil.so -std=gnu++99 -O9 -Wextra'
+    );
+
+    expect(options).toEqual({ warnings: ['-Wextra'] });
+    expect(ignored).toEqual([
+      '-Wl,--export-all',
+      '-fplugin=evil.so',
+      '-std=gnu++99',
+      '-O9',
+    ]);
+  });
+});
+
+describe('mergeCompilerOptions', () => {
+  it('replaces scalars and appends lists', () => {
+    expect(
+      mergeCompilerOptions(
+        { cStandard: 'c11', optimization: 'O0', defines: ['A'] },
+        { optimization: 'O2', defines: ['B'] }
+      )
+    ).toEqual({
+      cStandard: 'c11',
+      optimization: 'O2',
+      defines: ['A', 'B'],
+      warnings: [],
+      libraries: [],
+    });
+  });
+});
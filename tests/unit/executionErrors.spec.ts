@@ -0,0 +1,43 @@
+import { describe, expect, it } from 'vitest';
+import {
+  ErrorCategory,
+  createExecutionError,
+} from '../../packages/execution/src/errors/ExecutionError';
+
+describe('out-of-memory errors', () => {
+  it('are reported as their own category with the heap limit', () => {
+    const error = createExecutionError({
+      name: 'OutOfMemoryError',
+      message:
+        'Out of memory: the run exceeded the 512 MB heap limit and the worker was restarted',
+    });
+
+    expect(error.category).toBe(ErrorCategory.OUT_OF_MEMORY);
+    expect(error.friendlyMessage).toContain('512 MB');
+    expect(error.friendlyMessage).toContain('restarted');
+    expect(error.getIcon()).toBe('💾');
+    expect(error.suggestions.map((s) => s.title)).toEqual([
+      'Raise the memory limit',
+      'Process data in smaller chunks',
+    ]);
+  });
+
+  it('are recognised from a rejected execution message alone', () => {
+    const error = createExecutionError(
+      new Error(
+        'Out of memory: the run exceeded the default heap limit and the worker was restarted'
+      )
+    );
+
+    expect(error.category).toBe(ErrorCategory.OUT_OF_MEMORY);
+    expect(error.friendlyMessage).not.toContain('MB');
+  });
+
+  it('leave other memory errors in the generic category', () => {
+    const error = createExecutionError(
+      new RangeError('Maximum call stack size exceeded')
+    );
+
+    expect(error.category).toBe(ErrorCategory.MEMORY);
+  });
+});
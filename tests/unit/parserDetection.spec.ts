@@ -1,7 +1,7 @@
 import { describe, expect, it } from 'vitest';
 import { detectWithParsers } from '../../packages/languages/src/detection/parserDetection';
 
-describe('parserDetection C/C++ support', () => {
+describe('parserDetection C/C++/Rust support', () => {
   it('detects C code with stdio main program', () => {
     const result = detectWithParsers(
       '#include <stdio.h>\nint main(){ printf("Hello from C\\n"); return 0; }'
@@ -19,4 +19,20 @@ describe('parserDetection C/C++ support', () => {
     expect(result?.monacoId).toBe('cpp');
     expect(result?.isExecutable).toBe(true);
   });
+
+  it('detects Rust code with main and println! macro', () => {
+    const result = detectWithParsers(
+      'use std::collections::HashMap;\n\nfn main() {\n    let mut counts: HashMap<&str, i32> = HashMap::new();\n    counts.insert("a", 1);\n    println!("{:?}", counts);\n}'
+    );
+
+    expect(result?.monacoId).toBe('rust');
+    expect(result?.isExecutable).toBe(true);
+  });
+
+  it('keeps an empty Rust buffer as Rust', () => {
+    const result = detectWithParsers('', { currentLanguage: 'rust' });
+
+    expect(result?.monacoId).toBe('rust');
+    expect(result?.source).toBe('sticky');
+  });
 });
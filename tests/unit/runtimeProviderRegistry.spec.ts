@@ -10,6 +10,7 @@ const { mockGetRuntimeProviderId } = vi.hoisted(() => ({
   mockGetRuntimeProviderId: vi.fn((language: string) => {
     if (language === 'python') return 'pyodide';
     if (language === 'c' || language === 'cpp') return 'wasi-clang';
+    if (language === 'rust') return 'wasi-rustc';
     return 'node-vm';
   }),
 }));
@@ -35,6 +36,7 @@ describe('runtimeProviderRegistry', () => {
     mockGetRuntimeProviderId.mockImplementation((language: string) => {
       if (language === 'python') return 'pyodide';
       if (language === 'c' || language === 'cpp') return 'wasi-clang';
+      if (language === 'rust') return 'wasi-rustc';
       return 'node-vm';
     });
   });
@@ -98,6 +100,7 @@ describe('runtimeProviderRegistry', () => {
   it('should resolve providers from the language registry', () => {
     expect(resolveRuntimeExecutionProvider('python')?.id).toBe('pyodide');
     expect(resolveRuntimeExecutionProvider('c')?.id).toBe('wasi-clang');
+    expect(resolveRuntimeExecutionProvider('rust')?.id).toBe('wasi-rustc');
     expect(resolveRuntimeExecutionProvider('javascript')?.id).toBe('node-vm');
   });
 
@@ -123,6 +126,28 @@ describe('runtimeProviderRegistry', () => {
     expect(result).toBe('wasi-ok');
   });
 
+  it('should execute Rust requests through the WASI worker', async () => {
+    const provider = getRuntimeExecutionProvider('wasi-rustc');
+    (mockWorkerPool.executeWasi as any).mockResolvedValue('rust-ok');
+
+    const result = await provider?.execute({
+      request: {
+        id: 'rust-test',
+        code: 'fn main() { println!("hi"); }',
+        language: 'rust',
+        options: {},
+      },
+      workerPool: mockWorkerPool,
+      transformCode: mockTransformCode,
+    });
+
+    expect(mockTransformCode).not.toHaveBeenCalled();
+    expect(mockWorkerPool.executeWasi).toHaveBeenCalledWith(
+      expect.objectContaining({ language: 'rust' })
+    );
+    expect(result).toBe('rust-ok');
+  });
+
   it('should fall back to the default provider when no runtime is declared', () => {
     mockGetRuntimeProviderId.mockReturnValueOnce(undefined);
 
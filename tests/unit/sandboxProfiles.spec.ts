@@ -0,0 +1,60 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import {
+  SANDBOX_PROFILES,
+  resolveSandboxPermissions,
+} from '../../packages/core/src/contracts/runner';
+import { useSettingsStore } from '@cheesejs/app/store/storeHooks';
+
+describe('resolveSandboxPermissions', () => {
+  it('falls back to the standard profile', () => {
+    expect(resolveSandboxPermissions(undefined)).toEqual(
+      SANDBOX_PROFILES.standard
+    );
+    expect(SANDBOX_PROFILES.standard.childProcess).toBe('deny');
+  });
+
+  it('answers prompts with remembered decisions', () => {
+    expect(
+      resolveSandboxPermissions('ask', { fetch: 'allow', env: 'deny' })
+    ).toEqual({
+      fs: 'prompt',
+      net: 'prompt',
+      fetch: 'allow',
+      env: 'deny',
+      childProcess: 'prompt',
+    });
+  });
+
+  it('does not let remembered decisions override the profile', () => {
+    expect(
+      resolveSandboxPermissions('offline', { fetch: 'allow', fs: 'deny' })
+    ).toEqual(SANDBOX_PROFILES.offline);
+  });
+});
+
+describe('remembered sandbox decisions', () => {
+  beforeEach(() => {
+    useSettingsStore.getState().forgetSandboxDecisions('/work/a');
+    useSettingsStore.getState().forgetSandboxDecisions('/work/b');
+  });
+
+  it('are stored per workspace', () => {
+    const settings = useSettingsStore.getState();
+    settings.rememberSandboxDecision('/work/a', 'fs', 'allow');
+    settings.rememberSandboxDecision('/work/a', 'net', 'deny');
+    settings.rememberSandboxDecision('/work/b', 'fs', 'deny');
+
+    expect(useSettingsStore.getState().sandboxDecisions).toMatchObject({
+      '/work/a': { fs: 'allow', net: 'deny' },
+      '/work/b': { fs: 'deny' },
+    });
+
+    useSettingsStore.getState().forgetSandboxDecisions('/work/a');
+    expect(useSettingsStore.getState().sandboxDecisions['/work/a']).toBe(
+      undefined
+    );
+    expect(useSettingsStore.getState().sandboxDecisions['/work/b']).toEqual({
+      fs: 'deny',
+    });
+  });
+});
@@ -90,6 +90,8 @@ export default defineConfig(({ mode }) => ({
           },
           build: {
             rollupOptions: {
+              // Transpiles TypeScript files imported on the ES module path
+              external: ['@swc/core'],
               output: {
                 format: 'es',
               },
@@ -114,7 +116,7 @@ export default defineConfig(({ mode }) => ({
         },
       },
       {
-        // Worker thread for WASI C/C++ execution
+        // Worker thread for WASI C/C++/Rust execution
         entry: 'electron/workers/wasiExecutor.ts',
         vite: {
           resolve: {
@@ -129,6 +131,22 @@ export default defineConfig(({ mode }) => ({
           },
         },
       },
+      {
+        // Nested thread that runs compiled WASI modules for the WASI worker
+        entry: 'electron/workers/wasiModuleRunner.ts',
+        vite: {
+          resolve: {
+            alias: packageAliases,
+          },
+          build: {
+            rollupOptions: {
+              output: {
+                format: 'es',
+              },
+            },
+          },
+        },
+      },
       {
         // SWC transpiler worker (dedicated worker for 20-70x faster transpilation)
         entry: 'electron/workers/swcTranspilerWorker.ts',